@@ -0,0 +1,19 @@
+-- OHLCV candles aggregated from the agent's quote stream
+-- Migration: 005_market_candles.sql
+
+CREATE TABLE IF NOT EXISTS market_candles (
+    input_mint VARCHAR(50) NOT NULL,
+    output_mint VARCHAR(50) NOT NULL,
+    interval VARCHAR(4) NOT NULL, -- '1m', '5m', '1h'
+    open_time TIMESTAMP WITH TIME ZONE NOT NULL,
+    open DOUBLE PRECISION NOT NULL,
+    high DOUBLE PRECISION NOT NULL,
+    low DOUBLE PRECISION NOT NULL,
+    close DOUBLE PRECISION NOT NULL,
+    volume DOUBLE PRECISION NOT NULL DEFAULT 0, -- quoted input amount
+    quote_count INTEGER NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (input_mint, output_mint, interval, open_time)
+);
+
+CREATE INDEX IF NOT EXISTS idx_market_candles_open_time ON market_candles(interval, open_time);
@@ -0,0 +1,19 @@
+-- Wallet-signed transactions submitted through the API and their on-chain status
+-- Migration: 007_submitted_transactions.sql
+
+CREATE TABLE IF NOT EXISTS submitted_transactions (
+    signature VARCHAR(88) PRIMARY KEY,
+    user_id UUID NOT NULL,
+    fee_payer VARCHAR(44) NOT NULL,
+    action VARCHAR(40), -- ICM instruction name, NULL when not recognised
+    pool_id VARCHAR(64), -- bucket PDA the instruction targets
+    recent_blockhash VARCHAR(44) NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'submitted', -- 'submitted', 'confirmed', 'finalized', 'failed', 'expired'
+    slot BIGINT,
+    error TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_submitted_transactions_user ON submitted_transactions(user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_submitted_transactions_pending ON submitted_transactions(created_at) WHERE status IN ('submitted', 'confirmed');
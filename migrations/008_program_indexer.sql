@@ -0,0 +1,36 @@
+-- On-chain indexer: ICM program instructions mirrored into pool tables
+-- Migration: 008_program_indexer.sql
+
+-- Buckets created outside the API have no user profile
+ALTER TABLE trading_pools DROP CONSTRAINT IF EXISTS trading_pools_creator_pubkey_fkey;
+
+ALTER TABLE trading_pools ADD COLUMN IF NOT EXISTS bucket_pda VARCHAR(44);
+ALTER TABLE trading_pools ADD COLUMN IF NOT EXISTS status VARCHAR(16) NOT NULL DEFAULT 'Raising'; -- 'Raising', 'Trading', 'Closed'
+ALTER TABLE trading_pools ADD COLUMN IF NOT EXISTS closed_at TIMESTAMP WITH TIME ZONE;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_trading_pools_bucket_pda ON trading_pools(bucket_pda);
+
+-- Every ICM instruction applied by the indexer; the primary key makes replays no-ops
+CREATE TABLE IF NOT EXISTS indexed_program_instructions (
+    signature VARCHAR(88) NOT NULL,
+    instruction_index SMALLINT NOT NULL,
+    action VARCHAR(40) NOT NULL, -- ICM instruction name
+    bucket_pda VARCHAR(44),
+    slot BIGINT NOT NULL,
+    block_time TIMESTAMP WITH TIME ZONE,
+    indexed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (signature, instruction_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_indexed_program_instructions_bucket ON indexed_program_instructions(bucket_pda, slot);
+
+-- Newest signature processed per indexed address
+CREATE TABLE IF NOT EXISTS indexer_checkpoints (
+    name VARCHAR(64) PRIMARY KEY,
+    last_signature VARCHAR(88) NOT NULL,
+    last_slot BIGINT NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_pool_contributions_pool ON pool_contributions(pool_id, contributor_pubkey);
+CREATE INDEX IF NOT EXISTS idx_trade_records_pool ON trade_records(pool_id, executed_at);
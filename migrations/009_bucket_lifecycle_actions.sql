@@ -0,0 +1,19 @@
+-- Actions taken by the bucket lifecycle scheduler
+-- Migration: 009_bucket_lifecycle_actions.sql
+
+CREATE EXTENSION IF NOT EXISTS pgcrypto;
+
+CREATE TABLE IF NOT EXISTS bucket_lifecycle_actions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    bucket_pda VARCHAR(44) NOT NULL,
+    bucket_name VARCHAR NOT NULL,
+    creator_pubkey VARCHAR(44) NOT NULL,
+    action VARCHAR(32) NOT NULL, -- 'start_trading', 'start_agent', 'stop_agent', 'unwind', 'close_bucket'
+    status VARCHAR(16) NOT NULL, -- 'succeeded', 'failed'
+    signature VARCHAR(88),
+    details JSONB NOT NULL DEFAULT '{}',
+    error TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_bucket_lifecycle_actions_bucket ON bucket_lifecycle_actions(bucket_pda, action, created_at DESC);
@@ -0,0 +1,15 @@
+-- Fee vault deposits and withdrawals recorded by the program indexer
+-- Migration: 010_fee_vault_movements.sql
+
+CREATE TABLE IF NOT EXISTS fee_vault_movements (
+    signature VARCHAR(88) PRIMARY KEY,
+    slot BIGINT NOT NULL,
+    block_time TIMESTAMP WITH TIME ZONE,
+    action VARCHAR(40), -- ICM instruction that moved the funds
+    bucket_pda VARCHAR(44), -- NULL for withdrawals
+    delta BIGINT NOT NULL, -- USDC base units; negative for withdrawals
+    indexed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_fee_vault_movements_bucket ON fee_vault_movements(bucket_pda) WHERE delta > 0;
+CREATE INDEX IF NOT EXISTS idx_fee_vault_movements_withdrawals ON fee_vault_movements(slot DESC) WHERE delta < 0;
@@ -295,6 +295,8 @@ Be conservative with risk assessment and prioritize capital preservation."#.to_s
             volume_24h: json["volume_24h"].as_f64().unwrap_or(0.0),
             price_trend,
             liquidity_score: json["liquidity_score"].as_f64().unwrap_or(0.5),
+            indicators: None,
+            oracle_divergence_bps: None,
         })
     }
 
@@ -0,0 +1,417 @@
+use std::collections::VecDeque;
+use std::str::FromStr;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use dashmap::DashMap;
+use deadpool_postgres::Pool;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+use crate::agent::token_metadata::to_ui_amount;
+use crate::agent::types::{AgentError, MarketIndicators, QuoteData};
+use crate::database::models::MarketCandle;
+
+/// Candles kept in memory per pair and interval for indicators
+const MAX_CANDLES_IN_MEMORY: usize = 500;
+
+/// How often old candles are pruned from Postgres
+const PRUNE_EVERY_MINUTES: i64 = 60;
+
+/// Candle widths aggregated from the quote stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CandleInterval {
+    #[serde(rename = "1m")]
+    OneMinute,
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "1h")]
+    OneHour,
+}
+
+impl CandleInterval {
+    pub const ALL: [CandleInterval; 3] = [CandleInterval::OneMinute, CandleInterval::FiveMinutes, CandleInterval::OneHour];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CandleInterval::OneMinute => "1m",
+            CandleInterval::FiveMinutes => "5m",
+            CandleInterval::OneHour => "1h",
+        }
+    }
+
+    pub fn seconds(&self) -> i64 {
+        match self {
+            CandleInterval::OneMinute => 60,
+            CandleInterval::FiveMinutes => 300,
+            CandleInterval::OneHour => 3_600,
+        }
+    }
+
+    /// How long candles of this width are kept in Postgres
+    pub fn retention(&self) -> Duration {
+        match self {
+            CandleInterval::OneMinute => Duration::days(2),
+            CandleInterval::FiveMinutes => Duration::days(14),
+            CandleInterval::OneHour => Duration::days(180),
+        }
+    }
+
+    /// Start of the candle containing `timestamp`
+    pub fn open_time(&self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        let secs = timestamp.timestamp();
+        let start = secs - secs.rem_euclid(self.seconds());
+        Utc.timestamp_opt(start, 0).single().unwrap_or(timestamp)
+    }
+}
+
+impl FromStr for CandleInterval {
+    type Err = AgentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1m" => Ok(CandleInterval::OneMinute),
+            "5m" => Ok(CandleInterval::FiveMinutes),
+            "1h" => Ok(CandleInterval::OneHour),
+            other => Err(AgentError::Configuration(format!("Unknown candle interval: {}", other))),
+        }
+    }
+}
+
+type SeriesKey = (String, String, CandleInterval);
+
+/// Turns the quote stream into 1m/5m/1h OHLCV candles per pair and persists them
+pub struct CandleAggregator {
+    db_pool: Pool,
+    /// Candles still open for new quotes
+    live: DashMap<SeriesKey, MarketCandle>,
+    /// Closed candles, oldest first
+    history: DashMap<SeriesKey, VecDeque<MarketCandle>>,
+    last_prune: Mutex<DateTime<Utc>>,
+}
+
+impl CandleAggregator {
+    pub fn new(db_pool: Pool) -> Self {
+        Self {
+            db_pool,
+            live: DashMap::new(),
+            history: DashMap::new(),
+            last_prune: Mutex::new(DateTime::<Utc>::MIN_UTC),
+        }
+    }
+
+    /// Fold a quote into every interval's current candle. Candles that close are persisted.
+    pub async fn ingest(&self, quote: &QuoteData) {
+        let Some(price) = quote.normalized_price().map(|p| p.price) else {
+            debug!("[ingest] Skipping {}/{} quote without mint decimals", quote.input_mint, quote.output_mint);
+            return;
+        };
+        let mut closed = Vec::new();
+
+        for interval in CandleInterval::ALL {
+            let key = (quote.input_mint.clone(), quote.output_mint.clone(), interval);
+            let open_time = interval.open_time(quote.timestamp);
+
+            let mut entry = self.live.entry(key.clone()).or_insert_with(|| new_candle(quote, interval, open_time, price));
+            let candle = entry.value_mut();
+
+            if candle.open_time < open_time {
+                let finished = std::mem::replace(candle, new_candle(quote, interval, open_time, price));
+                closed.push((key, finished));
+            } else if candle.open_time == open_time {
+                candle.high = candle.high.max(price);
+                candle.low = candle.low.min(price);
+                candle.close = price;
+            } else {
+                // Late quote for a candle that already closed
+                continue;
+            }
+            candle.volume += to_ui_amount(quote.input_amount, quote.input_decimals.unwrap_or_default());
+            candle.quote_count += 1;
+        }
+
+        if closed.is_empty() {
+            return;
+        }
+
+        let finished: Vec<MarketCandle> = closed.iter().map(|(_, candle)| candle.clone()).collect();
+        for (key, candle) in closed {
+            let mut series = self.history.entry(key).or_default();
+            series.push_back(candle);
+            while series.len() > MAX_CANDLES_IN_MEMORY {
+                series.pop_front();
+            }
+        }
+
+        if let Err(e) = MarketCandle::upsert_many(&self.db_pool, &finished).await {
+            warn!("[ingest] Failed to persist {} closed candles: {}", finished.len(), e);
+        }
+    }
+
+    /// Persist open candles so charts see the current bar, and prune expired rows
+    pub async fn flush(&self) -> Result<(), AgentError> {
+        let open: Vec<MarketCandle> = self.live.iter().map(|entry| entry.value().clone()).collect();
+        if !open.is_empty() {
+            MarketCandle::upsert_many(&self.db_pool, &open).await
+                .map_err(|e| AgentError::Database(e.to_string()))?;
+            debug!("[flush] Persisted {} open candles", open.len());
+        }
+
+        let mut last_prune = self.last_prune.lock().await;
+        if Utc::now() - *last_prune >= Duration::minutes(PRUNE_EVERY_MINUTES) {
+            for interval in CandleInterval::ALL {
+                let cutoff = Utc::now() - interval.retention();
+                match MarketCandle::delete_before(&self.db_pool, interval.as_str(), cutoff).await {
+                    Ok(0) => {}
+                    Ok(deleted) => info!("[flush] Pruned {} {} candles older than {}", deleted, interval.as_str(), cutoff),
+                    Err(e) => warn!("[flush] Failed to prune {} candles: {}", interval.as_str(), e),
+                }
+            }
+            *last_prune = Utc::now();
+        }
+        Ok(())
+    }
+
+    /// Closed candles followed by the open one, oldest first
+    pub fn candles(&self, input_mint: &str, output_mint: &str, interval: CandleInterval) -> Vec<MarketCandle> {
+        let key = (input_mint.to_string(), output_mint.to_string(), interval);
+        let mut candles: Vec<MarketCandle> = self.history.get(&key)
+            .map(|series| series.iter().cloned().collect())
+            .unwrap_or_default();
+        if let Some(open) = self.live.get(&key) {
+            candles.push(open.clone());
+        }
+        candles
+    }
+
+    /// ATR, realized volatility and EMAs for a pair on one interval
+    pub fn indicators(&self, input_mint: &str, output_mint: &str, interval: CandleInterval, period: usize) -> Option<MarketIndicators> {
+        let candles = self.candles(input_mint, output_mint, interval);
+        if candles.len() < 2 {
+            return None;
+        }
+        let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
+
+        Some(MarketIndicators {
+            interval: interval.as_str().to_string(),
+            candle_count: candles.len(),
+            atr: average_true_range(&candles, period),
+            realized_volatility_24h: realized_volatility(&candles, interval, period),
+            ema_fast: ema(&closes, period.div_ceil(2).max(2)),
+            ema_slow: ema(&closes, period.max(2)),
+            last_close: closes.last().copied(),
+        })
+    }
+}
+
+impl std::fmt::Debug for CandleAggregator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CandleAggregator")
+            .field("live_series", &self.live.len())
+            .finish()
+    }
+}
+
+fn new_candle(quote: &QuoteData, interval: CandleInterval, open_time: DateTime<Utc>, price: f64) -> MarketCandle {
+    MarketCandle {
+        input_mint: quote.input_mint.clone(),
+        output_mint: quote.output_mint.clone(),
+        interval: interval.as_str().to_string(),
+        open_time,
+        open: price,
+        high: price,
+        low: price,
+        close: price,
+        volume: 0.0,
+        quote_count: 0,
+    }
+}
+
+/// Wilder's average true range over the last `period` candles
+pub fn average_true_range(candles: &[MarketCandle], period: usize) -> Option<f64> {
+    if candles.len() < 2 || period == 0 {
+        return None;
+    }
+    let true_ranges: Vec<f64> = candles.windows(2)
+        .map(|w| {
+            let (prev, c) = (&w[0], &w[1]);
+            (c.high - c.low)
+                .max((c.high - prev.close).abs())
+                .max((c.low - prev.close).abs())
+        })
+        .collect();
+
+    let period = period.min(true_ranges.len());
+    let mut atr = true_ranges[..period].iter().sum::<f64>() / period as f64;
+    for tr in &true_ranges[period..] {
+        atr = (atr * (period as f64 - 1.0) + tr) / period as f64;
+    }
+    Some(atr)
+}
+
+/// Standard deviation of log close-to-close returns over the last `period`
+/// candles, scaled to a 24h horizon by the candle width
+pub fn realized_volatility(candles: &[MarketCandle], interval: CandleInterval, period: usize) -> Option<f64> {
+    let start = candles.len().saturating_sub(period + 1);
+    let returns: Vec<f64> = candles[start..].windows(2)
+        .filter(|w| w[0].close > 0.0 && w[1].close > 0.0)
+        .map(|w| (w[1].close / w[0].close).ln())
+        .collect();
+    if returns.len() < 2 {
+        return None;
+    }
+
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+    let periods_per_day = 86_400.0 / interval.seconds() as f64;
+    Some(variance.sqrt() * periods_per_day.sqrt())
+}
+
+/// Exponential moving average seeded with the simple average of the first `period` values
+pub fn ema(values: &[f64], period: usize) -> Option<f64> {
+    if values.is_empty() || period == 0 {
+        return None;
+    }
+    let period = period.min(values.len());
+    let alpha = 2.0 / (period as f64 + 1.0);
+    let seed = values[..period].iter().sum::<f64>() / period as f64;
+    Some(values[period..].iter().fold(seed, |ema, v| alpha * v + (1.0 - alpha) * ema))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    fn approx(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(1_700_000_040 + secs, 0).unwrap()
+    }
+
+    /// SOL/USDC quote of one SOL at `price` USDC
+    fn quote(price: f64, timestamp: DateTime<Utc>) -> QuoteData {
+        QuoteData {
+            input_mint: SOL.to_string(),
+            output_mint: USDC.to_string(),
+            input_amount: 1_000_000_000,
+            output_amount: (price * 1_000_000.0) as u64,
+            other_amount_threshold: 0,
+            swap_mode: "ExactIn".to_string(),
+            slippage_bps: 50,
+            platform_fee_bps: 0,
+            price_impact_pct: 0.0,
+            route_plan: Vec::new(),
+            timestamp,
+            input_decimals: Some(9),
+            output_decimals: Some(6),
+            input_price_usd: None,
+            output_price_usd: None,
+        }
+    }
+
+    fn candle(high: f64, low: f64, close: f64) -> MarketCandle {
+        MarketCandle {
+            input_mint: SOL.to_string(),
+            output_mint: USDC.to_string(),
+            interval: "1m".to_string(),
+            open_time: at(0),
+            open: close,
+            high,
+            low,
+            close,
+            volume: 0.0,
+            quote_count: 1,
+        }
+    }
+
+    /// Pool whose connections are refused, so closed candles fail to persist
+    fn unreachable_pool() -> Pool {
+        let mut config = tokio_postgres::Config::new();
+        config.host("127.0.0.1").port(1).user("icm").dbname("icm");
+        let manager = deadpool_postgres::Manager::new(config, tokio_postgres::NoTls);
+        Pool::builder(manager).runtime(deadpool_postgres::Runtime::Tokio1).build().unwrap()
+    }
+
+    #[test]
+    fn open_time_floors_to_the_interval() {
+        let timestamp = Utc.timestamp_opt(1_700_003_725, 0).unwrap();
+        assert_eq!(CandleInterval::OneMinute.open_time(timestamp).timestamp(), 1_700_003_700);
+        assert_eq!(CandleInterval::FiveMinutes.open_time(timestamp).timestamp(), 1_700_003_700);
+        assert_eq!(CandleInterval::OneHour.open_time(timestamp).timestamp(), 1_700_002_800);
+    }
+
+    #[test]
+    fn intervals_parse_from_their_names() {
+        for interval in CandleInterval::ALL {
+            assert_eq!(interval.as_str().parse::<CandleInterval>().unwrap(), interval);
+        }
+        assert!("15m".parse::<CandleInterval>().is_err());
+    }
+
+    #[tokio::test]
+    async fn quotes_fold_into_ohlc_and_roll_over() {
+        let aggregator = CandleAggregator::new(unreachable_pool());
+        for (price, secs) in [(100.0, 0), (104.0, 10), (98.0, 20), (101.0, 50), (103.0, 65)] {
+            aggregator.ingest(&quote(price, at(secs))).await;
+        }
+
+        let minutes = aggregator.candles(SOL, USDC, CandleInterval::OneMinute);
+        assert_eq!(minutes.len(), 2);
+        let closed = &minutes[0];
+        assert_eq!((closed.open, closed.high, closed.low, closed.close), (100.0, 104.0, 98.0, 101.0));
+        assert_eq!(closed.quote_count, 4);
+        approx(closed.volume, 4.0);
+        assert_eq!(minutes[1].open, 103.0);
+        assert_eq!(minutes[1].open_time, at(60));
+
+        let hours = aggregator.candles(SOL, USDC, CandleInterval::OneHour);
+        assert_eq!(hours.len(), 1);
+        assert_eq!((hours[0].high, hours[0].low, hours[0].close), (104.0, 98.0, 103.0));
+    }
+
+    #[tokio::test]
+    async fn late_quotes_do_not_reopen_closed_candles() {
+        let aggregator = CandleAggregator::new(unreachable_pool());
+        aggregator.ingest(&quote(100.0, at(0))).await;
+        aggregator.ingest(&quote(101.0, at(60))).await;
+        aggregator.ingest(&quote(90.0, at(30))).await;
+
+        let minutes = aggregator.candles(SOL, USDC, CandleInterval::OneMinute);
+        assert_eq!(minutes[0].low, 100.0);
+        assert_eq!(minutes[1].low, 101.0);
+    }
+
+    #[test]
+    fn atr_uses_gaps_to_the_previous_close() {
+        let candles = [candle(10.0, 8.0, 9.0), candle(11.0, 9.0, 10.0), candle(12.0, 9.5, 11.5), candle(11.0, 10.0, 10.5)];
+        // True ranges 2.0, 2.5 and 1.5; seeded with (2.0 + 2.5) / 2, then Wilder-smoothed
+        approx(average_true_range(&candles, 2).unwrap(), 1.875);
+        approx(average_true_range(&candles, 10).unwrap(), 2.0);
+        assert!(average_true_range(&candles[..1], 2).is_none());
+    }
+
+    #[test]
+    fn ema_is_seeded_with_the_simple_average() {
+        approx(ema(&[1.0, 2.0, 3.0, 4.0, 5.0], 3).unwrap(), 4.0);
+        approx(ema(&[7.0; 10], 4).unwrap(), 7.0);
+        assert!(ema(&[], 3).is_none());
+    }
+
+    #[test]
+    fn realized_volatility_scales_to_a_day() {
+        let flat: Vec<MarketCandle> = (0..10).map(|_| candle(100.0, 100.0, 100.0)).collect();
+        approx(realized_volatility(&flat, CandleInterval::OneMinute, 5).unwrap(), 0.0);
+
+        let choppy: Vec<MarketCandle> = [100.0, 110.0, 100.0, 105.0, 100.0]
+            .iter()
+            .map(|close| candle(*close, *close, *close))
+            .collect();
+        let per_minute = realized_volatility(&choppy, CandleInterval::OneMinute, 4).unwrap();
+        let per_hour = realized_volatility(&choppy, CandleInterval::OneHour, 4).unwrap();
+        approx(per_minute / per_hour, 60f64.sqrt());
+        assert!(realized_volatility(&choppy[..2], CandleInterval::OneMinute, 4).is_none());
+    }
+}
@@ -0,0 +1,354 @@
+use std::collections::VecDeque;
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use crate::agent::types::{pair_key, AgentError, QuoteData};
+
+/// Log returns kept per pair for the jump test
+const RETURN_WINDOW: usize = 50;
+
+/// When a pair's breaker trips and how long it stays open
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    /// Quotes older than this count as stale
+    pub max_quote_age_ms: u64,
+    /// Trip when a return is this many standard deviations from the mean
+    pub jump_sigma: f64,
+    /// Returns needed before the jump test applies
+    pub min_samples: usize,
+    pub max_consecutive_failures: u32,
+    pub max_price_impact_pct: f64,
+    pub cooldown_secs: i64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            max_quote_age_ms: 30_000,
+            jump_sigma: 6.0,
+            min_samples: 20,
+            max_consecutive_failures: 5,
+            max_price_impact_pct: 5.0,
+            cooldown_secs: 300,
+        }
+    }
+}
+
+/// Why a breaker tripped
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum TripReason {
+    StaleData { age_ms: i64 },
+    PriceJump { sigma: f64 },
+    FetchFailures { count: u32 },
+    PriceImpact { pct: f64 },
+    InvalidQuote { detail: String },
+}
+
+impl std::fmt::Display for TripReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TripReason::StaleData { age_ms } => write!(f, "quote is {}ms old", age_ms),
+            TripReason::PriceJump { sigma } => write!(f, "price moved {:.1} sigma", sigma),
+            TripReason::FetchFailures { count } => write!(f, "{} consecutive fetch failures", count),
+            TripReason::PriceImpact { pct } => write!(f, "price impact {:.2}%", pct),
+            TripReason::InvalidQuote { detail } => write!(f, "invalid quote: {}", detail),
+        }
+    }
+}
+
+/// Open breaker as reported in agent stats
+#[derive(Debug, Clone, Serialize)]
+pub struct TrippedBreaker {
+    pub pair: String,
+    pub reason: TripReason,
+    pub tripped_at: DateTime<Utc>,
+    pub resumes_at: DateTime<Utc>,
+    pub trip_count: u32,
+}
+
+#[derive(Debug, Default)]
+struct PairBreaker {
+    last_price: Option<f64>,
+    last_quote_at: Option<DateTime<Utc>>,
+    returns: VecDeque<f64>,
+    consecutive_failures: u32,
+    trip: Option<(TripReason, DateTime<Utc>)>,
+    trip_count: u32,
+}
+
+/// Per-pair breakers that pause planning on bad market data and resume after a cooldown
+#[derive(Debug)]
+pub struct CircuitBreakers {
+    config: CircuitBreakerConfig,
+    pairs: DashMap<String, PairBreaker>,
+}
+
+impl CircuitBreakers {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            pairs: DashMap::new(),
+        }
+    }
+
+    /// Screen a fresh quote. Returns the trip reason if the quote is unusable.
+    pub fn record_quote(&self, quote: &QuoteData) -> Result<(), TripReason> {
+        let pair = pair_key(&quote.input_mint, &quote.output_mint);
+        let mut breaker = self.pairs.entry(pair.clone()).or_default();
+        breaker.consecutive_failures = 0;
+
+        let reason = if quote.input_amount == 0 || quote.output_amount == 0 {
+            Some(TripReason::InvalidQuote { detail: "zero amount".to_string() })
+        } else if quote.price_impact_pct > self.config.max_price_impact_pct {
+            Some(TripReason::PriceImpact { pct: quote.price_impact_pct })
+        } else {
+            let age_ms = (Utc::now() - quote.timestamp).num_milliseconds();
+            if age_ms > self.config.max_quote_age_ms as i64 {
+                Some(TripReason::StaleData { age_ms })
+            } else {
+                quote.normalized_price()
+                    .and_then(|price| self.observe_price(&mut breaker, price.price))
+            }
+        };
+
+        if breaker.trip.is_none() {
+            breaker.last_quote_at = Some(quote.timestamp);
+        }
+        match reason {
+            Some(reason) => {
+                self.trip(&pair, &mut breaker, reason.clone());
+                Err(reason)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Count a failed fetch for a pair
+    pub fn record_failure(&self, input_mint: &str, output_mint: &str) {
+        let pair = pair_key(input_mint, output_mint);
+        let mut breaker = self.pairs.entry(pair.clone()).or_default();
+        breaker.consecutive_failures += 1;
+
+        if breaker.consecutive_failures >= self.config.max_consecutive_failures && breaker.trip.is_none() {
+            let count = breaker.consecutive_failures;
+            self.trip(&pair, &mut breaker, TripReason::FetchFailures { count });
+        }
+    }
+
+    /// Whether planning may use this pair right now. Resets breakers whose cooldown has passed.
+    pub fn check(&self, input_mint: &str, output_mint: &str) -> Result<(), AgentError> {
+        let pair = pair_key(input_mint, output_mint);
+        let Some(mut breaker) = self.pairs.get_mut(&pair) else {
+            return Ok(());
+        };
+
+        if let Some((reason, tripped_at)) = breaker.trip.clone() {
+            let resumes_at = tripped_at + Duration::seconds(self.config.cooldown_secs);
+            if Utc::now() < resumes_at {
+                return Err(AgentError::StaleMarketData(format!(
+                    "Circuit breaker open for {}: {} (resumes at {})", pair, reason, resumes_at
+                )));
+            }
+            info!("[check] Circuit breaker for {} reset after cooldown", pair);
+            breaker.trip = None;
+            breaker.consecutive_failures = 0;
+            // Start the jump statistics over so a new price level is not compared with the old one
+            breaker.returns.clear();
+            breaker.last_price = None;
+            breaker.last_quote_at = None;
+            return Ok(());
+        }
+
+        if let Some(last_quote_at) = breaker.last_quote_at {
+            let age_ms = (Utc::now() - last_quote_at).num_milliseconds();
+            if age_ms > self.config.max_quote_age_ms as i64 {
+                let reason = TripReason::StaleData { age_ms };
+                self.trip(&pair, &mut breaker, reason.clone());
+                return Err(AgentError::StaleMarketData(format!("Circuit breaker open for {}: {}", pair, reason)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Breakers that are currently open
+    pub fn tripped(&self) -> Vec<TrippedBreaker> {
+        let now = Utc::now();
+        self.pairs.iter()
+            .filter_map(|entry| {
+                let (reason, tripped_at) = entry.trip.clone()?;
+                let resumes_at = tripped_at + Duration::seconds(self.config.cooldown_secs);
+                (resumes_at > now).then(|| TrippedBreaker {
+                    pair: entry.key().clone(),
+                    reason,
+                    tripped_at,
+                    resumes_at,
+                    trip_count: entry.trip_count,
+                })
+            })
+            .collect()
+    }
+
+    /// Add a price to the pair's return window, returning a jump if it is an outlier
+    fn observe_price(&self, breaker: &mut PairBreaker, price: f64) -> Option<TripReason> {
+        if price <= 0.0 {
+            return Some(TripReason::InvalidQuote { detail: "non-positive price".to_string() });
+        }
+        let last_price = breaker.last_price.replace(price)?;
+        let log_return = (price / last_price).ln();
+
+        if breaker.returns.len() >= self.config.min_samples.max(2) {
+            let n = breaker.returns.len() as f64;
+            let mean = breaker.returns.iter().sum::<f64>() / n;
+            let std = (breaker.returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+            if std > f64::EPSILON {
+                let sigma = (log_return - mean).abs() / std;
+                if sigma > self.config.jump_sigma {
+                    // Keep the pre-jump price as the reference
+                    breaker.last_price = Some(last_price);
+                    return Some(TripReason::PriceJump { sigma });
+                }
+            }
+        }
+
+        breaker.returns.push_back(log_return);
+        while breaker.returns.len() > RETURN_WINDOW {
+            breaker.returns.pop_front();
+        }
+        None
+    }
+
+    fn trip(&self, pair: &str, breaker: &mut PairBreaker, reason: TripReason) {
+        if breaker.trip.is_none() {
+            breaker.trip_count += 1;
+            warn!("[trip] Circuit breaker tripped for {}: {} (cooldown {}s)", pair, reason, self.config.cooldown_secs);
+        }
+        breaker.trip = Some((reason, Utc::now()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    /// SOL/USDC quote of one SOL for `output_usdc` base units
+    fn quote(output_usdc: u64) -> QuoteData {
+        QuoteData {
+            input_mint: SOL.to_string(),
+            output_mint: USDC.to_string(),
+            input_amount: 1_000_000_000,
+            output_amount: output_usdc,
+            other_amount_threshold: 0,
+            swap_mode: "ExactIn".to_string(),
+            slippage_bps: 50,
+            platform_fee_bps: 0,
+            price_impact_pct: 0.1,
+            route_plan: Vec::new(),
+            timestamp: Utc::now(),
+            input_decimals: Some(9),
+            output_decimals: Some(6),
+            input_price_usd: None,
+            output_price_usd: None,
+        }
+    }
+
+    fn breakers(cooldown_secs: i64) -> CircuitBreakers {
+        CircuitBreakers::new(CircuitBreakerConfig { cooldown_secs, ..CircuitBreakerConfig::default() })
+    }
+
+    #[test]
+    fn stale_quote_trips_the_pair() {
+        let breakers = breakers(300);
+        let stale = QuoteData { timestamp: Utc::now() - Duration::seconds(60), ..quote(150_000_000) };
+
+        assert!(matches!(breakers.record_quote(&stale), Err(TripReason::StaleData { age_ms }) if age_ms >= 60_000));
+        assert!(breakers.check(SOL, USDC).is_err());
+        // The reverse direction has its own breaker
+        assert!(breakers.check(USDC, SOL).is_ok());
+    }
+
+    #[test]
+    fn jump_past_the_sigma_threshold_trips() {
+        let breakers = breakers(300);
+        for i in 0..25 {
+            let output = if i % 2 == 0 { 150_000_000 } else { 150_150_000 };
+            assert_eq!(breakers.record_quote(&quote(output)), Ok(()));
+        }
+
+        let reason = breakers.record_quote(&quote(225_000_000)).unwrap_err();
+        assert!(matches!(reason, TripReason::PriceJump { sigma } if sigma > 6.0));
+        assert!(breakers.check(SOL, USDC).is_err());
+    }
+
+    #[test]
+    fn jump_test_waits_for_enough_samples() {
+        let breakers = breakers(300);
+        for _ in 0..5 {
+            assert_eq!(breakers.record_quote(&quote(150_000_000)), Ok(()));
+        }
+        assert_eq!(breakers.record_quote(&quote(225_000_000)), Ok(()));
+    }
+
+    #[test]
+    fn consecutive_fetch_failures_trip_at_the_limit() {
+        let breakers = breakers(300);
+        for _ in 0..4 {
+            breakers.record_failure(SOL, USDC);
+        }
+        assert!(breakers.check(SOL, USDC).is_ok());
+
+        breakers.record_failure(SOL, USDC);
+        assert!(breakers.check(SOL, USDC).is_err());
+        let tripped = breakers.tripped();
+        assert_eq!(tripped.len(), 1);
+        assert_eq!(tripped[0].reason, TripReason::FetchFailures { count: 5 });
+    }
+
+    #[test]
+    fn a_good_quote_resets_the_failure_count() {
+        let breakers = breakers(300);
+        for _ in 0..4 {
+            breakers.record_failure(SOL, USDC);
+        }
+        assert_eq!(breakers.record_quote(&quote(150_000_000)), Ok(()));
+        breakers.record_failure(SOL, USDC);
+        assert!(breakers.check(SOL, USDC).is_ok());
+    }
+
+    #[test]
+    fn excessive_price_impact_trips() {
+        let breakers = breakers(300);
+        let impact = QuoteData { price_impact_pct: 7.5, ..quote(150_000_000) };
+        assert_eq!(breakers.record_quote(&impact), Err(TripReason::PriceImpact { pct: 7.5 }));
+        assert!(breakers.check(SOL, USDC).is_err());
+    }
+
+    #[test]
+    fn breaker_stays_open_until_the_cooldown_passes() {
+        let breakers = breakers(300);
+        let impact = QuoteData { price_impact_pct: 7.5, ..quote(150_000_000) };
+        breakers.record_quote(&impact).unwrap_err();
+
+        let tripped = breakers.tripped();
+        assert_eq!(tripped.len(), 1);
+        assert_eq!(tripped[0].resumes_at - tripped[0].tripped_at, Duration::seconds(300));
+        assert!(breakers.check(SOL, USDC).is_err());
+    }
+
+    #[test]
+    fn breaker_resets_after_the_cooldown() {
+        let breakers = breakers(0);
+        let impact = QuoteData { price_impact_pct: 7.5, ..quote(150_000_000) };
+        breakers.record_quote(&impact).unwrap_err();
+
+        assert!(breakers.check(SOL, USDC).is_ok());
+        assert!(breakers.tripped().is_empty());
+        // Jump statistics start over at the new price level
+        assert_eq!(breakers.record_quote(&quote(300_000_000)), Ok(()));
+    }
+}
@@ -0,0 +1,255 @@
+use std::sync::Arc;
+use std::time::Duration;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status_client_types::{
+    option_serializer::OptionSerializer,
+    TransactionConfirmationStatus,
+    UiTransactionEncoding,
+    UiTransactionStatusMeta,
+    UiTransactionTokenBalance,
+};
+use serde::Serialize;
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+use crate::agent::executor::TransactionStatus;
+use crate::agent::types::AgentError;
+use crate::onchain_instance::rpc::SolanaRpc;
+
+/// Token accounts touched by a swap whose balances we want to measure
+#[derive(Debug, Clone, Copy)]
+pub struct SwapBalanceAccounts {
+    pub input_token_account: Pubkey,
+    pub output_token_account: Pubkey,
+}
+
+/// Outcome of a swap after it finalized or failed
+#[derive(Debug, Clone)]
+pub struct ConfirmedSwap {
+    pub status: TransactionStatus,
+    pub slot: Option<u64>,
+    /// Lamports the fee payer lost in this transaction (fees, priority fees, rent)
+    pub lamports_spent: Option<u64>,
+    pub fee_lamports: Option<u64>,
+    pub compute_units_consumed: Option<u64>,
+    pub input_spent: Option<u64>,
+    pub output_received: Option<u64>,
+    pub error: Option<String>,
+}
+
+impl ConfirmedSwap {
+    pub fn costs(&self) -> TransactionCosts {
+        TransactionCosts {
+            slot: self.slot,
+            fee_lamports: self.fee_lamports.unwrap_or(0),
+            compute_units_consumed: self.compute_units_consumed.unwrap_or(0),
+        }
+    }
+}
+
+/// Fees and compute of confirmed transactions, summed over route legs and slices
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct TransactionCosts {
+    /// Slot the latest transaction landed in
+    pub slot: Option<u64>,
+    pub fee_lamports: u64,
+    pub compute_units_consumed: u64,
+}
+
+impl TransactionCosts {
+    /// Add another transaction's costs, keeping the later slot
+    pub fn add(&mut self, other: TransactionCosts) {
+        self.slot = self.slot.max(other.slot);
+        self.fee_lamports += other.fee_lamports;
+        self.compute_units_consumed += other.compute_units_consumed;
+    }
+}
+
+/// Last signature status seen while waiting for finality
+#[derive(Debug, Clone)]
+pub struct ObservedStatus {
+    pub status: TransactionStatus,
+    /// Commitment the status was observed at; failures are reported as soon as they land
+    pub commitment: CommitmentConfig,
+    pub error: Option<String>,
+}
+
+/// Follows submitted signatures to finalized commitment and reads the
+/// resulting balance changes from the transaction metadata
+#[derive(Clone)]
+pub struct ConfirmationTracker {
+    rpc: Arc<SolanaRpc>,
+    poll_interval: Duration,
+    timeout: Duration,
+}
+
+impl ConfirmationTracker {
+    pub fn new(rpc: Arc<SolanaRpc>, poll_interval: Duration, timeout: Duration) -> Self {
+        Self {
+            rpc,
+            poll_interval,
+            timeout,
+        }
+    }
+
+    /// Wait for a signature to finalize, then measure what the swap actually did
+    pub async fn track_swap(
+        &self,
+        signature: &Signature,
+        accounts: SwapBalanceAccounts,
+    ) -> Result<ConfirmedSwap, AgentError> {
+        let observed = self.await_finalized(signature).await?;
+        let unmeasured = |status: TransactionStatus, error: Option<String>| ConfirmedSwap {
+            status,
+            slot: None,
+            lamports_spent: None,
+            fee_lamports: None,
+            compute_units_consumed: None,
+            input_spent: None,
+            output_received: None,
+            error,
+        };
+
+        if matches!(observed.status, TransactionStatus::Expired | TransactionStatus::Pending) {
+            warn!("[track_swap] {} did not finalize within {:?}", signature, self.timeout);
+            return Ok(unmeasured(observed.status, Some("Transaction did not finalize before timeout".to_string())));
+        }
+
+        // Transactions can only be fetched at confirmed or finalized
+        let commitment = if observed.commitment.is_finalized() {
+            CommitmentConfig::finalized()
+        } else {
+            CommitmentConfig::confirmed()
+        };
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(commitment),
+            max_supported_transaction_version: Some(0),
+        };
+        let fetched = self.rpc.call(|rpc| async move { rpc.get_transaction_with_config(signature, config).await }).await
+            .map_err(|e| AgentError::TransactionFailed(format!("Failed to fetch transaction {}: {}", signature, e)))
+            .and_then(|confirmed| match confirmed.transaction.meta.clone() {
+                Some(meta) => Ok((confirmed, meta)),
+                None => Err(AgentError::TransactionFailed(format!("Transaction {} has no status meta", signature))),
+            });
+        let (confirmed, meta) = match fetched {
+            Ok(fetched) => fetched,
+            // A failure is known from its status even when its details cannot be read
+            Err(e) if matches!(observed.status, TransactionStatus::Failed) => {
+                warn!("[track_swap] {} failed; {}", signature, e);
+                return Ok(unmeasured(TransactionStatus::Failed, observed.error));
+            }
+            Err(e) => return Err(e),
+        };
+        let account_keys = confirmed.transaction.transaction.decode()
+            .map(|tx| tx.message.static_account_keys().to_vec())
+            .unwrap_or_default();
+
+        let input_index = account_index(&account_keys, &accounts.input_token_account);
+        let output_index = account_index(&account_keys, &accounts.output_token_account);
+
+        let input_spent = input_index.and_then(|i| {
+            let pre = token_balance(&meta.pre_token_balances, i)?;
+            let post = token_balance(&meta.post_token_balances, i).unwrap_or(0);
+            Some(pre.saturating_sub(post))
+        });
+        let output_received = output_index.map(|i| {
+            let pre = token_balance(&meta.pre_token_balances, i).unwrap_or(0);
+            let post = token_balance(&meta.post_token_balances, i).unwrap_or(0);
+            post.saturating_sub(pre)
+        });
+
+        let outcome = ConfirmedSwap {
+            status: if meta.err.is_some() { TransactionStatus::Failed } else { TransactionStatus::Confirmed },
+            slot: Some(confirmed.slot),
+            lamports_spent: fee_payer_spent(&meta),
+            fee_lamports: Some(meta.fee),
+            compute_units_consumed: match meta.compute_units_consumed {
+                OptionSerializer::Some(units) => Some(units),
+                _ => None,
+            },
+            input_spent,
+            output_received,
+            error: meta.err.as_ref().map(|e| e.to_string()),
+        };
+
+        info!(
+            "[track_swap] {} landed in slot {}: in={:?} out={:?} lamports_spent={:?} fee={} cu={:?}",
+            signature, confirmed.slot, outcome.input_spent, outcome.output_received, outcome.lamports_spent,
+            meta.fee, outcome.compute_units_consumed
+        );
+
+        Ok(outcome)
+    }
+
+    /// Poll signature status until it is finalized, errors, or the timeout elapses
+    pub async fn await_finalized(&self, signature: &Signature) -> Result<ObservedStatus, AgentError> {
+        let started = Instant::now();
+
+        while started.elapsed() < self.timeout {
+            let statuses = self.rpc.call(|rpc| async move { rpc.get_signature_statuses(&[*signature]).await }).await
+                .map_err(|e| AgentError::TransactionFailed(format!("Failed to fetch signature status: {}", e)))?;
+
+            if let Some(Some(status)) = statuses.value.first() {
+                let commitment = match status.confirmation_status {
+                    Some(TransactionConfirmationStatus::Finalized) => CommitmentConfig::finalized(),
+                    Some(TransactionConfirmationStatus::Confirmed) => CommitmentConfig::confirmed(),
+                    _ => CommitmentConfig::processed(),
+                };
+                if let Some(err) = &status.err {
+                    return Ok(ObservedStatus { status: TransactionStatus::Failed, commitment, error: Some(err.to_string()) });
+                }
+                if commitment.is_finalized() {
+                    return Ok(ObservedStatus { status: TransactionStatus::Confirmed, commitment, error: None });
+                }
+                debug!("[await_finalized] {} at {:?}", signature, status.confirmation_status);
+            }
+
+            tokio::time::sleep(self.poll_interval).await;
+        }
+
+        Ok(ObservedStatus { status: TransactionStatus::Expired, commitment: CommitmentConfig::processed(), error: None })
+    }
+}
+
+impl std::fmt::Debug for ConfirmationTracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConfirmationTracker")
+            .field("rpc_url", &self.rpc.url())
+            .field("poll_interval", &self.poll_interval)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+/// Slippage in bps of the actual output against the output the plan was quoted.
+/// Output at or above the expectation counts as zero slippage.
+pub fn slippage_bps(expected_output: u64, actual_output: u64) -> u16 {
+    if expected_output == 0 || actual_output >= expected_output {
+        return 0;
+    }
+    let shortfall = (expected_output - actual_output) as f64 / expected_output as f64;
+    (shortfall * 10_000.0).round().min(u16::MAX as f64) as u16
+}
+
+fn account_index(keys: &[Pubkey], account: &Pubkey) -> Option<u8> {
+    keys.iter().position(|k| k == account).map(|i| i as u8)
+}
+
+fn token_balance(balances: &OptionSerializer<Vec<UiTransactionTokenBalance>>, index: u8) -> Option<u64> {
+    match balances {
+        OptionSerializer::Some(balances) => balances
+            .iter()
+            .find(|b| b.account_index == index)
+            .and_then(|b| b.ui_token_amount.amount.parse().ok()),
+        _ => None,
+    }
+}
+
+fn fee_payer_spent(meta: &UiTransactionStatusMeta) -> Option<u64> {
+    let pre = meta.pre_balances.first()?;
+    let post = meta.post_balances.first()?;
+    Some(pre.saturating_sub(*post))
+}
@@ -10,6 +10,8 @@ use dashmap::DashMap;
 use tracing::{info, warn, error, debug};
 
 use crate::agent::types::{QuoteData, RoutePlan, SwapInfo, AgentError};
+use crate::agent::token_metadata::TokenMetadataCache;
+use crate::agent::circuit_breaker::CircuitBreakers;
 
 const JUPITER_QUOTE_API: &str = "https://quote-api.jup.ag/v6";
 const JUPITER_PRICE_API: &str = "https://api.jup.ag/price/v2";
@@ -20,6 +22,8 @@ pub struct DataFetcher {
     client: Client,
     quote_cache: Arc<DashMap<String, QuoteData>>,
     price_cache: Arc<DashMap<String, f64>>,
+    token_metadata: Arc<TokenMetadataCache>,
+    circuit_breakers: Arc<CircuitBreakers>,
     token_pairs: Vec<(String, String)>,
     fetch_interval: Duration,
     quote_sender: mpsc::UnboundedSender<QuoteData>,
@@ -30,6 +34,8 @@ impl DataFetcher {
     pub fn new(
         token_pairs: Vec<(String, String)>,
         fetch_interval_ms: u64,
+        token_metadata: Arc<TokenMetadataCache>,
+        circuit_breakers: Arc<CircuitBreakers>,
     ) -> (Self, mpsc::UnboundedReceiver<QuoteData>) {
         let client = Client::builder()
             .timeout(Duration::from_secs(10))
@@ -42,6 +48,8 @@ impl DataFetcher {
             client,
             quote_cache: Arc::new(DashMap::new()),
             price_cache: Arc::new(DashMap::new()),
+            token_metadata,
+            circuit_breakers,
             token_pairs,
             fetch_interval: Duration::from_millis(fetch_interval_ms),
             quote_sender,
@@ -81,7 +89,7 @@ impl DataFetcher {
             let results = futures::future::join_all(fetch_tasks).await;
             
             let mut successful_fetches = 0;
-            for result in results {
+            for ((input, output), result) in self.token_pairs.iter().zip(results) {
                 match result {
                     Ok(quote) => {
                         let cache_key = format!("{}_{}", quote.input_mint, quote.output_mint);
@@ -94,7 +102,8 @@ impl DataFetcher {
                         }
                     }
                     Err(e) => {
-                        error!("Failed to fetch quote: {}", e);
+                        error!("Failed to fetch quote for {}/{}: {}", input, output, e);
+                        self.circuit_breakers.record_failure(input, output);
                     }
                 }
             }
@@ -131,7 +140,29 @@ impl DataFetcher {
         output_mint: String,
     ) -> Result<QuoteData, AgentError> {
         let amount = 1_000_000; // 1 token in smallest units for price discovery
-        
+        self.request_quote(input_mint, output_mint, amount).await
+    }
+
+    /// Fetch a fresh quote for an exact input amount and refresh the cache
+    pub async fn fetch_quote(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: u64,
+    ) -> Result<QuoteData, AgentError> {
+        let quote = self.request_quote(input_mint.to_string(), output_mint.to_string(), amount).await?;
+        let cache_key = format!("{}_{}", quote.input_mint, quote.output_mint);
+        self.quote_cache.insert(cache_key, quote.clone());
+        Ok(quote)
+    }
+
+    /// Request a quote from Jupiter
+    async fn request_quote(
+        &self,
+        input_mint: String,
+        output_mint: String,
+        amount: u64,
+    ) -> Result<QuoteData, AgentError> {
         let url = format!(
             "{}/quote?inputMint={}&outputMint={}&amount={}&slippageBps=50",
             JUPITER_QUOTE_API, input_mint, output_mint, amount
@@ -154,7 +185,7 @@ impl DataFetcher {
         let json: Value = response.json().await?;
         
         // Parse the Jupiter quote response
-        let quote = QuoteData {
+        let mut quote = QuoteData {
             input_mint: json["inputMint"]
                 .as_str()
                 .unwrap_or(&input_mint)
@@ -191,11 +222,30 @@ impl DataFetcher {
                 .unwrap_or(0.0),
             route_plan: self.parse_route_plan(&json["routePlan"])?,
             timestamp: Utc::now(),
+            input_decimals: None,
+            output_decimals: None,
+            input_price_usd: None,
+            output_price_usd: None,
         };
+        self.annotate_quote(&mut quote).await;
 
         Ok(quote)
     }
 
+    /// Attach mint decimals and cached USD prices so consumers can normalize amounts
+    async fn annotate_quote(&self, quote: &mut QuoteData) {
+        match self.token_metadata.decimals(&quote.input_mint).await {
+            Ok(decimals) => quote.input_decimals = Some(decimals),
+            Err(e) => warn!("No decimals for {}: {}", quote.input_mint, e),
+        }
+        match self.token_metadata.decimals(&quote.output_mint).await {
+            Ok(decimals) => quote.output_decimals = Some(decimals),
+            Err(e) => warn!("No decimals for {}: {}", quote.output_mint, e),
+        }
+        quote.input_price_usd = self.get_cached_price(&quote.input_mint);
+        quote.output_price_usd = self.get_cached_price(&quote.output_mint);
+    }
+
     /// Parse Jupiter route plan from JSON
     fn parse_route_plan(&self, route_plan_json: &Value) -> Result<Vec<RoutePlan>, AgentError> {
         let mut route_plans = Vec::new();
@@ -298,11 +348,22 @@ impl DataFetcher {
         self.quote_cache.get(&cache_key).map(|entry| entry.value().clone())
     }
 
+    /// Put a quote in the cache as if it had just been fetched
+    #[cfg(test)]
+    pub fn cache_quote(&self, quote: QuoteData) {
+        self.quote_cache.insert(format!("{}_{}", quote.input_mint, quote.output_mint), quote);
+    }
+
     /// Get cached price for a token
     pub fn get_cached_price(&self, token_mint: &str) -> Option<f64> {
         self.price_cache.get(token_mint).map(|entry| *entry.value())
     }
 
+    /// Shared mint metadata cache
+    pub fn token_metadata(&self) -> Arc<TokenMetadataCache> {
+        Arc::clone(&self.token_metadata)
+    }
+
     /// Get all cached quotes
     pub fn get_all_cached_quotes(&self) -> HashMap<String, QuoteData> {
         self.quote_cache
@@ -2,33 +2,34 @@ use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{RwLock, mpsc, Semaphore};
 use tokio::time::{timeout, Instant};
-use reqwest::Client;
-use serde_json::{json, Value};
 use tracing::{info, warn, error, debug};
 use chrono::Utc;
 use anchor_lang::prelude::*;
 use crate::agent::types::{TradingPlan, AgentError, ExecutionSettings};
-use crate::state_structs::{SwapTokensRequest, UnsignedTransactionResponse};
+use crate::agent::confirmation::{ConfirmationTracker, TransactionCosts, slippage_bps};
+use crate::agent::data_fetcher::DataFetcher;
+use crate::agent::signer::{BucketDirectory, SignerChain, SigningAuthority};
+use crate::agent::pool_resolver::RaydiumPoolResolver;
+use crate::agent::router::{LegFill, RollbackReport, RouteExecution, SwapRouter};
+use crate::agent::sliced_execution::{SliceFill, SlicedExecution, SlicedExecutor};
+use crate::agent::trading_agent::TradingAgentConfig;
+use crate::services::event_bus::{DomainEvent, EventPublisher, ExecutionSummary};
 use crate::onchain_instance::instance::IcmProgramInstance;
-use solana_sdk::signature::{
-    read_keypair_file,
-    Signer,
-};
-use std::str::FromStr;
 
 use std::result::Result as StdResult;
 
-// Jupiter API removed - now using Raydium
-// const JUPITER_SWAP_API: &str = "https://quote-api.jup.ag/v6";
-
 /// Executes trading plans by building and submitting transactions
 #[derive(Debug)]
 pub struct Executor {
-    icm_client: Arc<IcmProgramInstance>,
-    http_client: Client,
+    router: Arc<SwapRouter>,
+    sliced_executor: Arc<SlicedExecutor>,
+    bucket_directory: Arc<BucketDirectory>,
+    signer: Arc<dyn SigningAuthority>,
     execution_semaphore: Arc<Semaphore>,
-    plan_receiver: Option<mpsc::UnboundedReceiver<TradingPlan>>,
+    /// Plans from the planner; taken when the execution loop starts
+    plan_receiver: tokio::sync::Mutex<Option<mpsc::UnboundedReceiver<TradingPlan>>>,
     execution_results: mpsc::UnboundedSender<ExecutionResult>,
+    events: EventPublisher,
     is_active: Arc<RwLock<bool>>,
     metrics: Arc<RwLock<ExecutionMetrics>>,
 }
@@ -40,8 +41,19 @@ pub struct ExecutionResult {
     pub transaction_signature: Option<String>,
     pub execution_time_ms: u64,
     pub actual_slippage_bps: Option<u16>,
+    /// Output tokens the vault actually received, read from post-trade balances
+    pub actual_output_amount: Option<u64>,
     pub error_message: Option<String>,
+    /// Lamports the fee payer spent on the transaction
     pub gas_used: Option<u64>,
+    /// Landing slot, fees and compute units of the plan's confirmed transactions
+    pub costs: TransactionCosts,
+    /// Per-leg fills when the plan was routed through more than one pool
+    pub route_legs: Vec<LegFill>,
+    /// Recovery attempt for tokens stranded by a failed route leg
+    pub rollback: Option<RollbackReport>,
+    /// Child order fills when the plan was executed in slices
+    pub slices: Vec<SliceFill>,
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
@@ -56,63 +68,74 @@ pub struct ExecutionMetrics {
 }
 
 impl Executor {
-
-    pub async fn start_with_receiver(&mut self, plan_receiver: mpsc::UnboundedReceiver<TradingPlan>) -> StdResult<(), AgentError> {
-        self.plan_receiver = Some(plan_receiver);
-        // You can add your execution loop logic here
-        Ok(())
-    }
+    /// Executor consuming `plan_receiver`; returns it with the receiver of its execution results
     pub fn new(
         icm_client: Arc<IcmProgramInstance>,
-        max_concurrent_executions: usize,
-    ) -> (Self, mpsc::UnboundedReceiver<TradingPlan>, mpsc::UnboundedReceiver<ExecutionResult>) {
-        let (plan_sender, plan_receiver) = mpsc::unbounded_channel();
+        data_fetcher: Arc<DataFetcher>,
+        db_pool: deadpool_postgres::Pool,
+        config: &TradingAgentConfig,
+        events: EventPublisher,
+        plan_receiver: mpsc::UnboundedReceiver<TradingPlan>,
+    ) -> (Self, mpsc::UnboundedReceiver<ExecutionResult>) {
         let (result_sender, result_receiver) = mpsc::unbounded_channel();
 
-        let http_client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .build()
-            .expect("Failed to create HTTP client");
+        let confirmation_tracker = ConfirmationTracker::new(
+            icm_client.rpc(),
+            Duration::from_millis(500),
+            Duration::from_secs(60),
+        );
+
+        let bucket_directory = Arc::new(BucketDirectory::new(db_pool.clone(), Arc::clone(&icm_client)));
+        let signer: Arc<dyn SigningAuthority> = Arc::new(SignerChain::with_delegated_key(db_pool, config.delegated_key.as_deref()));
+        let pool_resolver = Arc::new(RaydiumPoolResolver::from_config(icm_client.rpc(), &config.pool_resolver));
+        let router = Arc::new(SwapRouter::new(
+            Arc::clone(&icm_client),
+            Arc::clone(&data_fetcher),
+            pool_resolver,
+            confirmation_tracker,
+            config.route_mode,
+        ));
+        let sliced_executor = Arc::new(SlicedExecutor::new(Arc::clone(&router), data_fetcher, config.slicing.clone()));
 
         let executor = Self {
-            icm_client,
-            http_client,
-            execution_semaphore: Arc::new(Semaphore::new(max_concurrent_executions)),
-            plan_receiver: Some(plan_receiver),
+            router,
+            sliced_executor,
+            bucket_directory,
+            signer,
+            execution_semaphore: Arc::new(Semaphore::new(config.max_concurrent_executions)),
+            plan_receiver: tokio::sync::Mutex::new(Some(plan_receiver)),
             execution_results: result_sender,
+            events,
             is_active: Arc::new(RwLock::new(false)),
             metrics: Arc::new(RwLock::new(ExecutionMetrics::default())),
         };
 
-        // Return the plan_sender wrapped in a way that can be used by planner
-        let (plan_tx, plan_rx) = mpsc::unbounded_channel();
-        (executor, plan_rx, result_receiver)
+        (executor, result_receiver)
     }
 
     /// Start the execution loop
-    pub async fn start(&mut self) -> StdResult<(), AgentError> {
-        {
-            let mut is_active = self.is_active.write().await;
-            if *is_active {
-                return Ok(());
-            }
-            *is_active = true;
-        }
-
-        let mut plan_receiver = self.plan_receiver.take()
+    pub async fn start(&self) -> StdResult<(), AgentError> {
+        let mut plan_receiver = self.plan_receiver.lock().await.take()
             .ok_or_else(|| AgentError::Configuration("Executor already started".to_string()))?;
+        *self.is_active.write().await = true;
 
         info!("Starting executor");
 
         while *self.is_active.read().await {
             tokio::select! {
                 Some(plan) = plan_receiver.recv() => {
+                    if !*self.is_active.read().await {
+                        break;
+                    }
                     // Clone necessary data for async execution
                     let executor_clone = ExecutorHandle {
-                        icm_client: Arc::clone(&self.icm_client),
-                        http_client: self.http_client.clone(),
+                        router: Arc::clone(&self.router),
+                        sliced_executor: Arc::clone(&self.sliced_executor),
+                        bucket_directory: Arc::clone(&self.bucket_directory),
+                        signer: Arc::clone(&self.signer),
                         execution_semaphore: Arc::clone(&self.execution_semaphore),
                         result_sender: self.execution_results.clone(),
+                        events: self.events.clone(),
                         metrics: Arc::clone(&self.metrics),
                     };
 
@@ -121,6 +144,8 @@ impl Executor {
                         executor_clone.execute_plan(plan).await;
                     });
                 }
+                // The planner is gone
+                else => break,
             }
         }
 
@@ -159,12 +184,21 @@ impl Executor {
     }
 }
 
+/// How a plan's swap was carried out
+enum SwapOutcome {
+    Routed(RouteExecution),
+    Sliced(SlicedExecution),
+}
+
 /// Helper struct for executing plans concurrently
 struct ExecutorHandle {
-    icm_client: Arc<IcmProgramInstance>,
-    http_client: Client,
+    router: Arc<SwapRouter>,
+    sliced_executor: Arc<SlicedExecutor>,
+    bucket_directory: Arc<BucketDirectory>,
+    signer: Arc<dyn SigningAuthority>,
     execution_semaphore: Arc<Semaphore>,
     result_sender: mpsc::UnboundedSender<ExecutionResult>,
+    events: EventPublisher,
     metrics: Arc<RwLock<ExecutionMetrics>>,
 }
 
@@ -195,24 +229,21 @@ impl ExecutorHandle {
         }
 
         let result = match self.execute_swap(&plan).await {
-            Ok(tx_response) => ExecutionResult {
-                plan_id,
-                success: true,
-                transaction_signature: Some(tx_response.transaction),
-                execution_time_ms: start_time.elapsed().as_millis() as u64,
-                actual_slippage_bps: None, // Would be calculated from actual execution
-                error_message: None,
-                gas_used: Some(5000), // Placeholder - would get from transaction receipt
-                timestamp: Utc::now(),
-            },
+            Ok(SwapOutcome::Routed(route)) => Self::route_result(&plan, route, start_time),
+            Ok(SwapOutcome::Sliced(sliced)) => Self::sliced_result(&plan, sliced, start_time),
             Err(e) => ExecutionResult {
                 plan_id,
                 success: false,
                 transaction_signature: None,
                 execution_time_ms: start_time.elapsed().as_millis() as u64,
                 actual_slippage_bps: None,
+                actual_output_amount: None,
                 error_message: Some(e.to_string()),
                 gas_used: None,
+                costs: TransactionCosts::default(),
+                route_legs: Vec::new(),
+                rollback: None,
+                slices: Vec::new(),
                 timestamp: Utc::now(),
             },
         };
@@ -221,6 +252,7 @@ impl ExecutorHandle {
         self.update_metrics(&result).await;
 
         // Send result
+        self.events.publish(DomainEvent::TradeExecuted(ExecutionSummary::from(&result)));
         if let Err(e) = self.result_sender.send(result) {
             error!("Failed to send execution result: {}", e);
         }
@@ -228,89 +260,66 @@ impl ExecutorHandle {
         drop(permit);
     }
 
-    /// Execute the swap transaction
-    async fn execute_swap(&self, plan: &TradingPlan) -> StdResult<UnsignedTransactionResponse, AgentError> {
-        // Directly call the agent_swap_tokens_transaction method from IcmProgramInstance
-        // You may need to load the keypair and other arguments as needed
-        let keypair = read_keypair_file("/path/to/your/keypair.json")
-            .map_err(|e| AgentError::Configuration(format!("Failed to load keypair: {}", e)))?;
-
-        let swap_request = SwapTokensRequest {
-            bucket: plan.bucket_pubkey.to_string(),
-            input_mint: plan.input_mint.to_string(),
-            output_mint: plan.output_mint.to_string(),
-            in_amount: plan.input_amount,
-            quoted_out_amount: plan.min_output_amount,
-            slippage_bps: plan.max_slippage_bps,
-            // Raydium fields - these should be populated from plan or environment
-            amm: None, // TODO: Get from plan or configuration
-            amm_authority: None, // TODO: Get from plan or configuration
-            pool_coin_token_account: None, // TODO: Get from plan or configuration
-            pool_pc_token_account: None, // TODO: Get from plan or configuration
-        };
-
-        // Fetch bucket_name from the database using plan.bucket_pubkey
-        // This is a stub. Replace with actual DB fetch logic as needed.
-    let bucket_name = Self::fetch_bucket_name_by_pubkey(plan.bucket_pubkey)
-            .await
-            .map_err(|e| AgentError::Configuration(format!("Failed to fetch bucket name: {}", e)))?;
+    /// Turn a measured route into an execution result
+    fn route_result(plan: &TradingPlan, route: RouteExecution, start_time: Instant) -> ExecutionResult {
+        // Single-leg routes report their fill directly on the result
+        let route_legs = if route.legs.len() > 1 { route.legs } else { Vec::new() };
 
-        let input_mint = plan.input_mint;
-        let output_mint = plan.output_mint;
-        
-        // Raydium parameters
-        let raydium_amm_program = Pubkey::from_str(&std::env::var("RAYDIUM_AMM_PROGRAM").unwrap_or_else(|_| "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8".to_string())).expect("Invalid RAYDIUM_AMM_PROGRAM");
-        let amm = Pubkey::from_str(&std::env::var("DEFAULT_AMM").expect("DEFAULT_AMM env var required")).expect("Invalid DEFAULT_AMM");
-        let amm_authority = Pubkey::from_str(&std::env::var("DEFAULT_AMM_AUTHORITY").expect("DEFAULT_AMM_AUTHORITY env var required")).expect("Invalid DEFAULT_AMM_AUTHORITY");
-        let pool_coin_token_account = Pubkey::from_str(&std::env::var("DEFAULT_POOL_COIN_TOKEN_ACCOUNT").expect("DEFAULT_POOL_COIN_TOKEN_ACCOUNT env var required")).expect("Invalid DEFAULT_POOL_COIN_TOKEN_ACCOUNT");
-        let pool_pc_token_account = Pubkey::from_str(&std::env::var("DEFAULT_POOL_PC_TOKEN_ACCOUNT").expect("DEFAULT_POOL_PC_TOKEN_ACCOUNT env var required")).expect("Invalid DEFAULT_POOL_PC_TOKEN_ACCOUNT");
-        
-        // For user_authority, use the bucket PDA (same as in routes)
-        let creator = keypair.pubkey();
-        let (bucket_pda, _) = Pubkey::find_program_address(
-            &[b"bucket", bucket_name.as_bytes(), creator.as_ref()],
-            &crate::onchain_instance::instance::ICM_PROGRAM_ID,
-        );
-        let user_authority = bucket_pda;
-
-        let tx_response = self.icm_client.agent_swap_tokens_transaction(
-            swap_request,
-            keypair,
-            &bucket_name,
-            input_mint,
-            output_mint,
-            raydium_amm_program,
-            amm,
-            amm_authority,
-            pool_coin_token_account,
-            pool_pc_token_account,
-            user_authority,
-        ).await.map_err(|e| AgentError::TransactionFailed(format!("ICM swap failed: {}", e)))?;
-
-        Ok(tx_response)
+        ExecutionResult {
+            plan_id: plan.id,
+            success: route.success,
+            transaction_signature: route.signature,
+            execution_time_ms: start_time.elapsed().as_millis() as u64,
+            actual_slippage_bps: route.output_amount
+                .map(|out| slippage_bps(plan.quoted_output_amount, out)),
+            actual_output_amount: route.output_amount,
+            error_message: route.error,
+            gas_used: route.lamports_spent,
+            costs: route.costs,
+            route_legs,
+            rollback: route.rollback,
+            slices: Vec::new(),
+            timestamp: Utc::now(),
+        }
     }
 
-// Stub for fetching bucket_name from DB by pubkey
-pub async fn fetch_bucket_name_by_pubkey(_bucket_pubkey: Pubkey) -> StdResult<String, AgentError> {
-    // TODO: Implement actual DB lookup
-    Ok("bucket_name_from_db".to_string())
-}
-
-    // Jupiter API functions removed - now using Raydium direct integration
-    // TODO: Implement Raydium-specific routing and quote functions if needed
-    /*
-    /// Get swap instructions from Jupiter API (DEPRECATED - moved to Raydium)
-    async fn get_jupiter_swap_instructions(&self, plan: &TradingPlan) -> StdResult<Value, AgentError> {
-        // Function removed - using direct Raydium integration instead
-        Err(AgentError::Configuration("Jupiter integration removed - using Raydium".to_string()))
+    /// Summarize a sliced parent order, keeping its child fills alongside
+    fn sliced_result(plan: &TradingPlan, sliced: SlicedExecution, start_time: Instant) -> ExecutionResult {
+        let success = sliced.is_complete(plan);
+        // Compare against the plan's quoted output for the portion actually filled
+        let expected = (plan.quoted_output_amount as u128 * sliced.filled_input as u128)
+            .checked_div(plan.input_amount as u128)
+            .unwrap_or(0) as u64;
+        let filled = sliced.filled_input > 0;
+
+        ExecutionResult {
+            plan_id: plan.id,
+            success,
+            transaction_signature: sliced.slices.iter().rev().find_map(|s| s.signature.clone()),
+            execution_time_ms: start_time.elapsed().as_millis() as u64,
+            actual_slippage_bps: filled.then(|| slippage_bps(expected, sliced.filled_output)),
+            actual_output_amount: filled.then_some(sliced.filled_output),
+            error_message: sliced.aborted,
+            gas_used: Some(sliced.lamports_spent),
+            costs: sliced.costs,
+            route_legs: Vec::new(),
+            rollback: None,
+            slices: sliced.slices,
+            timestamp: Utc::now(),
+        }
     }
 
-    /// Decode route plan from binary format (DEPRECATED - moved to Raydium)
-    fn decode_route_plan(&self, encoded: &[u8]) -> StdResult<Value, AgentError> {
-        // Function removed - Raydium uses different routing mechanism
-        Err(AgentError::Configuration("Jupiter route plan deprecated - using Raydium".to_string()))
+    /// Route and execute the swap for a plan with the bucket's signing authority,
+    /// slicing large plans into child orders
+    async fn execute_swap(&self, plan: &TradingPlan) -> StdResult<SwapOutcome, AgentError> {
+        let bucket = self.bucket_directory.resolve(plan.bucket_pubkey).await?;
+        let keypair = self.signer.signer_for(&bucket).await?;
+
+        if self.sliced_executor.should_slice(plan) {
+            return Ok(SwapOutcome::Sliced(self.sliced_executor.execute(plan, &bucket, &keypair).await?));
+        }
+        Ok(SwapOutcome::Routed(self.router.execute(plan, &bucket, &keypair).await?))
     }
-    */
 
     /// Update execution metrics
     async fn update_metrics(&self, result: &ExecutionResult) {
@@ -352,13 +361,19 @@ pub async fn fetch_bucket_name_by_pubkey(_bucket_pubkey: Pubkey) -> StdResult<St
             transaction_signature: None,
             execution_time_ms: start_time.elapsed().as_millis() as u64,
             actual_slippage_bps: None,
+            actual_output_amount: None,
             error_message: Some(error),
             gas_used: None,
+            costs: TransactionCosts::default(),
+            route_legs: Vec::new(),
+            rollback: None,
+            slices: Vec::new(),
             timestamp: Utc::now(),
         };
 
         self.update_metrics(&result).await;
 
+        self.events.publish(DomainEvent::TradeExecuted(ExecutionSummary::from(&result)));
         if let Err(e) = self.result_sender.send(result) {
             error!("Failed to send failure result: {}", e);
         }
@@ -1,12 +1,23 @@
 pub mod agent_plan;
 pub mod types;
 pub mod data_fetcher;
+pub mod candles;
+pub mod oracle;
+pub mod circuit_breaker;
 pub mod strategy;
 pub mod planner;
+pub mod position_sizing;
 pub mod executor;
+pub mod confirmation;
+pub mod signer;
+pub mod pool_resolver;
+pub mod token_metadata;
+pub mod router;
+pub mod sliced_execution;
 pub mod observer;
 pub mod ai_client;
 pub mod trading_agent;
+pub mod registry;
 
 pub use trading_agent::TradingAgent;
 pub use types::*;
@@ -11,6 +11,7 @@ use crate::agent::types::{
     Position, PerformanceMetrics, AgentError, StrategyType,
 };
 use crate::agent::executor::ExecutionResult;
+use crate::services::event_bus::{DomainEvent, EventPublisher};
 use crate::database::models::{Portfolio, PortfolioAsset};
 // Jupiter price API endpoint
 const JUPITER_PRICE_API: &str = "https://price.jup.ag/v4/price";
@@ -28,12 +29,14 @@ struct JupiterTokenPrice {
 /// Observer monitors execution results and provides feedback for learning
 #[derive(Debug)]
 pub struct Observer {
-    execution_receiver: Option<mpsc::UnboundedReceiver<ExecutionResult>>,
+    /// Results from the executor; taken when the monitoring loop starts
+    execution_receiver: tokio::sync::Mutex<Option<mpsc::UnboundedReceiver<ExecutionResult>>>,
     performance_metrics: Arc<RwLock<PerformanceMetrics>>,
     active_positions: Arc<DashMap<String, Position>>,
     execution_history: Arc<RwLock<Vec<ExecutionResult>>>,
     learning_feedback: mpsc::UnboundedSender<LearningFeedback>,
     position_updates: mpsc::UnboundedSender<HashMap<String, Position>>,
+    events: EventPublisher,
     is_active: Arc<RwLock<bool>>,
     monitoring_interval: Duration,
     db_pool: deadpool_postgres::Pool,
@@ -97,28 +100,27 @@ impl Observer {
         prices
     }
 
-    pub async fn start_with_receiver(&mut self, execution_receiver: mpsc::UnboundedReceiver<ExecutionResult>) -> Result<(), AgentError> {
-        self.execution_receiver = Some(execution_receiver);
-        // You can add your monitoring loop logic here
-        Ok(())
-    }
+    /// Observer consuming the executor's results; returns it with the receivers of
+    /// its learning feedback and position updates
     pub fn new(
         monitoring_interval_ms: u64,
         db_pool: deadpool_postgres::Pool,
         data_fetcher: Arc<crate::agent::data_fetcher::DataFetcher>,
         portfolio_id: uuid::Uuid,
-    ) -> (Self, mpsc::UnboundedReceiver<ExecutionResult>, mpsc::UnboundedReceiver<LearningFeedback>, mpsc::UnboundedReceiver<HashMap<String, Position>>) {
-        let (exec_sender, exec_receiver) = mpsc::unbounded_channel();
+        events: EventPublisher,
+        execution_receiver: mpsc::UnboundedReceiver<ExecutionResult>,
+    ) -> (Self, mpsc::UnboundedReceiver<LearningFeedback>, mpsc::UnboundedReceiver<HashMap<String, Position>>) {
         let (feedback_sender, feedback_receiver) = mpsc::unbounded_channel();
         let (position_sender, position_receiver) = mpsc::unbounded_channel();
 
         let observer = Self {
-            execution_receiver: None, // We'll set this after creation
+            execution_receiver: tokio::sync::Mutex::new(Some(execution_receiver)),
             performance_metrics: Arc::new(RwLock::new(Self::default_performance_metrics())),
             active_positions: Arc::new(DashMap::new()),
             execution_history: Arc::new(RwLock::new(Vec::new())),
             learning_feedback: feedback_sender,
             position_updates: position_sender,
+            events,
             is_active: Arc::new(RwLock::new(false)),
             monitoring_interval: Duration::from_millis(monitoring_interval_ms),
             db_pool,
@@ -126,26 +128,14 @@ impl Observer {
             portfolio_id,
         };
 
-        (observer, exec_receiver, feedback_receiver, position_receiver)
-    }
-
-    /// Set the execution receiver (called after creation)
-    pub fn set_execution_receiver(&mut self, receiver: mpsc::UnboundedReceiver<ExecutionResult>) {
-        self.execution_receiver = Some(receiver);
+        (observer, feedback_receiver, position_receiver)
     }
 
     /// Start the observer monitoring loop
-    pub async fn start(&mut self) -> Result<(), AgentError> {
-        {
-            let mut is_active = self.is_active.write().await;
-            if *is_active {
-                return Ok(());
-            }
-            *is_active = true;
-        }
-
-        let mut execution_receiver = self.execution_receiver.take()
+    pub async fn start(&self) -> Result<(), AgentError> {
+        let mut execution_receiver = self.execution_receiver.lock().await.take()
             .ok_or_else(|| AgentError::Configuration("Observer already started".to_string()))?;
+        *self.is_active.write().await = true;
 
         info!("Starting observer");
 
@@ -205,7 +195,8 @@ impl Observer {
         let positions: HashMap<String, Position> = self.active_positions.iter()
             .map(|entry| (entry.key().clone(), entry.value().clone()))
             .collect();
-        
+
+        self.events.publish(DomainEvent::PositionsChanged(positions.clone()));
         if let Err(e) = self.position_updates.send(positions) {
             warn!("Failed to send position updates: {}", e);
         }
@@ -260,6 +251,7 @@ impl Observer {
                 bucket_pubkey: solana_sdk::pubkey::Pubkey::new_unique(),
                 token_mint: solana_sdk::pubkey::Pubkey::new_unique(),
                 amount: 1000000, // 1 token
+                decimals: 6,
                 entry_price: 100.0,
                 current_price: 100.0,
                 unrealized_pnl: 0.0,
@@ -450,13 +442,12 @@ impl Observer {
         // 3. Calculate total portfolio value
 
         for mut position in self.active_positions.iter_mut() {
-            // Simulate price updates
-            let price_change = (rand::random::<f64>() - 0.5) * 0.02; // ±1% random change
-            position.current_price *= 1.0 + price_change;
-            
-            // Calculate unrealized PnL
-            let price_change_pct = (position.current_price / position.entry_price) - 1.0;
-            position.unrealized_pnl = position.amount as f64 * position.entry_price * price_change_pct;
+            if let Some(price) = self.data_fetcher.get_cached_price(&position.token_mint.to_string()) {
+                position.current_price = price;
+            }
+
+            // PnL in USD on whole-token amounts
+            position.unrealized_pnl = position.unrealized_pnl_usd();
         }
     }
 
@@ -514,6 +505,11 @@ impl Observer {
         self.performance_metrics.read().await.clone()
     }
 
+    /// Get recorded execution results, including child fills of sliced plans
+    pub async fn get_execution_history(&self) -> Vec<ExecutionResult> {
+        self.execution_history.read().await.clone()
+    }
+
     /// Get current positions
     pub async fn get_positions(&self) -> HashMap<String, Position> {
         self.active_positions.iter()
@@ -0,0 +1,450 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use chrono::{DateTime, TimeZone, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use tracing::{debug, info, warn};
+use crate::agent::types::{pair_key, AgentError, QuoteData};
+use crate::onchain_instance::rpc::SolanaRpc;
+
+/// Legacy Pyth v2 price account magic number
+const PYTH_LEGACY_MAGIC: u32 = 0xa1b2c3d4;
+const LEGACY_EXPONENT_OFFSET: usize = 20;
+const LEGACY_TIMESTAMP_OFFSET: usize = 96;
+const LEGACY_AGG_PRICE_OFFSET: usize = 208;
+const LEGACY_AGG_CONF_OFFSET: usize = 216;
+const LEGACY_AGG_STATUS_OFFSET: usize = 224;
+const LEGACY_STATUS_TRADING: u32 = 1;
+
+/// `PriceUpdateV2` (Pyth receiver) starts its verification level after discriminator and write authority
+const PRICE_UPDATE_VERIFICATION_OFFSET: usize = 40;
+
+/// Float noise tolerated when comparing a divergence with the band
+const DIVERGENCE_TOLERANCE_BPS: f64 = 1e-6;
+
+/// Oracle cross-check settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OracleConfig {
+    /// Largest allowed gap between quote and oracle price
+    pub max_divergence_bps: u16,
+    /// Oracle prices older than this are ignored
+    pub max_staleness_secs: i64,
+    /// Block plans for pairs the oracle cannot price
+    pub require_oracle: bool,
+    /// JSON object of mint to Pyth price account
+    pub price_feeds_path: Option<PathBuf>,
+}
+
+impl Default for OracleConfig {
+    fn default() -> Self {
+        Self {
+            max_divergence_bps: 200,
+            max_staleness_secs: 60,
+            require_oracle: false,
+            price_feeds_path: None,
+        }
+    }
+}
+
+/// USD price of one whole token read from a price account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OraclePrice {
+    pub mint: String,
+    pub price_account: String,
+    pub price_usd: f64,
+    pub confidence_usd: f64,
+    pub publish_time: DateTime<Utc>,
+}
+
+/// Quote price compared against the oracle-implied price for the same pair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OracleCheck {
+    pub input_mint: String,
+    pub output_mint: String,
+    /// Output tokens per input token implied by the quote
+    pub quote_price: f64,
+    /// Output tokens per input token implied by the oracle
+    pub oracle_price: f64,
+    /// Positive when the quote pays more than the oracle implies
+    pub divergence_bps: f64,
+    pub within_band: bool,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Reads Pyth price accounts for configured mints and sanity-checks quotes against them
+pub struct PriceOracle {
+    rpc: Arc<SolanaRpc>,
+    feeds: HashMap<Pubkey, Pubkey>,
+    config: OracleConfig,
+    /// Latest check per `input_output` pair
+    checks: DashMap<String, OracleCheck>,
+}
+
+impl PriceOracle {
+    pub fn new(rpc: Arc<SolanaRpc>, feeds: HashMap<Pubkey, Pubkey>, config: OracleConfig) -> Self {
+        Self {
+            rpc,
+            feeds,
+            config,
+            checks: DashMap::new(),
+        }
+    }
+
+    /// Build an oracle reading the price feeds at `config.price_feeds_path`
+    pub fn from_config(rpc: Arc<SolanaRpc>, config: OracleConfig) -> Self {
+        let feeds = match &config.price_feeds_path {
+            Some(path) => match Self::load_feeds(path) {
+                Ok(feeds) => {
+                    info!("Loaded {} Pyth price feeds from {}", feeds.len(), path.display());
+                    feeds
+                }
+                Err(e) => {
+                    warn!("Failed to load Pyth price feeds {}: {}", path.display(), e);
+                    HashMap::new()
+                }
+            },
+            None => HashMap::new(),
+        };
+
+        Self::new(rpc, feeds, config)
+    }
+
+    /// Read a JSON object of mint to price account
+    pub fn load_feeds(path: &Path) -> Result<HashMap<Pubkey, Pubkey>, AgentError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| AgentError::Configuration(format!("Cannot read {}: {}", path.display(), e)))?;
+        let entries: HashMap<String, String> = serde_json::from_str(&contents)?;
+        entries.iter()
+            .map(|(mint, account)| Ok((Pubkey::from_str(mint)?, Pubkey::from_str(account)?)))
+            .collect()
+    }
+
+    /// Read the oracle price of a mint; `None` when no feed is configured
+    pub async fn price(&self, mint: &Pubkey) -> Result<Option<OraclePrice>, AgentError> {
+        let Some(price_account) = self.feeds.get(mint) else {
+            return Ok(None);
+        };
+
+        let data = self.rpc.call(|rpc| async move { rpc.get_account_data(price_account).await }).await
+            .map_err(|e| AgentError::StaleMarketData(format!("Failed to read price account {}: {}", price_account, e)))?;
+        let (price_usd, confidence_usd, publish_time) = decode_price_account(&data)
+            .ok_or_else(|| AgentError::StaleMarketData(format!("{} is not a usable Pyth price account", price_account)))?;
+
+        let age = Utc::now().timestamp() - publish_time;
+        if age > self.config.max_staleness_secs {
+            return Err(AgentError::StaleMarketData(format!(
+                "Oracle price for {} is {}s old (max {}s)", mint, age, self.config.max_staleness_secs
+            )));
+        }
+
+        Ok(Some(OraclePrice {
+            mint: mint.to_string(),
+            price_account: price_account.to_string(),
+            price_usd,
+            confidence_usd,
+            publish_time: Utc.timestamp_opt(publish_time, 0).single().unwrap_or_else(Utc::now),
+        }))
+    }
+
+    /// Compare a quote with the oracle and remember the result for its pair.
+    /// Returns `None` when either mint has no oracle price or the quote cannot be normalized;
+    /// any earlier check for the pair is then forgotten.
+    pub async fn check_quote(&self, quote: &QuoteData) -> Result<Option<OracleCheck>, AgentError> {
+        let key = pair_key(&quote.input_mint, &quote.output_mint);
+        let result = self.compare_quote(quote).await;
+        match &result {
+            Ok(Some(check)) => {
+                self.checks.insert(key, check.clone());
+            }
+            _ => {
+                self.checks.remove(&key);
+            }
+        }
+        result
+    }
+
+    async fn compare_quote(&self, quote: &QuoteData) -> Result<Option<OracleCheck>, AgentError> {
+        let Some(quote_price) = quote.normalized_price().map(|p| p.price) else {
+            return Ok(None);
+        };
+        let input = self.price(&Pubkey::from_str(&quote.input_mint)?).await?;
+        let output = self.price(&Pubkey::from_str(&quote.output_mint)?).await?;
+        let (Some(input), Some(output)) = (input, output) else {
+            return Ok(None);
+        };
+
+        Ok(self.compare_prices(quote, quote_price, &input, &output))
+    }
+
+    /// Check `quote_price` against the price the two oracle prices imply
+    fn compare_prices(&self, quote: &QuoteData, quote_price: f64, input: &OraclePrice, output: &OraclePrice) -> Option<OracleCheck> {
+        if output.price_usd <= 0.0 {
+            return None;
+        }
+
+        let oracle_price = input.price_usd / output.price_usd;
+        let divergence_bps = (quote_price / oracle_price - 1.0) * 10_000.0;
+        let check = OracleCheck {
+            input_mint: quote.input_mint.clone(),
+            output_mint: quote.output_mint.clone(),
+            quote_price,
+            oracle_price,
+            divergence_bps,
+            within_band: divergence_bps.abs() <= self.config.max_divergence_bps as f64 + DIVERGENCE_TOLERANCE_BPS,
+            checked_at: Utc::now(),
+        };
+
+        debug!(
+            "[check_quote] {}/{} quote {:.6} vs oracle {:.6} ({:+.1} bps)",
+            quote.input_mint, quote.output_mint, quote_price, oracle_price, divergence_bps
+        );
+        Some(check)
+    }
+
+    /// Latest check recorded for a pair
+    pub fn latest_check(&self, input_mint: &str, output_mint: &str) -> Option<OracleCheck> {
+        self.checks.get(&pair_key(input_mint, output_mint)).map(|c| c.clone())
+    }
+
+    /// Reject a pair whose latest quote sits outside the oracle band, or that
+    /// has no oracle check when one is required
+    pub fn gate(&self, input_mint: &str, output_mint: &str) -> Result<(), AgentError> {
+        match self.latest_check(input_mint, output_mint) {
+            Some(check) if !check.within_band => Err(AgentError::RiskLimitExceeded(format!(
+                "Quote for {}/{} is {:+.1} bps from the oracle (band {} bps)",
+                input_mint, output_mint, check.divergence_bps, self.config.max_divergence_bps
+            ))),
+            Some(_) => Ok(()),
+            None if self.config.require_oracle => Err(AgentError::StaleMarketData(format!(
+                "No oracle price to verify {}/{}", input_mint, output_mint
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::fmt::Debug for PriceOracle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PriceOracle")
+            .field("feeds", &self.feeds.len())
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+fn read_bytes<const N: usize>(data: &[u8], offset: usize) -> Option<[u8; N]> {
+    data.get(offset..offset + N).and_then(|bytes| bytes.try_into().ok())
+}
+
+/// Decode price, confidence (both in USD) and publish time from either a legacy
+/// Pyth price account or a Pyth receiver `PriceUpdateV2` account
+fn decode_price_account(data: &[u8]) -> Option<(f64, f64, i64)> {
+    let scale = |value: f64, exponent: i32| value * 10f64.powi(exponent);
+
+    if read_bytes::<4>(data, 0).map(u32::from_le_bytes) == Some(PYTH_LEGACY_MAGIC) {
+        let exponent = i32::from_le_bytes(read_bytes(data, LEGACY_EXPONENT_OFFSET)?);
+        let timestamp = i64::from_le_bytes(read_bytes(data, LEGACY_TIMESTAMP_OFFSET)?);
+        let price = i64::from_le_bytes(read_bytes(data, LEGACY_AGG_PRICE_OFFSET)?);
+        let conf = u64::from_le_bytes(read_bytes(data, LEGACY_AGG_CONF_OFFSET)?);
+        let status = u32::from_le_bytes(read_bytes(data, LEGACY_AGG_STATUS_OFFSET)?);
+        if status != LEGACY_STATUS_TRADING || price <= 0 {
+            return None;
+        }
+        return Some((scale(price as f64, exponent), scale(conf as f64, exponent), timestamp));
+    }
+
+    // PriceUpdateV2: verification level is `Partial { num_signatures: u8 }` (2 bytes) or `Full` (1 byte)
+    let message_offset = match data.get(PRICE_UPDATE_VERIFICATION_OFFSET)? {
+        0 => PRICE_UPDATE_VERIFICATION_OFFSET + 2,
+        1 => PRICE_UPDATE_VERIFICATION_OFFSET + 1,
+        _ => return None,
+    };
+    // feed_id [32], price i64, conf u64, exponent i32, publish_time i64
+    let price_offset = message_offset + 32;
+    let price = i64::from_le_bytes(read_bytes(data, price_offset)?);
+    let conf = u64::from_le_bytes(read_bytes(data, price_offset + 8)?);
+    let exponent = i32::from_le_bytes(read_bytes(data, price_offset + 16)?);
+    let publish_time = i64::from_le_bytes(read_bytes(data, price_offset + 20)?);
+    if price <= 0 {
+        return None;
+    }
+    Some((scale(price as f64, exponent), scale(conf as f64, exponent), publish_time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::onchain_instance::rpc::RpcConfig;
+
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    fn approx(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    fn put<const N: usize>(data: &mut [u8], offset: usize, bytes: [u8; N]) {
+        data[offset..offset + N].copy_from_slice(&bytes);
+    }
+
+    fn legacy_account(price: i64, conf: u64, exponent: i32, timestamp: i64, status: u32) -> Vec<u8> {
+        let mut data = vec![0u8; 240];
+        put(&mut data, 0, PYTH_LEGACY_MAGIC.to_le_bytes());
+        put(&mut data, LEGACY_EXPONENT_OFFSET, exponent.to_le_bytes());
+        put(&mut data, LEGACY_TIMESTAMP_OFFSET, timestamp.to_le_bytes());
+        put(&mut data, LEGACY_AGG_PRICE_OFFSET, price.to_le_bytes());
+        put(&mut data, LEGACY_AGG_CONF_OFFSET, conf.to_le_bytes());
+        put(&mut data, LEGACY_AGG_STATUS_OFFSET, status.to_le_bytes());
+        data
+    }
+
+    /// `verification` is the Borsh-encoded verification level
+    fn price_update_account(verification: &[u8], price: i64, conf: u64, exponent: i32, publish_time: i64) -> Vec<u8> {
+        let mut data = vec![0u8; PRICE_UPDATE_VERIFICATION_OFFSET];
+        data.extend_from_slice(verification);
+        data.extend_from_slice(&[7u8; 32]);
+        data.extend_from_slice(&price.to_le_bytes());
+        data.extend_from_slice(&conf.to_le_bytes());
+        data.extend_from_slice(&exponent.to_le_bytes());
+        data.extend_from_slice(&publish_time.to_le_bytes());
+        // prev_publish_time, ema_price, ema_conf, posted_slot
+        data.extend_from_slice(&[0u8; 32]);
+        data
+    }
+
+    fn oracle(urls: Vec<String>, config: OracleConfig) -> PriceOracle {
+        let rpc = Arc::new(SolanaRpc::new(RpcConfig {
+            urls,
+            request_timeout: std::time::Duration::from_secs(2),
+            ..RpcConfig::default()
+        }));
+        let feeds = HashMap::from([
+            (Pubkey::from_str(SOL).unwrap(), Pubkey::new_unique()),
+            (Pubkey::from_str(USDC).unwrap(), Pubkey::new_unique()),
+        ]);
+        PriceOracle::new(rpc, feeds, config)
+    }
+
+    fn quote() -> QuoteData {
+        QuoteData {
+            input_mint: SOL.to_string(),
+            output_mint: USDC.to_string(),
+            input_amount: 1_000_000_000,
+            output_amount: 150_000_000,
+            other_amount_threshold: 0,
+            swap_mode: "ExactIn".to_string(),
+            slippage_bps: 50,
+            platform_fee_bps: 0,
+            price_impact_pct: 0.0,
+            route_plan: Vec::new(),
+            timestamp: Utc::now(),
+            input_decimals: Some(9),
+            output_decimals: Some(6),
+            input_price_usd: None,
+            output_price_usd: None,
+        }
+    }
+
+    fn oracle_price(mint: &str, price_usd: f64) -> OraclePrice {
+        OraclePrice {
+            mint: mint.to_string(),
+            price_account: Pubkey::new_unique().to_string(),
+            price_usd,
+            confidence_usd: 0.0,
+            publish_time: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn decodes_legacy_price_account() {
+        let data = legacy_account(15_012_345_678, 2_500_000, -8, 1_700_000_000, LEGACY_STATUS_TRADING);
+        let (price, conf, publish_time) = decode_price_account(&data).unwrap();
+        approx(price, 150.12345678);
+        approx(conf, 0.025);
+        assert_eq!(publish_time, 1_700_000_000);
+    }
+
+    #[test]
+    fn rejects_legacy_price_that_is_not_trading() {
+        let data = legacy_account(15_012_345_678, 2_500_000, -8, 1_700_000_000, 0);
+        assert!(decode_price_account(&data).is_none());
+        let data = legacy_account(0, 2_500_000, -8, 1_700_000_000, LEGACY_STATUS_TRADING);
+        assert!(decode_price_account(&data).is_none());
+    }
+
+    #[test]
+    fn decodes_fully_verified_price_update() {
+        let data = price_update_account(&[1], 99_980_000, 12_000, -8, 1_700_000_123);
+        let (price, conf, publish_time) = decode_price_account(&data).unwrap();
+        approx(price, 0.9998);
+        approx(conf, 0.00012);
+        assert_eq!(publish_time, 1_700_000_123);
+    }
+
+    #[test]
+    fn decodes_partially_verified_price_update() {
+        let data = price_update_account(&[0, 3], 15_000_000, 1_000, -5, 1_700_000_456);
+        let (price, conf, publish_time) = decode_price_account(&data).unwrap();
+        approx(price, 150.0);
+        approx(conf, 0.01);
+        assert_eq!(publish_time, 1_700_000_456);
+    }
+
+    #[test]
+    fn rejects_truncated_or_unknown_accounts() {
+        let data = price_update_account(&[1], 99_980_000, 12_000, -8, 1_700_000_123);
+        assert!(decode_price_account(&data[..60]).is_none());
+        let data = price_update_account(&[2], 99_980_000, 12_000, -8, 1_700_000_123);
+        assert!(decode_price_account(&data).is_none());
+    }
+
+    #[test]
+    fn divergence_at_the_band_edge_is_allowed() {
+        let oracle = oracle(Vec::new(), OracleConfig { max_divergence_bps: 200, ..OracleConfig::default() });
+        let (input, output) = (oracle_price(SOL, 1.0), oracle_price(USDC, 1.0));
+
+        let at_edge = oracle.compare_prices(&quote(), 1.02, &input, &output).unwrap();
+        assert!(at_edge.within_band, "{} bps", at_edge.divergence_bps);
+        let below_edge = oracle.compare_prices(&quote(), 0.98, &input, &output).unwrap();
+        assert!(below_edge.within_band, "{} bps", below_edge.divergence_bps);
+
+        let past_edge = oracle.compare_prices(&quote(), 1.0201, &input, &output).unwrap();
+        assert!(!past_edge.within_band);
+    }
+
+    #[test]
+    fn oracle_price_is_the_ratio_of_usd_prices() {
+        let oracle = oracle(Vec::new(), OracleConfig::default());
+        let check = oracle.compare_prices(&quote(), 150.0, &oracle_price(SOL, 150.0), &oracle_price(USDC, 1.0)).unwrap();
+        approx(check.oracle_price, 150.0);
+        approx(check.divergence_bps, 0.0);
+        assert!(oracle.compare_prices(&quote(), 150.0, &oracle_price(SOL, 150.0), &oracle_price(USDC, 0.0)).is_none());
+    }
+
+    #[tokio::test]
+    async fn rpc_failure_blocks_when_the_oracle_is_required() {
+        let config = OracleConfig { require_oracle: true, ..OracleConfig::default() };
+        let oracle = oracle(vec!["http://127.0.0.1:1".to_string()], config);
+        let quote = quote();
+
+        // A passing check from before the outage must not keep the pair open
+        let input = oracle_price(SOL, 150.0);
+        let output = oracle_price(USDC, 1.0);
+        let earlier = oracle.compare_prices(&quote, 150.0, &input, &output).unwrap();
+        oracle.checks.insert(pair_key(SOL, USDC), earlier);
+
+        assert!(oracle.check_quote(&quote).await.is_err());
+        assert!(matches!(oracle.gate(SOL, USDC), Err(AgentError::StaleMarketData(_))));
+    }
+
+    #[tokio::test]
+    async fn rpc_failure_does_not_block_when_the_oracle_is_optional() {
+        let oracle = oracle(vec!["http://127.0.0.1:1".to_string()], OracleConfig::default());
+        assert!(oracle.check_quote(&quote()).await.is_err());
+        assert!(oracle.gate(SOL, USDC).is_ok());
+    }
+}
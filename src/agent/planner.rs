@@ -11,14 +11,29 @@ use crate::agent::types::{
 };
 use crate::agent::strategy::{Strategy, StrategyFactory};
 use crate::agent::ai_client::AIClient;
+use crate::agent::position_sizing::{PositionSizer, SizingMode};
+use crate::agent::candles::{CandleAggregator, CandleInterval};
+use crate::agent::oracle::PriceOracle;
+use crate::agent::circuit_breaker::{CircuitBreakers, TrippedBreaker};
+use crate::services::event_bus::{DomainEvent, EventPublisher, RiskRejection, StopLossTriggered};
+
+/// Candles used for ATR, realized volatility and the slow EMA
+const INDICATOR_PERIOD: usize = 14;
 
 /// The planner evaluates market data and generates trading plans
 pub struct Planner {
     strategies: HashMap<StrategyType, Box<dyn Strategy>>,
     ai_client: AIClient,
+    sizer: Arc<PositionSizer>,
+    candles: Arc<CandleAggregator>,
+    oracle: Arc<PriceOracle>,
+    circuit_breakers: Arc<CircuitBreakers>,
+    events: EventPublisher,
     plan_queue: mpsc::UnboundedSender<TradingPlan>,
     market_conditions: Arc<RwLock<MarketConditions>>,
     current_positions: Arc<RwLock<HashMap<String, Position>>>,
+    /// Positions already reported past their stop-loss
+    stopped_out: Arc<RwLock<std::collections::HashSet<String>>>,
     strategy_configs: HashMap<StrategyType, StrategyConfig>,
     evaluation_interval: Duration,
     is_active: Arc<RwLock<bool>>,
@@ -29,6 +44,11 @@ impl Planner {
     ai_client: AIClient,
         strategy_configs: Vec<StrategyConfig>,
         evaluation_interval_ms: u64,
+        sizer: Arc<PositionSizer>,
+        candles: Arc<CandleAggregator>,
+        oracle: Arc<PriceOracle>,
+        circuit_breakers: Arc<CircuitBreakers>,
+        events: EventPublisher,
     ) -> (Self, mpsc::UnboundedReceiver<TradingPlan>) {
         let (plan_sender, plan_receiver) = mpsc::unbounded_channel();
         
@@ -45,9 +65,15 @@ impl Planner {
         let planner = Self {
             strategies,
             ai_client,
+            sizer,
+            candles,
+            oracle,
+            circuit_breakers,
+            events,
             plan_queue: plan_sender,
             market_conditions: Arc::new(RwLock::new(Self::default_market_conditions())),
             current_positions: Arc::new(RwLock::new(HashMap::new())),
+            stopped_out: Arc::new(RwLock::new(std::collections::HashSet::new())),
             strategy_configs: configs_map,
             evaluation_interval: Duration::from_millis(evaluation_interval_ms),
             is_active: Arc::new(RwLock::new(false)),
@@ -80,7 +106,25 @@ impl Planner {
                 // Process incoming quotes
                 Some(quote) = quote_receiver.recv() => {
                     debug!("Received quote for {}/{}", quote.input_mint, quote.output_mint);
+
+                    // Bad quotes never reach candles, market conditions or strategies
+                    if let Err(reason) = self.circuit_breakers.record_quote(&quote) {
+                        warn!("Dropping quote for {}/{}: {}", quote.input_mint, quote.output_mint, reason);
+                        self.events.publish(DomainEvent::RiskRejected(RiskRejection {
+                            plan_id: None,
+                            input_mint: quote.input_mint.clone(),
+                            output_mint: quote.output_mint.clone(),
+                            reason: reason.to_string(),
+                        }));
+                        continue;
+                    }
+                    self.events.publish(DomainEvent::QuoteReceived(Box::new(quote.clone())));
                     
+                    self.candles.ingest(&quote).await;
+                    if let Err(e) = self.oracle.check_quote(&quote).await {
+                        warn!("Oracle check failed for {}/{}: {}", quote.input_mint, quote.output_mint, e);
+                    }
+
                     // Store recent quotes for analysis
                     recent_quotes.push(quote.clone());
                     if recent_quotes.len() > max_recent_quotes {
@@ -98,6 +142,9 @@ impl Planner {
 
                 // Periodic comprehensive evaluation
                 _ = evaluation_timer.tick() => {
+                    if let Err(e) = self.candles.flush().await {
+                        warn!("Failed to flush candles: {}", e);
+                    }
                     if !recent_quotes.is_empty() {
                         self.perform_comprehensive_evaluation(&recent_quotes).await;
                     }
@@ -118,11 +165,45 @@ impl Planner {
 
     /// Update current positions (called by executor/observer)
     pub async fn update_positions(&self, positions: HashMap<String, Position>) {
+        self.check_stop_losses(&positions).await;
         let mut current_positions = self.current_positions.write().await;
         *current_positions = positions;
         info!("Updated {} positions", current_positions.len());
     }
 
+    /// Report positions that fell past the tightest configured stop-loss, once each
+    async fn check_stop_losses(&self, positions: &HashMap<String, Position>) {
+        let Some(stop_loss_pct) = self.strategy_configs.values()
+            .map(|config| config.risk_limits.stop_loss_pct)
+            .filter(|pct| *pct > 0.0)
+            .min_by(|a, b| a.total_cmp(b))
+        else {
+            return;
+        };
+
+        let mut stopped_out = self.stopped_out.write().await;
+        stopped_out.retain(|key| positions.contains_key(key));
+        for (key, position) in positions {
+            if position.entry_price <= 0.0 || stopped_out.contains(key) {
+                continue;
+            }
+            let loss_pct = (position.entry_price - position.current_price) / position.entry_price * 100.0;
+            if loss_pct < stop_loss_pct {
+                continue;
+            }
+            warn!("Position {} in {} is down {:.2}%, past the {:.2}% stop-loss", key, position.token_mint, loss_pct, stop_loss_pct);
+            self.events.publish(DomainEvent::StopLossTriggered(StopLossTriggered {
+                bucket: position.bucket_pubkey.to_string(),
+                token_mint: position.token_mint.to_string(),
+                entry_price: position.entry_price,
+                current_price: position.current_price,
+                loss_pct,
+                stop_loss_pct,
+            }));
+            stopped_out.insert(key.clone());
+        }
+    }
+
     /// Add or update strategy configuration
     pub async fn update_strategy_config(&self, config: StrategyConfig) -> Result<(), AgentError> {
         // Validate configuration
@@ -136,6 +217,9 @@ impl Planner {
 
     /// Evaluate time-sensitive strategies (arbitrage, scalping)
     async fn evaluate_time_sensitive_strategies(&self, quote: &QuoteData) {
+        if !self.pair_allowed(quote) {
+            return;
+        }
         let market_conditions = self.market_conditions.read().await;
         let positions = self.current_positions.read().await;
 
@@ -143,11 +227,15 @@ impl Planner {
         for (strategy_type, strategy) in &self.strategies {
             if matches!(strategy_type, StrategyType::Arbitrage) {
                 if let Some(config) = self.strategy_configs.get(strategy_type) {
-                    match strategy.evaluate(quote, &market_conditions, &positions, config).await {
+                    match strategy.evaluate(quote, &market_conditions, &positions, config, &self.sizer).await {
                         Ok(Some(plan)) => {
                             info!("Time-sensitive plan generated: {:?} with confidence {}", 
                                   plan.strategy_type, plan.confidence_score);
                             
+                            if !self.oracle_allows(&plan) {
+                                continue;
+                            }
+                            self.events.publish(DomainEvent::PlanCreated(Box::new(plan.clone())));
                             if let Err(e) = self.plan_queue.send(plan) {
                                 error!("Failed to send plan to queue: {}", e);
                             }
@@ -221,7 +309,10 @@ impl Planner {
             ) {
                 // Evaluate strategy for most recent quotes
                 for quote in recent_quotes.iter().rev().take(5) {
-                    match strategy.evaluate(quote, &market_conditions, &positions, config).await {
+                    if !self.pair_allowed(quote) {
+                        continue;
+                    }
+                    match strategy.evaluate(quote, &market_conditions, &positions, config, &self.sizer).await {
                         Ok(Some(mut plan)) => {
                             // Enhance plan with AI insights
                             plan.execution_context.ai_reasoning = format!(
@@ -237,6 +328,10 @@ impl Planner {
                             info!("Generated {} plan with enhanced confidence {:.2}", 
                                   strategy_type.to_string(), plan.confidence_score);
 
+                            if !self.oracle_allows(&plan) {
+                                break;
+                            }
+                            self.events.publish(DomainEvent::PlanCreated(Box::new(plan.clone())));
                             if let Err(e) = self.plan_queue.send(plan) {
                                 error!("Failed to send enhanced plan: {}", e);
                             }
@@ -262,10 +357,17 @@ impl Planner {
         for (strategy_type, strategy) in &self.strategies {
             if let Some(config) = self.strategy_configs.get(strategy_type) {
                 for quote in recent_quotes.iter().rev().take(3) {
-                    match strategy.evaluate(quote, &market_conditions, &positions, config).await {
+                    if !self.pair_allowed(quote) {
+                        continue;
+                    }
+                    match strategy.evaluate(quote, &market_conditions, &positions, config, &self.sizer).await {
                         Ok(Some(plan)) => {
                             info!("Standard plan generated: {:?}", strategy_type);
                             
+                            if !self.oracle_allows(&plan) {
+                                break;
+                            }
+                            self.events.publish(DomainEvent::PlanCreated(Box::new(plan.clone())));
                             if let Err(e) = self.plan_queue.send(plan) {
                                 error!("Failed to send standard plan: {}", e);
                             }
@@ -282,6 +384,34 @@ impl Planner {
         }
     }
 
+    /// Skip pairs whose circuit breaker is open
+    fn pair_allowed(&self, quote: &QuoteData) -> bool {
+        match self.circuit_breakers.check(&quote.input_mint, &quote.output_mint) {
+            Ok(()) => true,
+            Err(e) => {
+                debug!("Skipping {}/{}: {}", quote.input_mint, quote.output_mint, e);
+                false
+            }
+        }
+    }
+
+    /// Block plans whose pair is quoted outside the oracle band
+    fn oracle_allows(&self, plan: &TradingPlan) -> bool {
+        match self.oracle.gate(&plan.input_mint.to_string(), &plan.output_mint.to_string()) {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("Blocked plan {}: {}", plan.id, e);
+                self.events.publish(DomainEvent::RiskRejected(RiskRejection {
+                    plan_id: Some(plan.id),
+                    input_mint: plan.input_mint.to_string(),
+                    output_mint: plan.output_mint.to_string(),
+                    reason: e.to_string(),
+                }));
+                false
+            }
+        }
+    }
+
     /// Calculate strategy priority score based on AI response
     fn calculate_strategy_priority(
         &self,
@@ -310,14 +440,19 @@ impl Planner {
 
         // Calculate volatility from recent quotes
         let prices: Vec<f64> = recent_quotes.iter()
-            .map(|q| if q.output_amount > 0 && q.input_amount > 0 {
-                q.output_amount as f64 / q.input_amount as f64
-            } else {
-                1.0
-            })
+            .map(|q| q.normalized_price().map(|p| p.price).unwrap_or(1.0))
             .collect();
 
-        let volatility = if prices.len() > 1 {
+        // Prefer realized volatility from 5m candles, then 1m candles, of the latest pair
+        let latest = &recent_quotes[recent_quotes.len() - 1];
+        let indicators = [CandleInterval::FiveMinutes, CandleInterval::OneMinute]
+            .into_iter()
+            .filter_map(|interval| self.candles.indicators(&latest.input_mint, &latest.output_mint, interval, INDICATOR_PERIOD))
+            .find(|indicators| indicators.realized_volatility_24h.is_some());
+
+        let volatility = if let Some(realized) = indicators.as_ref().and_then(|i| i.realized_volatility_24h) {
+            realized
+        } else if prices.len() > 1 {
             let mean = prices.iter().sum::<f64>() / prices.len() as f64;
             let variance = prices.iter()
                 .map(|price| (price - mean).powi(2))
@@ -361,6 +496,10 @@ impl Planner {
             volume_24h,
             price_trend: price_trend.clone(),
             liquidity_score,
+            indicators,
+            oracle_divergence_bps: self.oracle
+                .latest_check(&latest.input_mint, &latest.output_mint)
+                .map(|check| check.divergence_bps),
         };
 
         let mut market_conditions = self.market_conditions.write().await;
@@ -379,6 +518,8 @@ impl Planner {
             volume_24h: 1_000_000.0,
             price_trend: crate::agent::types::PriceTrend::Sideways,
             liquidity_score: 0.5,
+            indicators: None,
+            oracle_divergence_bps: None,
         }
     }
 
@@ -410,6 +551,7 @@ impl Planner {
                 retry_attempts: 3,
                 jito_tip_lamports: 10_000,
             },
+            position_sizing: SizingMode::default(),
         }
     }
 
@@ -439,6 +581,7 @@ impl Planner {
             active_strategies: self.strategies.len(),
             current_positions: self.current_positions.read().await.len(),
             market_conditions: self.market_conditions.read().await.clone(),
+            tripped_breakers: self.circuit_breakers.tripped(),
         }
     }
 }
@@ -462,4 +605,5 @@ pub struct PlannerStats {
     pub active_strategies: usize,
     pub current_positions: usize,
     pub market_conditions: MarketConditions,
+    pub tripped_breakers: Vec<TrippedBreaker>,
 }
@@ -0,0 +1,324 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use anchor_client::solana_account_decoder::UiAccountEncoding;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+use crate::agent::types::AgentError;
+use crate::onchain_instance::rpc::SolanaRpc;
+
+pub const RAYDIUM_AMM_V4_PROGRAM: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
+
+/// Raydium AMM v4 `AmmInfo` layout (752 bytes)
+const AMM_INFO_LEN: u64 = 752;
+const NONCE_OFFSET: usize = 8;
+const COIN_VAULT_OFFSET: usize = 336;
+const PC_VAULT_OFFSET: usize = 368;
+const COIN_MINT_OFFSET: usize = 400;
+const PC_MINT_OFFSET: usize = 432;
+const LP_AMOUNT_OFFSET: usize = 720;
+const AMM_AUTHORITY_SEED: &[u8] = b"amm authority";
+
+/// Accounts the ICM `swap_tokens` instruction needs to route through a Raydium pool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RaydiumPoolAccounts {
+    pub amm: String,
+    pub amm_authority: String,
+    pub pool_coin_token_account: String,
+    pub pool_pc_token_account: String,
+    pub coin_mint: String,
+    pub pc_mint: String,
+}
+
+/// Parsed form of [`RaydiumPoolAccounts`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RaydiumPool {
+    pub amm: Pubkey,
+    pub amm_authority: Pubkey,
+    pub pool_coin_token_account: Pubkey,
+    pub pool_pc_token_account: Pubkey,
+    pub coin_mint: Pubkey,
+    pub pc_mint: Pubkey,
+}
+
+impl TryFrom<&RaydiumPoolAccounts> for RaydiumPool {
+    type Error = AgentError;
+
+    fn try_from(entry: &RaydiumPoolAccounts) -> Result<Self, Self::Error> {
+        Ok(Self {
+            amm: Pubkey::from_str(&entry.amm)?,
+            amm_authority: Pubkey::from_str(&entry.amm_authority)?,
+            pool_coin_token_account: Pubkey::from_str(&entry.pool_coin_token_account)?,
+            pool_pc_token_account: Pubkey::from_str(&entry.pool_pc_token_account)?,
+            coin_mint: Pubkey::from_str(&entry.coin_mint)?,
+            pc_mint: Pubkey::from_str(&entry.pc_mint)?,
+        })
+    }
+}
+
+/// Where the resolver looks for Raydium pools
+#[derive(Debug, Clone)]
+pub struct PoolResolverConfig {
+    pub program_id: Pubkey,
+    /// JSON array of [`RaydiumPoolAccounts`]
+    pub registry_path: Option<PathBuf>,
+    /// Pool whose accounts are read before any program scan
+    pub seed_amm: Option<Pubkey>,
+}
+
+impl Default for PoolResolverConfig {
+    fn default() -> Self {
+        Self {
+            program_id: Pubkey::from_str(RAYDIUM_AMM_V4_PROGRAM).expect("valid Raydium program id"),
+            registry_path: None,
+            seed_amm: None,
+        }
+    }
+}
+
+/// Finds and caches Raydium AMM accounts for any mint pair.
+///
+/// Lookups check the cache, then the configured registry, then scan the
+/// Raydium program accounts by coin/pc mint.
+pub struct RaydiumPoolResolver {
+    rpc: Arc<SolanaRpc>,
+    program_id: Pubkey,
+    registry: HashMap<(Pubkey, Pubkey), RaydiumPool>,
+    seed_amms: Mutex<Vec<Pubkey>>,
+    cache: DashMap<(Pubkey, Pubkey), RaydiumPool>,
+}
+
+impl RaydiumPoolResolver {
+    pub fn new(rpc: Arc<SolanaRpc>, program_id: Pubkey, registry: Vec<RaydiumPool>, seed_amms: Vec<Pubkey>) -> Self {
+        let registry = registry.into_iter()
+            .map(|pool| (pair_key(pool.coin_mint, pool.pc_mint), pool))
+            .collect();
+
+        Self {
+            rpc,
+            program_id,
+            registry,
+            seed_amms: Mutex::new(seed_amms),
+            cache: DashMap::new(),
+        }
+    }
+
+    /// Build a resolver from the server's pool settings
+    pub fn from_config(rpc: Arc<SolanaRpc>, config: &PoolResolverConfig) -> Self {
+        let registry = match &config.registry_path {
+            Some(path) => match Self::load_registry(path) {
+                Ok(pools) => {
+                    info!("Loaded {} Raydium pools from {}", pools.len(), path.display());
+                    pools
+                }
+                Err(e) => {
+                    warn!("Failed to load Raydium pool registry {}: {}", path.display(), e);
+                    Vec::new()
+                }
+            },
+            None => Vec::new(),
+        };
+
+        Self::new(rpc, config.program_id, registry, config.seed_amm.into_iter().collect())
+    }
+
+    /// Read a JSON array of [`RaydiumPoolAccounts`]
+    pub fn load_registry(path: &Path) -> Result<Vec<RaydiumPool>, AgentError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| AgentError::Configuration(format!("Cannot read {}: {}", path.display(), e)))?;
+        let entries: Vec<RaydiumPoolAccounts> = serde_json::from_str(&contents)?;
+        entries.iter().map(RaydiumPool::try_from).collect()
+    }
+
+    pub fn program_id(&self) -> Pubkey {
+        self.program_id
+    }
+
+    /// Resolve the pool trading `mint_a` against `mint_b`, in either direction
+    pub async fn resolve(&self, mint_a: Pubkey, mint_b: Pubkey) -> Result<RaydiumPool, AgentError> {
+        let key = pair_key(mint_a, mint_b);
+
+        if let Some(pool) = self.cache.get(&key) {
+            return Ok(*pool);
+        }
+
+        if let Some(pool) = self.registry.get(&key) {
+            self.cache.insert(key, *pool);
+            return Ok(*pool);
+        }
+
+        self.load_seed_amms().await;
+        if let Some(pool) = self.cache.get(&key) {
+            return Ok(*pool);
+        }
+
+        let pool = self.scan_for_pair(mint_a, mint_b).await?
+            .ok_or_else(|| AgentError::Configuration(format!("No Raydium pool found for {} / {}", mint_a, mint_b)))?;
+        info!("[resolve] Discovered Raydium pool {} for {} / {}", pool.amm, mint_a, mint_b);
+        self.cache.insert(key, pool);
+        Ok(pool)
+    }
+
+    /// Decode pools configured by address only and add them to the cache
+    async fn load_seed_amms(&self) {
+        let seeds: Vec<Pubkey> = std::mem::take(&mut *self.seed_amms.lock().await);
+        for amm in seeds {
+            match self.rpc.call(|rpc| async move { rpc.get_account_data(&amm).await }).await {
+                Ok(data) => match decode_amm_info(&self.program_id, amm, &data) {
+                    Some((pool, _)) => {
+                        self.cache.insert(pair_key(pool.coin_mint, pool.pc_mint), pool);
+                    }
+                    None => warn!("[load_seed_amms] {} is not a Raydium AMM v4 account", amm),
+                },
+                Err(e) => warn!("[load_seed_amms] Failed to fetch {}: {}", amm, e),
+            }
+        }
+    }
+
+    /// Scan Raydium program accounts for pools with this coin/pc pair, keeping the deepest one
+    async fn scan_for_pair(&self, mint_a: Pubkey, mint_b: Pubkey) -> Result<Option<RaydiumPool>, AgentError> {
+        let mut best: Option<(RaydiumPool, u64)> = None;
+
+        for (coin, pc) in [(mint_a, mint_b), (mint_b, mint_a)] {
+            let config = RpcProgramAccountsConfig {
+                filters: Some(vec![
+                    RpcFilterType::DataSize(AMM_INFO_LEN),
+                    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(COIN_MINT_OFFSET, coin.as_ref())),
+                    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(PC_MINT_OFFSET, pc.as_ref())),
+                ]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+
+            let program_id = self.program_id;
+            let accounts = self.rpc
+                .call(|rpc| {
+                    let config = config.clone();
+                    async move { rpc.get_program_accounts_with_config(&program_id, config).await }
+                })
+                .await
+                .map_err(|e| AgentError::Configuration(format!("Raydium pool scan failed: {}", e)))?;
+            debug!("[scan_for_pair] {} candidate pools for {} / {}", accounts.len(), coin, pc);
+
+            for (amm, account) in accounts {
+                if let Some((pool, lp_amount)) = decode_amm_info(&self.program_id, amm, &account.data)
+                    && best.as_ref().is_none_or(|(_, best_lp)| lp_amount > *best_lp)
+                {
+                    best = Some((pool, lp_amount));
+                }
+            }
+        }
+
+        Ok(best.map(|(pool, _)| pool))
+    }
+}
+
+impl std::fmt::Debug for RaydiumPoolResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RaydiumPoolResolver")
+            .field("program_id", &self.program_id)
+            .field("registry", &self.registry.len())
+            .field("cached_pools", &self.cache.len())
+            .finish()
+    }
+}
+
+/// Order-independent cache key for a mint pair
+fn pair_key(a: Pubkey, b: Pubkey) -> (Pubkey, Pubkey) {
+    if a <= b { (a, b) } else { (b, a) }
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Option<Pubkey> {
+    data.get(offset..offset + 32).and_then(|bytes| Pubkey::try_from(bytes).ok())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    data.get(offset..offset + 8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+}
+
+/// Decode the swap accounts and LP supply from a Raydium `AmmInfo` account
+fn decode_amm_info(program_id: &Pubkey, amm: Pubkey, data: &[u8]) -> Option<(RaydiumPool, u64)> {
+    if data.len() as u64 != AMM_INFO_LEN {
+        return None;
+    }
+
+    let nonce = read_u64(data, NONCE_OFFSET)? as u8;
+    let amm_authority = Pubkey::create_program_address(&[AMM_AUTHORITY_SEED, &[nonce]], program_id).ok()?;
+
+    let pool = RaydiumPool {
+        amm,
+        amm_authority,
+        pool_coin_token_account: read_pubkey(data, COIN_VAULT_OFFSET)?,
+        pool_pc_token_account: read_pubkey(data, PC_VAULT_OFFSET)?,
+        coin_mint: read_pubkey(data, COIN_MINT_OFFSET)?,
+        pc_mint: read_pubkey(data, PC_MINT_OFFSET)?,
+    };
+    Some((pool, read_u64(data, LP_AMOUNT_OFFSET)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Authority of every Raydium AMM v4 pool, derived with nonce 254
+    const RAYDIUM_AMM_V4_AUTHORITY: &str = "5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1";
+
+    fn amm_info(nonce: u64, pool: &RaydiumPool, lp_amount: u64) -> Vec<u8> {
+        let mut data = vec![0u8; AMM_INFO_LEN as usize];
+        let mut put = |offset: usize, bytes: &[u8]| data[offset..offset + bytes.len()].copy_from_slice(bytes);
+        put(NONCE_OFFSET, &nonce.to_le_bytes());
+        put(COIN_VAULT_OFFSET, pool.pool_coin_token_account.as_ref());
+        put(PC_VAULT_OFFSET, pool.pool_pc_token_account.as_ref());
+        put(COIN_MINT_OFFSET, pool.coin_mint.as_ref());
+        put(PC_MINT_OFFSET, pool.pc_mint.as_ref());
+        put(LP_AMOUNT_OFFSET, &lp_amount.to_le_bytes());
+        data
+    }
+
+    fn expected_pool() -> RaydiumPool {
+        RaydiumPool {
+            amm: Pubkey::new_unique(),
+            amm_authority: Pubkey::from_str(RAYDIUM_AMM_V4_AUTHORITY).unwrap(),
+            pool_coin_token_account: Pubkey::new_unique(),
+            pool_pc_token_account: Pubkey::new_unique(),
+            coin_mint: Pubkey::new_unique(),
+            pc_mint: Pubkey::new_unique(),
+        }
+    }
+
+    #[test]
+    fn decodes_amm_info_vaults_mints_and_authority() {
+        let program_id = Pubkey::from_str(RAYDIUM_AMM_V4_PROGRAM).unwrap();
+        let expected = expected_pool();
+        let data = amm_info(254, &expected, 4_200_000);
+
+        let (pool, lp_amount) = decode_amm_info(&program_id, expected.amm, &data).unwrap();
+        assert_eq!(pool, expected);
+        assert_eq!(lp_amount, 4_200_000);
+    }
+
+    #[test]
+    fn rejects_accounts_that_are_not_amm_info() {
+        let program_id = Pubkey::from_str(RAYDIUM_AMM_V4_PROGRAM).unwrap();
+        let expected = expected_pool();
+        let data = amm_info(254, &expected, 0);
+        assert!(decode_amm_info(&program_id, expected.amm, &data[..700]).is_none());
+    }
+
+    #[test]
+    fn pair_key_ignores_mint_order() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert_eq!(pair_key(a, b), pair_key(b, a));
+    }
+}
@@ -0,0 +1,346 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use tracing::{debug, info};
+use crate::agent::token_metadata::{from_ui_amount, to_ui_amount, TokenMetadataCache};
+use crate::agent::types::{AgentError, MarketConditions, NormalizedPrice, Position, QuoteData, StrategyConfig};
+use crate::onchain_instance::instance::{token_account_fields, ICM_PROGRAM_ID, VAULT_SEED};
+use crate::onchain_instance::rpc::SolanaRpc;
+
+
+/// How much of the bucket a single plan may put to work
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum SizingMode {
+    /// Constant share of bucket NAV
+    FixedFraction { fraction: f64 },
+    /// Share of NAV scaled so the position carries `target_volatility` of daily risk
+    VolatilityTarget { target_volatility: f64, max_fraction: f64 },
+    /// Kelly fraction from the strategy's edge, scaled down and capped
+    CappedKelly { win_rate: f64, payoff_ratio: f64, kelly_multiplier: f64, max_fraction: f64 },
+}
+
+impl Default for SizingMode {
+    fn default() -> Self {
+        SizingMode::FixedFraction { fraction: 0.05 }
+    }
+}
+
+impl SizingMode {
+    /// Share of NAV this mode asks for under the given daily volatility
+    pub fn target_fraction(&self, volatility: f64) -> f64 {
+        let fraction = match self {
+            SizingMode::FixedFraction { fraction } => *fraction,
+            SizingMode::VolatilityTarget { target_volatility, max_fraction } => {
+                if volatility > 0.0 {
+                    (target_volatility / volatility).min(*max_fraction)
+                } else {
+                    *max_fraction
+                }
+            }
+            SizingMode::CappedKelly { win_rate, payoff_ratio, kelly_multiplier, max_fraction } => {
+                if *payoff_ratio <= 0.0 {
+                    return 0.0;
+                }
+                let kelly = win_rate - (1.0 - win_rate) / payoff_ratio;
+                (kelly * kelly_multiplier).min(*max_fraction)
+            }
+        };
+        fraction.clamp(0.0, 1.0)
+    }
+}
+
+/// Limit that cut the requested size down
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SizingLimit {
+    MaxPositionSize,
+    VaultBalance,
+}
+
+/// Sizing decision recorded with each plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SizingDecision {
+    pub mode: SizingMode,
+    pub nav_usd: f64,
+    pub volatility: f64,
+    pub target_fraction: f64,
+    pub requested_usd: f64,
+    pub size_usd: f64,
+    pub input_price_usd: f64,
+    /// Raw balance of the bucket's input vault
+    pub vault_balance: u64,
+    pub input_amount: u64,
+    pub limited_by: Option<SizingLimit>,
+    pub decided_at: DateTime<Utc>,
+}
+
+/// Vault holdings of one mint
+#[derive(Debug, Clone, Copy)]
+struct VaultHolding {
+    amount: u64,
+    decimals: u8,
+}
+
+impl VaultHolding {
+    fn ui_amount(&self) -> f64 {
+        to_ui_amount(self.amount, self.decimals)
+    }
+}
+
+/// Sizes plans against the bucket's vault balances and the strategy's sizing mode
+pub struct PositionSizer {
+    rpc: Arc<SolanaRpc>,
+    usdc_mint: Pubkey,
+    token_metadata: Arc<TokenMetadataCache>,
+}
+
+impl PositionSizer {
+    pub fn new(rpc: Arc<SolanaRpc>, usdc_mint: Pubkey, token_metadata: Arc<TokenMetadataCache>) -> Self {
+        Self {
+            rpc,
+            usdc_mint,
+            token_metadata,
+        }
+    }
+
+    /// Decide how much of `quote.input_mint` the bucket should trade
+    pub async fn size(
+        &self,
+        bucket: Pubkey,
+        quote: &QuoteData,
+        market_conditions: &MarketConditions,
+        positions: &HashMap<String, Position>,
+        config: &StrategyConfig,
+    ) -> Result<SizingDecision, AgentError> {
+        let input_mint = Pubkey::from_str(&quote.input_mint)?;
+        let output_mint = Pubkey::from_str(&quote.output_mint)?;
+
+        let input = self.vault_holding(bucket, input_mint).await?;
+        let output = self.vault_holding(bucket, output_mint).await?;
+        let usdc = if self.usdc_mint == input_mint || self.usdc_mint == output_mint {
+            None
+        } else {
+            Some(self.vault_holding(bucket, self.usdc_mint).await?)
+        };
+
+        let (input_price_usd, output_price_usd) = self.prices(quote, input, output, positions)?;
+        let nav_usd = input.ui_amount() * input_price_usd
+            + output.ui_amount() * output_price_usd
+            + usdc.map(|h| h.ui_amount()).unwrap_or(0.0);
+
+        let mode = config.position_sizing.clone();
+        let volatility = market_conditions.volatility_24h;
+        let target_fraction = mode.target_fraction(volatility);
+        let requested_usd = nav_usd * target_fraction;
+
+        let vault_usd = input.ui_amount() * input_price_usd;
+        let (size_usd, limited_by) = clamp_size(requested_usd, config.risk_limits.max_position_size_usd, vault_usd);
+
+        let input_amount = from_ui_amount(size_usd / input_price_usd, input.decimals).min(input.amount);
+        if input_amount == 0 {
+            return Err(AgentError::InsufficientFunds(format!(
+                "Bucket {} has nothing to trade: NAV ${:.2}, {} vault holds {}",
+                bucket, nav_usd, input_mint, input.amount
+            )));
+        }
+
+        debug!(
+            "[size] {:?} on NAV ${:.2} (vol {:.4}) -> {:.4} of NAV, ${:.2} requested",
+            mode, nav_usd, volatility, target_fraction, requested_usd
+        );
+        info!(
+            "[size] Bucket {} sized at ${:.2} ({} of {}){}",
+            bucket,
+            size_usd,
+            input_amount,
+            input_mint,
+            limited_by.map(|l| format!(", limited by {:?}", l)).unwrap_or_default()
+        );
+
+        Ok(SizingDecision {
+            mode,
+            nav_usd,
+            volatility,
+            target_fraction,
+            requested_usd,
+            size_usd,
+            input_price_usd,
+            vault_balance: input.amount,
+            input_amount,
+            limited_by,
+            decided_at: Utc::now(),
+        })
+    }
+
+    /// USD prices of both sides of the quote, from the quote itself, USDC legs or tracked positions
+    fn prices(
+        &self,
+        quote: &QuoteData,
+        input: VaultHolding,
+        output: VaultHolding,
+        positions: &HashMap<String, Position>,
+    ) -> Result<(f64, f64), AgentError> {
+        let input_mint = Pubkey::from_str(&quote.input_mint)?;
+        let output_mint = Pubkey::from_str(&quote.output_mint)?;
+
+        // Whole output tokens received per whole input token
+        let rate = quote.normalized_price()
+            .or_else(|| NormalizedPrice::from_amounts(
+                &quote.input_mint, quote.input_amount, input.decimals,
+                &quote.output_mint, quote.output_amount, output.decimals,
+            ))
+            .map(|p| p.price)
+            .unwrap_or(0.0);
+
+        let known_price = |mint: Pubkey| -> Option<f64> {
+            if mint == self.usdc_mint {
+                return Some(1.0);
+            }
+            if mint == input_mint && let Some(price) = quote.input_price_usd {
+                return Some(price);
+            }
+            if mint == output_mint && let Some(price) = quote.output_price_usd {
+                return Some(price);
+            }
+            positions.values()
+                .find(|p| p.token_mint == mint && p.current_price > 0.0)
+                .map(|p| p.current_price)
+        };
+
+        let input_price = known_price(input_mint)
+            .or_else(|| known_price(output_mint).filter(|_| rate > 0.0).map(|price| price * rate));
+        let output_price = known_price(output_mint)
+            .or_else(|| input_price.filter(|_| rate > 0.0).map(|price| price / rate));
+
+        match (input_price, output_price) {
+            (Some(input_price), Some(output_price)) if input_price > 0.0 => Ok((input_price, output_price)),
+            _ => Err(AgentError::StaleMarketData(format!(
+                "No USD price for {} / {}", quote.input_mint, quote.output_mint
+            ))),
+        }
+    }
+
+    /// Balance and decimals of the bucket's vault for a mint; missing vaults count
+    /// as empty, RPC failures are errors
+    async fn vault_holding(&self, bucket: Pubkey, mint: Pubkey) -> Result<VaultHolding, AgentError> {
+        let (vault, _) = Pubkey::find_program_address(
+            &[VAULT_SEED, bucket.as_ref(), mint.as_ref()],
+            &ICM_PROGRAM_ID,
+        );
+
+        let account = self.rpc.call(|rpc| async move { rpc.get_account_with_commitment(&vault, rpc.commitment()).await }).await
+            .map_err(|e| AgentError::StaleMarketData(format!("Failed to read vault {} for mint {}: {}", vault, mint, e)))?
+            .value;
+        let amount = match account {
+            Some(account) => token_account_fields(&account).map(|(_, _, amount)| amount)
+                .ok_or_else(|| AgentError::StaleMarketData(format!("Vault {} is not a token account", vault)))?,
+            None => {
+                debug!("[vault_holding] No vault {} for mint {}", vault, mint);
+                0
+            }
+        };
+
+        Ok(VaultHolding { amount, decimals: self.token_metadata.get(&mint).await?.decimals })
+    }
+}
+
+/// Cut `requested_usd` to the position size limit, then to what the vault holds
+fn clamp_size(requested_usd: f64, max_position_size_usd: f64, vault_usd: f64) -> (f64, Option<SizingLimit>) {
+    let mut size_usd = requested_usd;
+    let mut limited_by = None;
+    if size_usd > max_position_size_usd {
+        size_usd = max_position_size_usd;
+        limited_by = Some(SizingLimit::MaxPositionSize);
+    }
+    if size_usd > vault_usd {
+        size_usd = vault_usd;
+        limited_by = Some(SizingLimit::VaultBalance);
+    }
+    (size_usd, limited_by)
+}
+
+impl std::fmt::Debug for PositionSizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PositionSizer")
+            .field("usdc_mint", &self.usdc_mint)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::onchain_instance::rpc::RpcConfig;
+
+    fn approx(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn fixed_fraction_ignores_volatility() {
+        let mode = SizingMode::FixedFraction { fraction: 0.1 };
+        approx(mode.target_fraction(0.0), 0.1);
+        approx(mode.target_fraction(0.8), 0.1);
+        approx(SizingMode::FixedFraction { fraction: 1.5 }.target_fraction(0.0), 1.0);
+    }
+
+    #[test]
+    fn volatility_target_scales_inversely_and_caps() {
+        let mode = SizingMode::VolatilityTarget { target_volatility: 0.02, max_fraction: 0.25 };
+        approx(mode.target_fraction(0.1), 0.2);
+        approx(mode.target_fraction(0.4), 0.05);
+        // Calm markets and missing volatility fall back to the cap
+        approx(mode.target_fraction(0.05), 0.25);
+        approx(mode.target_fraction(0.0), 0.25);
+    }
+
+    #[test]
+    fn capped_kelly_scales_the_kelly_fraction() {
+        let mode = SizingMode::CappedKelly { win_rate: 0.6, payoff_ratio: 2.0, kelly_multiplier: 0.5, max_fraction: 0.5 };
+        // Kelly = 0.6 - 0.4 / 2 = 0.4, halved
+        approx(mode.target_fraction(0.0), 0.2);
+
+        let capped = SizingMode::CappedKelly { win_rate: 0.6, payoff_ratio: 2.0, kelly_multiplier: 1.0, max_fraction: 0.1 };
+        approx(capped.target_fraction(0.0), 0.1);
+    }
+
+    #[test]
+    fn capped_kelly_without_an_edge_sizes_nothing() {
+        let negative = SizingMode::CappedKelly { win_rate: 0.3, payoff_ratio: 1.0, kelly_multiplier: 0.5, max_fraction: 0.5 };
+        approx(negative.target_fraction(0.0), 0.0);
+        let no_payoff = SizingMode::CappedKelly { win_rate: 0.9, payoff_ratio: 0.0, kelly_multiplier: 0.5, max_fraction: 0.5 };
+        approx(no_payoff.target_fraction(0.0), 0.0);
+    }
+
+    #[test]
+    fn size_within_limits_is_unchanged() {
+        assert_eq!(clamp_size(500.0, 1_000.0, 2_000.0), (500.0, None));
+    }
+
+    #[test]
+    fn size_is_clamped_to_the_position_limit() {
+        assert_eq!(clamp_size(1_500.0, 1_000.0, 2_000.0), (1_000.0, Some(SizingLimit::MaxPositionSize)));
+    }
+
+    #[test]
+    fn vault_balance_clamp_wins_over_the_position_limit() {
+        assert_eq!(clamp_size(1_500.0, 1_000.0, 800.0), (800.0, Some(SizingLimit::VaultBalance)));
+        assert_eq!(clamp_size(900.0, 1_000.0, 800.0), (800.0, Some(SizingLimit::VaultBalance)));
+    }
+
+    #[tokio::test]
+    async fn vault_read_failure_is_an_error_not_an_empty_vault() {
+        let rpc = Arc::new(SolanaRpc::new(RpcConfig {
+            urls: vec!["http://127.0.0.1:1".to_string()],
+            ..RpcConfig::default()
+        }));
+        let sizer = PositionSizer::new(Arc::clone(&rpc), Pubkey::new_unique(), Arc::new(TokenMetadataCache::new(rpc)));
+
+        let result = sizer.vault_holding(Pubkey::new_unique(), Pubkey::new_unique()).await;
+        assert!(matches!(result, Err(AgentError::StaleMarketData(_))));
+    }
+}
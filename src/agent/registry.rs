@@ -0,0 +1,41 @@
+//! Running trading agents by bucket PDA, shared by the API and the bucket scheduler.
+
+use dashmap::DashMap;
+
+use crate::agent::trading_agent::TradingAgent;
+use crate::agent::types::AgentError;
+
+/// Bucket agents that have been started and not yet stopped
+#[derive(Default)]
+pub struct AgentRegistry {
+    agents: DashMap<String, TradingAgent>,
+}
+
+impl AgentRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start `agent` and register it for `pool_id`, stopping the agent it replaces
+    pub async fn start(&self, pool_id: String, agent: TradingAgent) -> Result<(), AgentError> {
+        agent.start().await?;
+        if let Some(previous) = self.agents.insert(pool_id, agent) {
+            previous.stop().await?;
+        }
+        Ok(())
+    }
+
+    /// Whether an agent is registered for `pool_id`
+    pub fn contains(&self, pool_id: &str) -> bool {
+        self.agents.contains_key(pool_id)
+    }
+
+    /// Stop and remove the agent of `pool_id`; `false` when none is running
+    pub async fn stop(&self, pool_id: &str) -> Result<bool, AgentError> {
+        let Some((_, agent)) = self.agents.remove(pool_id) else {
+            return Ok(false);
+        };
+        agent.stop().await?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,663 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature};
+use tracing::{info, warn, error, debug};
+use crate::agent::confirmation::{ConfirmationTracker, SwapBalanceAccounts, TransactionCosts};
+use crate::agent::data_fetcher::DataFetcher;
+use crate::agent::executor::TransactionStatus;
+use crate::agent::pool_resolver::{RaydiumPool, RaydiumPoolResolver};
+use crate::agent::signer::BucketIdentity;
+use crate::agent::types::{AgentError, TradingPlan};
+use crate::onchain_instance::compute_budget::ComputeBudgetOptions;
+use crate::onchain_instance::instance::{IcmProgramInstance, SwapLeg, ICM_PROGRAM_ID, VAULT_SEED};
+use crate::state_structs::SwapTokensRequest;
+
+
+/// How a multi-hop route is sent to the cluster
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RouteExecutionMode {
+    /// All legs in one transaction; intermediate legs are sized from their minimum output
+    Atomic,
+    /// One transaction per leg, each sized from the previous leg's measured output
+    #[default]
+    Sequential,
+}
+
+/// One hop of a route
+#[derive(Debug, Clone)]
+pub struct RouteLeg {
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub pool: RaydiumPool,
+    pub input_amount: u64,
+    pub expected_output: u64,
+}
+
+/// A direct or two-hop path between two mints
+#[derive(Debug, Clone)]
+pub struct SwapRoute {
+    pub legs: Vec<RouteLeg>,
+    pub via: Option<Pubkey>,
+    pub expected_output: u64,
+}
+
+impl SwapRoute {
+    pub fn is_direct(&self) -> bool {
+        self.legs.len() == 1
+    }
+}
+
+/// Fill of a single route leg
+#[derive(Debug, Clone, Serialize)]
+pub struct LegFill {
+    pub input_mint: String,
+    pub output_mint: String,
+    pub signature: Option<String>,
+    pub input_amount: u64,
+    pub output_amount: Option<u64>,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// What happened to tokens left in an intermediate vault after a failed leg
+#[derive(Debug, Clone, Serialize)]
+pub struct RollbackReport {
+    pub stranded_mint: String,
+    pub stranded_amount: u64,
+    pub attempted: bool,
+    pub signature: Option<String>,
+    pub recovered_amount: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// Result of carrying out a route
+#[derive(Debug, Clone)]
+pub struct RouteExecution {
+    pub success: bool,
+    pub signature: Option<String>,
+    pub output_amount: Option<u64>,
+    pub lamports_spent: Option<u64>,
+    pub costs: TransactionCosts,
+    pub legs: Vec<LegFill>,
+    pub rollback: Option<RollbackReport>,
+    pub error: Option<String>,
+}
+
+/// Plans direct or two-hop routes (through USDC or SOL) and executes them
+pub struct SwapRouter {
+    icm_client: Arc<IcmProgramInstance>,
+    data_fetcher: Arc<DataFetcher>,
+    pool_resolver: Arc<RaydiumPoolResolver>,
+    confirmation_tracker: ConfirmationTracker,
+    intermediates: Vec<Pubkey>,
+    mode: RouteExecutionMode,
+}
+
+impl SwapRouter {
+    pub fn new(
+        icm_client: Arc<IcmProgramInstance>,
+        data_fetcher: Arc<DataFetcher>,
+        pool_resolver: Arc<RaydiumPoolResolver>,
+        confirmation_tracker: ConfirmationTracker,
+        mode: RouteExecutionMode,
+    ) -> Self {
+        let usdc_mint = icm_client.usdc_mint();
+
+        Self {
+            icm_client,
+            data_fetcher,
+            pool_resolver,
+            confirmation_tracker,
+            intermediates: vec![usdc_mint, spl_token::native_mint::ID],
+            mode,
+        }
+    }
+
+    /// Compare the direct path with two-hop paths and return the one with the best expected output.
+    ///
+    /// `fallback_output` is used for the direct path when no fresh quote is cached.
+    pub async fn plan_route(
+        &self,
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        amount: u64,
+        fallback_output: u64,
+    ) -> Result<SwapRoute, AgentError> {
+        let mut best: Option<SwapRoute> = None;
+
+        match self.pool_resolver.resolve(input_mint, output_mint).await {
+            Ok(pool) => {
+                let expected = self.estimate_output(&input_mint, &output_mint, amount).unwrap_or(fallback_output);
+                best = Some(SwapRoute {
+                    legs: vec![RouteLeg { input_mint, output_mint, pool, input_amount: amount, expected_output: expected }],
+                    via: None,
+                    expected_output: expected,
+                });
+            }
+            Err(e) => debug!("[plan_route] No direct pool {} -> {}: {}", input_mint, output_mint, e),
+        }
+
+        for via in self.intermediates.iter().copied() {
+            if via == input_mint || via == output_mint {
+                continue;
+            }
+            let Some(route) = self.two_hop_route(input_mint, via, output_mint, amount).await else {
+                continue;
+            };
+            if best.as_ref().is_none_or(|b| route.expected_output > b.expected_output) {
+                best = Some(route);
+            }
+        }
+
+        let route = best.ok_or_else(|| AgentError::NoOpportunity(format!(
+            "No direct or routed path from {} to {}", input_mint, output_mint
+        )))?;
+
+        info!(
+            "[plan_route] {} -> {} via {:?}: expected output {}",
+            input_mint, output_mint, route.via, route.expected_output
+        );
+        Ok(route)
+    }
+
+    /// Build a route through `via`; needs pools and fresh cached quotes for both legs
+    async fn two_hop_route(&self, input_mint: Pubkey, via: Pubkey, output_mint: Pubkey, amount: u64) -> Option<SwapRoute> {
+        let first_pool = self.pool_resolver.resolve(input_mint, via).await.ok()?;
+        let second_pool = self.pool_resolver.resolve(via, output_mint).await.ok()?;
+        let first_output = self.estimate_output(&input_mint, &via, amount)?;
+        let second_output = self.estimate_output(&via, &output_mint, first_output)?;
+
+        Some(SwapRoute {
+            legs: vec![
+                RouteLeg { input_mint, output_mint: via, pool: first_pool, input_amount: amount, expected_output: first_output },
+                RouteLeg { input_mint: via, output_mint, pool: second_pool, input_amount: first_output, expected_output: second_output },
+            ],
+            via: Some(via),
+            expected_output: second_output,
+        })
+    }
+
+    /// Scale a fresh cached quote to `amount`
+    fn estimate_output(&self, input_mint: &Pubkey, output_mint: &Pubkey, amount: u64) -> Option<u64> {
+        let quote = self.data_fetcher.get_cached_quote(&input_mint.to_string(), &output_mint.to_string())?;
+        if !self.data_fetcher.is_data_fresh(&quote) || quote.input_amount == 0 {
+            return None;
+        }
+        Some(scale_amount(quote.output_amount, amount, quote.input_amount))
+    }
+
+    /// Plan and carry out the best route for a trading plan
+    pub async fn execute(
+        &self,
+        plan: &TradingPlan,
+        bucket: &BucketIdentity,
+        keypair: &Keypair,
+    ) -> Result<RouteExecution, AgentError> {
+        let route = self.plan_route(plan.input_mint, plan.output_mint, plan.input_amount, plan.quoted_output_amount).await?;
+
+        if !route.is_direct() && self.mode == RouteExecutionMode::Atomic {
+            self.execute_atomic(plan, bucket, keypair, &route).await
+        } else {
+            Ok(self.execute_sequential(plan, bucket, keypair, &route).await)
+        }
+    }
+
+    /// Send every leg in one transaction
+    async fn execute_atomic(
+        &self,
+        plan: &TradingPlan,
+        bucket: &BucketIdentity,
+        keypair: &Keypair,
+        route: &SwapRoute,
+    ) -> Result<RouteExecution, AgentError> {
+        let raydium_amm_program = self.pool_resolver.program_id();
+        let mut legs = Vec::with_capacity(route.legs.len());
+        let mut in_amount = plan.input_amount;
+
+        for leg in &route.legs {
+            let quoted_out = scale_amount(leg.expected_output, in_amount, leg.input_amount);
+            legs.push(SwapLeg {
+                input_mint: leg.input_mint,
+                output_mint: leg.output_mint,
+                in_amount,
+                quoted_out_amount: quoted_out,
+                slippage_bps: plan.max_slippage_bps,
+                raydium_amm_program,
+                amm: leg.pool.amm,
+                amm_authority: leg.pool.amm_authority,
+                pool_coin_token_account: leg.pool.pool_coin_token_account,
+                pool_pc_token_account: leg.pool.pool_pc_token_account,
+            });
+            // The next leg can only spend what this one is guaranteed to deliver
+            in_amount = min_output(quoted_out, plan.max_slippage_bps);
+        }
+
+        let response = self.icm_client
+            .with_compute_budget(plan_compute_budget(plan))
+            .agent_swap_route_transaction(keypair, &bucket.name, &legs)
+            .await
+            .map_err(|e| AgentError::TransactionFailed(format!("Atomic route failed: {}", e)))?;
+
+        let accounts = vault_accounts(bucket, plan.input_mint, plan.output_mint);
+        let confirmed = self.track(&response.transaction, accounts).await;
+        let success = confirmed.as_ref().is_some_and(|c| matches!(c.status, TransactionStatus::Confirmed));
+
+        let fills = legs.iter().enumerate().map(|(i, leg)| LegFill {
+            input_mint: leg.input_mint.to_string(),
+            output_mint: leg.output_mint.to_string(),
+            signature: Some(response.transaction.clone()),
+            input_amount: leg.in_amount,
+            output_amount: if i + 1 == legs.len() { confirmed.as_ref().and_then(|c| c.output_received) } else { None },
+            success,
+            error: None,
+        }).collect();
+
+        Ok(RouteExecution {
+            success,
+            signature: Some(response.transaction),
+            output_amount: confirmed.as_ref().and_then(|c| c.output_received),
+            lamports_spent: confirmed.as_ref().and_then(|c| c.lamports_spent),
+            costs: confirmed.as_ref().map(|c| c.costs()).unwrap_or_default(),
+            legs: fills,
+            rollback: None,
+            error: confirmed.and_then(|c| c.error),
+        })
+    }
+
+    /// Send legs one at a time, sizing each from the previous leg's measured output
+    async fn execute_sequential(
+        &self,
+        plan: &TradingPlan,
+        bucket: &BucketIdentity,
+        keypair: &Keypair,
+        route: &SwapRoute,
+    ) -> RouteExecution {
+        let mut fills = Vec::with_capacity(route.legs.len());
+        let mut lamports_spent = 0u64;
+        let mut costs = TransactionCosts::default();
+        let mut in_amount = plan.input_amount;
+        let mut last_signature = None;
+
+        for (index, leg) in route.legs.iter().enumerate() {
+            let quoted_out = if route.is_direct() {
+                plan.quoted_output_amount
+            } else {
+                scale_amount(leg.expected_output, in_amount, leg.input_amount)
+            };
+
+            let outcome = self.send_leg(plan, bucket, keypair, leg.input_mint, leg.output_mint, &leg.pool, in_amount, quoted_out).await;
+            lamports_spent += outcome.lamports_spent.unwrap_or(0);
+            costs.add(outcome.costs);
+
+            let failed = !outcome.fill.success;
+            let received = outcome.fill.output_amount
+                .unwrap_or_else(|| min_output(quoted_out, plan.max_slippage_bps));
+            last_signature = outcome.fill.signature.clone().or(last_signature);
+            let error = outcome.fill.error.clone();
+            fills.push(outcome.fill);
+
+            if failed {
+                // Tokens bought by earlier legs are stuck in an intermediate vault
+                let rollback = if index > 0 {
+                    Some(self.unwind(plan, bucket, keypair, leg.input_mint, in_amount).await)
+                } else {
+                    None
+                };
+                error!("[execute_sequential] Leg {} of plan {} failed: {:?}", index + 1, plan.id, error);
+                return RouteExecution {
+                    success: false,
+                    signature: last_signature,
+                    output_amount: None,
+                    lamports_spent: Some(lamports_spent),
+                    costs,
+                    legs: fills,
+                    rollback,
+                    error,
+                };
+            }
+
+            in_amount = received;
+        }
+
+        RouteExecution {
+            success: true,
+            signature: last_signature,
+            output_amount: fills.last().and_then(|f| f.output_amount),
+            lamports_spent: Some(lamports_spent),
+            costs,
+            legs: fills,
+            rollback: None,
+            error: None,
+        }
+    }
+
+    /// Try to swap stranded intermediate tokens back into the plan's input mint
+    async fn unwind(
+        &self,
+        plan: &TradingPlan,
+        bucket: &BucketIdentity,
+        keypair: &Keypair,
+        stranded_mint: Pubkey,
+        stranded_amount: u64,
+    ) -> RollbackReport {
+        let mut report = RollbackReport {
+            stranded_mint: stranded_mint.to_string(),
+            stranded_amount,
+            attempted: false,
+            signature: None,
+            recovered_amount: None,
+            error: None,
+        };
+
+        let pool = match self.pool_resolver.resolve(stranded_mint, plan.input_mint).await {
+            Ok(pool) => pool,
+            Err(e) => {
+                report.error = Some(format!("No pool to unwind: {}", e));
+                error!("[unwind] {} of {} left stranded for plan {}: {:?}", stranded_amount, stranded_mint, plan.id, report.error);
+                return report;
+            }
+        };
+        let Some(expected) = self.estimate_output(&stranded_mint, &plan.input_mint, stranded_amount) else {
+            report.error = Some("No fresh quote to unwind safely".to_string());
+            error!("[unwind] {} of {} left stranded for plan {}: {:?}", stranded_amount, stranded_mint, plan.id, report.error);
+            return report;
+        };
+
+        warn!("[unwind] Returning {} of {} to {} for plan {}", stranded_amount, stranded_mint, plan.input_mint, plan.id);
+        report.attempted = true;
+        let outcome = self.send_leg(plan, bucket, keypair, stranded_mint, plan.input_mint, &pool, stranded_amount, expected).await;
+        report.signature = outcome.fill.signature;
+        report.recovered_amount = outcome.fill.output_amount;
+        report.error = outcome.fill.error;
+        if outcome.fill.success {
+            info!("[unwind] Recovered {:?} of {} for plan {} in {:?}", report.recovered_amount, plan.input_mint, plan.id, report.signature);
+        } else {
+            error!("[unwind] Unwind of {} {} failed for plan {}: {:?}", stranded_amount, stranded_mint, plan.id, report.error);
+        }
+        report
+    }
+
+    /// Send one swap leg and measure it
+    #[allow(clippy::too_many_arguments)]
+    async fn send_leg(
+        &self,
+        plan: &TradingPlan,
+        bucket: &BucketIdentity,
+        keypair: &Keypair,
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        pool: &RaydiumPool,
+        in_amount: u64,
+        quoted_out: u64,
+    ) -> LegOutcome {
+        let request = SwapTokensRequest {
+            bucket: bucket.pubkey.to_string(),
+            input_mint: input_mint.to_string(),
+            output_mint: output_mint.to_string(),
+            in_amount,
+            quoted_out_amount: quoted_out,
+            slippage_bps: plan.max_slippage_bps,
+            amm: Some(pool.amm.to_string()),
+            amm_authority: Some(pool.amm_authority.to_string()),
+            pool_coin_token_account: Some(pool.pool_coin_token_account.to_string()),
+            pool_pc_token_account: Some(pool.pool_pc_token_account.to_string()),
+        };
+
+        let mut fill = LegFill {
+            input_mint: input_mint.to_string(),
+            output_mint: output_mint.to_string(),
+            signature: None,
+            input_amount: in_amount,
+            output_amount: None,
+            success: false,
+            error: None,
+        };
+
+        let sent = self.icm_client.with_compute_budget(plan_compute_budget(plan)).agent_swap_tokens_transaction(
+            request,
+            keypair,
+            &bucket.name,
+            input_mint,
+            output_mint,
+            self.pool_resolver.program_id(),
+            pool.amm,
+            pool.amm_authority,
+            pool.pool_coin_token_account,
+            pool.pool_pc_token_account,
+            bucket.pubkey,
+        ).await;
+
+        let signature = match sent {
+            Ok(response) => response.transaction,
+            Err(e) => {
+                fill.error = Some(format!("ICM swap failed: {}", e));
+                return LegOutcome { fill, lamports_spent: None, costs: TransactionCosts::default() };
+            }
+        };
+        fill.signature = Some(signature.clone());
+
+        let confirmed = self.track(&signature, vault_accounts(bucket, input_mint, output_mint)).await;
+        match confirmed {
+            Some(confirmed) => {
+                let costs = confirmed.costs();
+                fill.success = matches!(confirmed.status, TransactionStatus::Confirmed);
+                fill.output_amount = confirmed.output_received;
+                fill.error = confirmed.error;
+                LegOutcome { fill, lamports_spent: confirmed.lamports_spent, costs }
+            }
+            None => {
+                // Sent but never confirmed; later legs must not spend what it may not have delivered
+                fill.error = Some(format!("Swap {} was sent but its outcome is unknown", signature));
+                LegOutcome { fill, lamports_spent: None, costs: TransactionCosts::default() }
+            }
+        }
+    }
+
+    async fn track(&self, signature: &str, accounts: SwapBalanceAccounts) -> Option<crate::agent::confirmation::ConfirmedSwap> {
+        let signature = Signature::from_str(signature).ok()?;
+        match self.confirmation_tracker.track_swap(&signature, accounts).await {
+            Ok(confirmed) => Some(confirmed),
+            Err(e) => {
+                warn!("[track] Could not track {}: {}", signature, e);
+                None
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for SwapRouter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SwapRouter")
+            .field("intermediates", &self.intermediates)
+            .field("mode", &self.mode)
+            .finish()
+    }
+}
+
+struct LegOutcome {
+    fill: LegFill,
+    lamports_spent: Option<u64>,
+    costs: TransactionCosts,
+}
+
+/// The plan's priority fee caps what its transactions pay
+fn plan_compute_budget(plan: &TradingPlan) -> ComputeBudgetOptions {
+    ComputeBudgetOptions::default().with_max_priority_fee_lamports(plan.priority_fee)
+}
+
+/// Vault token accounts for a bucket's input and output mints
+fn vault_accounts(bucket: &BucketIdentity, input_mint: Pubkey, output_mint: Pubkey) -> SwapBalanceAccounts {
+    let (input_token_account, _) = Pubkey::find_program_address(
+        &[VAULT_SEED, bucket.pubkey.as_ref(), input_mint.as_ref()],
+        &ICM_PROGRAM_ID,
+    );
+    let (output_token_account, _) = Pubkey::find_program_address(
+        &[VAULT_SEED, bucket.pubkey.as_ref(), output_mint.as_ref()],
+        &ICM_PROGRAM_ID,
+    );
+    SwapBalanceAccounts { input_token_account, output_token_account }
+}
+
+/// `value * numerator / denominator` without intermediate overflow
+pub(crate) fn scale_amount(value: u64, numerator: u64, denominator: u64) -> u64 {
+    if denominator == 0 {
+        return 0;
+    }
+    ((value as u128 * numerator as u128) / denominator as u128).min(u64::MAX as u128) as u64
+}
+
+/// Minimum output a swap may deliver under a slippage tolerance
+pub(crate) fn min_output(quoted_out: u64, slippage_bps: u16) -> u64 {
+    scale_amount(quoted_out, 10_000u64.saturating_sub(slippage_bps as u64), 10_000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use anchor_client::Cluster;
+    use chrono::Utc;
+    use crate::agent::circuit_breaker::{CircuitBreakerConfig, CircuitBreakers};
+    use crate::agent::pool_resolver::RAYDIUM_AMM_V4_PROGRAM;
+    use crate::agent::token_metadata::TokenMetadataCache;
+    use crate::agent::types::QuoteData;
+    use crate::onchain_instance::rpc::{RpcConfig, SolanaRpc};
+
+    struct Fixture {
+        router: SwapRouter,
+        data_fetcher: Arc<DataFetcher>,
+        usdc: Pubkey,
+        input: Pubkey,
+        output: Pubkey,
+    }
+
+    fn pool(mint_a: Pubkey, mint_b: Pubkey) -> RaydiumPool {
+        RaydiumPool {
+            amm: Pubkey::new_unique(),
+            amm_authority: Pubkey::new_unique(),
+            pool_coin_token_account: Pubkey::new_unique(),
+            pool_pc_token_account: Pubkey::new_unique(),
+            coin_mint: mint_a,
+            pc_mint: mint_b,
+        }
+    }
+
+    /// Router over registered pools for `pairs`; pool scans fail against an unreachable RPC
+    fn fixture(pairs: &[(usize, usize)]) -> Fixture {
+        let rpc = Arc::new(SolanaRpc::new(RpcConfig {
+            urls: vec!["http://127.0.0.1:1".to_string()],
+            ..RpcConfig::default()
+        }));
+        let usdc = Pubkey::new_unique();
+        let input = Pubkey::new_unique();
+        let output = Pubkey::new_unique();
+        let mints = [input, output, usdc];
+
+        let registry = pairs.iter().map(|(a, b)| pool(mints[*a], mints[*b])).collect();
+        let program_id = Pubkey::from_str(RAYDIUM_AMM_V4_PROGRAM).unwrap();
+        let pool_resolver = Arc::new(RaydiumPoolResolver::new(Arc::clone(&rpc), program_id, registry, Vec::new()));
+        let (data_fetcher, _quotes) = DataFetcher::new(
+            Vec::new(),
+            60_000,
+            Arc::new(TokenMetadataCache::new(Arc::clone(&rpc))),
+            Arc::new(CircuitBreakers::new(CircuitBreakerConfig::default())),
+        );
+        let data_fetcher = Arc::new(data_fetcher);
+        let icm_client = Arc::new(IcmProgramInstance::new(Cluster::Localnet, usdc, Arc::clone(&rpc)).unwrap());
+        let tracker = ConfirmationTracker::new(rpc, Duration::from_millis(100), Duration::from_secs(1));
+
+        Fixture {
+            router: SwapRouter::new(icm_client, Arc::clone(&data_fetcher), pool_resolver, tracker, RouteExecutionMode::Sequential),
+            data_fetcher,
+            usdc,
+            input,
+            output,
+        }
+    }
+
+    impl Fixture {
+        /// Cache a fresh quote paying `output_amount` for 1_000 of `input_mint`
+        fn quote(&self, input_mint: Pubkey, output_mint: Pubkey, output_amount: u64) {
+            self.data_fetcher.cache_quote(QuoteData {
+                input_mint: input_mint.to_string(),
+                output_mint: output_mint.to_string(),
+                input_amount: 1_000,
+                output_amount,
+                other_amount_threshold: 0,
+                swap_mode: "ExactIn".to_string(),
+                slippage_bps: 50,
+                platform_fee_bps: 0,
+                price_impact_pct: 0.0,
+                route_plan: Vec::new(),
+                timestamp: Utc::now(),
+                input_decimals: None,
+                output_decimals: None,
+                input_price_usd: None,
+                output_price_usd: None,
+            });
+        }
+    }
+
+    const INPUT: usize = 0;
+    const OUTPUT: usize = 1;
+    const USDC: usize = 2;
+
+    #[tokio::test]
+    async fn direct_route_wins_when_it_pays_more() {
+        let f = fixture(&[(INPUT, OUTPUT), (INPUT, USDC), (USDC, OUTPUT)]);
+        f.quote(f.input, f.output, 2_000);
+        f.quote(f.input, f.usdc, 500);
+        f.quote(f.usdc, f.output, 3_000);
+
+        let route = f.router.plan_route(f.input, f.output, 10_000, 0).await.unwrap();
+        assert!(route.is_direct());
+        assert_eq!(route.via, None);
+        assert_eq!(route.expected_output, 20_000);
+    }
+
+    #[tokio::test]
+    async fn hop_wins_when_it_pays_more() {
+        let f = fixture(&[(INPUT, OUTPUT), (INPUT, USDC), (USDC, OUTPUT)]);
+        f.quote(f.input, f.output, 2_000);
+        f.quote(f.input, f.usdc, 500);
+        f.quote(f.usdc, f.output, 5_000);
+
+        let route = f.router.plan_route(f.input, f.output, 10_000, 0).await.unwrap();
+        assert_eq!(route.via, Some(f.usdc));
+        assert_eq!(route.legs.len(), 2);
+        assert_eq!(route.legs[0].expected_output, 5_000);
+        assert_eq!(route.legs[1].input_amount, 5_000);
+        assert_eq!(route.expected_output, 25_000);
+    }
+
+    #[tokio::test]
+    async fn direct_route_falls_back_to_the_plan_quote() {
+        let f = fixture(&[(INPUT, OUTPUT), (INPUT, USDC), (USDC, OUTPUT)]);
+        // The hop has no quote for its second leg, so only the direct path is priced
+        f.quote(f.input, f.usdc, 500);
+
+        let route = f.router.plan_route(f.input, f.output, 10_000, 12_345).await.unwrap();
+        assert!(route.is_direct());
+        assert_eq!(route.expected_output, 12_345);
+    }
+
+    #[tokio::test]
+    async fn hop_is_used_without_a_direct_pool() {
+        let f = fixture(&[(INPUT, USDC), (USDC, OUTPUT)]);
+        f.quote(f.input, f.usdc, 500);
+        f.quote(f.usdc, f.output, 1_000);
+
+        let route = f.router.plan_route(f.input, f.output, 10_000, 0).await.unwrap();
+        assert_eq!(route.via, Some(f.usdc));
+        assert_eq!(route.expected_output, 5_000);
+    }
+
+    #[tokio::test]
+    async fn no_pools_means_no_route() {
+        let f = fixture(&[]);
+        let result = f.router.plan_route(f.input, f.output, 10_000, 0).await;
+        assert!(matches!(result, Err(AgentError::NoOpportunity(_))));
+    }
+}
@@ -0,0 +1,230 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use dashmap::DashMap;
+use deadpool_postgres::Pool;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use std::str::FromStr;
+use tracing::{debug, info, warn};
+use crate::agent::types::AgentError;
+use crate::database::models::{DatabaseTradingPool, UserProfile};
+use crate::onchain_instance::instance::{IcmProgramInstance, ICM_PROGRAM_ID};
+
+/// Name and creator of a bucket, enough to derive every PDA the swap needs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BucketIdentity {
+    pub pubkey: Pubkey,
+    pub name: String,
+    pub creator: Pubkey,
+}
+
+impl BucketIdentity {
+    /// Derive the bucket PDA from a name and creator
+    pub fn derive(name: &str, creator: Pubkey) -> Self {
+        let (pubkey, _) = Pubkey::find_program_address(
+            &[b"bucket", name.as_bytes(), creator.as_ref()],
+            &ICM_PROGRAM_ID,
+        );
+        Self { pubkey, name: name.to_string(), creator }
+    }
+}
+
+/// Resolves bucket pubkeys to their name and creator, database first and chain second
+pub struct BucketDirectory {
+    db_pool: Pool,
+    icm_client: Arc<IcmProgramInstance>,
+    cache: DashMap<Pubkey, BucketIdentity>,
+}
+
+impl BucketDirectory {
+    pub fn new(db_pool: Pool, icm_client: Arc<IcmProgramInstance>) -> Self {
+        Self {
+            db_pool,
+            icm_client,
+            cache: DashMap::new(),
+        }
+    }
+
+    /// Look up the name and creator of a bucket
+    pub async fn resolve(&self, bucket_pubkey: Pubkey) -> Result<BucketIdentity, AgentError> {
+        if let Some(identity) = self.cache.get(&bucket_pubkey) {
+            return Ok(identity.clone());
+        }
+
+        let identity = match self.resolve_from_database(bucket_pubkey).await {
+            Ok(Some(identity)) => identity,
+            Ok(None) => self.resolve_from_chain(bucket_pubkey).await?,
+            Err(e) => {
+                warn!("[resolve] Database lookup for bucket {} failed, falling back to chain: {}", bucket_pubkey, e);
+                self.resolve_from_chain(bucket_pubkey).await?
+            }
+        };
+
+        self.cache.insert(bucket_pubkey, identity.clone());
+        Ok(identity)
+    }
+
+    /// Look the bucket up by the PDA the indexer records for each pool
+    async fn resolve_from_database(&self, bucket_pubkey: Pubkey) -> Result<Option<BucketIdentity>, AgentError> {
+        let Some(pool) = DatabaseTradingPool::fetch_by_bucket_pda(&self.db_pool, &bucket_pubkey.to_string())
+            .await
+            .map_err(|e| AgentError::Database(e.to_string()))?
+        else {
+            return Ok(None);
+        };
+
+        let Ok(creator) = Pubkey::from_str(pool.creator_pubkey.trim()) else {
+            return Ok(None);
+        };
+        // A row whose name and creator derive another PDA is stale; trust the chain instead
+        let identity = BucketIdentity::derive(pool.name.trim(), creator);
+        if identity.pubkey != bucket_pubkey {
+            warn!("[resolve_from_database] Pool {} does not derive bucket {}", pool.id, bucket_pubkey);
+            return Ok(None);
+        }
+        debug!("[resolve_from_database] Bucket {} is '{}' by {}", bucket_pubkey, identity.name, creator);
+        Ok(Some(identity))
+    }
+
+    /// Read the bucket account on chain
+    async fn resolve_from_chain(&self, bucket_pubkey: Pubkey) -> Result<BucketIdentity, AgentError> {
+        let (name, creator) = self.icm_client.fetch_bucket_identity(bucket_pubkey)
+            .await
+            .map_err(|e| AgentError::Configuration(format!("Bucket {} not found: {}", bucket_pubkey, e)))?;
+        debug!("[resolve_from_chain] Bucket {} is '{}' by {}", bucket_pubkey, name, creator);
+        Ok(BucketIdentity { pubkey: bucket_pubkey, name, creator })
+    }
+}
+
+impl std::fmt::Debug for BucketDirectory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BucketDirectory")
+            .field("cached_buckets", &self.cache.len())
+            .finish()
+    }
+}
+
+/// Provides the keypair allowed to sign swaps for a bucket
+#[async_trait]
+pub trait SigningAuthority: Send + Sync + std::fmt::Debug {
+    async fn signer_for(&self, bucket: &BucketIdentity) -> Result<Keypair, AgentError>;
+}
+
+/// Signs with the bucket creator's custodial key stored in `user_profiles`
+#[derive(Debug)]
+pub struct CreatorSigner {
+    db_pool: Pool,
+}
+
+impl CreatorSigner {
+    pub fn new(db_pool: Pool) -> Self {
+        Self { db_pool }
+    }
+}
+
+#[async_trait]
+impl SigningAuthority for CreatorSigner {
+    async fn signer_for(&self, bucket: &BucketIdentity) -> Result<Keypair, AgentError> {
+        let creator = bucket.creator.to_string();
+        let bytes = UserProfile::fetch_private_key_by_pubkey(&self.db_pool, &creator)
+            .await
+            .map_err(|e| AgentError::Database(e.to_string()))?
+            .ok_or_else(|| AgentError::Configuration(format!("No custodial key for creator {}", creator)))?;
+
+        let bytes: Vec<u8> = bytes.into_iter().map(|b| b as u8).collect();
+        let keypair = Keypair::try_from(&bytes[..])
+            .map_err(|e| AgentError::Configuration(format!("Invalid keypair bytes for {}: {}", creator, e)))?;
+
+        if keypair.pubkey() != bucket.creator {
+            return Err(AgentError::Configuration(format!("Stored key does not match creator {}", creator)));
+        }
+        Ok(keypair)
+    }
+}
+
+/// Signs with a key delegated to the agent.
+///
+/// The program only accepts the bucket creator as the swap signer, so the
+/// delegated key can sign for buckets it created itself.
+pub struct DelegatedAgentSigner {
+    keypair: Keypair,
+}
+
+impl DelegatedAgentSigner {
+    pub fn new(keypair: Keypair) -> Self {
+        Self { keypair }
+    }
+
+    /// Decode a base58 64-byte secret key
+    pub fn from_base58(encoded: &str) -> Result<Self, AgentError> {
+        bs58::decode(encoded.trim()).into_vec().ok()
+            .and_then(|bytes| Keypair::try_from(&bytes[..]).ok())
+            .map(Self::new)
+            .ok_or_else(|| AgentError::Configuration("AGENT_PRIVATE_KEY is not a valid base58 keypair".to_string()))
+    }
+}
+
+impl std::fmt::Debug for DelegatedAgentSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DelegatedAgentSigner")
+            .field("pubkey", &self.keypair.pubkey())
+            .finish()
+    }
+}
+
+#[async_trait]
+impl SigningAuthority for DelegatedAgentSigner {
+    async fn signer_for(&self, bucket: &BucketIdentity) -> Result<Keypair, AgentError> {
+        if self.keypair.pubkey() != bucket.creator {
+            return Err(AgentError::Configuration(format!(
+                "Delegated agent {} is not authorized for bucket '{}'",
+                self.keypair.pubkey(), bucket.name
+            )));
+        }
+        Ok(self.keypair.insecure_clone())
+    }
+}
+
+/// Tries each authority in order and returns the first signer that applies
+#[derive(Debug, Default)]
+pub struct SignerChain {
+    authorities: Vec<Arc<dyn SigningAuthority>>,
+}
+
+impl SignerChain {
+    pub fn new(authorities: Vec<Arc<dyn SigningAuthority>>) -> Self {
+        Self { authorities }
+    }
+
+    /// Delegated agent key (if configured) followed by the creator's custodial key
+    pub fn with_delegated_key(db_pool: Pool, delegated_key: Option<&str>) -> Self {
+        let mut authorities: Vec<Arc<dyn SigningAuthority>> = Vec::new();
+        match delegated_key.map(DelegatedAgentSigner::from_base58) {
+            Some(Ok(delegated)) => {
+                info!("Delegated agent signer loaded: {}", delegated.keypair.pubkey());
+                authorities.push(Arc::new(delegated));
+            }
+            Some(Err(e)) => warn!("{}", e),
+            None => {}
+        }
+        authorities.push(Arc::new(CreatorSigner::new(db_pool)));
+        Self::new(authorities)
+    }
+}
+
+#[async_trait]
+impl SigningAuthority for SignerChain {
+    async fn signer_for(&self, bucket: &BucketIdentity) -> Result<Keypair, AgentError> {
+        let mut last_error = AgentError::Configuration("No signing authority configured".to_string());
+        for authority in &self.authorities {
+            match authority.signer_for(bucket).await {
+                Ok(keypair) => return Ok(keypair),
+                Err(e) => {
+                    debug!("[signer_for] {:?} cannot sign for '{}': {}", authority, bucket.name, e);
+                    last_error = e;
+                }
+            }
+        }
+        Err(last_error)
+    }
+}
@@ -0,0 +1,272 @@
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use solana_sdk::signature::Keypair;
+use tracing::{info, warn};
+use crate::agent::confirmation::TransactionCosts;
+use crate::agent::data_fetcher::DataFetcher;
+use crate::agent::router::{scale_amount, RouteExecution, SwapRouter};
+use crate::agent::signer::BucketIdentity;
+use crate::agent::types::{AgentError, TradingPlan};
+
+/// How a large parent order is cut into child orders
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SliceSchedule {
+    /// Equal child orders spaced evenly in time
+    TimeWeighted { slices: u32, interval_ms: u64 },
+    /// Child orders sized so each quote stays within a price-impact budget
+    PriceImpactBudget { max_impact_pct: f64, interval_ms: u64, max_slices: u32 },
+}
+
+/// When and how the executor slices plans
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SlicingConfig {
+    pub enabled: bool,
+    /// Plans with at least this `input_amount` are sliced
+    pub min_parent_amount: u64,
+    pub schedule: SliceSchedule,
+}
+
+impl Default for SlicingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_parent_amount: 1_000_000_000, // 1,000 tokens at 6 decimals
+            schedule: SliceSchedule::TimeWeighted { slices: 4, interval_ms: 15_000 },
+        }
+    }
+}
+
+/// Fill of one child order
+#[derive(Debug, Clone, Serialize)]
+pub struct SliceFill {
+    pub child_id: uuid::Uuid,
+    pub slice_index: u32,
+    pub input_amount: u64,
+    pub quoted_output: u64,
+    pub output_amount: Option<u64>,
+    pub signature: Option<String>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub executed_at: DateTime<Utc>,
+}
+
+/// Outcome of a sliced parent order
+#[derive(Debug, Clone)]
+pub struct SlicedExecution {
+    pub slices: Vec<SliceFill>,
+    pub filled_input: u64,
+    pub filled_output: u64,
+    pub lamports_spent: u64,
+    pub costs: TransactionCosts,
+    /// Why the parent stopped before filling completely
+    pub aborted: Option<String>,
+}
+
+impl SlicedExecution {
+    pub fn is_complete(&self, plan: &TradingPlan) -> bool {
+        self.aborted.is_none() && self.filled_input >= plan.input_amount
+    }
+}
+
+/// Splits large plans into re-quoted child orders and stops if price runs away
+pub struct SlicedExecutor {
+    router: Arc<SwapRouter>,
+    data_fetcher: Arc<DataFetcher>,
+    config: SlicingConfig,
+}
+
+impl SlicedExecutor {
+    pub fn new(router: Arc<SwapRouter>, data_fetcher: Arc<DataFetcher>, config: SlicingConfig) -> Self {
+        Self { router, data_fetcher, config }
+    }
+
+    pub fn should_slice(&self, plan: &TradingPlan) -> bool {
+        self.config.enabled && plan.input_amount >= self.config.min_parent_amount
+    }
+
+    /// Execute a plan as a series of child orders
+    pub async fn execute(
+        &self,
+        plan: &TradingPlan,
+        bucket: &BucketIdentity,
+        keypair: &Keypair,
+    ) -> Result<SlicedExecution, AgentError> {
+        if plan.input_amount == 0 {
+            return Err(AgentError::StrategyExecution("Cannot slice an empty plan".to_string()));
+        }
+
+        let input_mint = plan.input_mint.to_string();
+        let output_mint = plan.output_mint.to_string();
+        // Output per unit of input the plan was quoted; drift is measured from here
+        let reference_price = plan.quoted_output_amount as f64 / plan.input_amount as f64;
+        let (interval, max_slices) = match &self.config.schedule {
+            SliceSchedule::TimeWeighted { slices, interval_ms } => (*interval_ms, (*slices).max(1)),
+            SliceSchedule::PriceImpactBudget { interval_ms, max_slices, .. } => (*interval_ms, (*max_slices).max(1)),
+        };
+
+        let mut execution = SlicedExecution {
+            slices: Vec::new(),
+            filled_input: 0,
+            filled_output: 0,
+            lamports_spent: 0,
+            costs: TransactionCosts::default(),
+            aborted: None,
+        };
+
+        info!("[execute] Slicing plan {} ({} in) into up to {} child orders", plan.id, plan.input_amount, max_slices);
+
+        for slice_index in 0..max_slices {
+            let remaining = plan.input_amount - execution.filled_input;
+            if remaining == 0 {
+                break;
+            }
+            if Utc::now() > plan.expires_at {
+                execution.aborted = Some("Parent plan expired".to_string());
+                break;
+            }
+
+            let is_last = slice_index + 1 == max_slices;
+            let size = match self.slice_size(plan, remaining, max_slices - slice_index, is_last).await {
+                Ok(size) => size,
+                Err(e) => {
+                    execution.aborted = Some(format!("Slice sizing failed: {}", e));
+                    break;
+                }
+            };
+
+            // Re-quote the child right before sending it
+            let quote = match self.data_fetcher.fetch_quote(&input_mint, &output_mint, size).await {
+                Ok(quote) => quote,
+                Err(e) => {
+                    execution.aborted = Some(format!("Re-quote failed: {}", e));
+                    break;
+                }
+            };
+
+            let price = quote.output_amount as f64 / size as f64;
+            let drift_bps = if reference_price > 0.0 {
+                (reference_price - price) / reference_price * 10_000.0
+            } else {
+                0.0
+            };
+            if drift_bps > plan.max_slippage_bps as f64 {
+                warn!("[execute] Price moved {:.1} bps against plan {}, stopping", drift_bps, plan.id);
+                execution.aborted = Some(format!(
+                    "Price moved {:.1} bps, beyond max_slippage_bps {}", drift_bps, plan.max_slippage_bps
+                ));
+                break;
+            }
+
+            let child = Self::child_plan(plan, size);
+            let fill = self.router.execute(&child, bucket, keypair).await;
+            let fill = Self::record_fill(&mut execution, &child, slice_index, quote.output_amount, fill);
+
+            if !fill.success {
+                execution.aborted = Some(format!("Child order {} failed: {}", slice_index, fill.error.unwrap_or_default()));
+                break;
+            }
+
+            if execution.filled_input < plan.input_amount && !is_last {
+                tokio::time::sleep(Duration::from_millis(interval)).await;
+            }
+        }
+
+        info!(
+            "[execute] Plan {} filled {}/{} in over {} slices{}",
+            plan.id,
+            execution.filled_input,
+            plan.input_amount,
+            execution.slices.len(),
+            execution.aborted.as_deref().map(|r| format!(" (stopped: {})", r)).unwrap_or_default()
+        );
+        Ok(execution)
+    }
+
+    /// Size the next child order according to the schedule
+    async fn slice_size(&self, plan: &TradingPlan, remaining: u64, slices_left: u32, is_last: bool) -> Result<u64, AgentError> {
+        if is_last {
+            return Ok(remaining);
+        }
+
+        match &self.config.schedule {
+            SliceSchedule::TimeWeighted { .. } => Ok(remaining.div_ceil(slices_left as u64)),
+            SliceSchedule::PriceImpactBudget { max_impact_pct, .. } => {
+                let quote = self.data_fetcher
+                    .fetch_quote(&plan.input_mint.to_string(), &plan.output_mint.to_string(), remaining)
+                    .await?;
+                if quote.price_impact_pct <= *max_impact_pct || quote.price_impact_pct <= 0.0 {
+                    return Ok(remaining);
+                }
+                // Impact grows roughly linearly with size for constant-product pools
+                let fraction = (*max_impact_pct / quote.price_impact_pct).clamp(0.0, 1.0);
+                let floor = remaining.div_ceil(slices_left as u64);
+                Ok(((remaining as f64 * fraction) as u64).max(floor).min(remaining))
+            }
+        }
+    }
+
+    /// Child order for a slice of the parent
+    fn child_plan(plan: &TradingPlan, size: u64) -> TradingPlan {
+        let mut child = plan.clone();
+        child.id = uuid::Uuid::new_v4();
+        child.input_amount = size;
+        child.quoted_output_amount = scale_amount(plan.quoted_output_amount, size, plan.input_amount);
+        child.min_output_amount = scale_amount(plan.min_output_amount, size, plan.input_amount);
+        child
+    }
+
+    fn record_fill(
+        execution: &mut SlicedExecution,
+        child: &TradingPlan,
+        slice_index: u32,
+        quoted_output: u64,
+        result: Result<RouteExecution, AgentError>,
+    ) -> SliceFill {
+        let fill = match result {
+            Ok(route) => {
+                execution.lamports_spent += route.lamports_spent.unwrap_or(0);
+                execution.costs.add(route.costs);
+                if route.success {
+                    execution.filled_input += child.input_amount;
+                    execution.filled_output += route.output_amount.unwrap_or(0);
+                }
+                SliceFill {
+                    child_id: child.id,
+                    slice_index,
+                    input_amount: child.input_amount,
+                    quoted_output,
+                    output_amount: route.output_amount,
+                    signature: route.signature,
+                    success: route.success,
+                    error: route.error,
+                    executed_at: Utc::now(),
+                }
+            }
+            Err(e) => SliceFill {
+                child_id: child.id,
+                slice_index,
+                input_amount: child.input_amount,
+                quoted_output,
+                output_amount: None,
+                signature: None,
+                success: false,
+                error: Some(e.to_string()),
+                executed_at: Utc::now(),
+            },
+        };
+
+        execution.slices.push(fill.clone());
+        fill
+    }
+}
+
+impl std::fmt::Debug for SlicedExecutor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SlicedExecutor")
+            .field("config", &self.config)
+            .finish()
+    }
+}
@@ -8,16 +8,20 @@ use crate::agent::types::{
     RiskLimits, ExecutionSettings, MarketConditions, Position, AgentError,
     ExecutionContext, RiskAssessment, PriceTrend,
 };
+use crate::agent::position_sizing::PositionSizer;
+use crate::agent::router::{min_output, scale_amount};
 
 #[async_trait]
 pub trait Strategy: Send + Sync {
-    /// Evaluate market data and generate trading plan if conditions are met
+    /// Evaluate market data and generate trading plan if conditions are met,
+    /// sizing it with `sizer`
     async fn evaluate(
         &self,
         quote: &QuoteData,
         market_conditions: &MarketConditions,
         current_positions: &HashMap<String, Position>,
         config: &StrategyConfig,
+        sizer: &PositionSizer,
     ) -> Result<Option<TradingPlan>, AgentError>;
 
     /// Get strategy type
@@ -27,6 +31,22 @@ pub trait Strategy: Send + Sync {
     fn validate_parameters(&self, params: &StrategyParameters) -> Result<(), AgentError>;
 }
 
+/// Bucket a plan trades for, taken from the `bucket_pubkey` custom parameter
+fn configured_bucket(config: &StrategyConfig) -> Result<solana_sdk::pubkey::Pubkey, AgentError> {
+    use std::str::FromStr;
+
+    let value = config.parameters.custom_params.get("bucket_pubkey")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| AgentError::Configuration("Strategy config has no bucket_pubkey".to_string()))?;
+    Ok(solana_sdk::pubkey::Pubkey::from_str(value)?)
+}
+
+/// Quoted output scaled to `input_amount`, and the minimum output after `max_slippage_bps`
+fn plan_outputs(quote: &QuoteData, input_amount: u64, max_slippage_bps: u16) -> (u64, u64) {
+    let quoted_output = scale_amount(quote.output_amount, input_amount, quote.input_amount);
+    (quoted_output, min_output(quoted_output, max_slippage_bps))
+}
+
 /// Arbitrage strategy implementation
 pub struct ArbitrageStrategy;
 
@@ -38,6 +58,7 @@ impl Strategy for ArbitrageStrategy {
         market_conditions: &MarketConditions,
         current_positions: &HashMap<String, Position>,
         config: &StrategyConfig,
+        sizer: &PositionSizer,
     ) -> Result<Option<TradingPlan>, AgentError> {
         // Calculate effective spread considering fees and slippage
         let spread_bps = self.calculate_effective_spread(quote)?;
@@ -63,7 +84,7 @@ impl Strategy for ArbitrageStrategy {
         self.check_risk_limits(current_positions, config)?;
 
         // Generate trading plan
-        let plan = self.create_trading_plan(quote, config, spread_bps).await?;
+        let plan = self.create_trading_plan(quote, market_conditions, current_positions, config, sizer, spread_bps).await?;
         
         info!(
             "Arbitrage opportunity detected: spread {} bps, confidence {}",
@@ -103,10 +124,15 @@ impl ArbitrageStrategy {
         let price_impact_bps = (quote.price_impact_pct * 100.0) as u16;
         let total_fees_bps = quote.slippage_bps + quote.platform_fee_bps + price_impact_bps;
 
-        // Calculate raw spread (simplified)
-        let input_price = quote.input_amount as f64;
-        let output_price = quote.output_amount as f64;
-        let raw_spread = ((output_price / input_price - 1.0) * 10000.0) as u16;
+        // Compare what the swap pays out against what it costs, both in USD
+        let (Some(input_value), Some(output_value)) = (quote.input_value_usd(), quote.output_value_usd()) else {
+            debug!("No USD valuation for {}/{}, skipping spread", quote.input_mint, quote.output_mint);
+            return Ok(0);
+        };
+        if input_value <= 0.0 {
+            return Ok(0);
+        }
+        let raw_spread = ((output_value / input_value - 1.0) * 10000.0) as u16;
 
         // Effective spread after costs
         let effective_spread = raw_spread.saturating_sub(total_fees_bps);
@@ -140,7 +166,7 @@ impl ArbitrageStrategy {
         config: &StrategyConfig,
     ) -> Result<(), AgentError> {
         let total_position_value: f64 = positions.values()
-            .map(|p| p.amount as f64 * p.current_price)
+            .map(|p| p.value_usd())
             .sum();
 
         if total_position_value > config.risk_limits.max_position_size_usd {
@@ -158,7 +184,10 @@ impl ArbitrageStrategy {
     async fn create_trading_plan(
         &self,
         quote: &QuoteData,
+        market_conditions: &MarketConditions,
+        current_positions: &HashMap<String, Position>,
         config: &StrategyConfig,
+        sizer: &PositionSizer,
         spread_bps: u16,
     ) -> Result<TradingPlan, AgentError> {
         use solana_sdk::pubkey::Pubkey;
@@ -170,8 +199,10 @@ impl ArbitrageStrategy {
         let output_mint = Pubkey::from_str(&quote.output_mint)
             .map_err(|e| AgentError::Configuration(format!("Invalid output mint: {}", e)))?;
 
-        // Calculate position size based on strategy config
-        let position_size = (config.parameters.position_size_usd * 1_000_000.0) as u64; // Convert to lamports/tokens
+        let bucket_pubkey = configured_bucket(config)?;
+        let sizing = sizer.size(bucket_pubkey, quote, market_conditions, current_positions, config).await?;
+        let position_size = sizing.input_amount;
+        let (quoted_output, min_output_amount) = plan_outputs(quote, position_size, config.parameters.max_slippage_bps);
 
         // Calculate confidence based on spread and market conditions
         let confidence = self.calculate_confidence(spread_bps, config);
@@ -179,11 +210,12 @@ impl ArbitrageStrategy {
         let plan = TradingPlan {
             id: uuid::Uuid::new_v4(),
             strategy_type: StrategyType::Arbitrage,
-            bucket_pubkey: input_mint, // Placeholder - should be actual bucket
+            bucket_pubkey,
             input_mint,
             output_mint,
             input_amount: position_size,
-            min_output_amount: quote.output_amount,
+            quoted_output_amount: quoted_output,
+            min_output_amount,
             max_slippage_bps: config.parameters.max_slippage_bps,
             priority_fee: self.calculate_priority_fee(&config.execution_settings),
             route_plan: self.encode_route_plan(&quote.route_plan)?,
@@ -196,6 +228,8 @@ impl ArbitrageStrategy {
                     volume_24h: 0.0,
                     price_trend: PriceTrend::Sideways,
                     liquidity_score: 0.5,
+                    indicators: None,
+                    oracle_divergence_bps: None,
                 },
                 risk_assessment: RiskAssessment {
                     risk_score: 0.3, // Arbitrage is generally low risk
@@ -204,6 +238,7 @@ impl ArbitrageStrategy {
                     market_risk_factors: vec!["slippage".to_string(), "timing".to_string()],
                 },
                 ai_reasoning: format!("Arbitrage opportunity with {}bps spread", spread_bps),
+                sizing: Some(sizing),
             },
         };
 
@@ -246,6 +281,7 @@ impl Strategy for GridTradingStrategy {
         market_conditions: &MarketConditions,
         current_positions: &HashMap<String, Position>,
         config: &StrategyConfig,
+        _sizer: &PositionSizer,
     ) -> Result<Option<TradingPlan>, AgentError> {
         // Grid trading logic - check if price hits grid levels
         match market_conditions.price_trend {
@@ -318,6 +354,7 @@ impl Strategy for DCAStrategy {
         market_conditions: &MarketConditions,
         current_positions: &HashMap<String, Position>,
         config: &StrategyConfig,
+        sizer: &PositionSizer,
     ) -> Result<Option<TradingPlan>, AgentError> {
         let pair_key = format!("{}_{}", quote.input_mint, quote.output_mint);
         
@@ -330,7 +367,7 @@ impl Strategy for DCAStrategy {
         }
 
         // DCA regardless of market conditions (that's the point)
-        self.create_dca_plan(quote, config).await.map(Some)
+        self.create_dca_plan(quote, market_conditions, current_positions, config, sizer).await.map(Some)
     }
 
     fn strategy_type(&self) -> StrategyType {
@@ -353,22 +390,29 @@ impl DCAStrategy {
     async fn create_dca_plan(
         &self,
         quote: &QuoteData,
+        market_conditions: &MarketConditions,
+        current_positions: &HashMap<String, Position>,
         config: &StrategyConfig,
+        sizer: &PositionSizer,
     ) -> Result<TradingPlan, AgentError> {
         use solana_sdk::pubkey::Pubkey;
         use std::str::FromStr;
 
         let input_mint = Pubkey::from_str(&quote.input_mint)?;
         let output_mint = Pubkey::from_str(&quote.output_mint)?;
+        let bucket_pubkey = configured_bucket(config)?;
+        let sizing = sizer.size(bucket_pubkey, quote, market_conditions, current_positions, config).await?;
+        let (quoted_output, min_output_amount) = plan_outputs(quote, sizing.input_amount, config.parameters.max_slippage_bps);
 
         let plan = TradingPlan {
             id: uuid::Uuid::new_v4(),
             strategy_type: StrategyType::DCA,
-            bucket_pubkey: input_mint,
+            bucket_pubkey,
             input_mint,
             output_mint,
-            input_amount: (config.parameters.position_size_usd * 1_000_000.0) as u64,
-            min_output_amount: (quote.output_amount as f64 * 0.95) as u64, // 5% slippage tolerance
+            input_amount: sizing.input_amount,
+            quoted_output_amount: quoted_output,
+            min_output_amount,
             max_slippage_bps: config.parameters.max_slippage_bps,
             priority_fee: config.execution_settings.max_priority_fee_lamports / 2, // Lower priority for DCA
             route_plan: bincode::serialize(&quote.route_plan)?,
@@ -381,14 +425,17 @@ impl DCAStrategy {
                     volume_24h: 0.0,
                     price_trend: PriceTrend::Sideways,
                     liquidity_score: 0.5,
+                    indicators: None,
+                    oracle_divergence_bps: None,
                 },
                 risk_assessment: RiskAssessment {
                     risk_score: 0.2, // DCA is low risk
-                    max_loss_estimate: config.parameters.position_size_usd * 0.1, // 10% max loss estimate
+                    max_loss_estimate: sizing.size_usd * 0.1, // 10% max loss estimate
                     position_risk_pct: 2.0,
                     market_risk_factors: vec!["timing".to_string()],
                 },
                 ai_reasoning: "Regular DCA execution regardless of market conditions".to_string(),
+                sizing: Some(sizing),
             },
         };
 
@@ -410,6 +457,11 @@ impl StrategyFactory {
         }
     }
 
+    /// Whether plans of this strategy trade a bucket's vault and so need `bucket_pubkey`
+    pub fn requires_bucket(strategy_type: &StrategyType) -> bool {
+        !matches!(strategy_type, StrategyType::GridTrading)
+    }
+
     pub fn validate_strategy_config(config: &StrategyConfig) -> Result<(), AgentError> {
         let strategy = Self::create_strategy(config.strategy_type.clone());
         strategy.validate_parameters(&config.parameters)
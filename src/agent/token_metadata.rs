@@ -0,0 +1,77 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use tracing::debug;
+use crate::agent::types::AgentError;
+use crate::onchain_instance::rpc::SolanaRpc;
+
+/// Offset of `decimals` in the SPL mint layout, shared by Token and Token-2022
+const MINT_DECIMALS_OFFSET: usize = 44;
+
+/// On-chain facts about a mint needed to interpret raw amounts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenMetadata {
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub token_program: Pubkey,
+}
+
+/// Reads mint accounts once and keeps their metadata for the life of the process
+pub struct TokenMetadataCache {
+    rpc: Arc<SolanaRpc>,
+    cache: DashMap<Pubkey, TokenMetadata>,
+}
+
+impl TokenMetadataCache {
+    pub fn new(rpc: Arc<SolanaRpc>) -> Self {
+        Self {
+            rpc,
+            cache: DashMap::new(),
+        }
+    }
+
+    /// Metadata for a mint, read from chain on first use
+    pub async fn get(&self, mint: &Pubkey) -> Result<TokenMetadata, AgentError> {
+        if let Some(metadata) = self.cache.get(mint) {
+            return Ok(*metadata);
+        }
+
+        let account = self.rpc.call(|rpc| async move { rpc.get_account(mint).await }).await
+            .map_err(|e| AgentError::Configuration(format!("Failed to read mint {}: {}", mint, e)))?;
+        if account.owner != spl_token::ID && account.owner != spl_token_2022::ID {
+            return Err(AgentError::Configuration(format!("{} is not an SPL token mint", mint)));
+        }
+        let decimals = *account.data.get(MINT_DECIMALS_OFFSET)
+            .ok_or_else(|| AgentError::Configuration(format!("Mint {} account is too short", mint)))?;
+
+        let metadata = TokenMetadata { mint: *mint, decimals, token_program: account.owner };
+        debug!("[get] Mint {} has {} decimals", mint, decimals);
+        self.cache.insert(*mint, metadata);
+        Ok(metadata)
+    }
+
+    /// Decimals for a base58 mint address
+    pub async fn decimals(&self, mint: &str) -> Result<u8, AgentError> {
+        Ok(self.get(&Pubkey::from_str(mint)?).await?.decimals)
+    }
+}
+
+impl std::fmt::Debug for TokenMetadataCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenMetadataCache")
+            .field("cached_mints", &self.cache.len())
+            .finish()
+    }
+}
+
+/// Raw base units to whole tokens
+pub fn to_ui_amount(amount: u64, decimals: u8) -> f64 {
+    amount as f64 / 10f64.powi(decimals as i32)
+}
+
+/// Whole tokens to raw base units, rounding down
+pub fn from_ui_amount(ui_amount: f64, decimals: u8) -> u64 {
+    (ui_amount * 10f64.powi(decimals as i32)).max(0.0) as u64
+}
@@ -1,18 +1,27 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{RwLock, mpsc};
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 use chrono::Utc;
 
 use crate::agent::types::{
     StrategyConfig, StrategyType, AgentState, AgentError, 
-    PerformanceMetrics, LearningParameters,
+    PerformanceMetrics, LearningParameters, Position, QuoteData,
 };
 use crate::agent::ai_client::AIClient;
 use crate::agent::data_fetcher::{DataFetcher, DataFetcherStats};
 use crate::agent::planner::{Planner, PlannerStats};
 use crate::agent::executor::{Executor, ExecutorStats};
-use crate::agent::observer::{Observer, ObserverStats};
+use crate::agent::observer::{LearningFeedback, Observer, ObserverStats};
+use crate::agent::candles::CandleAggregator;
+use crate::agent::oracle::{OracleConfig, PriceOracle};
+use crate::agent::circuit_breaker::{CircuitBreakerConfig, CircuitBreakers, TrippedBreaker};
+use crate::services::event_bus::{EventBus, EventPublisher};
+use crate::agent::position_sizing::{PositionSizer, SizingMode};
+use crate::agent::token_metadata::TokenMetadataCache;
+use crate::agent::sliced_execution::SlicingConfig;
+use crate::agent::pool_resolver::PoolResolverConfig;
+use crate::agent::router::RouteExecutionMode;
 use crate::onchain_instance::instance::IcmProgramInstance;
 
 /// Main trading agent that orchestrates all components
@@ -21,11 +30,21 @@ pub struct TradingAgent {
     planner: Arc<Planner>,
     executor: Arc<Executor>,
     observer: Arc<Observer>,
+    circuit_breakers: Arc<CircuitBreakers>,
+    events: EventPublisher,
     agent_state: Arc<RwLock<AgentState>>,
     is_running: Arc<RwLock<bool>>,
+    /// Channel ends no component owns; taken by `start`
+    receivers: tokio::sync::Mutex<Option<AgentReceivers>>,
 }
 
-#[derive(Debug, Clone)]
+struct AgentReceivers {
+    quotes: mpsc::UnboundedReceiver<QuoteData>,
+    learning: mpsc::UnboundedReceiver<LearningFeedback>,
+    positions: mpsc::UnboundedReceiver<HashMap<String, Position>>,
+}
+
+#[derive(Clone)]
 pub struct TradingAgentConfig {
     pub openai_api_key: String,
     pub token_pairs: Vec<(String, String)>,
@@ -35,6 +54,37 @@ pub struct TradingAgentConfig {
     pub monitoring_interval_ms: u64,
     pub max_concurrent_executions: usize,
     pub portfolio_id: uuid::Uuid,
+    /// Bucket PDA this agent trades for; tags every event it publishes
+    pub pool_id: Option<String>,
+    pub slicing: SlicingConfig,
+    pub oracle: OracleConfig,
+    pub circuit_breakers: CircuitBreakerConfig,
+    pub route_mode: RouteExecutionMode,
+    pub pool_resolver: PoolResolverConfig,
+    /// Base58 key the agent may sign with for buckets it created
+    pub delegated_key: Option<String>,
+}
+
+impl std::fmt::Debug for TradingAgentConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TradingAgentConfig")
+            .field("openai_api_key", &"<redacted>")
+            .field("token_pairs", &self.token_pairs)
+            .field("strategy_configs", &self.strategy_configs)
+            .field("data_fetch_interval_ms", &self.data_fetch_interval_ms)
+            .field("plan_evaluation_interval_ms", &self.plan_evaluation_interval_ms)
+            .field("monitoring_interval_ms", &self.monitoring_interval_ms)
+            .field("max_concurrent_executions", &self.max_concurrent_executions)
+            .field("portfolio_id", &self.portfolio_id)
+            .field("pool_id", &self.pool_id)
+            .field("slicing", &self.slicing)
+            .field("oracle", &self.oracle)
+            .field("circuit_breakers", &self.circuit_breakers)
+            .field("route_mode", &self.route_mode)
+            .field("pool_resolver", &self.pool_resolver)
+            .field("delegated_key", &self.delegated_key.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
 }
 
 impl TradingAgent {
@@ -45,39 +95,61 @@ impl TradingAgent {
         config: TradingAgentConfig,
         icm_client: Arc<IcmProgramInstance>,
         db_pool: deadpool_postgres::Pool,
+        event_bus: &EventBus,
     ) -> Result<Self, AgentError> {
         info!("Initializing trading agent with {} token pairs and {} strategies",
               config.token_pairs.len(), config.strategy_configs.len());
 
+        let events = event_bus.publisher(config.pool_id.clone());
+
+        // Shared by the data fetcher (fetch failures) and planner (quote screening)
+        let circuit_breakers = Arc::new(CircuitBreakers::new(config.circuit_breakers.clone()));
+
+        // Every component reads chain state through the instance's shared RPC client
+        let rpc = icm_client.rpc();
+
         // Initialize data fetcher
-        let (data_fetcher, _quote_receiver) = DataFetcher::new(
+        let (data_fetcher, quote_receiver) = DataFetcher::new(
             config.token_pairs.clone(),
             config.data_fetch_interval_ms,
+            Arc::new(TokenMetadataCache::new(Arc::clone(&rpc))),
+            Arc::clone(&circuit_breakers),
         );
         let data_fetcher = Arc::new(data_fetcher);
 
         // Initialize planner
         let ai_client = AIClient::new(config.openai_api_key.clone());
-        let (planner, _plan_receiver) = Planner::new(
+        let (planner, plan_receiver) = Planner::new(
             ai_client,
             config.strategy_configs.clone(),
             config.plan_evaluation_interval_ms,
+            Arc::new(PositionSizer::new(Arc::clone(&rpc), icm_client.usdc_mint(), data_fetcher.token_metadata())),
+            Arc::new(CandleAggregator::new(db_pool.clone())),
+            Arc::new(PriceOracle::from_config(rpc, config.oracle.clone())),
+            Arc::clone(&circuit_breakers),
+            events.clone(),
         );
         let planner = Arc::new(planner);
 
-        // Initialize executor
-    let (executor, _plan_rx, _execution_receiver) = Executor::new(
+        // Initialize executor, fed by the planner's plans
+        let (executor, execution_receiver) = Executor::new(
             icm_client,
-            config.max_concurrent_executions,
+            Arc::clone(&data_fetcher),
+            db_pool.clone(),
+            &config,
+            events.clone(),
+            plan_receiver,
         );
         let executor = Arc::new(executor);
 
-        // Initialize observer
-        let (observer, _exec_rx, learning_receiver, position_receiver) = Observer::new(
+        // Initialize observer, fed by the executor's results
+        let (observer, learning_receiver, position_receiver) = Observer::new(
             config.monitoring_interval_ms,
             db_pool.clone(),
             Arc::clone(&data_fetcher),
             config.portfolio_id,
+            events.clone(),
+            execution_receiver,
         );
         let observer = Arc::new(observer);
 
@@ -98,8 +170,15 @@ impl TradingAgent {
             planner,
             executor,
             observer,
+            circuit_breakers,
+            events,
             agent_state: Arc::new(RwLock::new(initial_state)),
             is_running: Arc::new(RwLock::new(false)),
+            receivers: tokio::sync::Mutex::new(Some(AgentReceivers {
+                quotes: quote_receiver,
+                learning: learning_receiver,
+                positions: position_receiver,
+            })),
         };
 
         info!("Trading agent initialized successfully");
@@ -107,46 +186,73 @@ impl TradingAgent {
     }
 
 
-    /// Start the trading agent
-    pub async fn start(
-        &self,
-        mut quote_receiver: mpsc::UnboundedReceiver<crate::agent::types::QuoteData>,
-        mut plan_receiver: mpsc::UnboundedReceiver<crate::agent::types::TradingPlan>,
-        mut execution_receiver: mpsc::UnboundedReceiver<crate::agent::executor::ExecutionResult>,
-    ) -> Result<(), AgentError> {
+    /// Start the trading agent. An agent runs once; a stopped agent cannot be restarted.
+    pub async fn start(&self) -> Result<(), AgentError> {
         use tokio::task;
         let mut is_running = self.is_running.write().await;
         if *is_running {
             return Ok(());
         }
+        let AgentReceivers { quotes, mut learning, mut positions } = self.receivers.lock().await.take()
+            .ok_or_else(|| AgentError::Configuration("Trading agent already ran and cannot be restarted".to_string()))?;
         *is_running = true;
 
-        // Start DataFetcher
+        // Quotes -> planner -> executor -> observer
         let data_fetcher = Arc::clone(&self.data_fetcher);
+        let events = self.events.clone();
         task::spawn(async move {
-            let _ = data_fetcher.start().await;
+            if let Err(e) = data_fetcher.start().await {
+                warn!("Data fetcher exited: {}", e);
+                events.component_health("data_fetcher", false, Some(e.to_string()));
+            }
         });
 
-        // Start Planner
         let planner = Arc::clone(&self.planner);
+        let events = self.events.clone();
         task::spawn(async move {
-            let _ = planner.start(quote_receiver).await;
+            if let Err(e) = planner.start(quotes).await {
+                warn!("Planner exited: {}", e);
+                events.component_health("planner", false, Some(e.to_string()));
+            }
         });
 
-        // Start Executor
         let executor = Arc::clone(&self.executor);
+        let events = self.events.clone();
         task::spawn(async move {
-            let mut exec = Arc::try_unwrap(executor).ok().expect("Executor Arc should be unique");
-            let _ = exec.start_with_receiver(plan_receiver).await;
+            if let Err(e) = executor.start().await {
+                warn!("Executor exited: {}", e);
+                events.component_health("executor", false, Some(e.to_string()));
+            }
         });
 
-        // Start Observer
         let observer = Arc::clone(&self.observer);
+        let events = self.events.clone();
         task::spawn(async move {
-            let mut obs = Arc::try_unwrap(observer).ok().expect("Observer Arc should be unique");
-            let _ = obs.start_with_receiver(execution_receiver).await;
+            if let Err(e) = observer.start().await {
+                warn!("Observer exited: {}", e);
+                events.component_health("observer", false, Some(e.to_string()));
+            }
         });
 
+        // Observed positions feed back into planning
+        let planner = Arc::clone(&self.planner);
+        task::spawn(async move {
+            while let Some(update) = positions.recv().await {
+                planner.update_positions(update).await;
+            }
+        });
+        task::spawn(async move {
+            while let Some(feedback) = learning.recv().await {
+                debug!("Learning feedback for {:?}: {:?}", feedback.strategy_type, feedback.suggested_adjustments);
+            }
+        });
+
+        self.agent_state.write().await.is_active = true;
+        for component in ["data_fetcher", "planner", "executor", "observer"] {
+            self.events.component_health(component, true, Some("started".to_string()));
+        }
+        info!("Trading agent started");
+
         Ok(())
     }
 
@@ -156,6 +262,11 @@ impl TradingAgent {
 
         let mut is_running = self.is_running.write().await;
         *is_running = false;
+        self.data_fetcher.stop().await;
+        self.planner.stop().await;
+        self.executor.stop().await;
+        self.observer.stop().await;
+        self.events.component_health("agent", false, Some("stopped".to_string()));
 
         // Update agent state
         {
@@ -188,9 +299,15 @@ impl TradingAgent {
             performance: state.performance.clone(),
             active_positions: state.current_positions.len(),
             current_strategy: state.strategy_config.strategy_type.clone(),
+            tripped_breakers: self.circuit_breakers.tripped(),
         })
     }
 
+    /// Bucket PDA the agent trades for
+    pub fn pool_id(&self) -> Option<&str> {
+        self.events.pool_id()
+    }
+
     /// Get current agent state
     pub async fn get_state(&self) -> AgentState {
         self.agent_state.read().await.clone()
@@ -270,13 +387,8 @@ impl TradingAgent {
                 stop_loss_pct: 3.0,
                 take_profit_pct: 10.0,
             },
-            execution_settings: ExecutionSettings {
-                priority_fee_percentile: 75,
-                max_priority_fee_lamports: 100_000,
-                transaction_timeout_ms: 30_000,
-                retry_attempts: 3,
-                jito_tip_lamports: 10_000,
-            },
+            execution_settings: ExecutionSettings::default(),
+            position_sizing: SizingMode::default(),
         }
     }
 
@@ -310,6 +422,8 @@ pub struct AgentStats {
     pub performance: PerformanceMetrics,
     pub active_positions: usize,
     pub current_strategy: StrategyType,
+    /// Pairs currently paused by a circuit breaker
+    pub tripped_breakers: Vec<TrippedBreaker>,
 }
 
 /// Builder for creating trading agent configurations
@@ -322,6 +436,13 @@ pub struct AgentStats {
         monitoring_interval_ms: u64,
         max_concurrent_executions: usize,
         portfolio_id: Option<uuid::Uuid>,
+        pool_id: Option<String>,
+        slicing: SlicingConfig,
+        oracle: OracleConfig,
+        circuit_breakers: CircuitBreakerConfig,
+        route_mode: RouteExecutionMode,
+        pool_resolver: PoolResolverConfig,
+        delegated_key: Option<String>,
     }
 
     impl TradingAgentConfigBuilder {
@@ -335,6 +456,13 @@ pub struct AgentStats {
                 monitoring_interval_ms: 30000, // 30 seconds
                 max_concurrent_executions: 5,
                 portfolio_id: None,
+                pool_id: None,
+                slicing: SlicingConfig::default(),
+                oracle: OracleConfig::default(),
+                circuit_breakers: CircuitBreakerConfig::default(),
+                route_mode: RouteExecutionMode::default(),
+                pool_resolver: PoolResolverConfig::default(),
+                delegated_key: None,
             }
         }
 
@@ -364,6 +492,95 @@ pub struct AgentStats {
             self
         }
 
+        pub fn with_pool_id(mut self, pool_id: String) -> Self {
+            self.pool_id = Some(pool_id);
+            self
+        }
+
+        pub fn with_slicing(mut self, slicing: SlicingConfig) -> Self {
+            self.slicing = slicing;
+            self
+        }
+
+        pub fn with_oracle(mut self, oracle: OracleConfig) -> Self {
+            self.oracle = oracle;
+            self
+        }
+
+        pub fn with_circuit_breakers(mut self, circuit_breakers: CircuitBreakerConfig) -> Self {
+            self.circuit_breakers = circuit_breakers;
+            self
+        }
+
+        /// Every ordered pair of the bucket's tokens and one strategy config, tagged
+        /// with the bucket PDA. Unknown strategy names fall back to DCA.
+        pub fn for_bucket(mut self, bucket_pubkey: Option<String>, strategy: &str, token_bucket: &[String]) -> Self {
+            use crate::agent::types::*;
+
+            self.token_pairs = token_bucket.iter()
+                .flat_map(|input| token_bucket.iter().filter(move |output| *output != input).map(move |output| (input.clone(), output.clone())))
+                .collect();
+
+            let strategy_type = match strategy.to_lowercase().as_str() {
+                "arbitrage" => StrategyType::Arbitrage,
+                "gridtrading" | "grid_trading" => StrategyType::GridTrading,
+                "dca" => StrategyType::DCA,
+                "meanreversion" | "mean_reversion" => StrategyType::MeanReversion,
+                "trendfollowing" | "trend_following" => StrategyType::TrendFollowing,
+                _ => StrategyType::DCA,
+            };
+            let mut custom_params = HashMap::new();
+            if let Some(bucket_pubkey) = &bucket_pubkey {
+                custom_params.insert("bucket_pubkey".to_string(), serde_json::json!(bucket_pubkey));
+            }
+            self.strategy_configs = vec![StrategyConfig {
+                strategy_type,
+                parameters: StrategyParameters {
+                    min_spread_bps: 10,
+                    max_slippage_bps: 50,
+                    position_size_usd: 100.0,
+                    rebalance_threshold_pct: 5.0,
+                    lookback_periods: 10,
+                    custom_params,
+                },
+                risk_limits: RiskLimits {
+                    max_position_size_usd: 1000.0,
+                    max_daily_loss_pct: 10.0,
+                    max_drawdown_pct: 20.0,
+                    stop_loss_pct: 5.0,
+                    take_profit_pct: 10.0,
+                },
+                execution_settings: ExecutionSettings {
+                    priority_fee_percentile: 90,
+                    max_priority_fee_lamports: 10000,
+                    transaction_timeout_ms: 60000,
+                    retry_attempts: 3,
+                    jito_tip_lamports: 0,
+                },
+                position_sizing: Default::default(),
+            }];
+            self.pool_id = bucket_pubkey;
+            self
+        }
+
+        /// Intervals, concurrency, routing, keys and API key from the server
+        /// configuration; an API key already set on the builder is kept
+        pub fn with_agent_defaults(mut self, defaults: &crate::config::AgentDefaults) -> Self {
+            self.route_mode = defaults.route_mode;
+            self.pool_resolver = defaults.pool_resolver.clone();
+            self.delegated_key = defaults.delegated_key.clone();
+            self.data_fetch_interval_ms = defaults.data_fetch_interval_ms;
+            self.plan_evaluation_interval_ms = defaults.plan_evaluation_interval_ms;
+            self.monitoring_interval_ms = defaults.monitoring_interval_ms;
+            self.max_concurrent_executions = defaults.max_concurrent_executions;
+            if self.openai_api_key.is_none() {
+                self.openai_api_key = defaults.openai_api_key.clone();
+            }
+            self.with_slicing(defaults.slicing.clone())
+                .with_oracle(defaults.oracle.clone())
+                .with_circuit_breakers(defaults.circuit_breakers.clone())
+        }
+
         pub fn build(self) -> Result<TradingAgentConfig, AgentError> {
             let openai_api_key = self.openai_api_key
                 .ok_or_else(|| AgentError::Configuration("OpenAI API key required".to_string()))?;
@@ -387,6 +604,13 @@ pub struct AgentStats {
                 monitoring_interval_ms: self.monitoring_interval_ms,
                 max_concurrent_executions: self.max_concurrent_executions,
                 portfolio_id,
+                pool_id: self.pool_id,
+                slicing: self.slicing,
+                oracle: self.oracle,
+                circuit_breakers: self.circuit_breakers,
+                route_mode: self.route_mode,
+                pool_resolver: self.pool_resolver,
+                delegated_key: self.delegated_key,
             })
         }
 }
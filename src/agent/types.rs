@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use solana_sdk::pubkey::Pubkey;
 use rust_decimal::Decimal;
+use crate::agent::position_sizing::{SizingDecision, SizingMode};
+use crate::agent::token_metadata::to_ui_amount;
 
 /// Market data and quotes from Jupiter API
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +20,72 @@ pub struct QuoteData {
     pub price_impact_pct: f64,
     pub route_plan: Vec<RoutePlan>,
     pub timestamp: DateTime<Utc>,
+    /// Mint decimals, filled from the token metadata cache
+    #[serde(default)]
+    pub input_decimals: Option<u8>,
+    #[serde(default)]
+    pub output_decimals: Option<u8>,
+    /// USD price of one whole token, when known
+    #[serde(default)]
+    pub input_price_usd: Option<f64>,
+    #[serde(default)]
+    pub output_price_usd: Option<f64>,
+}
+
+impl QuoteData {
+    /// Output tokens per input token in whole-token units
+    pub fn normalized_price(&self) -> Option<NormalizedPrice> {
+        NormalizedPrice::from_amounts(
+            &self.input_mint,
+            self.input_amount,
+            self.input_decimals?,
+            &self.output_mint,
+            self.output_amount,
+            self.output_decimals?,
+        )
+    }
+
+    pub fn input_value_usd(&self) -> Option<f64> {
+        Some(to_ui_amount(self.input_amount, self.input_decimals?) * self.input_price_usd?)
+    }
+
+    pub fn output_value_usd(&self) -> Option<f64> {
+        Some(to_ui_amount(self.output_amount, self.output_decimals?) * self.output_price_usd?)
+    }
+}
+
+/// Key of a swap direction in per-pair maps, `<input_mint>_<output_mint>`
+pub fn pair_key(input_mint: &str, output_mint: &str) -> String {
+    format!("{}_{}", input_mint, output_mint)
+}
+
+/// Price of one whole `base_mint` token in whole `quote_mint` tokens
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NormalizedPrice {
+    pub base_mint: String,
+    pub quote_mint: String,
+    pub price: f64,
+}
+
+impl NormalizedPrice {
+    /// Price implied by swapping `base_amount` for `quote_amount` (both raw units)
+    pub fn from_amounts(
+        base_mint: &str,
+        base_amount: u64,
+        base_decimals: u8,
+        quote_mint: &str,
+        quote_amount: u64,
+        quote_decimals: u8,
+    ) -> Option<Self> {
+        if base_amount == 0 || quote_amount == 0 {
+            return None;
+        }
+        Some(Self {
+            base_mint: base_mint.to_string(),
+            quote_mint: quote_mint.to_string(),
+            price: to_ui_amount(quote_amount, quote_decimals) / to_ui_amount(base_amount, base_decimals),
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,6 +113,8 @@ pub struct StrategyConfig {
     pub parameters: StrategyParameters,
     pub risk_limits: RiskLimits,
     pub execution_settings: ExecutionSettings,
+    #[serde(default)]
+    pub position_sizing: SizingMode,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Hash, Eq, PartialEq)]
@@ -84,6 +154,18 @@ pub struct ExecutionSettings {
     pub jito_tip_lamports: u64,
 }
 
+impl Default for ExecutionSettings {
+    fn default() -> Self {
+        Self {
+            priority_fee_percentile: 75,
+            max_priority_fee_lamports: 100_000,
+            transaction_timeout_ms: 30_000,
+            retry_attempts: 3,
+            jito_tip_lamports: 10_000,
+        }
+    }
+}
+
 /// Trading plan generated by the agent
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradingPlan {
@@ -93,6 +175,10 @@ pub struct TradingPlan {
     pub input_mint: Pubkey,
     pub output_mint: Pubkey,
     pub input_amount: u64,
+    /// Quoted output for `input_amount`, before slippage
+    #[serde(default)]
+    pub quoted_output_amount: u64,
+    /// `quoted_output_amount` less `max_slippage_bps`
     pub min_output_amount: u64,
     pub max_slippage_bps: u16,
     pub priority_fee: u64,
@@ -108,6 +194,9 @@ pub struct ExecutionContext {
     pub market_conditions: MarketConditions,
     pub risk_assessment: RiskAssessment,
     pub ai_reasoning: String,
+    /// How the plan's input amount was chosen
+    #[serde(default)]
+    pub sizing: Option<SizingDecision>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -116,6 +205,25 @@ pub struct MarketConditions {
     pub volume_24h: f64,
     pub price_trend: PriceTrend,
     pub liquidity_score: f64,
+    /// Candle-based indicators for the pair that triggered the evaluation
+    #[serde(default)]
+    pub indicators: Option<MarketIndicators>,
+    /// Gap between the latest quote and the oracle-implied price, in bps
+    #[serde(default)]
+    pub oracle_divergence_bps: Option<f64>,
+}
+
+/// Indicators computed from OHLCV candles of one interval
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketIndicators {
+    pub interval: String,
+    pub candle_count: usize,
+    pub atr: Option<f64>,
+    /// Realized volatility of log returns, scaled to 24 hours
+    pub realized_volatility_24h: Option<f64>,
+    pub ema_fast: Option<f64>,
+    pub ema_slow: Option<f64>,
+    pub last_close: Option<f64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -164,12 +272,29 @@ pub struct Position {
     pub bucket_pubkey: Pubkey,
     pub token_mint: Pubkey,
     pub amount: u64,
+    /// Mint decimals of `amount`
+    pub decimals: u8,
+    /// USD per whole token
     pub entry_price: f64,
     pub current_price: f64,
     pub unrealized_pnl: f64,
     pub opened_at: DateTime<Utc>,
 }
 
+impl Position {
+    pub fn ui_amount(&self) -> f64 {
+        to_ui_amount(self.amount, self.decimals)
+    }
+
+    pub fn value_usd(&self) -> f64 {
+        self.ui_amount() * self.current_price
+    }
+
+    pub fn unrealized_pnl_usd(&self) -> f64 {
+        self.ui_amount() * (self.current_price - self.entry_price)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LearningParameters {
     pub learning_rate: f64,
@@ -264,3 +389,70 @@ where
         .collect();
     string_map.serialize(serializer)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    const BONK: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+
+    fn approx(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() <= expected.abs() * 1e-12, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn price_accounts_for_both_mints_decimals() {
+        // 2 SOL (9 decimals) for 300 USDC (6 decimals)
+        let price = NormalizedPrice::from_amounts(SOL, 2_000_000_000, 9, USDC, 300_000_000, 6).unwrap();
+        assert_eq!(price.base_mint, SOL);
+        assert_eq!(price.quote_mint, USDC);
+        approx(price.price, 150.0);
+    }
+
+    #[test]
+    fn inverse_direction_gives_the_reciprocal() {
+        let forward = NormalizedPrice::from_amounts(SOL, 2_000_000_000, 9, USDC, 300_000_000, 6).unwrap();
+        let inverse = NormalizedPrice::from_amounts(USDC, 300_000_000, 6, SOL, 2_000_000_000, 9).unwrap();
+        approx(forward.price * inverse.price, 1.0);
+    }
+
+    #[test]
+    fn tiny_prices_keep_their_precision() {
+        // 1 USDC buys 45,000 BONK (5 decimals)
+        let price = NormalizedPrice::from_amounts(BONK, 4_500_000_000, 5, USDC, 1_000_000, 6).unwrap();
+        approx(price.price, 1.0 / 45_000.0);
+    }
+
+    #[test]
+    fn zero_amounts_have_no_price() {
+        assert!(NormalizedPrice::from_amounts(SOL, 0, 9, USDC, 300_000_000, 6).is_none());
+        assert!(NormalizedPrice::from_amounts(SOL, 2_000_000_000, 9, USDC, 0, 6).is_none());
+    }
+
+    #[test]
+    fn quote_price_needs_both_decimals() {
+        let mut quote = QuoteData {
+            input_mint: SOL.to_string(),
+            output_mint: USDC.to_string(),
+            input_amount: 1_000_000_000,
+            output_amount: 150_000_000,
+            other_amount_threshold: 0,
+            swap_mode: "ExactIn".to_string(),
+            slippage_bps: 50,
+            platform_fee_bps: 0,
+            price_impact_pct: 0.0,
+            route_plan: Vec::new(),
+            timestamp: Utc::now(),
+            input_decimals: Some(9),
+            output_decimals: None,
+            input_price_usd: None,
+            output_price_usd: None,
+        };
+        assert!(quote.normalized_price().is_none());
+
+        quote.output_decimals = Some(6);
+        approx(quote.normalized_price().unwrap().price, 150.0);
+    }
+}
@@ -1,3 +1,563 @@
+//! # Configuration
+//!
+//! Typed server configuration. Values come from an optional TOML file
+//! (`ICM_CONFIG_FILE`, or `icm.toml` in the working directory when present) and
+//! are then overridden by environment variables. [`AppConfig::load`] validates
+//! the result and refuses unsafe defaults when running in production.
+//!
+//! ## Environment variables
+//! - `APP_ENV`: `development` (default) or `production`
+//! - `PORT`: HTTP port (default 3000)
+//! - `SOLANA_CLUSTER`: `devnet` (default), `testnet`, `mainnet`, `localnet` or a URL
+//! - `SOLANA_RPC_URLS` (comma-separated) or `SOLANA_RPC_URL`: defaults to the cluster URL
+//! - `SOLANA_COMMITMENT`, `SOLANA_RPC_TIMEOUT_SECS`, `SOLANA_CONFIRM_TIMEOUT_SECS`
+//! - `ICM_PROGRAM_ID`, `USDC_MINT`
+//! - `CORS_ALLOWED_ORIGINS` (comma-separated)
+//! - `JWT_SECRET`
+//! - `OPENAI_API_KEY`, `AGENT_DATA_FETCH_INTERVAL_MS`, `AGENT_PLAN_EVALUATION_INTERVAL_MS`,
+//!   `AGENT_MONITORING_INTERVAL_MS`, `AGENT_MAX_CONCURRENT_EXECUTIONS`
+//! - `AGENT_ROUTE_MODE`: `sequential` (default) or `atomic`
+//! - `AGENT_PRIVATE_KEY` (base58): delegated key agents sign with for buckets it created
+//! - `RAYDIUM_AMM_PROGRAM`, `RAYDIUM_POOL_REGISTRY` (JSON pool list), `DEFAULT_AMM`
+//! - `PYTH_PRICE_FEEDS`: JSON object of mint to Pyth price account
+//! - `PRIORITY_FEE_PERCENTILE`, `MAX_PRIORITY_FEE_LAMPORTS`, `COMPUTE_UNIT_HEADROOM_PERCENT`
+//! - `BUCKET_SCHEDULER_ENABLED`, `BUCKET_SCHEDULER_POLL_SECS`, `BUCKET_UNWIND_LEAD_MINUTES`,
+//!   `BUCKET_UNWIND_SLIPPAGE_BPS`
+//!
+//! ## TOML file
+//! ```toml
+//! environment = "production"
+//! port = 3000
+//! cors_origins = ["https://fr-icm-ui.vercel.app"]
+//!
+//! [solana]
+//! cluster = "devnet"
+//! rpc_urls = ["https://api.devnet.solana.com"]
+//! commitment = "confirmed"
+//! usdc_mint = "2RgRJx3z426TMCL84ZMXTRVCS5ee7iGVE4ogqcUAd3tg"
+//!
+//! [agent]
+//! data_fetch_interval_ms = 5000
+//! route_mode = "atomic"
+//! raydium_pool_registry = "/etc/icm/raydium_pools.json"
+//!
+//! [agent.oracle]
+//! max_divergence_bps = 150
+//! require_oracle = true
+//! price_feeds_path = "/etc/icm/pyth_feeds.json"
+//!
+//! [agent.circuit_breakers]
+//! cooldown_secs = 600
+//!
+//! [execution]
+//! priority_fee_percentile = 75
+//! max_priority_fee_lamports = 100000
+//!
+//! [scheduler]
+//! enabled = true
+//! unwind_lead_minutes = 15
+//! ```
 
-// This module previously defined configuration statics and structs.
-// All unused code has been removed to ensure zero warnings.
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use anchor_client::Cluster;
+use anyhow::{anyhow, Context, Result};
+use axum::http::HeaderValue;
+use serde::Deserialize;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::agent::circuit_breaker::CircuitBreakerConfig;
+use crate::agent::oracle::{OracleConfig, PriceOracle};
+use crate::agent::pool_resolver::{PoolResolverConfig, RaydiumPoolResolver};
+use crate::agent::router::RouteExecutionMode;
+use crate::agent::signer::DelegatedAgentSigner;
+use crate::agent::sliced_execution::SlicingConfig;
+use crate::agent::types::ExecutionSettings;
+use crate::onchain_instance::compute_budget::ComputeBudgetPolicy;
+use crate::onchain_instance::instance::ICM_PROGRAM_ID;
+use crate::onchain_instance::rpc::RpcConfig;
+use crate::services::bucket_scheduler::SchedulerConfig;
+
+/// Devnet USDC mint used by the deployed program and the faucet
+pub const DEFAULT_USDC_MINT: &str = "2RgRJx3z426TMCL84ZMXTRVCS5ee7iGVE4ogqcUAd3tg";
+const DEFAULT_CONFIG_FILE: &str = "icm.toml";
+const DEV_JWT_SECRET: &str = "dev_secret";
+const MIN_JWT_SECRET_LEN: usize = 32;
+const DEFAULT_CORS_ORIGINS: [&str; 2] = ["https://fr-icm-ui.vercel.app", "http://localhost:3001"];
+
+/// Deployment environment; production enables the strict checks in [`AppConfig::validate`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    Development,
+    Production,
+}
+
+impl FromStr for Environment {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "development" | "dev" => Ok(Self::Development),
+            "production" | "prod" => Ok(Self::Production),
+            other => Err(anyhow!("Unknown environment '{}', expected development or production", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for Environment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Development => write!(f, "development"),
+            Self::Production => write!(f, "production"),
+        }
+    }
+}
+
+/// Defaults applied to trading agents started through the API
+#[derive(Clone)]
+pub struct AgentDefaults {
+    pub openai_api_key: Option<String>,
+    pub data_fetch_interval_ms: u64,
+    pub plan_evaluation_interval_ms: u64,
+    pub monitoring_interval_ms: u64,
+    pub max_concurrent_executions: usize,
+    pub slicing: SlicingConfig,
+    pub oracle: OracleConfig,
+    pub circuit_breakers: CircuitBreakerConfig,
+    pub route_mode: RouteExecutionMode,
+    pub pool_resolver: PoolResolverConfig,
+    /// Base58 key agents sign with for buckets it created
+    pub delegated_key: Option<String>,
+}
+
+impl Default for AgentDefaults {
+    fn default() -> Self {
+        Self {
+            openai_api_key: None,
+            data_fetch_interval_ms: 5000,
+            plan_evaluation_interval_ms: 10000,
+            monitoring_interval_ms: 30000,
+            max_concurrent_executions: 5,
+            slicing: SlicingConfig::default(),
+            oracle: OracleConfig::default(),
+            circuit_breakers: CircuitBreakerConfig::default(),
+            route_mode: RouteExecutionMode::default(),
+            pool_resolver: PoolResolverConfig::default(),
+            delegated_key: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for AgentDefaults {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AgentDefaults")
+            .field("openai_api_key", &self.openai_api_key.as_ref().map(|_| "<redacted>"))
+            .field("data_fetch_interval_ms", &self.data_fetch_interval_ms)
+            .field("plan_evaluation_interval_ms", &self.plan_evaluation_interval_ms)
+            .field("monitoring_interval_ms", &self.monitoring_interval_ms)
+            .field("max_concurrent_executions", &self.max_concurrent_executions)
+            .field("slicing", &self.slicing)
+            .field("oracle", &self.oracle)
+            .field("circuit_breakers", &self.circuit_breakers)
+            .field("route_mode", &self.route_mode)
+            .field("pool_resolver", &self.pool_resolver)
+            .field("delegated_key", &self.delegated_key.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+/// Validated server configuration
+#[derive(Clone)]
+pub struct AppConfig {
+    pub environment: Environment,
+    pub port: u16,
+    pub cluster: Cluster,
+    pub rpc: RpcConfig,
+    pub program_id: Pubkey,
+    pub usdc_mint: Pubkey,
+    pub cors_origins: Vec<String>,
+    pub jwt_secret: String,
+    pub agent: AgentDefaults,
+    /// Compute budget and priority fee bounds for program transactions
+    pub compute_budget: ComputeBudgetPolicy,
+    /// Automated start, unwind and close of custodial buckets
+    pub scheduler: SchedulerConfig,
+}
+
+impl std::fmt::Debug for AppConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AppConfig")
+            .field("environment", &self.environment)
+            .field("port", &self.port)
+            .field("cluster", &self.cluster)
+            .field("rpc", &self.rpc)
+            .field("program_id", &self.program_id)
+            .field("usdc_mint", &self.usdc_mint)
+            .field("cors_origins", &self.cors_origins)
+            .field("jwt_secret", &"<redacted>")
+            .field("agent", &self.agent)
+            .field("compute_budget", &self.compute_budget)
+            .field("scheduler", &self.scheduler)
+            .finish()
+    }
+}
+
+/// Optional TOML file layout; every field may be omitted
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileConfig {
+    environment: Option<Environment>,
+    port: Option<u16>,
+    cors_origins: Option<Vec<String>>,
+    jwt_secret: Option<String>,
+    solana: SolanaFileConfig,
+    agent: AgentFileConfig,
+    execution: ExecutionFileConfig,
+    scheduler: SchedulerFileConfig,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SolanaFileConfig {
+    cluster: Option<String>,
+    rpc_urls: Option<Vec<String>>,
+    commitment: Option<String>,
+    request_timeout_secs: Option<u64>,
+    confirm_timeout_secs: Option<u64>,
+    program_id: Option<String>,
+    usdc_mint: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct AgentFileConfig {
+    openai_api_key: Option<String>,
+    data_fetch_interval_ms: Option<u64>,
+    plan_evaluation_interval_ms: Option<u64>,
+    monitoring_interval_ms: Option<u64>,
+    max_concurrent_executions: Option<usize>,
+    slicing: Option<SlicingConfig>,
+    oracle: Option<OracleConfig>,
+    circuit_breakers: Option<CircuitBreakerConfig>,
+    route_mode: Option<String>,
+    raydium_amm_program: Option<String>,
+    raydium_pool_registry: Option<PathBuf>,
+    default_amm: Option<String>,
+    delegated_key: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ExecutionFileConfig {
+    priority_fee_percentile: Option<u8>,
+    max_priority_fee_lamports: Option<u64>,
+    compute_unit_headroom_percent: Option<u32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SchedulerFileConfig {
+    enabled: Option<bool>,
+    poll_interval_secs: Option<u64>,
+    unwind_lead_minutes: Option<u64>,
+    unwind_slippage_bps: Option<u16>,
+}
+
+impl FileConfig {
+    fn read(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))
+    }
+
+    /// Override file values with any environment variables that are set
+    fn apply_env(&mut self) -> Result<()> {
+        if let Some(v) = env_parse("APP_ENV")? { self.environment = Some(v); }
+        if let Some(v) = env_parse("PORT")? { self.port = Some(v); }
+        if let Some(v) = env_list("CORS_ALLOWED_ORIGINS") { self.cors_origins = Some(v); }
+        if let Some(v) = env_var("JWT_SECRET") { self.jwt_secret = Some(v); }
+
+        let solana = &mut self.solana;
+        if let Some(v) = env_var("SOLANA_CLUSTER") { solana.cluster = Some(v); }
+        if let Some(v) = env_list("SOLANA_RPC_URLS").or_else(|| env_list("SOLANA_RPC_URL")) { solana.rpc_urls = Some(v); }
+        if let Some(v) = env_var("SOLANA_COMMITMENT") { solana.commitment = Some(v); }
+        if let Some(v) = env_parse("SOLANA_RPC_TIMEOUT_SECS")? { solana.request_timeout_secs = Some(v); }
+        if let Some(v) = env_parse("SOLANA_CONFIRM_TIMEOUT_SECS")? { solana.confirm_timeout_secs = Some(v); }
+        if let Some(v) = env_var("ICM_PROGRAM_ID") { solana.program_id = Some(v); }
+        if let Some(v) = env_var("USDC_MINT").or_else(|| env_var("USDC_MINT_ADDRESS")) { solana.usdc_mint = Some(v); }
+
+        let agent = &mut self.agent;
+        if let Some(v) = env_var("OPENAI_API_KEY") { agent.openai_api_key = Some(v); }
+        if let Some(v) = env_parse("AGENT_DATA_FETCH_INTERVAL_MS")? { agent.data_fetch_interval_ms = Some(v); }
+        if let Some(v) = env_parse("AGENT_PLAN_EVALUATION_INTERVAL_MS")? { agent.plan_evaluation_interval_ms = Some(v); }
+        if let Some(v) = env_parse("AGENT_MONITORING_INTERVAL_MS")? { agent.monitoring_interval_ms = Some(v); }
+        if let Some(v) = env_parse("AGENT_MAX_CONCURRENT_EXECUTIONS")? { agent.max_concurrent_executions = Some(v); }
+        if let Some(v) = env_var("AGENT_ROUTE_MODE") { agent.route_mode = Some(v); }
+        if let Some(v) = env_var("RAYDIUM_AMM_PROGRAM") { agent.raydium_amm_program = Some(v); }
+        if let Some(v) = env_var("RAYDIUM_POOL_REGISTRY") { agent.raydium_pool_registry = Some(PathBuf::from(v)); }
+        if let Some(v) = env_var("DEFAULT_AMM") { agent.default_amm = Some(v); }
+        if let Some(v) = env_var("AGENT_PRIVATE_KEY") { agent.delegated_key = Some(v); }
+        if let Some(v) = env_var("PYTH_PRICE_FEEDS") {
+            agent.oracle.get_or_insert_with(OracleConfig::default).price_feeds_path = Some(PathBuf::from(v));
+        }
+
+        let execution = &mut self.execution;
+        if let Some(v) = env_parse("PRIORITY_FEE_PERCENTILE")? { execution.priority_fee_percentile = Some(v); }
+        if let Some(v) = env_parse("MAX_PRIORITY_FEE_LAMPORTS")? { execution.max_priority_fee_lamports = Some(v); }
+        if let Some(v) = env_parse("COMPUTE_UNIT_HEADROOM_PERCENT")? { execution.compute_unit_headroom_percent = Some(v); }
+
+        let scheduler = &mut self.scheduler;
+        if let Some(v) = env_parse("BUCKET_SCHEDULER_ENABLED")? { scheduler.enabled = Some(v); }
+        if let Some(v) = env_parse("BUCKET_SCHEDULER_POLL_SECS")? { scheduler.poll_interval_secs = Some(v); }
+        if let Some(v) = env_parse("BUCKET_UNWIND_LEAD_MINUTES")? { scheduler.unwind_lead_minutes = Some(v); }
+        if let Some(v) = env_parse("BUCKET_UNWIND_SLIPPAGE_BPS")? { scheduler.unwind_slippage_bps = Some(v); }
+        Ok(())
+    }
+}
+
+impl AppConfig {
+    /// Load the TOML file (if any), apply environment overrides and validate
+    pub fn load() -> Result<Self> {
+        let path = match env_var("ICM_CONFIG_FILE") {
+            Some(path) => Some(PathBuf::from(path)),
+            None => Some(PathBuf::from(DEFAULT_CONFIG_FILE)).filter(|p| p.exists()),
+        };
+
+        let mut file = match &path {
+            Some(path) => {
+                tracing::info!("[load] Reading configuration from {}", path.display());
+                FileConfig::read(path)?
+            }
+            None => FileConfig::default(),
+        };
+        file.apply_env()?;
+
+        let config = Self::resolve(file)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Fill in defaults and parse typed values
+    fn resolve(file: FileConfig) -> Result<Self> {
+        let solana = file.solana;
+        let rpc_defaults = RpcConfig::default();
+
+        let cluster = match solana.cluster.as_deref() {
+            Some(name) => Cluster::from_str(name).map_err(|e| anyhow!("Invalid SOLANA_CLUSTER '{}': {}", name, e))?,
+            None => Cluster::Devnet,
+        };
+
+        let commitment = match solana.commitment.as_deref() {
+            None => rpc_defaults.commitment,
+            Some("processed") => CommitmentConfig::processed(),
+            Some("confirmed") => CommitmentConfig::confirmed(),
+            Some("finalized") => CommitmentConfig::finalized(),
+            Some(other) => return Err(anyhow!("Invalid SOLANA_COMMITMENT '{}', expected processed, confirmed or finalized", other)),
+        };
+
+        let rpc = RpcConfig {
+            urls: solana.rpc_urls
+                .filter(|urls| !urls.is_empty())
+                .unwrap_or_else(|| vec![cluster.url().to_string()]),
+            commitment,
+            request_timeout: solana.request_timeout_secs.map(Duration::from_secs).unwrap_or(rpc_defaults.request_timeout),
+            confirm_timeout: solana.confirm_timeout_secs.map(Duration::from_secs).unwrap_or(rpc_defaults.confirm_timeout),
+        };
+
+        let program_id = match solana.program_id.as_deref() {
+            Some(id) => Pubkey::from_str(id).map_err(|e| anyhow!("Invalid ICM_PROGRAM_ID '{}': {}", id, e))?,
+            None => ICM_PROGRAM_ID,
+        };
+        let usdc_mint = solana.usdc_mint.as_deref().unwrap_or(DEFAULT_USDC_MINT);
+        let usdc_mint = Pubkey::from_str(usdc_mint).map_err(|e| anyhow!("Invalid USDC_MINT '{}': {}", usdc_mint, e))?;
+
+        let route_mode = match file.agent.route_mode.as_deref() {
+            None | Some("sequential") => RouteExecutionMode::Sequential,
+            Some("atomic") => RouteExecutionMode::Atomic,
+            Some(other) => return Err(anyhow!("Invalid AGENT_ROUTE_MODE '{}', expected sequential or atomic", other)),
+        };
+        let mut pool_resolver = PoolResolverConfig {
+            registry_path: file.agent.raydium_pool_registry,
+            ..PoolResolverConfig::default()
+        };
+        if let Some(id) = file.agent.raydium_amm_program.as_deref() {
+            pool_resolver.program_id = Pubkey::from_str(id).map_err(|e| anyhow!("Invalid RAYDIUM_AMM_PROGRAM '{}': {}", id, e))?;
+        }
+        if let Some(amm) = file.agent.default_amm.as_deref() {
+            pool_resolver.seed_amm = Some(Pubkey::from_str(amm).map_err(|e| anyhow!("Invalid DEFAULT_AMM '{}': {}", amm, e))?);
+        }
+
+        let agent_defaults = AgentDefaults::default();
+        let agent = AgentDefaults {
+            openai_api_key: file.agent.openai_api_key,
+            data_fetch_interval_ms: file.agent.data_fetch_interval_ms.unwrap_or(agent_defaults.data_fetch_interval_ms),
+            plan_evaluation_interval_ms: file.agent.plan_evaluation_interval_ms.unwrap_or(agent_defaults.plan_evaluation_interval_ms),
+            monitoring_interval_ms: file.agent.monitoring_interval_ms.unwrap_or(agent_defaults.monitoring_interval_ms),
+            max_concurrent_executions: file.agent.max_concurrent_executions.unwrap_or(agent_defaults.max_concurrent_executions),
+            slicing: file.agent.slicing.unwrap_or(agent_defaults.slicing),
+            oracle: file.agent.oracle.unwrap_or(agent_defaults.oracle),
+            circuit_breakers: file.agent.circuit_breakers.unwrap_or(agent_defaults.circuit_breakers),
+            route_mode,
+            pool_resolver,
+            delegated_key: file.agent.delegated_key,
+        };
+
+        let execution_defaults = ExecutionSettings::default();
+        let execution = ExecutionSettings {
+            priority_fee_percentile: file.execution.priority_fee_percentile.unwrap_or(execution_defaults.priority_fee_percentile),
+            max_priority_fee_lamports: file.execution.max_priority_fee_lamports.unwrap_or(execution_defaults.max_priority_fee_lamports),
+            ..execution_defaults
+        };
+        let mut compute_budget = ComputeBudgetPolicy::from_execution_settings(&execution);
+        if let Some(headroom) = file.execution.compute_unit_headroom_percent {
+            compute_budget = compute_budget.with_headroom_percent(headroom);
+        }
+
+        let mut scheduler = SchedulerConfig::default();
+        if let Some(enabled) = file.scheduler.enabled {
+            scheduler = scheduler.with_enabled(enabled);
+        }
+        if let Some(secs) = file.scheduler.poll_interval_secs {
+            scheduler = scheduler.with_poll_interval(Duration::from_secs(secs));
+        }
+        if let Some(minutes) = file.scheduler.unwind_lead_minutes {
+            scheduler = scheduler.with_unwind_lead(Duration::from_secs(minutes * 60));
+        }
+        if let Some(bps) = file.scheduler.unwind_slippage_bps {
+            scheduler = scheduler.with_unwind_slippage_bps(bps);
+        }
+
+        Ok(Self {
+            environment: file.environment.unwrap_or(Environment::Development),
+            port: file.port.unwrap_or(3000),
+            cluster,
+            rpc,
+            program_id,
+            usdc_mint,
+            cors_origins: file.cors_origins
+                .unwrap_or_else(|| DEFAULT_CORS_ORIGINS.iter().map(|o| o.to_string()).collect()),
+            jwt_secret: file.jwt_secret.unwrap_or_else(|| DEV_JWT_SECRET.to_string()),
+            agent,
+            compute_budget,
+            scheduler,
+        })
+    }
+
+    /// Check the configuration, reporting every problem at once
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+
+        if self.program_id != ICM_PROGRAM_ID {
+            problems.push(format!(
+                "ICM_PROGRAM_ID {} does not match the program this server was built against ({})",
+                self.program_id, ICM_PROGRAM_ID
+            ));
+        }
+        for url in &self.rpc.urls {
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                problems.push(format!("RPC URL '{}' must be http(s)", url));
+            }
+        }
+        if let Err(e) = self.allowed_origins() {
+            problems.push(e.to_string());
+        }
+        if self.agent.data_fetch_interval_ms == 0
+            || self.agent.plan_evaluation_interval_ms == 0
+            || self.agent.monitoring_interval_ms == 0
+        {
+            problems.push("Agent intervals must be greater than zero".to_string());
+        }
+        if self.agent.max_concurrent_executions == 0 {
+            problems.push("AGENT_MAX_CONCURRENT_EXECUTIONS must be at least 1".to_string());
+        }
+        if self.agent.oracle.max_divergence_bps == 0 {
+            problems.push("agent.oracle.max_divergence_bps must be greater than zero".to_string());
+        }
+        if self.agent.circuit_breakers.max_quote_age_ms == 0 || self.agent.circuit_breakers.jump_sigma <= 0.0 {
+            problems.push("agent.circuit_breakers max_quote_age_ms and jump_sigma must be greater than zero".to_string());
+        }
+        if let Some(path) = &self.agent.pool_resolver.registry_path
+            && let Err(e) = RaydiumPoolResolver::load_registry(path)
+        {
+            problems.push(format!("RAYDIUM_POOL_REGISTRY {}: {}", path.display(), e));
+        }
+        if let Some(path) = &self.agent.oracle.price_feeds_path
+            && let Err(e) = PriceOracle::load_feeds(path)
+        {
+            problems.push(format!("PYTH_PRICE_FEEDS {}: {}", path.display(), e));
+        }
+        if let Some(key) = &self.agent.delegated_key
+            && let Err(e) = DelegatedAgentSigner::from_base58(key)
+        {
+            problems.push(e.to_string());
+        }
+        if self.compute_budget.priority_fee_percentile > 100 {
+            problems.push("PRIORITY_FEE_PERCENTILE must be between 0 and 100".to_string());
+        }
+        if self.scheduler.poll_interval.is_zero() {
+            problems.push("BUCKET_SCHEDULER_POLL_SECS must be greater than zero".to_string());
+        }
+        if self.scheduler.unwind_slippage_bps > 10_000 {
+            problems.push("BUCKET_UNWIND_SLIPPAGE_BPS must be at most 10000".to_string());
+        }
+
+        if self.is_production() {
+            if self.jwt_secret == DEV_JWT_SECRET {
+                problems.push("JWT_SECRET must be set in production".to_string());
+            } else if self.jwt_secret.len() < MIN_JWT_SECRET_LEN {
+                problems.push(format!("JWT_SECRET must be at least {} characters in production", MIN_JWT_SECRET_LEN));
+            }
+            if self.cors_origins.iter().any(|o| o.contains("localhost") || o.contains("127.0.0.1") || o == "*") {
+                problems.push("CORS_ALLOWED_ORIGINS must not include localhost or '*' in production".to_string());
+            }
+        } else if self.jwt_secret == DEV_JWT_SECRET {
+            tracing::warn!("[validate] JWT_SECRET not set, using the development secret");
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!("Invalid configuration:\n  - {}", problems.join("\n  - ")))
+        }
+    }
+
+    pub fn is_production(&self) -> bool {
+        self.environment == Environment::Production
+    }
+
+    /// CORS origins as header values
+    pub fn allowed_origins(&self) -> Result<Vec<HeaderValue>> {
+        self.cors_origins.iter()
+            .map(|origin| HeaderValue::from_str(origin).map_err(|_| anyhow!("Invalid CORS origin '{}'", origin)))
+            .collect()
+    }
+}
+
+/// Non-empty, trimmed environment variable
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Comma-separated environment variable
+fn env_list(name: &str) -> Option<Vec<String>> {
+    env_var(name)
+        .map(|v| v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect::<Vec<_>>())
+        .filter(|items| !items.is_empty())
+}
+
+/// Environment variable parsed as `T`; set but unparsable is an error
+fn env_parse<T>(name: &str) -> Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    env_var(name)
+        .map(|v| v.parse::<T>().map_err(|e| anyhow!("Invalid {} '{}': {}", name, v, e)))
+        .transpose()
+}
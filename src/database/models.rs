@@ -44,6 +44,17 @@ impl FromRow for UserProfile {
     }
 }
 
+impl UserProfile {
+    /// Fetch the stored private key bytes for a user by pubkey
+    pub async fn fetch_private_key_by_pubkey(pool: &Pool, user_pubkey: &str) -> Result<Option<Vec<i32>>> {
+        let client = pool.get().await?;
+        let row = client
+            .query_opt("SELECT private_key FROM user_profiles WHERE user_pubkey = $1", &[&user_pubkey])
+            .await?;
+        Ok(row.and_then(|r| r.try_get("private_key").ok()))
+    }
+}
+
 // Database Models
 //
 // Tokio-postgres compatible models for all database entities in the ICM system.
@@ -596,6 +607,13 @@ impl DatabaseTradingPool {
         }
     }
 
+    /// Fetch the pool recorded for a bucket PDA
+    pub async fn fetch_by_bucket_pda(pool: &Pool, bucket_pda: &str) -> Result<Option<DatabaseTradingPool>> {
+        let client = pool.get().await?;
+        let row = client.query_opt("SELECT * FROM trading_pools WHERE bucket_pda = $1", &[&bucket_pda]).await?;
+        Ok(row.as_ref().map(DatabaseTradingPool::from_row).transpose()?)
+    }
+
     /// Fetch all pool strategies as a map for efficient lookup
     pub async fn fetch_all_pool_strategies(pool: &Pool) -> Result<HashMap<String, String>> {
         let client = pool.get().await?;
@@ -659,3 +677,704 @@ impl DatabaseTradingPool {
         Ok(())
     }
 }
+
+/// OHLCV candle for a token pair, aggregated from quotes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketCandle {
+    pub input_mint: String,
+    pub output_mint: String,
+    pub interval: String,
+    pub open_time: DateTime<Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    pub quote_count: i32,
+}
+
+impl FromRow for MarketCandle {
+    fn from_row(row: &Row) -> Result<Self, tokio_postgres::Error> {
+        Ok(Self {
+            input_mint: row.try_get("input_mint")?,
+            output_mint: row.try_get("output_mint")?,
+            interval: row.try_get("interval")?,
+            open_time: row.try_get("open_time")?,
+            open: row.try_get("open")?,
+            high: row.try_get("high")?,
+            low: row.try_get("low")?,
+            close: row.try_get("close")?,
+            volume: row.try_get("volume")?,
+            quote_count: row.try_get("quote_count")?,
+        })
+    }
+}
+
+impl MarketCandle {
+    /// Insert or replace candles keyed by pair, interval and open time
+    pub async fn upsert_many(pool: &Pool, candles: &[MarketCandle]) -> Result<()> {
+        let client = pool.get().await?;
+        let statement = client.prepare(r#"
+            INSERT INTO market_candles (
+                input_mint, output_mint, interval, open_time,
+                open, high, low, close, volume, quote_count
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (input_mint, output_mint, interval, open_time) DO UPDATE SET
+                high = GREATEST(market_candles.high, EXCLUDED.high),
+                low = LEAST(market_candles.low, EXCLUDED.low),
+                close = EXCLUDED.close,
+                volume = EXCLUDED.volume,
+                quote_count = EXCLUDED.quote_count,
+                updated_at = NOW()
+        "#).await?;
+
+        for candle in candles {
+            client.execute(
+                &statement,
+                &[&candle.input_mint, &candle.output_mint, &candle.interval, &candle.open_time,
+                  &candle.open, &candle.high, &candle.low, &candle.close, &candle.volume, &candle.quote_count],
+            ).await?;
+        }
+        Ok(())
+    }
+
+    /// Fetch candles for a pair and interval, oldest first
+    pub async fn fetch_range(
+        pool: &Pool,
+        input_mint: &str,
+        output_mint: &str,
+        interval: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<MarketCandle>> {
+        let client = pool.get().await?;
+        let rows = client.query(r#"
+            SELECT * FROM (
+                SELECT * FROM market_candles
+                WHERE input_mint = $1 AND output_mint = $2 AND interval = $3
+                  AND open_time >= $4 AND open_time <= $5
+                ORDER BY open_time DESC
+                LIMIT $6
+            ) recent ORDER BY open_time ASC
+        "#, &[&input_mint, &output_mint, &interval, &from, &to, &limit]).await?;
+
+        let candles = rows.iter()
+            .map(MarketCandle::from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(candles)
+    }
+
+    /// Delete candles of an interval that opened before `cutoff`
+    pub async fn delete_before(pool: &Pool, interval: &str, cutoff: DateTime<Utc>) -> Result<u64> {
+        let client = pool.get().await?;
+        let deleted = client.execute(
+            "DELETE FROM market_candles WHERE interval = $1 AND open_time < $2",
+            &[&interval, &cutoff],
+        ).await?;
+        Ok(deleted)
+    }
+}
+
+/// Webhook endpoint registered by a user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEndpoint {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub url: String,
+    /// Only returned once, when the endpoint is created
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub event_types: Vec<String>,
+    pub pool_id: Option<String>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl FromRow for WebhookEndpoint {
+    fn from_row(row: &Row) -> Result<Self, tokio_postgres::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            user_id: row.try_get("user_id")?,
+            url: row.try_get("url")?,
+            secret: row.try_get("secret")?,
+            event_types: row.try_get("event_types")?,
+            pool_id: row.try_get("pool_id")?,
+            is_active: row.try_get("is_active")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+impl WebhookEndpoint {
+    pub async fn insert(
+        pool: &Pool,
+        user_id: Uuid,
+        url: &str,
+        secret: &str,
+        event_types: &[String],
+        pool_id: Option<&str>,
+    ) -> Result<WebhookEndpoint> {
+        let client = pool.get().await?;
+        let row = client.query_one(r#"
+            INSERT INTO webhook_endpoints (user_id, url, secret, event_types, pool_id)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *
+        "#, &[&user_id, &url, &secret, &event_types, &pool_id]).await?;
+        Ok(WebhookEndpoint::from_row(&row)?)
+    }
+
+    pub async fn fetch_by_id(pool: &Pool, id: Uuid) -> Result<Option<WebhookEndpoint>> {
+        let client = pool.get().await?;
+        let row = client.query_opt("SELECT * FROM webhook_endpoints WHERE id = $1", &[&id]).await?;
+        Ok(row.as_ref().map(WebhookEndpoint::from_row).transpose()?)
+    }
+
+    pub async fn fetch_by_user(pool: &Pool, user_id: Uuid) -> Result<Vec<WebhookEndpoint>> {
+        let client = pool.get().await?;
+        let rows = client.query(
+            "SELECT * FROM webhook_endpoints WHERE user_id = $1 ORDER BY created_at DESC",
+            &[&user_id],
+        ).await?;
+        Ok(rows.iter().map(WebhookEndpoint::from_row).collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Active endpoints subscribed to an event type, optionally limited to a pool
+    pub async fn fetch_subscribed(pool: &Pool, event_type: &str, pool_id: Option<&str>) -> Result<Vec<WebhookEndpoint>> {
+        let client = pool.get().await?;
+        let rows = client.query(r#"
+            SELECT * FROM webhook_endpoints
+            WHERE is_active
+              AND (cardinality(event_types) = 0 OR $1 = ANY(event_types))
+              AND (pool_id IS NULL OR pool_id = $2)
+        "#, &[&event_type, &pool_id]).await?;
+        Ok(rows.iter().map(WebhookEndpoint::from_row).collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Delete an endpoint owned by `user_id`; returns false if there was none
+    pub async fn delete(pool: &Pool, user_id: Uuid, id: Uuid) -> Result<bool> {
+        let client = pool.get().await?;
+        let deleted = client.execute(
+            "DELETE FROM webhook_endpoints WHERE id = $1 AND user_id = $2",
+            &[&id, &user_id],
+        ).await?;
+        Ok(deleted > 0)
+    }
+}
+
+/// One webhook delivery and the outcome of its latest attempt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub endpoint_id: Uuid,
+    pub event_id: Uuid,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl FromRow for WebhookDelivery {
+    fn from_row(row: &Row) -> Result<Self, tokio_postgres::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            endpoint_id: row.try_get("endpoint_id")?,
+            event_id: row.try_get("event_id")?,
+            event_type: row.try_get("event_type")?,
+            payload: row.try_get("payload")?,
+            status: row.try_get("status")?,
+            attempts: row.try_get("attempts")?,
+            response_status: row.try_get("response_status")?,
+            last_error: row.try_get("last_error")?,
+            next_attempt_at: row.try_get("next_attempt_at")?,
+            delivered_at: row.try_get("delivered_at")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+impl WebhookDelivery {
+    /// Queue a delivery. The caller attempts it right away; the due time only
+    /// lets the retry loop pick it up if that first attempt is never recorded.
+    pub async fn insert(
+        pool: &Pool,
+        endpoint_id: Uuid,
+        event_id: Uuid,
+        event_type: &str,
+        payload: &serde_json::Value,
+    ) -> Result<WebhookDelivery> {
+        let client = pool.get().await?;
+        let row = client.query_one(r#"
+            INSERT INTO webhook_deliveries (endpoint_id, event_id, event_type, payload, next_attempt_at)
+            VALUES ($1, $2, $3, $4, NOW() + INTERVAL '1 minute')
+            RETURNING *
+        "#, &[&endpoint_id, &event_id, &event_type, payload]).await?;
+        Ok(WebhookDelivery::from_row(&row)?)
+    }
+
+    pub async fn fetch_by_id(pool: &Pool, id: Uuid) -> Result<Option<WebhookDelivery>> {
+        let client = pool.get().await?;
+        let row = client.query_opt("SELECT * FROM webhook_deliveries WHERE id = $1", &[&id]).await?;
+        Ok(row.as_ref().map(WebhookDelivery::from_row).transpose()?)
+    }
+
+    /// Most recent deliveries for an endpoint
+    pub async fn fetch_by_endpoint(pool: &Pool, endpoint_id: Uuid, limit: i64) -> Result<Vec<WebhookDelivery>> {
+        let client = pool.get().await?;
+        let rows = client.query(
+            "SELECT * FROM webhook_deliveries WHERE endpoint_id = $1 ORDER BY created_at DESC LIMIT $2",
+            &[&endpoint_id, &limit],
+        ).await?;
+        Ok(rows.iter().map(WebhookDelivery::from_row).collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Pending deliveries whose next attempt is due
+    pub async fn fetch_due(pool: &Pool, limit: i64) -> Result<Vec<WebhookDelivery>> {
+        let client = pool.get().await?;
+        let rows = client.query(r#"
+            SELECT * FROM webhook_deliveries
+            WHERE status = 'pending' AND next_attempt_at <= NOW()
+            ORDER BY next_attempt_at ASC
+            LIMIT $1
+        "#, &[&limit]).await?;
+        Ok(rows.iter().map(WebhookDelivery::from_row).collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Store the outcome of an attempt
+    pub async fn record_attempt(&self, pool: &Pool) -> Result<()> {
+        let client = pool.get().await?;
+        client.execute(r#"
+            UPDATE webhook_deliveries SET
+                status = $2, attempts = $3, response_status = $4, last_error = $5,
+                next_attempt_at = $6, delivered_at = $7
+            WHERE id = $1
+        "#, &[&self.id, &self.status, &self.attempts, &self.response_status, &self.last_error,
+              &self.next_attempt_at, &self.delivered_at]).await?;
+        Ok(())
+    }
+}
+
+/// A wallet-signed transaction relayed by the API and its latest known status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmittedTransaction {
+    pub signature: String,
+    pub user_id: Uuid,
+    pub fee_payer: String,
+    pub action: Option<String>,
+    pub pool_id: Option<String>,
+    pub recent_blockhash: String,
+    pub status: String,
+    pub slot: Option<i64>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl FromRow for SubmittedTransaction {
+    fn from_row(row: &Row) -> Result<Self, tokio_postgres::Error> {
+        Ok(Self {
+            signature: row.try_get("signature")?,
+            user_id: row.try_get("user_id")?,
+            fee_payer: row.try_get("fee_payer")?,
+            action: row.try_get("action")?,
+            pool_id: row.try_get("pool_id")?,
+            recent_blockhash: row.try_get("recent_blockhash")?,
+            status: row.try_get("status")?,
+            slot: row.try_get("slot")?,
+            error: row.try_get("error")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+impl SubmittedTransaction {
+    /// Record a submission; resubmitting the same signature keeps the existing row
+    pub async fn insert(
+        pool: &Pool,
+        signature: &str,
+        user_id: Uuid,
+        fee_payer: &str,
+        action: Option<&str>,
+        pool_id: Option<&str>,
+        recent_blockhash: &str,
+    ) -> Result<SubmittedTransaction> {
+        let client = pool.get().await?;
+        let row = client.query_one(r#"
+            INSERT INTO submitted_transactions (signature, user_id, fee_payer, action, pool_id, recent_blockhash)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (signature) DO UPDATE SET updated_at = submitted_transactions.updated_at
+            RETURNING *
+        "#, &[&signature, &user_id, &fee_payer, &action, &pool_id, &recent_blockhash]).await?;
+        Ok(SubmittedTransaction::from_row(&row)?)
+    }
+
+    pub async fn fetch_by_signature(pool: &Pool, signature: &str) -> Result<Option<SubmittedTransaction>> {
+        let client = pool.get().await?;
+        let row = client.query_opt("SELECT * FROM submitted_transactions WHERE signature = $1", &[&signature]).await?;
+        Ok(row.as_ref().map(SubmittedTransaction::from_row).transpose()?)
+    }
+
+    /// Transactions that have not reached a final status, oldest first
+    pub async fn fetch_pending(pool: &Pool, limit: i64) -> Result<Vec<SubmittedTransaction>> {
+        let client = pool.get().await?;
+        let rows = client.query(r#"
+            SELECT * FROM submitted_transactions
+            WHERE status IN ('submitted', 'confirmed')
+            ORDER BY created_at ASC
+            LIMIT $1
+        "#, &[&limit]).await?;
+        Ok(rows.iter().map(SubmittedTransaction::from_row).collect::<Result<Vec<_>, _>>()?)
+    }
+
+    pub async fn update_status(&self, pool: &Pool) -> Result<()> {
+        let client = pool.get().await?;
+        client.execute(r#"
+            UPDATE submitted_transactions SET status = $2, slot = $3, error = $4, updated_at = NOW()
+            WHERE signature = $1
+        "#, &[&self.signature, &self.status, &self.slot, &self.error]).await?;
+        Ok(())
+    }
+}
+
+/// How one indexed ICM instruction changes the pool tables
+#[derive(Debug, Clone)]
+pub enum IndexedPoolChange {
+    Created {
+        creator_pubkey: String,
+        name: String,
+        token_bucket: Vec<String>,
+        target_amount: i64,
+        trading_end_time: DateTime<Utc>,
+        management_fee: i32,
+    },
+    Contributed {
+        contributor_pubkey: String,
+        amount: i64,
+    },
+    TradingStarted,
+    Traded {
+        trade_type: String,
+        from_token: String,
+        to_token: String,
+        amount_in: i64,
+        amount_out: i64,
+    },
+    Claimed {
+        contributor_pubkey: String,
+    },
+    Closed,
+}
+
+/// An ICM instruction decoded by the indexer
+#[derive(Debug, Clone)]
+pub struct IndexedInstruction {
+    pub instruction_index: i16,
+    pub action: String,
+    pub bucket_pda: String,
+    pub change: IndexedPoolChange,
+}
+
+/// A fee vault balance change decoded by the indexer
+#[derive(Debug, Clone)]
+pub struct IndexedFeeMovement {
+    pub action: Option<String>,
+    pub bucket_pda: Option<String>,
+    /// USDC base units; negative for withdrawals
+    pub delta: i64,
+}
+
+/// Newest signature the indexer has processed for an address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexerCheckpoint {
+    pub name: String,
+    pub last_signature: String,
+    pub last_slot: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl FromRow for IndexerCheckpoint {
+    fn from_row(row: &Row) -> Result<Self, tokio_postgres::Error> {
+        Ok(Self {
+            name: row.try_get("name")?,
+            last_signature: row.try_get("last_signature")?,
+            last_slot: row.try_get("last_slot")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+impl IndexerCheckpoint {
+    pub async fn fetch(pool: &Pool, name: &str) -> Result<Option<IndexerCheckpoint>> {
+        let client = pool.get().await?;
+        let row = client.query_opt("SELECT * FROM indexer_checkpoints WHERE name = $1", &[&name]).await?;
+        Ok(row.as_ref().map(IndexerCheckpoint::from_row).transpose()?)
+    }
+
+    /// Apply the instructions of one transaction and move the checkpoint to it,
+    /// atomically. Instructions already recorded for the signature are skipped.
+    pub async fn advance(
+        pool: &Pool,
+        name: &str,
+        signature: &str,
+        slot: i64,
+        block_time: Option<DateTime<Utc>>,
+        instructions: &[IndexedInstruction],
+        fee_movement: Option<&IndexedFeeMovement>,
+    ) -> Result<()> {
+        let mut client = pool.get().await?;
+        let tx = client.transaction().await?;
+        // pool_contributions and trade_records store times without a zone
+        let executed_at = block_time.unwrap_or_else(Utc::now).naive_utc();
+
+        for instruction in instructions {
+            let inserted = tx.execute(r#"
+                INSERT INTO indexed_program_instructions (signature, instruction_index, action, bucket_pda, slot, block_time)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (signature, instruction_index) DO NOTHING
+            "#, &[&signature, &instruction.instruction_index, &instruction.action, &instruction.bucket_pda, &slot, &block_time]).await?;
+            if inserted == 0 {
+                continue;
+            }
+
+            let bucket_pda = &instruction.bucket_pda;
+            let applied = match &instruction.change {
+                IndexedPoolChange::Created { creator_pubkey, name, token_bucket, target_amount, trading_end_time, management_fee } => {
+                    // Same id as pools saved by the API; their strategy is kept
+                    let pool_id = format!("{}_{}", creator_pubkey, name);
+                    tx.execute(r#"
+                        INSERT INTO trading_pools (
+                            id, creator_pubkey, name, strategy, token_bucket,
+                            total_amount_available_to_trade, trading_end_time, management_fee, bucket_pda, status
+                        ) VALUES ($1, $2, $3, 'unknown', $4, $5, $6, $7, $8, 'Raising')
+                        ON CONFLICT (id) DO UPDATE SET
+                            token_bucket = EXCLUDED.token_bucket,
+                            total_amount_available_to_trade = EXCLUDED.total_amount_available_to_trade,
+                            trading_end_time = EXCLUDED.trading_end_time,
+                            management_fee = EXCLUDED.management_fee,
+                            bucket_pda = EXCLUDED.bucket_pda,
+                            status = 'Raising',
+                            closed_at = NULL,
+                            updated_at = NOW()
+                    "#, &[&pool_id, creator_pubkey, name, token_bucket, target_amount, trading_end_time, management_fee, bucket_pda]).await?
+                }
+                IndexedPoolChange::Contributed { contributor_pubkey, amount } => {
+                    let inserted = tx.execute(r#"
+                        INSERT INTO pool_contributions (pool_id, contributor_pubkey, amount, pool_share_percentage, transaction_signature, contributed_at)
+                        SELECT id, $2, $3, 0, $4, $5 FROM trading_pools WHERE bucket_pda = $1
+                    "#, &[bucket_pda, contributor_pubkey, amount, &signature, &executed_at]).await?;
+                    // Shares are basis points of everything raised so far
+                    tx.execute(r#"
+                        UPDATE pool_contributions pc
+                        SET pool_share_percentage = (pc.amount * 10000 / totals.total)::INTEGER
+                        FROM (
+                            SELECT pool_id, SUM(amount) AS total FROM pool_contributions
+                            WHERE pool_id = (SELECT id FROM trading_pools WHERE bucket_pda = $1)
+                            GROUP BY pool_id
+                        ) totals
+                        WHERE pc.pool_id = totals.pool_id AND totals.total > 0
+                    "#, &[bucket_pda]).await?;
+                    inserted
+                }
+                IndexedPoolChange::TradingStarted => {
+                    tx.execute(
+                        "UPDATE trading_pools SET status = 'Trading', updated_at = NOW() WHERE bucket_pda = $1",
+                        &[bucket_pda],
+                    ).await?
+                }
+                IndexedPoolChange::Traded { trade_type, from_token, to_token, amount_in, amount_out } => {
+                    tx.execute(r#"
+                        INSERT INTO trade_records (pool_id, trade_type, from_token, to_token, amount_in, amount_out, transaction_signature, executed_at, success)
+                        SELECT id, $2, $3, $4, $5, $6, $7, $8, TRUE FROM trading_pools WHERE bucket_pda = $1
+                    "#, &[bucket_pda, trade_type, from_token, to_token, amount_in, amount_out, &signature, &executed_at]).await?
+                }
+                IndexedPoolChange::Claimed { contributor_pubkey } => {
+                    tx.execute(r#"
+                        UPDATE pool_contributions SET claimed = TRUE
+                        WHERE contributor_pubkey = $2 AND pool_id = (SELECT id FROM trading_pools WHERE bucket_pda = $1)
+                    "#, &[bucket_pda, contributor_pubkey]).await?
+                }
+                IndexedPoolChange::Closed => {
+                    tx.execute(
+                        "UPDATE trading_pools SET status = 'Closed', closed_at = COALESCE($2, NOW()), updated_at = NOW() WHERE bucket_pda = $1",
+                        &[bucket_pda, &block_time],
+                    ).await?
+                }
+            };
+            if applied == 0 {
+                tracing::debug!("[advance] {} in {} matched no indexed pool for bucket {}", instruction.action, signature, bucket_pda);
+            }
+        }
+
+        if let Some(movement) = fee_movement {
+            tx.execute(r#"
+                INSERT INTO fee_vault_movements (signature, slot, block_time, action, bucket_pda, delta)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (signature) DO NOTHING
+            "#, &[&signature, &slot, &block_time, &movement.action, &movement.bucket_pda, &movement.delta]).await?;
+        }
+
+        tx.execute(r#"
+            INSERT INTO indexer_checkpoints (name, last_signature, last_slot)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (name) DO UPDATE SET
+                last_signature = EXCLUDED.last_signature,
+                last_slot = EXCLUDED.last_slot,
+                updated_at = NOW()
+        "#, &[&name, &signature, &slot]).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+/// A fee vault deposit or withdrawal recorded by the indexer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeVaultMovementRecord {
+    pub signature: String,
+    pub slot: i64,
+    pub block_time: Option<DateTime<Utc>>,
+    pub action: Option<String>,
+    pub bucket_pda: Option<String>,
+    pub delta: i64,
+}
+
+impl FromRow for FeeVaultMovementRecord {
+    fn from_row(row: &Row) -> Result<Self, tokio_postgres::Error> {
+        Ok(Self {
+            signature: row.try_get("signature")?,
+            slot: row.try_get("slot")?,
+            block_time: row.try_get("block_time")?,
+            action: row.try_get("action")?,
+            bucket_pda: row.try_get("bucket_pda")?,
+            delta: row.try_get("delta")?,
+        })
+    }
+}
+
+/// Fees one ICM instruction paid into the vault for a bucket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BucketFeeTotal {
+    pub bucket_pda: String,
+    pub bucket_name: Option<String>,
+    pub creator_pubkey: Option<String>,
+    pub action: Option<String>,
+    /// USDC base units
+    pub total: i64,
+    pub transactions: i64,
+    pub last_fee_at: Option<DateTime<Utc>>,
+}
+
+impl FromRow for BucketFeeTotal {
+    fn from_row(row: &Row) -> Result<Self, tokio_postgres::Error> {
+        Ok(Self {
+            bucket_pda: row.try_get("bucket_pda")?,
+            bucket_name: row.try_get("bucket_name")?,
+            creator_pubkey: row.try_get("creator_pubkey")?,
+            action: row.try_get("action")?,
+            total: row.try_get("total")?,
+            transactions: row.try_get("transactions")?,
+            last_fee_at: row.try_get("last_fee_at")?,
+        })
+    }
+}
+
+impl FeeVaultMovementRecord {
+    /// Deposits per bucket and instruction, named from `trading_pools` when indexed
+    pub async fn fee_totals(pool: &Pool) -> Result<Vec<BucketFeeTotal>> {
+        let client = pool.get().await?;
+        let rows = client.query(r#"
+            SELECT m.bucket_pda, tp.name AS bucket_name, tp.creator_pubkey, m.action,
+                   SUM(m.delta)::BIGINT AS total, COUNT(*) AS transactions, MAX(m.block_time) AS last_fee_at
+            FROM fee_vault_movements m
+            LEFT JOIN trading_pools tp ON tp.bucket_pda = m.bucket_pda
+            WHERE m.delta > 0 AND m.bucket_pda IS NOT NULL
+            GROUP BY m.bucket_pda, tp.name, tp.creator_pubkey, m.action
+        "#, &[]).await?;
+        Ok(rows.iter().map(BucketFeeTotal::from_row).collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Withdrawals from the vault, newest first
+    pub async fn withdrawals(pool: &Pool, limit: i64) -> Result<Vec<FeeVaultMovementRecord>> {
+        let client = pool.get().await?;
+        let rows = client.query(
+            "SELECT * FROM fee_vault_movements WHERE delta < 0 ORDER BY slot DESC LIMIT $1",
+            &[&limit],
+        ).await?;
+        Ok(rows.iter().map(FeeVaultMovementRecord::from_row).collect::<Result<Vec<_>, _>>()?)
+    }
+}
+
+/// An action the bucket lifecycle scheduler took on a bucket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BucketLifecycleAction {
+    pub id: Uuid,
+    pub bucket_pda: String,
+    pub bucket_name: String,
+    pub creator_pubkey: String,
+    pub action: String,
+    /// `succeeded` or `failed`
+    pub status: String,
+    pub signature: Option<String>,
+    pub details: serde_json::Value,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl FromRow for BucketLifecycleAction {
+    fn from_row(row: &Row) -> Result<Self, tokio_postgres::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            bucket_pda: row.try_get("bucket_pda")?,
+            bucket_name: row.try_get("bucket_name")?,
+            creator_pubkey: row.try_get("creator_pubkey")?,
+            action: row.try_get("action")?,
+            status: row.try_get("status")?,
+            signature: row.try_get("signature")?,
+            details: row.try_get("details")?,
+            error: row.try_get("error")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+impl BucketLifecycleAction {
+    pub async fn insert(&self, pool: &Pool) -> Result<()> {
+        let client = pool.get().await?;
+        client.execute(r#"
+            INSERT INTO bucket_lifecycle_actions
+                (id, bucket_pda, bucket_name, creator_pubkey, action, status, signature, details, error, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        "#, &[&self.id, &self.bucket_pda, &self.bucket_name, &self.creator_pubkey, &self.action, &self.status,
+              &self.signature, &self.details, &self.error, &self.created_at]).await?;
+        Ok(())
+    }
+
+    /// Most recent record of an action on a bucket
+    pub async fn fetch_latest(pool: &Pool, bucket_pda: &str, action: &str) -> Result<Option<BucketLifecycleAction>> {
+        let client = pool.get().await?;
+        let row = client.query_opt(r#"
+            SELECT * FROM bucket_lifecycle_actions
+            WHERE bucket_pda = $1 AND action = $2
+            ORDER BY created_at DESC
+            LIMIT 1
+        "#, &[&bucket_pda, &action]).await?;
+        Ok(row.as_ref().map(BucketLifecycleAction::from_row).transpose()?)
+    }
+
+    /// Actions on a bucket, newest first
+    pub async fn fetch_for_bucket(pool: &Pool, bucket_pda: &str, limit: i64) -> Result<Vec<BucketLifecycleAction>> {
+        let client = pool.get().await?;
+        let rows = client.query(r#"
+            SELECT * FROM bucket_lifecycle_actions
+            WHERE bucket_pda = $1
+            ORDER BY created_at DESC
+            LIMIT $2
+        "#, &[&bucket_pda, &limit]).await?;
+        Ok(rows.iter().map(BucketLifecycleAction::from_row).collect::<Result<Vec<_>, _>>()?)
+    }
+}
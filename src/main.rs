@@ -14,7 +14,7 @@
 //! ## Architecture
 //! The server is organized into modules:
 //! - `server`: Core server initialization and configuration
-//! - `config`: Environment variable configuration management
+//! - `config`: Typed configuration from environment variables and an optional TOML file
 //! - `agent`: AI trading agent with multiple strategies
 //! - `routes`: HTTP route handlers organized by functionality
 //!   - `health`: Health check and monitoring endpoints
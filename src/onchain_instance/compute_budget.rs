@@ -0,0 +1,196 @@
+//! Compute budget sizing and priority fees.
+//!
+//! Every ICM transaction gets a compute unit limit estimated by simulation plus
+//! headroom, and a compute unit price taken from recent prioritization fees on
+//! the accounts it writes. The server-wide [`ComputeBudgetPolicy`] is bounded by
+//! the agent's [`ExecutionSettings`]; [`ComputeBudgetOptions`] overrides it per request.
+
+use serde::Deserialize;
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_sdk::instruction::Instruction;
+
+use crate::agent::types::ExecutionSettings;
+
+/// Most compute units a transaction may request
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+
+/// Server-wide compute budget defaults
+#[derive(Debug, Clone, Copy)]
+pub struct ComputeBudgetPolicy {
+    /// Extra units on top of the simulated consumption, in percent
+    pub headroom_percent: u32,
+    pub min_compute_units: u32,
+    /// Limit used when simulation does not report consumption
+    pub fallback_compute_units: u32,
+    /// Percentile of recent prioritization fees to pay
+    pub priority_fee_percentile: u8,
+    /// Cap on the priority fee of one transaction
+    pub max_priority_fee_lamports: u64,
+}
+
+impl Default for ComputeBudgetPolicy {
+    fn default() -> Self {
+        Self::from_execution_settings(&ExecutionSettings::default())
+    }
+}
+
+impl ComputeBudgetPolicy {
+    /// Policy bounded by the agent's execution settings
+    pub fn from_execution_settings(settings: &ExecutionSettings) -> Self {
+        Self {
+            headroom_percent: 20,
+            min_compute_units: 20_000,
+            fallback_compute_units: 400_000,
+            priority_fee_percentile: settings.priority_fee_percentile,
+            max_priority_fee_lamports: settings.max_priority_fee_lamports,
+        }
+    }
+
+    pub fn with_headroom_percent(mut self, headroom_percent: u32) -> Self {
+        self.headroom_percent = headroom_percent;
+        self
+    }
+
+    /// Compute unit limit for a simulated consumption
+    pub fn compute_unit_limit(&self, units_consumed: Option<u64>) -> u32 {
+        match units_consumed {
+            Some(units) => {
+                let with_headroom = units.saturating_mul(100 + self.headroom_percent as u64) / 100;
+                (with_headroom.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32).max(self.min_compute_units)
+            }
+            None => self.fallback_compute_units,
+        }
+    }
+}
+
+/// Per-request compute budget overrides; every field falls back to the policy
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct ComputeBudgetOptions {
+    /// Fixed compute unit limit instead of simulating
+    pub compute_unit_limit: Option<u32>,
+    /// Fixed price in micro-lamports per compute unit instead of recent fees
+    pub compute_unit_price: Option<u64>,
+    pub priority_fee_percentile: Option<u8>,
+    /// Lower cap on the priority fee; the policy cap still applies
+    pub max_priority_fee_lamports: Option<u64>,
+}
+
+impl ComputeBudgetOptions {
+    pub fn with_max_priority_fee_lamports(mut self, lamports: u64) -> Self {
+        self.max_priority_fee_lamports = Some(lamports);
+        self
+    }
+
+    pub fn priority_fee_percentile(&self, policy: &ComputeBudgetPolicy) -> u8 {
+        self.priority_fee_percentile.unwrap_or(policy.priority_fee_percentile).min(100)
+    }
+
+    pub fn max_priority_fee_lamports(&self, policy: &ComputeBudgetPolicy) -> u64 {
+        self.max_priority_fee_lamports
+            .map_or(policy.max_priority_fee_lamports, |max| max.min(policy.max_priority_fee_lamports))
+    }
+}
+
+/// Fee at `percentile` of recent prioritization fees, in micro-lamports per compute unit
+pub fn fee_percentile(mut fees: Vec<u64>, percentile: u8) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+    fees.sort_unstable();
+    let index = (fees.len() - 1) * percentile.min(100) as usize / 100;
+    fees[index]
+}
+
+/// Highest price that keeps `limit` units within `max_fee_lamports`
+pub fn capped_price(price: u64, limit: u32, max_fee_lamports: u64) -> u64 {
+    if limit == 0 {
+        return price;
+    }
+    let max_price = max_fee_lamports as u128 * MICRO_LAMPORTS_PER_LAMPORT / limit as u128;
+    price.min(max_price.min(u64::MAX as u128) as u64)
+}
+
+/// Instructions without any compute budget instructions
+pub fn without_compute_budget(ixs: &[Instruction]) -> Vec<Instruction> {
+    ixs.iter()
+        .filter(|ix| ix.program_id != solana_compute_budget_interface::ID)
+        .cloned()
+        .collect()
+}
+
+/// Compute budget instructions to put in front of a transaction
+pub fn compute_budget_instructions(limit: u32, price: u64) -> Vec<Instruction> {
+    let mut ixs = vec![ComputeBudgetInstruction::set_compute_unit_limit(limit)];
+    if price > 0 {
+        ixs.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+    }
+    ixs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: ComputeBudgetPolicy = ComputeBudgetPolicy {
+        headroom_percent: 20,
+        min_compute_units: 20_000,
+        fallback_compute_units: 400_000,
+        priority_fee_percentile: 75,
+        max_priority_fee_lamports: 10_000,
+    };
+
+    #[test]
+    fn limit_adds_headroom_to_the_simulated_units() {
+        assert_eq!(POLICY.compute_unit_limit(Some(100_000)), 120_000);
+    }
+
+    #[test]
+    fn limit_is_clamped_to_the_policy_minimum_and_cluster_maximum() {
+        assert_eq!(POLICY.compute_unit_limit(Some(1_000)), 20_000);
+        assert_eq!(POLICY.compute_unit_limit(Some(0)), 20_000);
+        assert_eq!(POLICY.compute_unit_limit(Some(1_300_000)), MAX_COMPUTE_UNIT_LIMIT);
+        assert_eq!(POLICY.compute_unit_limit(Some(u64::MAX)), MAX_COMPUTE_UNIT_LIMIT);
+    }
+
+    #[test]
+    fn limit_falls_back_without_a_simulation() {
+        assert_eq!(POLICY.compute_unit_limit(None), 400_000);
+    }
+
+    #[test]
+    fn percentile_picks_from_the_sorted_fees() {
+        let fees = vec![50, 10, 40, 20, 30];
+        assert_eq!(fee_percentile(fees.clone(), 0), 10);
+        assert_eq!(fee_percentile(fees.clone(), 50), 30);
+        assert_eq!(fee_percentile(fees.clone(), 75), 40);
+        assert_eq!(fee_percentile(fees.clone(), 100), 50);
+        assert_eq!(fee_percentile(fees, 250), 50);
+    }
+
+    #[test]
+    fn percentile_of_no_fees_is_zero() {
+        assert_eq!(fee_percentile(Vec::new(), 75), 0);
+    }
+
+    #[test]
+    fn price_is_capped_by_the_fee_budget() {
+        // 10_000 lamports over 200_000 units is 50_000 micro-lamports per unit
+        assert_eq!(capped_price(80_000, 200_000, 10_000), 50_000);
+        assert_eq!(capped_price(30_000, 200_000, 10_000), 30_000);
+        assert_eq!(capped_price(30_000, 200_000, 0), 0);
+    }
+
+    #[test]
+    fn price_is_not_capped_for_a_zero_limit() {
+        assert_eq!(capped_price(80_000, 0, 10_000), 80_000);
+        assert_eq!(capped_price(80_000, 0, 0), 80_000);
+    }
+
+    #[test]
+    fn price_instruction_is_skipped_when_free() {
+        assert_eq!(compute_budget_instructions(200_000, 0).len(), 1);
+        assert_eq!(compute_budget_instructions(200_000, 1).len(), 2);
+        assert!(without_compute_budget(&compute_budget_instructions(200_000, 1)).is_empty());
+    }
+}
@@ -0,0 +1,189 @@
+//! Typed ICM program errors.
+//!
+//! Custom error codes from failed transactions (or simulations) are decoded into
+//! [`IcmProgramError`], which carries a stable API code, an HTTP status and a
+//! user-facing message for each error in `idls/icm_program.json`.
+
+use axum::http::StatusCode;
+use solana_client::client_error::ClientError;
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::transaction::TransactionError;
+
+macro_rules! icm_program_errors {
+    ($($code:literal => $name:ident, $api_code:literal, $status:ident, $message:literal;)*) => {
+        /// Custom errors of the ICM program, numbered as in the IDL
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum IcmProgramError {
+            $($name = $code,)*
+        }
+
+        impl IcmProgramError {
+            pub fn from_code(code: u32) -> Option<Self> {
+                match code {
+                    $($code => Some(Self::$name),)*
+                    _ => None,
+                }
+            }
+
+            /// Stable identifier for API clients
+            pub fn api_code(&self) -> &'static str {
+                match self {
+                    $(Self::$name => $api_code,)*
+                }
+            }
+
+            pub fn status(&self) -> StatusCode {
+                match self {
+                    $(Self::$name => StatusCode::$status,)*
+                }
+            }
+
+            /// Message safe to show to end users
+            pub fn message(&self) -> &'static str {
+                match self {
+                    $(Self::$name => $message,)*
+                }
+            }
+        }
+    };
+}
+
+icm_program_errors! {
+    6000 => RaydiumCpiFailed, "RAYDIUM_CPI_FAILED", BAD_GATEWAY, "The swap through Raydium failed";
+    6001 => NameTooLong, "NAME_TOO_LONG", BAD_REQUEST, "Bucket name is too long (max 64 characters)";
+    6002 => InsufficientTokens, "INSUFFICIENT_TOKENS", BAD_REQUEST, "A bucket needs at least 2 tokens";
+    6003 => TooManyTokens, "TOO_MANY_TOKENS", BAD_REQUEST, "A bucket can hold at most 10 tokens";
+    6004 => InvalidContributionWindow, "INVALID_CONTRIBUTION_WINDOW", BAD_REQUEST, "The contribution window must be between 1 minute and 30 days";
+    6005 => InvalidTradingWindow, "INVALID_TRADING_WINDOW", BAD_REQUEST, "The trading window must be between 1 minute and 180 days";
+    6006 => FeeTooHigh, "FEE_TOO_HIGH", BAD_REQUEST, "Creator fee is too high (max 20%)";
+    6007 => FeeIsTooLow, "FEE_TOO_LOW", BAD_REQUEST, "Creator fee cannot be lower than 0.5%";
+    6008 => DuplicateTokens, "DUPLICATE_TOKENS", BAD_REQUEST, "A bucket cannot list the same token twice";
+    6009 => BucketNotRaising, "BUCKET_NOT_RAISING", CONFLICT, "This bucket is no longer accepting contributions";
+    6010 => ContributionDeadlinePassed, "CONTRIBUTION_DEADLINE_PASSED", CONFLICT, "The contribution deadline has passed";
+    6011 => TokenNotAllowed, "TOKEN_NOT_ALLOWED", BAD_REQUEST, "This token is not allowed in this bucket";
+    6012 => InvalidAmount, "INVALID_AMOUNT", BAD_REQUEST, "The amount is invalid";
+    6013 => UnauthorizedCreator, "UNAUTHORIZED_CREATOR", FORBIDDEN, "Only the bucket creator can do this";
+    6014 => ContributionStillActive, "CONTRIBUTION_STILL_ACTIVE", CONFLICT, "The contribution window is still open";
+    6015 => NoContributions, "NO_CONTRIBUTIONS", CONFLICT, "This bucket has no contributions";
+    6016 => BucketNotTrading, "BUCKET_NOT_TRADING", CONFLICT, "This bucket is not trading";
+    6017 => TradingStillActive, "TRADING_STILL_ACTIVE", CONFLICT, "The trading window is still open";
+    6018 => BucketNotClosed, "BUCKET_NOT_CLOSED", CONFLICT, "This bucket is not closed yet";
+    6019 => UnauthorizedContributor, "UNAUTHORIZED_CONTRIBUTOR", FORBIDDEN, "Only the contributor can do this";
+    6020 => NoRewardsAvailable, "NO_REWARDS_AVAILABLE", CONFLICT, "There are no rewards to claim";
+    6021 => TradingNotStarted, "TRADING_NOT_STARTED", CONFLICT, "Trading has not started yet";
+    6022 => InvalidTokenMint, "INVALID_TOKEN_MINT", BAD_REQUEST, "This token mint does not belong to the bucket";
+    6023 => TradingDeadlinePassed, "TRADING_DEADLINE_PASSED", CONFLICT, "The trading deadline has passed";
+    6024 => InvalidBucketStatus, "INVALID_BUCKET_STATUS", CONFLICT, "The bucket is not in the right state for this action";
+    6025 => InsufficientVaultBalance, "INSUFFICIENT_VAULT_BALANCE", UNPROCESSABLE_ENTITY, "The bucket vault does not hold enough tokens";
+    6026 => InvalidSwapAmount, "INVALID_SWAP_AMOUNT", BAD_REQUEST, "The swap amount is invalid";
+    6027 => Overflow, "ARITHMETIC_OVERFLOW", INTERNAL_SERVER_ERROR, "The amount is too large to process";
+    6028 => ProfileAlreadyExists, "PROFILE_ALREADY_EXISTS", CONFLICT, "A creator profile already exists for this wallet";
+    6029 => ProfileDoesNotExist, "PROFILE_NOT_FOUND", NOT_FOUND, "No creator profile exists for this wallet";
+    6030 => ProgramNotInitialized, "PROGRAM_NOT_INITIALIZED", SERVICE_UNAVAILABLE, "The program has not been initialized";
+    6031 => ProgramInitialized, "PROGRAM_ALREADY_INITIALIZED", CONFLICT, "The program is already initialized";
+    6032 => InvalidMint, "INVALID_MINT", BAD_REQUEST, "Invalid mint address";
+    6033 => InsufficientFunds, "INSUFFICIENT_FUNDS", UNPROCESSABLE_ENTITY, "Insufficient funds";
+    6034 => UnauthorizedDeployer, "UNAUTHORIZED_DEPLOYER", FORBIDDEN, "Only the deployer can initialize the program";
+}
+
+impl std::fmt::Display for IcmProgramError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.message(), *self as u32)
+    }
+}
+
+impl std::error::Error for IcmProgramError {}
+
+impl IcmProgramError {
+    /// The ICM program error behind a failed RPC call, if there is one
+    pub fn from_error(error: &anyhow::Error) -> Option<Self> {
+        custom_error_code(error).and_then(Self::from_code)
+    }
+
+    /// The ICM program error behind a failed transaction or simulation
+    pub fn from_transaction_error(error: &TransactionError) -> Option<Self> {
+        transaction_custom_code(error).and_then(Self::from_code)
+    }
+}
+
+/// Custom error code of the instruction that failed the transaction
+pub fn transaction_custom_code(error: &TransactionError) -> Option<u32> {
+    match error {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => Some(*code),
+        _ => None,
+    }
+}
+
+/// Custom error code of the failed instruction, read from the client error or,
+/// when the error was formatted into a string along the way, from its message
+pub fn custom_error_code(error: &anyhow::Error) -> Option<u32> {
+    let from_client = error.chain()
+        .filter_map(|cause| cause.downcast_ref::<ClientError>())
+        .find_map(|client_error| client_error.get_transaction_error().as_ref().and_then(transaction_custom_code));
+    if from_client.is_some() {
+        return from_client;
+    }
+
+    let text = format!("{:#}", error);
+    let hex = text.split("custom program error: 0x").nth(1)?;
+    let digits: String = hex.chars().take_while(|c| c.is_ascii_hexdigit()).collect();
+    u32::from_str_radix(&digits, 16).ok()
+}
+
+/// Whether an account the transaction creates already exists. Anchor `init` surfaces
+/// this as the system program's `AccountAlreadyInUse`, custom error 0.
+pub fn is_account_already_in_use(error: &anyhow::Error) -> bool {
+    custom_error_code(error) == Some(0) || format!("{:#}", error).contains("already in use")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    const SIMULATION_FAILURE: &str = "RPC response error -32002: Transaction simulation failed: \
+        Error processing Instruction 1: custom program error: 0x177a [5 log messages]";
+
+    #[test]
+    fn reads_the_code_from_a_formatted_message() {
+        let error = anyhow!(SIMULATION_FAILURE);
+        assert_eq!(custom_error_code(&error), Some(0x177a));
+        assert_eq!(IcmProgramError::from_error(&error), Some(IcmProgramError::ContributionDeadlinePassed));
+    }
+
+    #[test]
+    fn reads_the_code_through_added_context() {
+        let error = anyhow!(SIMULATION_FAILURE).context("Contribution failed");
+        assert_eq!(IcmProgramError::from_error(&error), Some(IcmProgramError::ContributionDeadlinePassed));
+    }
+
+    #[test]
+    fn no_code_without_a_custom_program_error() {
+        assert_eq!(custom_error_code(&anyhow!("Blockhash not found")), None);
+        assert_eq!(custom_error_code(&anyhow!("custom program error: 0xzz")), None);
+    }
+
+    #[test]
+    fn codes_outside_the_idl_are_not_program_errors() {
+        let error = anyhow!("custom program error: 0x1");
+        assert_eq!(custom_error_code(&error), Some(1));
+        assert_eq!(IcmProgramError::from_error(&error), None);
+    }
+
+    #[test]
+    fn decodes_transaction_errors() {
+        let error = TransactionError::InstructionError(0, InstructionError::Custom(6013));
+        let program_error = IcmProgramError::from_transaction_error(&error).unwrap();
+        assert_eq!(program_error, IcmProgramError::UnauthorizedCreator);
+        assert_eq!(program_error.api_code(), "UNAUTHORIZED_CREATOR");
+        assert_eq!(program_error.status(), StatusCode::FORBIDDEN);
+        assert_eq!(IcmProgramError::from_transaction_error(&TransactionError::AccountNotFound), None);
+    }
+
+    #[test]
+    fn detects_accounts_already_in_use() {
+        assert!(is_account_already_in_use(&anyhow!("custom program error: 0x0")));
+        assert!(is_account_already_in_use(&anyhow!("Allocate: account 7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU already in use")));
+        assert!(!is_account_already_in_use(&anyhow!(SIMULATION_FAILURE)));
+    }
+}
@@ -1,6 +1,7 @@
 // Add this constant for vault seed if not already present
-use anchor_client::{Client, Cluster};
+use anchor_client::Cluster;
 use anchor_lang::prelude::*;
+use anchor_lang::InstructionData;
 use std::sync::Arc;
 use std::str::FromStr;
 use anyhow::{anyhow, Result};
@@ -13,20 +14,37 @@ use solana_sdk::{
     signer::Signer,
     system_program,
     sysvar,
-    compute_budget::ComputeBudgetInstruction,
     instruction::Instruction,
 };
 use solana_sdk::transaction::Transaction;
+use solana_sdk::account::Account as SolanaAccount;
+use solana_sdk::hash::Hash;
+use solana_sdk::signature::Signature;
+use solana_transaction_status_client_types::TransactionStatus;
+use anchor_client::solana_account_decoder::UiAccountEncoding;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig, RpcTransactionConfig};
+use solana_transaction_status_client_types::{option_serializer::OptionSerializer, UiTransactionEncoding, UiTransactionStatusMeta, UiTransactionTokenBalance};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use crate::onchain_instance::rpc::SolanaRpc;
+use crate::onchain_instance::compute_budget::{
+    capped_price, compute_budget_instructions, fee_percentile, without_compute_budget, ComputeBudgetOptions,
+    ComputeBudgetPolicy, MAX_COMPUTE_UNIT_LIMIT,
+};
+use crate::onchain_instance::pool_query::{PoolCache, PoolPage, PoolQuery, BUCKET_CREATOR_OFFSET};
+use crate::onchain_instance::payout::{authoritative_net_amount, payout_amounts, PayoutFeeRates, PayoutPreview, SimulatedClaim, TokenPayout};
+use crate::onchain_instance::errors::{is_account_already_in_use, transaction_custom_code, IcmProgramError};
+use base64::prelude::{Engine as _, BASE64_STANDARD};
 use spl_associated_token_account::get_associated_token_address;
 
 declare_program!(icm_program);
 pub const ICM_PROGRAM_ID: Pubkey = icm_program::ID;
 pub const VAULT_SEED: &[u8] = b"vault";
 
-use icm_program::client::args::{CreateBucket, ContributeToBucket, StartTrading, ClaimRewards, CloseBucket, InitializeProgram};
-use icm_program::client::accounts::{CreateBucket as CreateBucketAccount, ContributeToBucket as ContributeToBucketAccount, StartTrading as StartTradingAccount, SwapTokens as SwapTokensAccount, ClaimRewards as ClaimRewardsAccount, CloseBucket as CloseBucketAccount, CreateProfile as CreateProfileAccount, InitializeProgram as InitializeProgramAccount};
+use icm_program::client::args::{CreateBucket, ContributeToBucket, StartTrading, ClaimRewards, CloseBucket, InitializeProgram, WithdrawFees};
+use icm_program::client::accounts::{CreateBucket as CreateBucketAccount, ContributeToBucket as ContributeToBucketAccount, StartTrading as StartTradingAccount, SwapTokens as SwapTokensAccount, ClaimRewards as ClaimRewardsAccount, CloseBucket as CloseBucketAccount, CreateProfile as CreateProfileAccount, InitializeProgram as InitializeProgramAccount, WithdrawFees as WithdrawFeesAccount};
 pub use crate::state_structs::{TradingPool, CreatorProfile, BucketAccount, BucketInfo};
-use crate::state_structs::{CreateBucketRequest, ContributeToBucketRequest, StartTradingRequest, SwapTokensRequest, ClaimRewardsRequest, CloseBucketRequest, InitializeProgramRequest, UnsignedTransactionResponse, GetCreatorProfileQuery, GetBucketQuery};
+use crate::state_structs::{BalanceChange, SimulationResponse, ContributionInfo, PoolContributionInfo, BucketContributions, TradeRecordInfo, CreateBucketRequest, ContributeToBucketRequest, StartTradingRequest, SwapTokensRequest, ClaimRewardsRequest, CloseBucketRequest, InitializeProgramRequest, UnsignedTransactionResponse, WalletTransactionResponse, GetCreatorProfileQuery, GetBucketQuery};
 
 /// Format seconds into a human-readable time string
 fn format_time_remaining(seconds: i64) -> String {
@@ -52,8 +70,13 @@ fn format_time_remaining(seconds: i64) -> String {
     parts.join(" ")
 }
 
+/// Convert human-readable USDC amount to lamports (multiply by 1e6)
+pub fn usdc_to_lamports(usdc_amount: f64) -> u64 {
+    (usdc_amount * 1_000_000.0) as u64
+}
+
 /// Convert lamports to human-readable USDC amount (divide by 1e6)
-fn lamports_to_usdc(lamports: u64) -> f64 {
+pub fn lamports_to_usdc(lamports: u64) -> f64 {
     lamports as f64 / 1_000_000.0
 }
 
@@ -86,28 +109,456 @@ fn calculate_time_remaining_for_bucket(
     }
 }
 
+/// A wallet-signed transaction accepted for submission
+#[derive(Debug, Clone)]
+pub struct SubmittedTransactionInfo {
+    pub signature: Signature,
+    pub fee_payer: Pubkey,
+    pub recent_blockhash: Hash,
+    /// ICM instruction name, when recognised
+    pub action: Option<&'static str>,
+    /// First account of the ICM instruction; the bucket PDA for bucket instructions
+    pub bucket: Option<Pubkey>,
+}
+
+/// Protocol fee settings and the fee vault balance, from `program_state`
+#[derive(Debug, Clone)]
+pub struct FeeVaultInfo {
+    pub program_state: Pubkey,
+    pub owner: Pubkey,
+    pub usdc_mint: Pubkey,
+    pub fee_vault: Pubkey,
+    pub fee_rate_bps: u16,
+    /// Lifetime fees recorded by the program, in USDC base units
+    pub total_fees_collected: u64,
+    /// Current fee vault balance, in USDC base units
+    pub balance: u64,
+}
+
+/// USDC a transaction moved into or out of the fee vault
+#[derive(Debug, Clone)]
+pub struct FeeVaultMovement {
+    /// ICM instruction that moved the funds, when recognised
+    pub action: Option<&'static str>,
+    /// Bucket the fee was charged on; `None` for withdrawals
+    pub bucket: Option<Pubkey>,
+    /// Change in the vault balance; negative for withdrawals
+    pub delta: i128,
+}
+
+/// A signature that mentions the ICM program
+#[derive(Debug, Clone, Copy)]
+pub struct ProgramSignature {
+    pub signature: Signature,
+    pub slot: u64,
+    pub failed: bool,
+}
+
+/// A bucket instruction decoded from a confirmed transaction
+#[derive(Debug, Clone)]
+pub enum ProgramAction {
+    CreateBucket {
+        creator: Pubkey,
+        name: String,
+        token_mints: Vec<Pubkey>,
+        contribution_window_minutes: u32,
+        trading_window_minutes: u32,
+        target_amount: u64,
+        management_fee: u64,
+    },
+    Contribute {
+        contributor: Pubkey,
+        /// Amount that reached the bucket vault
+        amount: u64,
+    },
+    StartTrading,
+    Swap {
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        amount_in: u64,
+        amount_out: u64,
+    },
+    ClaimRewards {
+        contributor: Pubkey,
+    },
+    CloseBucket,
+}
+
+impl ProgramAction {
+    /// ICM instruction name
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProgramAction::CreateBucket { .. } => "create_bucket",
+            ProgramAction::Contribute { .. } => "contribute_to_bucket",
+            ProgramAction::StartTrading => "start_trading",
+            ProgramAction::Swap { .. } => "swap_tokens",
+            ProgramAction::ClaimRewards { .. } => "claim_rewards",
+            ProgramAction::CloseBucket => "close_bucket",
+        }
+    }
+}
+
+/// One top-level ICM instruction of a transaction
+#[derive(Debug, Clone)]
+pub struct ProgramInstruction {
+    /// Position of the instruction in the transaction
+    pub index: u8,
+    pub bucket: Pubkey,
+    pub action: ProgramAction,
+}
+
+/// The bucket instructions of one successful transaction
+#[derive(Debug, Clone)]
+pub struct ProgramTransaction {
+    pub block_time: Option<i64>,
+    pub instructions: Vec<ProgramInstruction>,
+    /// Fee vault balance change, when the transaction moved its USDC
+    pub fee_vault_movement: Option<FeeVaultMovement>,
+}
+
+/// The program's singleton state account
+fn program_state_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"program_state"], &ICM_PROGRAM_ID).0
+}
+
+fn token_balance(balances: &OptionSerializer<Vec<UiTransactionTokenBalance>>, index: usize) -> u64 {
+    match balances {
+        OptionSerializer::Some(balances) => balances
+            .iter()
+            .find(|b| b.account_index as usize == index)
+            .and_then(|b| b.ui_token_amount.amount.parse().ok())
+            .unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// Mint, owner and amount of an SPL token account, read at their fixed offsets
+pub fn token_account_fields(account: &SolanaAccount) -> Option<(Pubkey, Pubkey, u64)> {
+    if account.owner != spl_token::ID || account.data.len() < 72 {
+        return None;
+    }
+    let mint = Pubkey::try_from(&account.data[0..32]).ok()?;
+    let owner = Pubkey::try_from(&account.data[32..64]).ok()?;
+    let amount = u64::from_le_bytes(account.data[64..72].try_into().ok()?);
+    Some((mint, owner, amount))
+}
+
+/// SOL and token balance changes of one account between two snapshots
+fn balance_changes(address: Pubkey, before: Option<&SolanaAccount>, after: Option<&SolanaAccount>) -> Vec<BalanceChange> {
+    let mut changes = Vec::new();
+    let lamports_before = before.map_or(0, |account| account.lamports);
+    let lamports_after = after.map_or(0, |account| account.lamports);
+    if lamports_before != lamports_after {
+        changes.push(BalanceChange {
+            account: address.to_string(),
+            mint: None,
+            owner: None,
+            decimals: 9,
+            before: lamports_before,
+            after: lamports_after,
+            delta: lamports_after as i64 - lamports_before as i64,
+        });
+    }
+
+    let token_before = before.and_then(token_account_fields);
+    let token_after = after.and_then(token_account_fields);
+    if let Some((mint, owner, _)) = token_after.or(token_before) {
+        let amount_before = token_before.map_or(0, |(_, _, amount)| amount);
+        let amount_after = token_after.map_or(0, |(_, _, amount)| amount);
+        if amount_before != amount_after {
+            changes.push(BalanceChange {
+                account: address.to_string(),
+                mint: Some(mint.to_string()),
+                owner: Some(owner.to_string()),
+                decimals: 0,
+                before: amount_before,
+                after: amount_after,
+                delta: amount_after as i64 - amount_before as i64,
+            });
+        }
+    }
+    changes
+}
+
+/// Name of the ICM instruction whose Anchor discriminator prefixes `data`
+fn icm_instruction_name(data: &[u8]) -> Option<&'static str> {
+    let known: [(&[u8], &'static str); 6] = [
+        (CreateBucket::DISCRIMINATOR, "create_bucket"),
+        (ContributeToBucket::DISCRIMINATOR, "contribute_to_bucket"),
+        (StartTrading::DISCRIMINATOR, "start_trading"),
+        (ClaimRewards::DISCRIMINATOR, "claim_rewards"),
+        (CloseBucket::DISCRIMINATOR, "close_bucket"),
+        (WithdrawFees::DISCRIMINATOR, "withdraw_fees"),
+    ];
+    known.iter()
+        .find(|(discriminator, _)| data.starts_with(discriminator))
+        .map(|(_, name)| *name)
+}
+
+/// Bucket and action of an ICM bucket instruction. `accounts` pairs each
+/// instruction account with its index in the transaction; token amounts come
+/// from the vault balance changes and fall back to the instruction arguments.
+fn decode_program_instruction(
+    data: &[u8],
+    accounts: &[(usize, Pubkey)],
+    meta: &UiTransactionStatusMeta,
+) -> Option<(Pubkey, ProgramAction)> {
+    let account = |position: usize| accounts.get(position).map(|(_, key)| *key);
+    let vault_delta = |position: usize| {
+        accounts.get(position).map_or(0, |(index, _)| {
+            token_balance(&meta.post_token_balances, *index) as i128
+                - token_balance(&meta.pre_token_balances, *index) as i128
+        })
+    };
+    let discriminator = data.get(..8)?;
+    let mut args = &data[8..];
+
+    if discriminator == CreateBucket::DISCRIMINATOR {
+        let args: CreateBucket = AnchorDeserialize::deserialize(&mut args).ok()?;
+        Some((account(0)?, ProgramAction::CreateBucket {
+            creator: account(8)?,
+            name: args.name,
+            token_mints: args.token_mints,
+            contribution_window_minutes: args.contribution_window_minutes,
+            trading_window_minutes: args.trading_window_minutes,
+            target_amount: args.target_amount,
+            management_fee: args.management_fee,
+        }))
+    } else if discriminator == ContributeToBucket::DISCRIMINATOR {
+        let args: ContributeToBucket = AnchorDeserialize::deserialize(&mut args).ok()?;
+        let deposited = vault_delta(4);
+        Some((account(0)?, ProgramAction::Contribute {
+            contributor: account(8)?,
+            amount: if deposited > 0 { deposited as u64 } else { args.amount },
+        }))
+    } else if discriminator == StartTrading::DISCRIMINATOR {
+        Some((account(0)?, ProgramAction::StartTrading))
+    } else if discriminator == icm_program::client::args::SwapTokens::DISCRIMINATOR {
+        let args: icm_program::client::args::SwapTokens = AnchorDeserialize::deserialize(&mut args).ok()?;
+        let (spent, received) = (-vault_delta(8), vault_delta(9));
+        Some((account(2)?, ProgramAction::Swap {
+            input_mint: account(3)?,
+            output_mint: account(6)?,
+            amount_in: if spent > 0 { spent as u64 } else { args.in_amount },
+            amount_out: if received > 0 { received as u64 } else { args.quoted_out_amount },
+        }))
+    } else if discriminator == ClaimRewards::DISCRIMINATOR {
+        Some((account(0)?, ProgramAction::ClaimRewards { contributor: account(9)? }))
+    } else if discriminator == CloseBucket::DISCRIMINATOR {
+        Some((account(0)?, ProgramAction::CloseBucket))
+    } else {
+        None
+    }
+}
+
+/// One `swap_tokens` hop between two bucket vaults through a Raydium pool
+#[derive(Debug, Clone, Copy)]
+pub struct SwapLeg {
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub in_amount: u64,
+    pub quoted_out_amount: u64,
+    pub slippage_bps: u16,
+    pub raydium_amm_program: Pubkey,
+    pub amm: Pubkey,
+    pub amm_authority: Pubkey,
+    pub pool_coin_token_account: Pubkey,
+    pub pool_pc_token_account: Pubkey,
+}
+
+/// Instruction for the ICM program from Anchor-generated accounts and args
+fn icm_instruction(accounts: impl ToAccountMetas, args: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: ICM_PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct IcmProgramInstance {
     pub cluster: Cluster,
-    pub payer_pubkey: Pubkey,
+    usdc_mint: Pubkey,
+    rpc: Arc<SolanaRpc>,
+    compute_budget: ComputeBudgetPolicy,
+    budget_options: ComputeBudgetOptions,
+    /// Shared by every clone of the instance
+    pool_cache: Arc<PoolCache>,
 }
 
 impl IcmProgramInstance {
-    /// Create a new instance of the ICM program client
-    pub fn new(cluster: Cluster, payer: Keypair) -> Result<Self> {
+    /// Create a new instance of the ICM program client on a shared RPC client
+    pub fn new(cluster: Cluster, usdc_mint: Pubkey, rpc: Arc<SolanaRpc>) -> Result<Self> {
         println!("ICM Program ID: {}", ICM_PROGRAM_ID);
         Ok(Self {
             cluster,
-            payer_pubkey: payer.pubkey(),
+            usdc_mint,
+            rpc,
+            compute_budget: ComputeBudgetPolicy::default(),
+            budget_options: ComputeBudgetOptions::default(),
+            pool_cache: Arc::new(PoolCache::default()),
         })
     }
+
+    pub fn with_compute_budget_policy(mut self, policy: ComputeBudgetPolicy) -> Self {
+        self.compute_budget = policy;
+        self
+    }
+
+    /// A handle whose transactions use these compute budget overrides
+    pub fn with_compute_budget(&self, options: ComputeBudgetOptions) -> Self {
+        Self {
+            budget_options: options,
+            ..self.clone()
+        }
+    }
+
+    /// USDC mint buckets raise and settle in
+    pub fn usdc_mint(&self) -> Pubkey {
+        self.usdc_mint
+    }
+
+    /// The shared RPC client, for components that read chain state directly
+    pub fn rpc(&self) -> Arc<SolanaRpc> {
+        Arc::clone(&self.rpc)
+    }
+
+    /// Fetch and deserialize an ICM program account
+    async fn fetch_account<T: AccountDeserialize>(&self, address: Pubkey) -> Result<T> {
+        let data = self.rpc.call(|rpc| async move { rpc.get_account_data(&address).await }).await?;
+        Ok(T::try_deserialize(&mut data.as_slice())?)
+    }
+
+    /// ICM program accounts of type `T` whose data holds each `(offset, pubkey)` pair
+    async fn fetch_program_accounts_where<T: AccountDeserialize + Discriminator>(
+        &self,
+        matches: &[(usize, Pubkey)],
+    ) -> Result<Vec<(Pubkey, T)>> {
+        let mut filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, T::DISCRIMINATOR))];
+        filters.extend(matches.iter().map(|(offset, key)| RpcFilterType::Memcmp(Memcmp::new_base58_encoded(*offset, key.as_ref()))));
+        let config = RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let accounts = self.rpc
+            .call(|rpc| {
+                let config = config.clone();
+                async move { rpc.get_program_accounts_with_config(&ICM_PROGRAM_ID, config).await }
+            })
+            .await?;
+
+        accounts.into_iter()
+            .map(|(pubkey, account)| Ok((pubkey, T::try_deserialize(&mut account.data.as_slice())?)))
+            .collect()
+    }
+
+    /// Fetch several ICM program accounts at once; `None` for missing or undecodable accounts
+    async fn fetch_multiple_accounts<T: AccountDeserialize>(&self, addresses: &[Pubkey]) -> Result<Vec<Option<T>>> {
+        let mut decoded = Vec::with_capacity(addresses.len());
+        // getMultipleAccounts takes at most 100 keys
+        for chunk in addresses.chunks(100) {
+            let accounts = self.rpc.call(|rpc| async move { rpc.get_multiple_accounts(chunk).await }).await?;
+            decoded.extend(accounts.into_iter().map(|account| {
+                account.and_then(|account| T::try_deserialize(&mut account.data.as_slice()).ok())
+            }));
+        }
+        Ok(decoded)
+    }
+
+    /// Sign with the per-request signer as fee payer, send and wait for confirmation
+    async fn send_signed(&self, ixs: &[Instruction], signer: &Keypair) -> Result<Signature> {
+        let ixs = self.with_compute_budget_instructions(ixs, signer.pubkey()).await;
+        let recent_blockhash = self.rpc.call(|rpc| async move { rpc.get_latest_blockhash().await }).await?;
+        // try_sign reports instructions needing other signers instead of panicking
+        let mut tx = Transaction::new_with_payer(&ixs, Some(&signer.pubkey()));
+        tx.try_sign(&[signer], recent_blockhash)
+            .map_err(|e| anyhow!("Failed to sign transaction as {}: {}", signer.pubkey(), e))?;
+        let tx = &tx;
+        Ok(self.rpc.call(|rpc| async move { rpc.send_and_confirm_transaction(tx).await }).await?)
+    }
+    /// Put a compute unit limit and price in front of `ixs`, replacing any already there.
+    /// The limit comes from simulation and the price from recent fees unless overridden;
+    /// estimation failures fall back to the policy limit and no priority fee.
+    async fn with_compute_budget_instructions(&self, ixs: &[Instruction], fee_payer: Pubkey) -> Vec<Instruction> {
+        let ixs = without_compute_budget(ixs);
+        let policy = &self.compute_budget;
+        let options = &self.budget_options;
+
+        let limit = match options.compute_unit_limit {
+            Some(limit) => limit.min(MAX_COMPUTE_UNIT_LIMIT),
+            None => {
+                let units = self.simulated_compute_units(&ixs, fee_payer).await.unwrap_or_else(|e| {
+                    tracing::warn!("[with_compute_budget_instructions] Compute unit simulation failed: {}", e);
+                    None
+                });
+                policy.compute_unit_limit(units)
+            }
+        };
+        let price = match options.compute_unit_price {
+            Some(price) => price,
+            None => self.recent_priority_fee(&ixs, options.priority_fee_percentile(policy)).await.unwrap_or_else(|e| {
+                tracing::warn!("[with_compute_budget_instructions] Could not read recent prioritization fees: {}", e);
+                0
+            }),
+        };
+        let price = capped_price(price, limit, options.max_priority_fee_lamports(policy));
+        tracing::debug!("[with_compute_budget_instructions] {} compute units at {} micro-lamports", limit, price);
+
+        let mut budgeted = compute_budget_instructions(limit, price);
+        budgeted.extend(ixs);
+        budgeted
+    }
+
+    /// Compute units `ixs` consume in simulation; `None` when the simulation fails
+    async fn simulated_compute_units(&self, ixs: &[Instruction], fee_payer: Pubkey) -> Result<Option<u64>> {
+        let mut simulated = compute_budget_instructions(MAX_COMPUTE_UNIT_LIMIT, 0);
+        simulated.extend_from_slice(ixs);
+        let tx = Transaction::new_with_payer(&simulated, Some(&fee_payer));
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(self.rpc.commitment()),
+            ..RpcSimulateTransactionConfig::default()
+        };
+        let tx = &tx;
+        let result = self.rpc
+            .call(|rpc| {
+                let config = config.clone();
+                async move { rpc.simulate_transaction_with_config(tx, config).await }
+            })
+            .await?
+            .value;
+
+        if let Some(err) = result.err {
+            tracing::debug!("[simulated_compute_units] Simulation failed, using the fallback limit: {}", err);
+            return Ok(None);
+        }
+        Ok(result.units_consumed)
+    }
+
+    /// Recent prioritization fee at `percentile` for the accounts `ixs` write to
+    async fn recent_priority_fee(&self, ixs: &[Instruction], percentile: u8) -> Result<u64> {
+        let mut writable: Vec<Pubkey> = ixs.iter()
+            .flat_map(|ix| ix.accounts.iter())
+            .filter(|meta| meta.is_writable)
+            .map(|meta| meta.pubkey)
+            .collect();
+        writable.sort_unstable();
+        writable.dedup();
+        // The RPC accepts at most 128 accounts
+        writable.truncate(128);
+
+        let addresses = writable.as_slice();
+        let fees = self.rpc.call(|rpc| async move { rpc.get_recent_prioritization_fees(addresses).await }).await?;
+        Ok(fee_percentile(fees.into_iter().map(|fee| fee.prioritization_fee).collect(), percentile))
+    }
     
     /// Check if the program is initialized
     pub async fn check_program_initialized(&self, usdc_mint: Pubkey) -> Result<bool> {
-        let cluster = self.cluster.clone();
-        let dummy_keypair = Keypair::new(); // We just need this for the client, won't be used for signing
-        let client = Client::new_with_options(cluster, Arc::new(dummy_keypair), CommitmentConfig::processed());
-        let program = client.program(ICM_PROGRAM_ID)?;
 
         // Derive program_state PDA
         let (program_state_pda, _) = Pubkey::find_program_address(
@@ -115,7 +566,7 @@ impl IcmProgramInstance {
             &ICM_PROGRAM_ID,
         );
 
-        match program.account::<icm_program::accounts::ProgramState>(program_state_pda).await {
+        match self.fetch_account::<icm_program::accounts::ProgramState>(program_state_pda).await {
             Ok(program_state_account) => {
                 Ok(program_state_account.initialized && program_state_account.usdc_mint == usdc_mint)
             },
@@ -127,14 +578,10 @@ impl IcmProgramInstance {
     pub async fn initialize_program_transaction(
         &self,
         request: InitializeProgramRequest,
-        owner_keypair: Keypair,
+        signer: &Keypair,
     ) -> Result<UnsignedTransactionResponse> {
-        let cluster = self.cluster.clone();
-        let keypair_for_client = owner_keypair.insecure_clone();
-        let client = Client::new_with_options(cluster, Arc::new(keypair_for_client), CommitmentConfig::processed());
-        let program = client.program(ICM_PROGRAM_ID)?;
 
-        let owner = owner_keypair.pubkey();
+        let owner = signer.pubkey();
         let usdc_mint = Pubkey::from_str(&request.usdc_mint)?;
 
         // Derive program_state PDA
@@ -153,12 +600,8 @@ impl IcmProgramInstance {
         tracing::info!("Fee Vault: {}", fee_vault);
         tracing::info!("Fee Rate BPS: {}", request.fee_rate_bps);
 
-        let ixs = program
-            .request()
-            .args(InitializeProgram {
-                fee_rate_bps: request.fee_rate_bps,
-            })
-            .accounts(InitializeProgramAccount {
+        let ixs = vec![icm_instruction(
+            InitializeProgramAccount {
                 program_state: program_state_pda,
                 fee_vault,
                 usdc_mint,
@@ -166,17 +609,13 @@ impl IcmProgramInstance {
                 token_program: spl_token::ID,
                 associated_token_program: spl_associated_token_account::ID,
                 system_program: system_program::ID,
-            })
-            .instructions()?;
+            },
+            InitializeProgram {
+                fee_rate_bps: request.fee_rate_bps,
+            },
+        )];
 
-        let recent_blockhash = program.rpc().get_latest_blockhash().await?;
-        let tx = Transaction::new_signed_with_payer(
-            &ixs,
-            Some(&owner),
-            &[&owner_keypair],
-            recent_blockhash,
-        );
-        let sig = program.rpc().send_and_confirm_transaction(&tx).await?;
+        let sig = self.send_signed(&ixs, signer).await?;
 
         Ok(UnsignedTransactionResponse {
             transaction: sig.to_string(),
@@ -188,7 +627,7 @@ impl IcmProgramInstance {
     pub async fn agent_swap_tokens_transaction(
         &self,
         request: SwapTokensRequest,
-        keypair: Keypair,
+        signer: &Keypair,
         bucket_name: &str,
         input_mint: Pubkey,
         output_mint: Pubkey,
@@ -199,13 +638,8 @@ impl IcmProgramInstance {
         pool_pc_token_account: Pubkey,
         user_authority: Pubkey,
     ) -> Result<UnsignedTransactionResponse> {
-        let cluster = self.cluster.clone();
-        let keypair_for_client = keypair.insecure_clone();
-        let keypair_for_sign = keypair.insecure_clone();
-        let client = Client::new_with_options(cluster, Arc::new(keypair_for_client), CommitmentConfig::processed());
-        let program = client.program(ICM_PROGRAM_ID)?;
 
-        let creator = keypair.pubkey();
+        let creator = signer.pubkey();
 
         // Derive bucket PDA
         let (bucket_pda, _) = Pubkey::find_program_address(
@@ -231,9 +665,8 @@ impl IcmProgramInstance {
             &ICM_PROGRAM_ID,
         );
 
-        let ixs = program
-            .request()
-            .accounts(SwapTokensAccount {
+        let ixs = vec![icm_instruction(
+            SwapTokensAccount {
                 trade_record: trade_record_pda,
                 creator,
                 bucket: bucket_pda,
@@ -254,22 +687,15 @@ impl IcmProgramInstance {
                 user_authority,
                 token_program: spl_token::ID,
                 rent: sysvar::rent::id(),
-            })
-            .args(icm_program::client::args::SwapTokens {
+            },
+            icm_program::client::args::SwapTokens {
                 in_amount: request.in_amount,
                 quoted_out_amount: request.quoted_out_amount,
                 slippage_bps: request.slippage_bps,
-            })
-            .instructions()?;
+            },
+        )];
 
-        let recent_blockhash = program.rpc().get_latest_blockhash().await?;
-        let tx = Transaction::new_signed_with_payer(
-            &ixs,
-            Some(&creator),
-            &[&keypair_for_sign],
-            recent_blockhash,
-        );
-        let sig = program.rpc().send_and_confirm_transaction(&tx).await?;
+        let sig = self.send_signed(&ixs, signer).await?;
 
         Ok(UnsignedTransactionResponse {
             transaction: sig.to_string(),
@@ -277,11 +703,91 @@ impl IcmProgramInstance {
         })
     }
 
+    /// Agent swap along a multi-hop route, all legs in one atomic transaction
+    pub async fn agent_swap_route_transaction(
+        &self,
+        signer: &Keypair,
+        bucket_name: &str,
+        legs: &[SwapLeg],
+    ) -> Result<UnsignedTransactionResponse> {
+
+        let ixs = self.swap_route_instructions(bucket_name, signer.pubkey(), legs);
+        let sig = self.send_signed(&ixs, signer).await?;
+
+        Ok(UnsignedTransactionResponse {
+            transaction: sig.to_string(),
+            message: format!("Agent {}-leg route swap for '{}'", legs.len(), bucket_name),
+        })
+    }
+
+    /// One `swap_tokens` instruction per leg, shared by route swaps and dry runs
+    fn swap_route_instructions(
+        &self,
+        bucket_name: &str,
+        creator: Pubkey,
+        legs: &[SwapLeg],
+    ) -> Vec<Instruction> {
+        // Derive bucket PDA
+        let (bucket_pda, _) = Pubkey::find_program_address(
+            &[b"bucket", bucket_name.as_bytes(), creator.as_ref()],
+            &ICM_PROGRAM_ID,
+        );
+
+        // Derive trade_record PDA
+        let (trade_record_pda, _) = Pubkey::find_program_address(
+            &[b"trade_record", bucket_pda.as_ref(), creator.as_ref()],
+            &ICM_PROGRAM_ID,
+        );
+
+        let mut ixs = Vec::with_capacity(legs.len());
+        for leg in legs {
+            let (vault_input_token_account, _) = Pubkey::find_program_address(
+                &[VAULT_SEED, bucket_pda.as_ref(), leg.input_mint.as_ref()],
+                &ICM_PROGRAM_ID,
+            );
+            let (vault_output_token_account, _) = Pubkey::find_program_address(
+                &[VAULT_SEED, bucket_pda.as_ref(), leg.output_mint.as_ref()],
+                &ICM_PROGRAM_ID,
+            );
+
+            ixs.push(icm_instruction(
+                SwapTokensAccount {
+                    trade_record: trade_record_pda,
+                    creator,
+                    bucket: bucket_pda,
+                    input_mint: leg.input_mint,
+                    system_program: system_program::id(),
+                    input_mint_program: spl_token::ID,
+                    output_mint: leg.output_mint,
+                    output_mint_program: spl_token::ID,
+                    vault_input_token_account,
+                    vault_output_token_account,
+                    raydium_amm_program: leg.raydium_amm_program,
+                    amm: leg.amm,
+                    amm_authority: leg.amm_authority,
+                    pool_coin_token_account: leg.pool_coin_token_account,
+                    pool_pc_token_account: leg.pool_pc_token_account,
+                    user_source_token_account: vault_input_token_account,
+                    user_destination_token_account: vault_output_token_account,
+                    user_authority: bucket_pda,
+                    token_program: spl_token::ID,
+                    rent: sysvar::rent::id(),
+                },
+                icm_program::client::args::SwapTokens {
+                    in_amount: leg.in_amount,
+                    quoted_out_amount: leg.quoted_out_amount,
+                    slippage_bps: leg.slippage_bps,
+                },
+            ));
+        }
+        ixs
+    }
+
     /// Manual swap tokens transaction for frontend signing using Raydium
     pub async fn manual_swap_tokens_transaction(
         &self,
         request: SwapTokensRequest,
-        keypair: Keypair,
+        signer: &Keypair,
         bucket_name: &str,
         input_mint: Pubkey,
         output_mint: Pubkey,
@@ -292,12 +798,8 @@ impl IcmProgramInstance {
         pool_pc_token_account: Pubkey,
         user_authority: Pubkey,
     ) -> Result<UnsignedTransactionResponse> {
-        let cluster = self.cluster.clone();
-        let keypair_for_client = keypair.insecure_clone();
-        let client = Client::new_with_options(cluster, Arc::new(keypair_for_client), CommitmentConfig::processed());
-        let program = client.program(ICM_PROGRAM_ID)?;
 
-        let creator = keypair.pubkey();
+        let creator = signer.pubkey();
 
         // Derive bucket PDA
         let (bucket_pda, _) = Pubkey::find_program_address(
@@ -323,9 +825,8 @@ impl IcmProgramInstance {
             &ICM_PROGRAM_ID,
         );
 
-        let ixs = program
-            .request()
-            .accounts(SwapTokensAccount {
+        let ixs = vec![icm_instruction(
+            SwapTokensAccount {
                 trade_record: trade_record_pda,
                 creator,
                 bucket: bucket_pda,
@@ -346,25 +847,26 @@ impl IcmProgramInstance {
                 user_authority,
                 token_program: spl_token::ID,
                 rent: sysvar::rent::id(),
-            })
-            .args(icm_program::client::args::SwapTokens {
+            },
+            icm_program::client::args::SwapTokens {
                 in_amount: request.in_amount,
                 quoted_out_amount: request.quoted_out_amount,
                 slippage_bps: request.slippage_bps,
-            })
-            .instructions()?;
+            },
+        )];
 
-        let recent_blockhash = program.rpc().get_latest_blockhash().await?;
+        let recent_blockhash = self.rpc.call(|rpc| async move { rpc.get_latest_blockhash().await }).await?;
         
         // Create unsigned transaction for frontend signing
-        let tx = Transaction::new_with_payer(
+        let mut tx = Transaction::new_with_payer(
             &ixs,
             Some(&creator),
         );
+        tx.message.recent_blockhash = recent_blockhash;
         
         // Serialize transaction for frontend
         let serialized_tx = bincode::serialize(&tx)?;
-        let base64_tx = base64::encode(serialized_tx);
+        let base64_tx = BASE64_STANDARD.encode(serialized_tx);
 
         Ok(UnsignedTransactionResponse {
             transaction: base64_tx,
@@ -376,43 +878,26 @@ impl IcmProgramInstance {
     pub async fn create_profile_transaction(
         &self,
         // no request parameter needed here
-        keypair: Keypair
+        signer: &Keypair
     ) -> Result<UnsignedTransactionResponse> {
-        let cluster = self.cluster.clone();
-        let keypair_for_client = keypair.insecure_clone();
-        let keypair_for_sign = keypair.insecure_clone();
-        let client = Client::new_with_options(cluster, Arc::new(keypair_for_client), CommitmentConfig::confirmed());
-        let program = client.program(ICM_PROGRAM_ID)?;
 
-        let creator = keypair.pubkey();
+        let creator = signer.pubkey();
         let (creator_profile_pda, _) = Pubkey::find_program_address(&[b"creator_profile", creator.as_ref()], &ICM_PROGRAM_ID);
 
         tracing::info!("[create_profile_transaction] Creating profile for creator: {}", creator);
         tracing::info!("[create_profile_transaction] Creator profile PDA: {}", creator_profile_pda);
 
-        let mut ixs = program
-            .request()
-            .accounts(CreateProfileAccount {
+        let ixs = vec![icm_instruction(
+            CreateProfileAccount {
                 creator_profile: creator_profile_pda,
                 creator,
                 system_program: system_program::id(),
-            })
-            .args(icm_program::client::args::CreateProfile {})
-            .instructions()?;
-
-        // Add compute budget instruction
-        let compute_budget_ix = ComputeBudgetInstruction::set_compute_unit_limit(300_000);
-        ixs.insert(0, compute_budget_ix);
+            },
+            icm_program::client::args::CreateProfile {},
+        )];
 
-        let recent_blockhash = program.rpc().get_latest_blockhash().await?;
-        let tx = Transaction::new_signed_with_payer(
-            &ixs,
-            Some(&creator),
-            &[&keypair_for_sign],
-            recent_blockhash,
-        );
         tracing::info!("[create_profile_transaction] Sending and confirming transaction...");
-        let sig = program.rpc().send_and_confirm_transaction(&tx).await?;
+        let sig = self.send_signed(&ixs, signer).await?;
 
         Ok(UnsignedTransactionResponse {
             transaction: sig.to_string(),
@@ -424,32 +909,14 @@ impl IcmProgramInstance {
     pub async fn create_bucket_transaction(
         &self,
         request: CreateBucketRequest,
-        keypair: Keypair
+        signer: &Keypair
     ) -> Result<UnsignedTransactionResponse> {
-        let cluster = self.cluster.clone();
-        let keypair_for_client = keypair.insecure_clone();
-        let keypair_for_sign = keypair.insecure_clone();
-        let client = Client::new_with_options(cluster, Arc::new(keypair_for_client), CommitmentConfig::confirmed());
-        let program = client.program(ICM_PROGRAM_ID)?;
-
-        let creator = keypair.pubkey();
-        let token_mints: Vec<Pubkey> = request.token_mints.iter().map(|m| Pubkey::from_str(m).unwrap()).collect();
 
-        // Derive PDAs for bucket, trading_pool, creator_profile
-        let (bucket_pda, _) = Pubkey::find_program_address(&[b"bucket", request.name.as_bytes(), creator.as_ref()], &ICM_PROGRAM_ID);
-        let (trading_pool_pda, _) = Pubkey::find_program_address(&[b"trading_pool", request.name.as_bytes(), creator.as_ref()], &ICM_PROGRAM_ID);
+        let creator = signer.pubkey();
         let (creator_profile_pda, _) = Pubkey::find_program_address(&[b"creator_profile", creator.as_ref()], &ICM_PROGRAM_ID);
-        let usdc_mint = Pubkey::from_str("2RgRJx3z426TMCL84ZMXTRVCS5ee7iGVE4ogqcUAd3tg")?;
-        
-        tracing::info!("=== CREATE BUCKET DEBUG INFO ===");
-        tracing::info!("Bucket name: {}", request.name);
-        tracing::info!("Creator: {}", creator);
-        tracing::info!("Bucket PDA: {}", bucket_pda);
-        tracing::info!("Trading Pool PDA: {}", trading_pool_pda);
-        tracing::info!("Creator Profile PDA: {}", creator_profile_pda);
-        
+
         // Verify creator profile exists before proceeding, create if it doesn't
-        match program.account::<icm_program::accounts::CreatorProfile>(creator_profile_pda).await {
+        match self.fetch_account::<icm_program::accounts::CreatorProfile>(creator_profile_pda).await {
             Ok(_) => {
                 tracing::info!("Creator profile verified - exists on chain");
             },
@@ -457,8 +924,7 @@ impl IcmProgramInstance {
                 tracing::warn!("Creator profile does NOT exist on chain: {}. Creating it now...", e);
                 
                 // Create the profile
-                let keypair_for_profile = keypair.insecure_clone();
-                match self.create_profile_transaction(keypair_for_profile).await {
+                match self.create_profile_transaction(signer).await {
                     Ok(profile_response) => {
                         tracing::info!("Creator profile created successfully with signature: {}", profile_response.transaction);
                         
@@ -466,7 +932,7 @@ impl IcmProgramInstance {
                         tokio::time::sleep(tokio::time::Duration::from_millis(1500)).await;
                         
                         // Verify it was created
-                        match program.account::<icm_program::accounts::CreatorProfile>(creator_profile_pda).await {
+                        match self.fetch_account::<icm_program::accounts::CreatorProfile>(creator_profile_pda).await {
                             Ok(_) => {
                                 tracing::info!("Creator profile verified after creation");
                             },
@@ -477,9 +943,8 @@ impl IcmProgramInstance {
                         }
                     },
                     Err(create_err) => {
-                        let error_str = create_err.to_string();
                         // If profile already exists (race condition), continue
-                        if error_str.contains("already in use") || error_str.contains("custom program error: 0x0") {
+                        if is_account_already_in_use(&create_err) {
                             tracing::info!("Creator profile already exists (race condition), continuing");
                         } else {
                             tracing::error!("Failed to create creator profile: {}", create_err);
@@ -490,6 +955,39 @@ impl IcmProgramInstance {
             }
         }
         
+        let ixs = self.create_bucket_instructions(&request, creator)?;
+
+        let sig = self.send_signed(&ixs, signer).await?;
+
+        Ok(UnsignedTransactionResponse {
+            transaction: sig.to_string(),
+            message: format!("Create bucket '{}'", request.name),
+        })
+    }
+
+    /// Instructions for `create_bucket`, shared by the custodial and wallet-signed paths
+    fn create_bucket_instructions(
+        &self,
+        request: &CreateBucketRequest,
+        creator: Pubkey,
+    ) -> Result<Vec<Instruction>> {
+        let token_mints = request.token_mints.iter()
+            .map(|m| Pubkey::from_str(m).map_err(|e| anyhow!("Invalid token mint '{}': {}", m, e)))
+            .collect::<Result<Vec<Pubkey>>>()?;
+
+        // Derive PDAs for bucket, trading_pool, creator_profile
+        let (bucket_pda, _) = Pubkey::find_program_address(&[b"bucket", request.name.as_bytes(), creator.as_ref()], &ICM_PROGRAM_ID);
+        let (trading_pool_pda, _) = Pubkey::find_program_address(&[b"trading_pool", request.name.as_bytes(), creator.as_ref()], &ICM_PROGRAM_ID);
+        let (creator_profile_pda, _) = Pubkey::find_program_address(&[b"creator_profile", creator.as_ref()], &ICM_PROGRAM_ID);
+        let usdc_mint = self.usdc_mint;
+
+        tracing::info!("=== CREATE BUCKET DEBUG INFO ===");
+        tracing::info!("Bucket name: {}", request.name);
+        tracing::info!("Creator: {}", creator);
+        tracing::info!("Bucket PDA: {}", bucket_pda);
+        tracing::info!("Trading Pool PDA: {}", trading_pool_pda);
+        tracing::info!("Creator Profile PDA: {}", creator_profile_pda);
+
         // Derive program_state PDA
         let (program_state_pda, _) = Pubkey::find_program_address(
             &[b"program_state"],
@@ -505,9 +1003,8 @@ impl IcmProgramInstance {
         // Derive fee_vault PDA (ATA for program_state and USDC mint)
         let fee_vault = get_associated_token_address(&program_state_pda, &usdc_mint);
 
-        let mut ixs = program
-            .request()
-            .accounts(CreateBucketAccount {
+        let ixs = vec![icm_instruction(
+            CreateBucketAccount {
                 bucket: bucket_pda,
                 trading_pool: trading_pool_pda,
                 creator_profile: creator_profile_pda,
@@ -520,8 +1017,8 @@ impl IcmProgramInstance {
                 token_program: spl_token::ID,
                 associated_token_program: spl_associated_token_account::ID,
                 system_program: system_program::id(),
-            })
-            .args(CreateBucket {
+            },
+            CreateBucket {
                 name: request.name.clone(),
                 token_mints: token_mints.clone(),
                 contribution_window_minutes: request.contribution_window_minutes, // Use minutes directly
@@ -531,47 +1028,38 @@ impl IcmProgramInstance {
                 min_contribution: request.min_contribution,
                 max_contribution: request.max_contribution,
                 management_fee: request.management_fee as u64,
-            })
-            .instructions()?;
-
-        // Add compute budget instruction to request more compute units
-        let compute_budget_ix = ComputeBudgetInstruction::set_compute_unit_limit(400_000);
-        ixs.insert(0, compute_budget_ix);
-
-        let recent_blockhash = program.rpc().get_latest_blockhash().await?;
-        let tx = Transaction::new_signed_with_payer(
-            &ixs,
-            Some(&creator),
-            &[&keypair_for_sign],
-            recent_blockhash,
-        );
-        let sig = program.rpc().send_and_confirm_transaction(&tx).await?;
+            },
+        )];
 
-        Ok(UnsignedTransactionResponse {
-            transaction: sig.to_string(),
-            message: format!("Create bucket '{}'", request.name),
-        })
+        Ok(ixs)
     }
 
     /// Contribute to bucket transaction for frontend signing
     pub async fn contribute_to_bucket_transaction(
         &self,
         request: ContributeToBucketRequest,
-        keypair: Keypair
+        signer: &Keypair
     ) -> Result<UnsignedTransactionResponse> {
-        let cluster = self.cluster.clone();
-        let keypair_for_client = keypair.insecure_clone();
-        let keypair_for_sign = keypair.insecure_clone();
-        let client = Client::new_with_options(cluster, Arc::new(keypair_for_client), CommitmentConfig::processed());
-        let program = client.program(ICM_PROGRAM_ID)?;
 
-        let contributor = keypair.pubkey();
+        let contributor = signer.pubkey();
+        let ixs = self.contribute_instructions(&request, contributor).await?;
+
+        let sig = self.send_signed(&ixs, signer).await?;
+        Ok(UnsignedTransactionResponse {
+            transaction: sig.to_string(),
+            message: format!("Contribute to bucket '{}'", request.bucket_name),
+        })
+    }
         
-        // log both contributor and keypair_for_sign.pubkey() for debugging
-        tracing::error!("Contributor: {}", contributor);
-        tracing::error!("Keypair for signing: {}", keypair_for_sign.pubkey());
+    /// Validated instructions for `contribute_to_bucket`, shared by the custodial and wallet-signed paths
+    async fn contribute_instructions(
+        &self,
+        request: &ContributeToBucketRequest,
+        contributor: Pubkey,
+    ) -> Result<Vec<Instruction>> {
+        tracing::debug!("[contribute_instructions] Contributor: {}", contributor);
         let creator = Pubkey::from_str(&request.creator_pubkey).map_err(|e| anyhow!(e))?;
-        let usdc_mint: Pubkey = Pubkey::from_str("2RgRJx3z426TMCL84ZMXTRVCS5ee7iGVE4ogqcUAd3tg").expect("Invalid pubkey");
+        let usdc_mint = self.usdc_mint;
 
         // Derive bucket PDA: [b"bucket", bucket_name, creator]
         let (bucket_pda, _) = Pubkey::find_program_address(
@@ -622,7 +1110,7 @@ impl IcmProgramInstance {
         tracing::info!("Fee Vault: {}", fee_vault);
         
         // Try to fetch the bucket account to verify it exists and check its state
-        match program.account::<icm_program::accounts::Bucket>(bucket_pda).await {
+        match self.fetch_account::<icm_program::accounts::Bucket>(bucket_pda).await {
             Ok(bucket_account) => {
                 tracing::info!("Bucket exists with status: {:?}", bucket_account.status);
                 tracing::info!("Bucket creator: {}", bucket_account.creator);
@@ -651,7 +1139,7 @@ impl IcmProgramInstance {
         }
         
         // Check if program state exists and is initialized
-        match program.account::<icm_program::accounts::ProgramState>(program_state_pda).await {
+        match self.fetch_account::<icm_program::accounts::ProgramState>(program_state_pda).await {
             Ok(program_state_account) => {
                 tracing::info!("Program state initialized: {}", program_state_account.initialized);
                 tracing::info!("Program state USDC mint: {}", program_state_account.usdc_mint);
@@ -671,7 +1159,7 @@ impl IcmProgramInstance {
         }
         
         // Check if contributor has sufficient balance
-        match program.rpc().get_token_account_balance(&contributor_token_account).await {
+        match self.rpc.call(|rpc| async move { rpc.get_token_account_balance(&contributor_token_account).await }).await {
             Ok(balance) => {
                 let balance_lamports = balance.amount.parse::<u64>().unwrap_or(0);
                 tracing::info!("Contributor token balance: {} lamports", balance_lamports);
@@ -687,7 +1175,7 @@ impl IcmProgramInstance {
         }
 
         // Verify vault token account exists (should have been created during create_bucket)
-        match program.rpc().get_account(&vault_token_account).await {
+        match self.rpc.call(|rpc| async move { rpc.get_account(&vault_token_account).await }).await {
             Ok(account) => {
                 tracing::info!("Vault token account exists with {} lamports", account.lamports);
             },
@@ -697,9 +1185,8 @@ impl IcmProgramInstance {
             }
         }
 
-        let ixs = program
-            .request()
-            .accounts(ContributeToBucketAccount{
+        let ixs = vec![icm_instruction(
+            ContributeToBucketAccount{
                 bucket: bucket_pda,
                 contribution_record: contribution_record_pda,
                 pool_contribution: pool_contribution_pda,
@@ -712,71 +1199,31 @@ impl IcmProgramInstance {
                 token_program: spl_token::ID,
                 associated_token_program: spl_associated_token_account::ID,
                 system_program: system_program::id(),
-            })
-            .args(ContributeToBucket {
+            },
+            ContributeToBucket {
                 // bucket_name: request.bucket_name.clone(),
                 amount: request.amount,
-            })
-            .instructions()?;
+            },
+        )];
 
-        let recent_blockhash = program.rpc().get_latest_blockhash().await?;
-        let tx = Transaction::new_signed_with_payer(
-            &ixs,
-            Some(&contributor),
-            &[&keypair_for_sign],
-            recent_blockhash,
-        );
-        let sig = program.rpc().send_and_confirm_transaction(&tx).await?;
-        Ok(UnsignedTransactionResponse {
-            transaction: sig.to_string(),
-            message: format!("Contribute to bucket '{}'", request.bucket_name),
-        })
+        Ok(ixs)
     }
-        
+
     /// Claim rewards transaction for frontend signing
     pub async fn claim_rewards_transaction(
         &self,
         request: ClaimRewardsRequest,
-        keypair: Keypair
+        signer: &Keypair
     ) -> Result<UnsignedTransactionResponse> {
-        let cluster = self.cluster.clone();
-        let keypair_for_client = keypair.insecure_clone();
-        let keypair_for_sign = keypair.insecure_clone();
-        let client = Client::new_with_options(cluster, Arc::new(keypair_for_client), CommitmentConfig::processed());
-        let program = client.program(ICM_PROGRAM_ID)?;
-
-        let contributor = keypair.pubkey();
-        let (bucket_pda, _) = Pubkey::find_program_address(&[b"bucket", request.bucket_name.as_bytes(), contributor.as_ref()], &ICM_PROGRAM_ID);
-        // TODO: Derive all required PDAs and accounts per IDL
-        let contribution_record = Pubkey::default();
-        let pool_contribution = Pubkey::default();
-        let contributor_token_account = Pubkey::default();
-        let vault_token_account = Pubkey::default();
-
-        let accounts = vec![
-            AccountMeta::new(bucket_pda, false),
-            AccountMeta::new(contribution_record, false),
-            AccountMeta::new(pool_contribution, true),
-            AccountMeta::new(contributor_token_account, true),
-            AccountMeta::new(vault_token_account, true),
-            AccountMeta::new(contributor, true),
-            AccountMeta::new_readonly(spl_token::ID, false),
-        ];
-
-        let ixs = program
-            .request()
-            .accounts(accounts)
-            .args(ClaimRewards {
-            })
-            .instructions()?;
-        let recent_blockhash = program.rpc().get_latest_blockhash().await?;
-        let tx = Transaction::new_signed_with_payer(
-            &ixs,
-            Some(&contributor),
-            &[&keypair_for_sign],
-            recent_blockhash,
-        );
-        let sig = program.rpc().send_and_confirm_transaction(&tx).await?;
+
+        let contributor = signer.pubkey();
+        let creator = match &request.creator_pubkey {
+            Some(creator) => Pubkey::from_str(creator).map_err(|e| anyhow!("Invalid creator_pubkey: {}", e))?,
+            None => contributor,
+        };
+        let token_mint = Pubkey::from_str(&request.token_mint).map_err(|e| anyhow!("Invalid token_mint: {}", e))?;
+        let ixs = self.claim_rewards_instructions(&request.bucket_name, creator, token_mint, contributor);
+        let sig = self.send_signed(&ixs, signer).await?;
 
         Ok(UnsignedTransactionResponse {
             transaction: sig.to_string(),
@@ -784,43 +1231,215 @@ impl IcmProgramInstance {
         })
     }
 
+    /// Bucket vault holding `mint`: contributions land in the bucket's USDC ATA,
+    /// swap proceeds in the `vault` PDA of their mint
+    fn bucket_vault(&self, bucket_pda: Pubkey, mint: Pubkey) -> Pubkey {
+        if mint == self.usdc_mint {
+            get_associated_token_address(&bucket_pda, &mint)
+        } else {
+            Pubkey::find_program_address(&[VAULT_SEED, bucket_pda.as_ref(), mint.as_ref()], &ICM_PROGRAM_ID).0
+        }
+    }
+
+    /// Vault balance of USDC and of each bucket token, in base units
+    pub async fn bucket_vault_balances(&self, bucket_name: &str, creator: Pubkey) -> Result<Vec<(Pubkey, u64)>> {
+        let (bucket_pda, _) = Pubkey::find_program_address(&[b"bucket", bucket_name.as_bytes(), creator.as_ref()], &ICM_PROGRAM_ID);
+        let bucket: icm_program::accounts::Bucket = self.fetch_account(bucket_pda).await
+            .map_err(|e| anyhow!("Bucket '{}' not found: {}", bucket_name, e))?;
+
+        let mut mints = vec![self.usdc_mint];
+        mints.extend(bucket.token_mints.into_iter().filter(|mint| *mint != self.usdc_mint));
+        let vaults: Vec<Pubkey> = mints.iter().map(|mint| self.bucket_vault(bucket_pda, *mint)).collect();
+        let vaults = vaults.as_slice();
+        let accounts = self.rpc.call(|rpc| async move { rpc.get_multiple_accounts(vaults).await }).await?;
+
+        Ok(mints.into_iter()
+            .zip(accounts)
+            .map(|(mint, account)| {
+                let amount = account.as_ref().and_then(token_account_fields).map_or(0, |(_, _, amount)| amount);
+                (mint, amount)
+            })
+            .collect())
+    }
+
+    /// Expected `claim_rewards` payout of each bucket token for one contributor,
+    /// checked against a simulated claim of every unclaimed token when `simulate` is set
+    pub async fn claim_payout_preview(
+        &self,
+        bucket_name: &str,
+        creator: Pubkey,
+        contributor: Pubkey,
+        simulate: bool,
+    ) -> Result<PayoutPreview> {
+        let (bucket_pda, _) = Pubkey::find_program_address(&[b"bucket", bucket_name.as_bytes(), creator.as_ref()], &ICM_PROGRAM_ID);
+        let bucket: icm_program::accounts::Bucket = self.fetch_account(bucket_pda).await
+            .map_err(|e| anyhow!("Bucket '{}' not found: {}", bucket_name, e))?;
+        let program_state = program_state_pda();
+        let state: icm_program::accounts::ProgramState = self.fetch_account(program_state).await
+            .map_err(|e| anyhow!("Program state not found, is the program initialized? {}", e))?;
+        let fee_vault = get_associated_token_address(&program_state, &state.usdc_mint);
+
+        let balances = self.bucket_vault_balances(bucket_name, creator).await?;
+        let mints: Vec<Pubkey> = balances.iter().map(|(mint, _)| *mint).collect();
+
+        // Every contributor's pool contribution of every mint, row by contributor
+        let records = self.fetch_program_accounts_where::<icm_program::accounts::ContributionRecord>(&[(40, bucket_pda)]).await?;
+        let mut contributors: Vec<Pubkey> = records.iter().map(|(_, record)| record.contributor).collect();
+        contributors.push(contributor);
+        contributors.sort();
+        contributors.dedup();
+        let pool_contribution_keys: Vec<Pubkey> = contributors.iter()
+            .flat_map(|holder| mints.iter().map(move |mint| Pubkey::find_program_address(
+                &[b"pool_contribution", bucket_pda.as_ref(), holder.as_ref(), mint.as_ref()],
+                &ICM_PROGRAM_ID,
+            ).0))
+            .collect();
+        let all_contributions = self.fetch_multiple_accounts::<icm_program::accounts::PoolContribution>(&pool_contribution_keys).await?;
+        let rows: Vec<&[Option<icm_program::accounts::PoolContribution>]> = all_contributions.chunks(mints.len().max(1)).collect();
+
+        // Contributions are made in USDC, which comes first
+        let usdc_contributions: Vec<Option<&icm_program::accounts::PoolContribution>> = rows.iter()
+            .map(|row| row.first().and_then(Option::as_ref))
+            .collect();
+        let own_row = contributors.binary_search(&contributor).unwrap_or_default();
+        let contribution = usdc_contributions.get(own_row).copied().flatten().cloned()
+            .ok_or_else(|| anyhow!("{} has no contribution in bucket '{}'", contributor, bucket_name))?;
+        // Claims are weighted by the stored shares; contribution amounts stand in until the program sets them
+        let use_shares = usdc_contributions.iter().flatten().all(|pc| pc.pool_share_percentage > 0);
+        let weight = |pc: &icm_program::accounts::PoolContribution| {
+            if use_shares { pc.pool_share_percentage } else { pc.contribution_amount }
+        };
+        let decimals = self.mint_decimals(&mints).await?;
+
+        let fee_rates = PayoutFeeRates {
+            creator_fee_bps: bucket.creator_fee_percent as u64,
+            performance_fee_bps: bucket.performance_fee,
+            program_fee_bps: state.fee_rate_bps as u64,
+        };
+
+        let mut tokens = Vec::with_capacity(balances.len());
+        for (index, (mint, vault_balance)) in balances.into_iter().enumerate() {
+            let claimed_by = |row: usize| rows[row].get(index).and_then(Option::as_ref).is_some_and(|pc| pc.claimed);
+            let claimed = claimed_by(own_row);
+            // This contributor stays in the total so a claimed token still shows its share
+            let unclaimed_shares: u64 = usdc_contributions.iter().enumerate()
+                .filter(|(row, _)| *row == own_row || !claimed_by(*row))
+                .filter_map(|(_, pc)| pc.map(weight))
+                .sum();
+            let amounts = payout_amounts(
+                vault_balance,
+                weight(&contribution),
+                unclaimed_shares,
+                contribution.contribution_amount,
+                fee_rates,
+                mint == self.usdc_mint,
+            );
+
+            let simulated = if simulate && !claimed {
+                let simulation = self.simulate_claim_rewards(bucket_name, creator, mint, contributor).await?;
+                let moved = |account: Pubkey| simulation.balance_changes.iter()
+                    .find(|change| change.mint.is_some() && change.account == account.to_string())
+                    .map_or(0, |change| change.delta.unsigned_abs());
+                let from_vault = moved(self.bucket_vault(bucket_pda, mint));
+                let to_contributor = moved(get_associated_token_address(&contributor, &mint));
+                let to_fee_vault = if mint == state.usdc_mint { moved(fee_vault) } else { 0 };
+                Some(SimulatedClaim {
+                    matches_estimate: simulation.success
+                        && to_contributor == amounts.net_amount
+                        && to_fee_vault == amounts.program_fee,
+                    success: simulation.success,
+                    error_code: simulation.error_code,
+                    error: simulation.error,
+                    from_vault,
+                    to_contributor,
+                    to_fee_vault,
+                })
+            } else {
+                None
+            };
+
+            let (net_amount, net_amount_source) = authoritative_net_amount(amounts.net_amount, simulated.as_ref());
+            tokens.push(TokenPayout {
+                mint: mint.to_string(),
+                decimals: decimals.get(&mint).copied().unwrap_or(0),
+                vault_balance,
+                unclaimed_shares,
+                gross_amount: amounts.gross_amount,
+                creator_fee: amounts.creator_fee,
+                performance_fee: amounts.performance_fee,
+                program_fee: amounts.program_fee,
+                estimated_net_amount: amounts.net_amount,
+                net_amount,
+                net_amount_source,
+                claimed,
+                simulated,
+            });
+        }
+
+        Ok(PayoutPreview {
+            bucket: bucket_pda.to_string(),
+            bucket_name: bucket.name,
+            creator: bucket.creator.to_string(),
+            contributor: contributor.to_string(),
+            status: format!("{:?}", bucket.status),
+            contribution_amount: contribution.contribution_amount,
+            raised_amount: bucket.raised_amount,
+            pool_share_percentage: contribution.pool_share_percentage,
+            fee_rates,
+            tokens,
+        })
+    }
+
+    /// Instructions for `claim_rewards` of one token, shared by the custodial path and dry runs
+    fn claim_rewards_instructions(
+        &self,
+        bucket_name: &str,
+        creator: Pubkey,
+        token_mint: Pubkey,
+        contributor: Pubkey,
+    ) -> Vec<Instruction> {
+        let (bucket_pda, _) = Pubkey::find_program_address(&[b"bucket", bucket_name.as_bytes(), creator.as_ref()], &ICM_PROGRAM_ID);
+        let (contribution_record, _) = Pubkey::find_program_address(
+            &[b"contribution", bucket_pda.as_ref(), contributor.as_ref(), token_mint.as_ref()],
+            &ICM_PROGRAM_ID,
+        );
+        let (pool_contribution, _) = Pubkey::find_program_address(
+            &[b"pool_contribution", bucket_pda.as_ref(), contributor.as_ref(), token_mint.as_ref()],
+            &ICM_PROGRAM_ID,
+        );
+        let (trading_pool, _) = Pubkey::find_program_address(&[b"trading_pool", bucket_name.as_bytes(), creator.as_ref()], &ICM_PROGRAM_ID);
+        let (creator_profile, _) = Pubkey::find_program_address(&[b"creator_profile", creator.as_ref()], &ICM_PROGRAM_ID);
+        let program_state = program_state_pda();
+
+        vec![icm_instruction(
+            ClaimRewardsAccount {
+                bucket: bucket_pda,
+                contribution_record,
+                pool_contribution,
+                trading_pool,
+                creator_profile,
+                contributor_token_account: get_associated_token_address(&contributor, &token_mint),
+                vault_token_account: self.bucket_vault(bucket_pda, token_mint),
+                program_state,
+                fee_vault: get_associated_token_address(&program_state, &self.usdc_mint),
+                contributor,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+            },
+            ClaimRewards {},
+        )]
+    }
+
     /// Close bucket transaction for frontend signing
     pub async fn close_bucket_transaction(
         &self,
         request: CloseBucketRequest,
-        keypair: Keypair
+        signer: &Keypair
     ) -> Result<UnsignedTransactionResponse> {
-        let cluster = self.cluster.clone();
-        let keypair_for_client = keypair.insecure_clone();
-        let keypair_for_sign = keypair.insecure_clone();
-        let client = Client::new_with_options(cluster, Arc::new(keypair_for_client), CommitmentConfig::processed());
-        let program = client.program(ICM_PROGRAM_ID)?;
 
         let creator = Pubkey::from_str(&request.creator_pubkey).map_err(|e| anyhow!(e))?;
-        let (bucket_pda, _) = Pubkey::find_program_address(&[b"bucket", request.bucket_name.as_bytes(), creator.as_ref()], &ICM_PROGRAM_ID);
-        let (trading_pool_pda, _) = Pubkey::find_program_address(&[b"trading_pool", request.bucket_name.as_bytes(), creator.as_ref()], &ICM_PROGRAM_ID);
-        let (creator_profile_pda, _) = Pubkey::find_program_address(&[b"creator_profile", creator.as_ref()], &ICM_PROGRAM_ID);
-
-        let accounts = vec![
-            AccountMeta::new(bucket_pda, true),
-            AccountMeta::new(trading_pool_pda, true),
-            AccountMeta::new(creator_profile_pda, true),
-            AccountMeta::new(creator, true),
-        ];
-
-        let ixs = program
-            .request()
-            .accounts(accounts)
-            .args(CloseBucket {})
-            .instructions()?;
-        let recent_blockhash = program.rpc().get_latest_blockhash().await?;
-        let tx = Transaction::new_signed_with_payer(
-            &ixs,
-            Some(&creator),
-            &[&keypair_for_sign],
-            recent_blockhash,
-        );
-        let sig = program.rpc().send_and_confirm_transaction(&tx).await?;
+        let ixs = self.close_bucket_instructions(&request.bucket_name, creator);
+        let sig = self.send_signed(&ixs, signer).await?;
 
         Ok(UnsignedTransactionResponse {
             transaction: sig.to_string(),
@@ -828,21 +1447,40 @@ impl IcmProgramInstance {
         })
     }
 
+    /// Instructions for `close_bucket`, shared by the custodial and wallet-signed paths
+    fn close_bucket_instructions(
+        &self,
+        bucket_name: &str,
+        creator: Pubkey,
+    ) -> Vec<Instruction> {
+        let (bucket_pda, _) = Pubkey::find_program_address(&[b"bucket", bucket_name.as_bytes(), creator.as_ref()], &ICM_PROGRAM_ID);
+        let (trading_pool, _) = Pubkey::find_program_address(&[b"trading_pool", bucket_name.as_bytes(), creator.as_ref()], &ICM_PROGRAM_ID);
+        let (creator_profile, _) = Pubkey::find_program_address(&[b"creator_profile", creator.as_ref()], &ICM_PROGRAM_ID);
+
+        vec![icm_instruction(
+            CloseBucketAccount {
+                bucket: bucket_pda,
+                vault_token_account: self.bucket_vault(bucket_pda, self.usdc_mint),
+                program_state: program_state_pda(),
+                trading_pool,
+                creator_profile,
+                creator,
+                token_program: spl_token::ID,
+            },
+            CloseBucket {},
+        )]
+    }
+
     /// Fetch a TradingPool by PDA (public key)
     pub async fn fetch_trading_pool_by_pda(
         &self,
-        request: GetBucketQuery,
-        keypair: Keypair,
+        request: GetBucketQuery
     ) -> Result<TradingPool> {
-        let cluster = self.cluster.clone();
-        let keypair_for_client = keypair.insecure_clone();
-        let client = Client::new_with_options(cluster, Arc::new(keypair_for_client), CommitmentConfig::processed());
-        let program = client.program(ICM_PROGRAM_ID)?;
         let creator = Pubkey::from_str(&request.creator_pubkey).map_err(|e| anyhow!(e))?;
 
         let (trading_pool_pda, _) = Pubkey::find_program_address(&[b"trading_pool", request.bucket_name.as_bytes(), creator.as_ref()], &ICM_PROGRAM_ID);
 
-        let anchor_pool: icm_program::accounts::TradingPool = program.account(trading_pool_pda).await?;
+        let anchor_pool: icm_program::accounts::TradingPool = self.fetch_account(trading_pool_pda).await?;
         Ok(TradingPool {
             pool_id: trading_pool_pda.to_string(),
             pool_bump: anchor_pool.pool_bump,
@@ -865,20 +1503,160 @@ impl IcmProgramInstance {
         })
     }
 
+    /// Raised and target amounts of a bucket, in USDC base units
+    pub async fn fetch_bucket_funding(&self, bucket_name: &str, creator: Pubkey) -> Result<(u64, u64)> {
+        let (bucket_pda, _) = Pubkey::find_program_address(&[b"bucket", bucket_name.as_bytes(), creator.as_ref()], &ICM_PROGRAM_ID);
+        let (trading_pool_pda, _) = Pubkey::find_program_address(&[b"trading_pool", bucket_name.as_bytes(), creator.as_ref()], &ICM_PROGRAM_ID);
+
+        let bucket: icm_program::accounts::Bucket = self.fetch_account(bucket_pda).await?;
+        let trading_pool: icm_program::accounts::TradingPool = self.fetch_account(trading_pool_pda).await?;
+        Ok((bucket.raised_amount, trading_pool.target_amount))
+    }
+
+    /// Fetch the name and creator stored in a bucket account
+    pub async fn fetch_bucket_identity(&self, bucket_pda: Pubkey) -> Result<(String, Pubkey)> {
+
+        let bucket: icm_program::accounts::Bucket = self.fetch_account(bucket_pda).await?;
+        Ok((bucket.name, bucket.creator))
+    }
+
+    /// Every contribution to a bucket, with each contributor's share of the raised amount
+    pub async fn fetch_bucket_contributions(&self, bucket_name: &str, creator: Pubkey) -> Result<BucketContributions> {
+        let (bucket_pda, _) = Pubkey::find_program_address(&[b"bucket", bucket_name.as_bytes(), creator.as_ref()], &ICM_PROGRAM_ID);
+        let bucket: icm_program::accounts::Bucket = self.fetch_account(bucket_pda).await
+            .map_err(|e| anyhow!("Bucket '{}' not found: {}", bucket_name, e))?;
+
+        // ContributionRecord layout: discriminator, contributor, bucket
+        let records = self.fetch_program_accounts_where::<icm_program::accounts::ContributionRecord>(&[(40, bucket_pda)]).await?;
+        let mut contributions = self.contribution_infos(records).await?;
+        contributions.sort_by(|a, b| b.amount.total_cmp(&a.amount));
+
+        Ok(BucketContributions {
+            bucket: bucket_pda.to_string(),
+            bucket_name: bucket.name,
+            creator: bucket.creator.to_string(),
+            raised_amount: lamports_to_usdc(bucket.raised_amount),
+            contributor_count: bucket.contributor_count,
+            contributions,
+        })
+    }
+
+    /// Contribution records of one wallet across every bucket, newest first
+    pub async fn fetch_contributor_records(&self, contributor: Pubkey) -> Result<Vec<ContributionInfo>> {
+        let records = self.fetch_program_accounts_where::<icm_program::accounts::ContributionRecord>(&[(8, contributor)]).await?;
+        let mut contributions = self.contribution_infos(records).await?;
+        contributions.sort_by_key(|c| std::cmp::Reverse(c.timestamp));
+        Ok(contributions)
+    }
+
+    /// Join contribution records with their buckets and `PoolContribution` accounts
+    async fn contribution_infos(
+        &self,
+        records: Vec<(Pubkey, icm_program::accounts::ContributionRecord)>,
+    ) -> Result<Vec<ContributionInfo>> {
+        let mut bucket_keys: Vec<Pubkey> = records.iter().map(|(_, record)| record.bucket).collect();
+        bucket_keys.sort();
+        bucket_keys.dedup();
+        let buckets: std::collections::HashMap<Pubkey, icm_program::accounts::Bucket> = bucket_keys.iter()
+            .copied()
+            .zip(self.fetch_multiple_accounts::<icm_program::accounts::Bucket>(&bucket_keys).await?)
+            .filter_map(|(key, bucket)| bucket.map(|bucket| (key, bucket)))
+            .collect();
+
+        let pool_contribution_keys: Vec<Pubkey> = records.iter()
+            .map(|(_, record)| Pubkey::find_program_address(
+                &[b"pool_contribution", record.bucket.as_ref(), record.contributor.as_ref(), record.token_mint.as_ref()],
+                &ICM_PROGRAM_ID,
+            ).0)
+            .collect();
+        let pool_contributions = self.fetch_multiple_accounts::<icm_program::accounts::PoolContribution>(&pool_contribution_keys).await?;
+
+        Ok(records.into_iter()
+            .zip(pool_contribution_keys.into_iter().zip(pool_contributions))
+            .map(|((address, record), (pool_contribution_key, pool_contribution))| {
+                let bucket = buckets.get(&record.bucket);
+                ContributionInfo {
+                    contribution_record: address.to_string(),
+                    contributor: record.contributor.to_string(),
+                    bucket: record.bucket.to_string(),
+                    bucket_name: bucket.map(|b| b.name.clone()),
+                    creator: bucket.map(|b| b.creator.to_string()),
+                    token_mint: record.token_mint.to_string(),
+                    amount: lamports_to_usdc(record.amount),
+                    timestamp: record.timestamp,
+                    share_percent: bucket
+                        .filter(|b| b.raised_amount > 0)
+                        .map(|b| record.amount as f64 / b.raised_amount as f64 * 100.0),
+                    pool_contribution: pool_contribution.map(|pc| PoolContributionInfo {
+                        address: pool_contribution_key.to_string(),
+                        contribution_amount: lamports_to_usdc(pc.contribution_amount),
+                        pool_share_percentage: pc.pool_share_percentage,
+                        claimed: pc.claimed,
+                    }),
+                }
+            })
+            .collect())
+    }
+
+    /// Trade records of a bucket. The program keeps one `TradeRecord` per bucket,
+    /// overwritten by each swap, so this holds at most the latest trade.
+    pub async fn fetch_bucket_trade_records(&self, bucket_name: &str, creator: Pubkey) -> Result<Vec<TradeRecordInfo>> {
+        let (bucket_pda, _) = Pubkey::find_program_address(&[b"bucket", bucket_name.as_bytes(), creator.as_ref()], &ICM_PROGRAM_ID);
+        let (trade_record_pda, _) = Pubkey::find_program_address(&[b"trade_record", bucket_pda.as_ref(), creator.as_ref()], &ICM_PROGRAM_ID);
+
+        let records: Vec<(Pubkey, icm_program::accounts::TradeRecord)> = self
+            .fetch_multiple_accounts::<icm_program::accounts::TradeRecord>(&[trade_record_pda])
+            .await?
+            .into_iter()
+            .flatten()
+            .map(|record| (trade_record_pda, record))
+            .collect();
+
+        let mut mints: Vec<Pubkey> = records.iter().flat_map(|(_, r)| [r.from_token, r.to_token]).collect();
+        mints.sort();
+        mints.dedup();
+        let decimals = self.mint_decimals(&mints).await?;
+        let ui_amount = |amount: u64, mint: &Pubkey| {
+            amount as f64 / 10f64.powi(decimals.get(mint).copied().unwrap_or(6) as i32)
+        };
+
+        Ok(records.into_iter()
+            .map(|(address, record)| TradeRecordInfo {
+                address: address.to_string(),
+                pool_id: record.pool_id.to_string(),
+                trade_id: record.trade_id,
+                timestamp: record.timestamp,
+                trade_type: format!("{:?}", record.trade_type),
+                from_token: record.from_token.to_string(),
+                to_token: record.to_token.to_string(),
+                amount_in: ui_amount(record.amount_in, &record.from_token),
+                amount_out: ui_amount(record.amount_out, &record.to_token),
+                success: record.success,
+            })
+            .collect())
+    }
+
+    /// Decimals of SPL token mints; the base mint layout stores them at byte 44
+    async fn mint_decimals(&self, mints: &[Pubkey]) -> Result<std::collections::HashMap<Pubkey, u8>> {
+        if mints.is_empty() {
+            return Ok(std::collections::HashMap::new());
+        }
+        let accounts = self.rpc.call(|rpc| async move { rpc.get_multiple_accounts(mints).await }).await?;
+        Ok(mints.iter()
+            .zip(accounts)
+            .filter_map(|(mint, account)| Some((*mint, *account?.data.get(44)?)))
+            .collect())
+    }
+
     /// Fetch a CreatorProfile by PDA (public key)
     pub async fn fetch_creator_profile_by_pda(
         &self, 
-        request: GetCreatorProfileQuery,
-        keypair: Keypair
+        request: GetCreatorProfileQuery
     ) -> Result<CreatorProfile> {
-        let cluster = self.cluster.clone();
-        let keypair_for_client = keypair.insecure_clone();
-        let client = Client::new_with_options(cluster, Arc::new(keypair_for_client), CommitmentConfig::processed());
-        let program = client.program(ICM_PROGRAM_ID)?;
         let creator = Pubkey::from_str(&request.creator_pubkey).map_err(|e| anyhow!(e))?;
         let (pda, _) = Pubkey::find_program_address(&[b"creator_profile", creator.as_ref()], &ICM_PROGRAM_ID);
 
-        let anchor_profile: icm_program::accounts::CreatorProfile = program.account(pda).await?;
+        let anchor_profile: icm_program::accounts::CreatorProfile = self.fetch_account(pda).await?;
         Ok(CreatorProfile {
             creator: anchor_profile.creator.to_string(),
             pools_created: anchor_profile.pools_created,
@@ -889,19 +1667,33 @@ impl IcmProgramInstance {
         })
     }
 
-    /// Get all pools (buckets) - stub implementation
+    /// Every bucket, with its strategy from the database
     pub async fn get_all_pools_by_pda(
-        &self, 
-        keypair: Keypair,
+        &self,
         db_pool: &deadpool_postgres::Pool
     ) -> Result<Vec<BucketInfo>> {
-        let cluster = self.cluster.clone();
-        let keypair_for_client = keypair.insecure_clone();
-        let client = Client::new_with_options(cluster, Arc::new(keypair_for_client), CommitmentConfig::processed());
-        let program = client.program(ICM_PROGRAM_ID)?;
+        Ok(self.fetch_pools(db_pool, None).await?.as_ref().clone())
+    }
+
+    /// One page of buckets matching the query
+    pub async fn query_pools(&self, db_pool: &deadpool_postgres::Pool, query: &PoolQuery) -> Result<PoolPage> {
+        let creator = query.creator.as_deref()
+            .map(Pubkey::from_str)
+            .transpose()
+            .map_err(|e| anyhow!("Invalid creator: {}", e))?;
+        let pools = self.fetch_pools(db_pool, creator).await?;
+        Ok(query.apply(&pools))
+    }
+
+    /// Buckets, only the creator's when given, with strategies joined from the
+    /// database. Lists are served from the pool cache while fresh.
+    async fn fetch_pools(&self, db_pool: &deadpool_postgres::Pool, creator: Option<Pubkey>) -> Result<Arc<Vec<BucketInfo>>> {
+        if let Some(pools) = self.pool_cache.get(creator) {
+            return Ok(pools);
+        }
 
-        // Fetch all Bucket accounts
-        let bucket_accounts = program.accounts::<icm_program::accounts::Bucket>(vec![]).await?;
+        let matches: Vec<(usize, Pubkey)> = creator.map(|creator| (BUCKET_CREATOR_OFFSET, creator)).into_iter().collect();
+        let bucket_accounts = self.fetch_program_accounts_where::<icm_program::accounts::Bucket>(&matches).await?;
 
         // Fetch all pool strategies from database for efficient lookup
         let strategies = crate::database::models::DatabaseTradingPool::fetch_all_pool_strategies(db_pool)
@@ -955,84 +1747,535 @@ impl IcmProgramInstance {
             })
             .collect();
 
-        // for bucket in &buckets {
-        //     println!("Bucket: {:?}", bucket);
-        // }
-        Ok(buckets)
+        let pools = Arc::new(buckets);
+        self.pool_cache.insert(creator, Arc::clone(&pools));
+        Ok(pools)
     }
 
     /// Start trading transaction - signs and submits transaction server-side
     pub async fn start_trading_transaction(
         &self,
         request: StartTradingRequest,
-        keypair: Keypair
+        signer: &Keypair
     ) -> Result<UnsignedTransactionResponse> {
         tracing::error!("[start_trading_transaction] 🔥 FUNCTION CALLED - This should appear in logs!");
         tracing::info!("[start_trading_transaction] Starting transaction creation and submission");
 
-        let cluster = self.cluster.clone();
+        let creator = Pubkey::from_str(&request.creator_pubkey).map_err(|e| anyhow!(e))?;
+        let instruction = self.start_trading_instructions(&request.bucket_name, creator)?;
+        tracing::info!("[start_trading_transaction] Instruction created successfully, {} instructions generated", instruction.len());
 
-        let keypair_for_client = keypair.insecure_clone();
-        let keypair_for_sign = keypair.insecure_clone();
-        
-        let client = Client::new_with_options(cluster, Arc::new(keypair_for_client), CommitmentConfig::processed());
+        // Sign server-side and submit like the other custodial methods
+        let sig = self.send_signed(&instruction, signer).await?;
+        tracing::info!("[start_trading_transaction] Transaction confirmed with signature: {}", sig);
 
-        let program = client.program(ICM_PROGRAM_ID)?;
+        Ok(UnsignedTransactionResponse {
+            transaction: sig.to_string(),
+            message: format!("Start trading for bucket '{}'", request.bucket_name),
+        })
+}
 
-        let creator = Pubkey::from_str(&request.creator_pubkey).map_err(|e| anyhow!(e))?;
-        
-        let (bucket_pda, bucket_bump) = Pubkey::find_program_address(
-            &[b"bucket", request.bucket_name.as_bytes(), creator.as_ref()], 
+    /// Instructions for `start_trading`, shared by the custodial and wallet-signed paths
+    fn start_trading_instructions(
+        &self,
+        bucket_name: &str,
+        creator: Pubkey,
+    ) -> Result<Vec<Instruction>> {
+        let (bucket_pda, _) = Pubkey::find_program_address(
+            &[b"bucket", bucket_name.as_bytes(), creator.as_ref()],
             &ICM_PROGRAM_ID
         );
-        
-        let (trading_pool_pda, trading_pool_bump) = Pubkey::find_program_address(
-            &[b"trading_pool", request.bucket_name.as_bytes(), creator.as_ref()], 
+        let (trading_pool_pda, _) = Pubkey::find_program_address(
+            &[b"trading_pool", bucket_name.as_bytes(), creator.as_ref()],
             &ICM_PROGRAM_ID
         );
 
-        let start_trading_accounts = StartTradingAccount {
-            bucket: bucket_pda,
-            trading_pool: trading_pool_pda,
-            creator,
-        };
-        tracing::debug!("[start_trading_transaction] All accounts prepared for StartTrading instruction");
-        
-        let start_trading_args = StartTrading {
-            bucket_name: request.bucket_name.clone(),
-        };
-        tracing::debug!("[start_trading_transaction] StartTrading arguments: bucket_name={}", start_trading_args.bucket_name);
-        
-        let instruction = program
-            .request()
-            .accounts(start_trading_accounts)
-            .args(start_trading_args)
-            .instructions()?;
-        tracing::info!("[start_trading_transaction] Instruction created successfully, {} instructions generated", instruction.len());
+        Ok(vec![icm_instruction(
+            StartTradingAccount {
+                bucket: bucket_pda,
+                trading_pool: trading_pool_pda,
+                creator,
+            },
+            StartTrading {
+                bucket_name: bucket_name.to_string(),
+            },
+        )])
+    }
 
-        tracing::info!("[start_trading_transaction] Fetching recent blockhash");
-        let recent_blockhash = program.rpc().get_latest_blockhash().await?;
-        tracing::debug!("[start_trading_transaction] Recent blockhash: {}", recent_blockhash);
-        
-        // Create signed transaction (server-side signing like other methods)
-        let tx = Transaction::new_signed_with_payer(
-            &instruction,
-            Some(&creator),
-            &[&keypair_for_sign],
-            recent_blockhash,
-        );
-        
-        tracing::info!("[start_trading_transaction] Transaction signed successfully");
-        
-        // Submit transaction to blockchain
-        let sig = program.rpc().send_and_confirm_transaction(&tx).await?;
-        tracing::info!("[start_trading_transaction] Transaction confirmed with signature: {}", sig);
+    /// Program fee settings and the current fee vault balance
+    pub async fn fee_vault_info(&self) -> Result<FeeVaultInfo> {
+        let program_state = program_state_pda();
+        let state = self.fetch_account::<icm_program::accounts::ProgramState>(program_state).await
+            .map_err(|e| anyhow!("Program state not found, is the program initialized? {}", e))?;
+        let fee_vault = get_associated_token_address(&program_state, &state.usdc_mint);
+        let balance = self.rpc
+            .call(|rpc| async move { rpc.get_token_account_balance(&fee_vault).await })
+            .await?
+            .amount
+            .parse::<u64>()?;
+
+        Ok(FeeVaultInfo {
+            program_state,
+            owner: state.owner,
+            usdc_mint: state.usdc_mint,
+            fee_vault,
+            fee_rate_bps: state.fee_rate_bps,
+            total_fees_collected: state.total_fees_collected,
+            balance,
+        })
+    }
+
+    /// Withdraw protocol fees to the owner's USDC account; `None` withdraws the whole balance
+    pub async fn withdraw_fees_transaction(
+        &self,
+        amount: Option<u64>,
+        signer: &Keypair,
+    ) -> Result<UnsignedTransactionResponse> {
+        let ixs = self.withdraw_fees_instructions(amount, signer.pubkey()).await?;
+        let sig = self.send_signed(&ixs, signer).await?;
 
         Ok(UnsignedTransactionResponse {
             transaction: sig.to_string(),
-            message: format!("Start trading for bucket '{}'", request.bucket_name),
+            message: "Protocol fees withdrawn".to_string(),
         })
-}
+    }
+
+    /// Instructions for `withdraw_fees`, shared by the custodial and wallet-signed paths.
+    /// Creates the owner's USDC account when it does not exist.
+    async fn withdraw_fees_instructions(
+        &self,
+        amount: Option<u64>,
+        owner: Pubkey,
+    ) -> Result<Vec<Instruction>> {
+        let info = self.fee_vault_info().await?;
+        if owner != info.owner {
+            return Err(anyhow!("Only the program owner {} can withdraw fees", info.owner));
+        }
+        match amount {
+            Some(0) => return Err(anyhow!("Withdrawal amount must be greater than zero")),
+            Some(amount) if amount > info.balance => {
+                return Err(anyhow!("Withdrawal of {} exceeds the fee vault balance of {}", amount, info.balance));
+            }
+            None if info.balance == 0 => return Err(anyhow!("Fee vault is empty")),
+            _ => {}
+        }
+
+        let owner_token_account = get_associated_token_address(&owner, &info.usdc_mint);
+        tracing::info!("[withdraw_fees_instructions] Withdrawing {:?} from {} to {}", amount, info.fee_vault, owner_token_account);
+
+        Ok(vec![
+            spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                &owner,
+                &owner,
+                &info.usdc_mint,
+                &spl_token::ID,
+            ),
+            icm_instruction(
+                WithdrawFeesAccount {
+                    program_state: info.program_state,
+                    fee_vault: info.fee_vault,
+                    owner_token_account,
+                    owner,
+                    token_program: spl_token::ID,
+                },
+                WithdrawFees { amount },
+            ),
+        ])
+    }
+
+    /// Finalized signatures of ICM program transactions newer than `until`, oldest first
+    pub async fn program_signatures(&self, until: Option<Signature>) -> Result<Vec<ProgramSignature>> {
+        // getSignaturesForAddress returns at most 1000 per page, newest first
+        let mut signatures = Vec::new();
+        let mut before = None;
+        loop {
+            let page = self.rpc
+                .call(|rpc| async move {
+                    let config = GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until,
+                        limit: Some(1000),
+                        commitment: Some(CommitmentConfig::finalized()),
+                    };
+                    rpc.get_signatures_for_address_with_config(&ICM_PROGRAM_ID, config).await
+                })
+                .await?;
+            let Some(last) = page.last() else { break };
+            before = Some(Signature::from_str(&last.signature)?);
+            let full_page = page.len() >= 1000;
+            for entry in page {
+                signatures.push(ProgramSignature {
+                    signature: Signature::from_str(&entry.signature)?,
+                    slot: entry.slot,
+                    failed: entry.err.is_some(),
+                });
+            }
+            if !full_page {
+                break;
+            }
+        }
+
+        signatures.reverse();
+        Ok(signatures)
+    }
+
+    /// Decoded bucket instructions of a finalized transaction; `None` when it failed
+    pub async fn program_transaction(&self, signature: Signature) -> Result<Option<ProgramTransaction>> {
+        let tx_config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(CommitmentConfig::finalized()),
+            max_supported_transaction_version: Some(0),
+        };
+        let confirmed = self.rpc
+            .call(|rpc| async move { rpc.get_transaction_with_config(&signature, tx_config).await })
+            .await?;
+        let Some(meta) = confirmed.transaction.meta else { return Ok(None) };
+        if meta.err.is_some() {
+            return Ok(None);
+        }
+        let Some(tx) = confirmed.transaction.transaction.decode() else {
+            return Err(anyhow!("Failed to decode transaction {}", signature));
+        };
+
+        // Static keys, then writable and read-only keys loaded from lookup tables
+        let mut keys = tx.message.static_account_keys().to_vec();
+        if let OptionSerializer::Some(loaded) = &meta.loaded_addresses {
+            for key in loaded.writable.iter().chain(&loaded.readonly) {
+                keys.push(Pubkey::from_str(key)?);
+            }
+        }
+
+        let instructions = tx.message.instructions().iter()
+            .enumerate()
+            .filter(|(_, ix)| keys.get(ix.program_id_index as usize) == Some(&ICM_PROGRAM_ID))
+            .filter_map(|(index, ix)| {
+                let accounts: Vec<(usize, Pubkey)> = ix.accounts.iter()
+                    .filter_map(|i| Some((*i as usize, *keys.get(*i as usize)?)))
+                    .collect();
+                let (bucket, action) = decode_program_instruction(&ix.data, &accounts, &meta)?;
+                Some(ProgramInstruction { index: index as u8, bucket, action })
+            })
+            .collect();
+
+        let program_state = program_state_pda();
+        let fee_vault = get_associated_token_address(&program_state, &self.usdc_mint);
+        let fee_vault_movement = keys.iter().position(|key| *key == fee_vault).and_then(|vault_index| {
+            let delta = token_balance(&meta.post_token_balances, vault_index) as i128
+                - token_balance(&meta.pre_token_balances, vault_index) as i128;
+            if delta == 0 {
+                return None;
+            }
+
+            let icm_ix = tx.message.instructions().iter()
+                .find(|ix| keys.get(ix.program_id_index as usize) == Some(&ICM_PROGRAM_ID));
+            let action = icm_ix.and_then(|ix| icm_instruction_name(&ix.data));
+            let bucket = icm_ix
+                .and_then(|ix| ix.accounts.first())
+                .and_then(|index| keys.get(*index as usize))
+                .copied()
+                .filter(|account| *account != program_state);
+            Some(FeeVaultMovement { action, bucket, delta })
+        });
+
+        Ok(Some(ProgramTransaction {
+            block_time: confirmed.block_time,
+            instructions,
+            fee_vault_movement,
+        }))
+    }
+
+    /// Serialize instructions into an unsigned transaction for the fee payer's wallet to sign
+    async fn wallet_transaction(
+        &self,
+        ixs: Vec<Instruction>,
+        fee_payer: Pubkey,
+        message: String,
+    ) -> Result<WalletTransactionResponse> {
+        let ixs = self.with_compute_budget_instructions(&ixs, fee_payer).await;
+        let commitment = self.rpc.commitment();
+        let (recent_blockhash, last_valid_block_height) = self.rpc
+            .call(|rpc| async move { rpc.get_latest_blockhash_with_commitment(commitment).await })
+            .await?;
+        let mut tx = Transaction::new_with_payer(&ixs, Some(&fee_payer));
+        tx.message.recent_blockhash = recent_blockhash;
+
+        Ok(WalletTransactionResponse {
+            transaction: BASE64_STANDARD.encode(bincode::serialize(&tx)?),
+            recent_blockhash: recent_blockhash.to_string(),
+            last_valid_block_height,
+            fee_payer: fee_payer.to_string(),
+            message,
+        })
+    }
+
+    /// Unsigned `create_bucket` transaction for the creator's wallet.
+    /// Creates the creator profile in the same transaction when it does not exist yet.
+    pub async fn wallet_create_bucket_transaction(
+        &self,
+        request: CreateBucketRequest,
+        creator: Pubkey,
+    ) -> Result<WalletTransactionResponse> {
+        let ixs = self.create_bucket_with_profile_instructions(&request, creator).await?;
+        self.wallet_transaction(ixs, creator, format!("Create bucket '{}'", request.name)).await
+    }
+
+    /// `create_bucket` instructions, preceded by `create_profile` when the creator has no profile yet
+    async fn create_bucket_with_profile_instructions(
+        &self,
+        request: &CreateBucketRequest,
+        creator: Pubkey,
+    ) -> Result<Vec<Instruction>> {
+        let mut ixs = self.create_bucket_instructions(request, creator)?;
+
+        let (creator_profile_pda, _) = Pubkey::find_program_address(&[b"creator_profile", creator.as_ref()], &ICM_PROGRAM_ID);
+        if self.fetch_account::<icm_program::accounts::CreatorProfile>(creator_profile_pda).await.is_err() {
+            tracing::info!("[create_bucket_with_profile_instructions] No creator profile for {}, adding create_profile", creator);
+            let profile_ixs = vec![icm_instruction(
+                CreateProfileAccount {
+                    creator_profile: creator_profile_pda,
+                    creator,
+                    system_program: system_program::id(),
+                },
+                icm_program::client::args::CreateProfile {},
+            )];
+            ixs.splice(0..0, profile_ixs);
+        }
+        Ok(ixs)
+    }
+
+    /// Unsigned `contribute_to_bucket` transaction for the contributor's wallet
+    pub async fn wallet_contribute_transaction(
+        &self,
+        request: ContributeToBucketRequest,
+        contributor: Pubkey,
+    ) -> Result<WalletTransactionResponse> {
+        let ixs = self.contribute_instructions(&request, contributor).await?;
+        self.wallet_transaction(ixs, contributor, format!("Contribute to bucket '{}'", request.bucket_name)).await
+    }
+
+    /// Unsigned `start_trading` transaction for the creator's wallet
+    pub async fn wallet_start_trading_transaction(
+        &self,
+        bucket_name: &str,
+        creator: Pubkey,
+    ) -> Result<WalletTransactionResponse> {
+        let ixs = self.start_trading_instructions(bucket_name, creator)?;
+        self.wallet_transaction(ixs, creator, format!("Start trading for bucket '{}'", bucket_name)).await
+    }
+
+    /// Unsigned `close_bucket` transaction for the creator's wallet
+    pub async fn wallet_close_bucket_transaction(
+        &self,
+        bucket_name: &str,
+        creator: Pubkey,
+    ) -> Result<WalletTransactionResponse> {
+        let ixs = self.close_bucket_instructions(bucket_name, creator);
+        self.wallet_transaction(ixs, creator, format!("Close bucket '{}'", bucket_name)).await
+    }
+
+    /// Unsigned `withdraw_fees` transaction for the program owner's wallet
+    pub async fn wallet_withdraw_fees_transaction(
+        &self,
+        amount: Option<u64>,
+        owner: Pubkey,
+    ) -> Result<WalletTransactionResponse> {
+        let ixs = self.withdraw_fees_instructions(amount, owner).await?;
+        self.wallet_transaction(ixs, owner, "Withdraw protocol fees".to_string()).await
+    }
+
+    /// Unsigned `claim_rewards` transaction for one token, for the contributor's wallet
+    pub async fn wallet_claim_rewards_transaction(
+        &self,
+        bucket_name: &str,
+        creator: Pubkey,
+        token_mint: Pubkey,
+        contributor: Pubkey,
+    ) -> Result<WalletTransactionResponse> {
+        let ixs = self.claim_rewards_instructions(bucket_name, creator, token_mint, contributor);
+        self.wallet_transaction(ixs, contributor, format!("Claim rewards from bucket '{}'", bucket_name)).await
+    }
+
+    /// Simulate instructions as an unsigned transaction from `fee_payer` without submitting it.
+    /// Writable accounts are read before and returned after the simulation to report balance changes.
+    async fn simulate_instructions(
+        &self,
+        ixs: Vec<Instruction>,
+        fee_payer: Pubkey,
+        message: String,
+    ) -> Result<SimulationResponse> {
+        // Simulate with the maximum limit so consumption is measured, not capped
+        let mut simulated = compute_budget_instructions(MAX_COMPUTE_UNIT_LIMIT, 0);
+        simulated.extend(without_compute_budget(&ixs));
+        let tx = Transaction::new_with_payer(&simulated, Some(&fee_payer));
+        let writable: Vec<Pubkey> = tx.message.account_keys.iter()
+            .enumerate()
+            .filter(|(index, _)| tx.message.is_maybe_writable(*index, None))
+            .map(|(_, key)| *key)
+            .collect();
+
+        let addresses = writable.as_slice();
+        let before = self.rpc.call(|rpc| async move { rpc.get_multiple_accounts(addresses).await }).await?;
+
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(self.rpc.commitment()),
+            accounts: Some(RpcSimulateTransactionAccountsConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                addresses: writable.iter().map(|key| key.to_string()).collect(),
+            }),
+            ..RpcSimulateTransactionConfig::default()
+        };
+        let tx = &tx;
+        let result = self.rpc
+            .call(|rpc| {
+                let config = config.clone();
+                async move { rpc.simulate_transaction_with_config(tx, config).await }
+            })
+            .await?
+            .value;
+
+        let mut changes = Vec::new();
+        if result.err.is_none() {
+            let after = result.accounts.clone().unwrap_or_default();
+            for (index, address) in writable.iter().enumerate() {
+                let before = before.get(index).cloned().flatten();
+                let after = after.get(index).cloned().flatten().and_then(|account| account.decode::<SolanaAccount>());
+                changes.extend(balance_changes(*address, before.as_ref(), after.as_ref()));
+            }
+            let mints: Vec<Pubkey> = changes.iter()
+                .filter_map(|change| change.mint.as_deref().and_then(|mint| Pubkey::from_str(mint).ok()))
+                .collect();
+            let decimals = self.mint_decimals(&mints).await?;
+            for change in changes.iter_mut() {
+                if let Some(mint) = change.mint.as_deref().and_then(|mint| Pubkey::from_str(mint).ok()) {
+                    change.decimals = decimals.get(&mint).copied().unwrap_or(0);
+                }
+            }
+        }
+
+        let program_error = result.err.as_ref().and_then(IcmProgramError::from_transaction_error);
+        tracing::info!("[simulate_instructions] {} from {}: {:?}, {:?} CU", message, fee_payer, result.err, result.units_consumed);
+
+        Ok(SimulationResponse {
+            success: result.err.is_none(),
+            message,
+            fee_payer: fee_payer.to_string(),
+            units_consumed: result.units_consumed,
+            logs: result.logs.unwrap_or_default(),
+            error: result.err.as_ref().map(|err| match program_error {
+                Some(program_error) => program_error.message().to_string(),
+                None => err.to_string(),
+            }),
+            error_code: program_error.map(|program_error| program_error.api_code()),
+            program_error_code: result.err.as_ref().and_then(transaction_custom_code),
+            balance_changes: changes,
+        })
+    }
+
+    /// Dry run of `create_bucket` for the creator's wallet
+    pub async fn simulate_create_bucket(
+        &self,
+        request: CreateBucketRequest,
+        creator: Pubkey,
+    ) -> Result<SimulationResponse> {
+        let ixs = self.create_bucket_with_profile_instructions(&request, creator).await?;
+        self.simulate_instructions(ixs, creator, format!("Create bucket '{}'", request.name)).await
+    }
+
+    /// Dry run of `contribute_to_bucket` for the contributor's wallet
+    pub async fn simulate_contribute(
+        &self,
+        request: ContributeToBucketRequest,
+        contributor: Pubkey,
+    ) -> Result<SimulationResponse> {
+        let ixs = self.contribute_instructions(&request, contributor).await?;
+        self.simulate_instructions(ixs, contributor, format!("Contribute to bucket '{}'", request.bucket_name)).await
+    }
+
+    /// Dry run of `start_trading` for the creator's wallet
+    pub async fn simulate_start_trading(
+        &self,
+        bucket_name: &str,
+        creator: Pubkey,
+    ) -> Result<SimulationResponse> {
+        let ixs = self.start_trading_instructions(bucket_name, creator)?;
+        self.simulate_instructions(ixs, creator, format!("Start trading for bucket '{}'", bucket_name)).await
+    }
+
+    /// Dry run of a `swap_tokens` leg for the bucket creator
+    pub async fn simulate_swap(
+        &self,
+        bucket_name: &str,
+        creator: Pubkey,
+        leg: SwapLeg,
+    ) -> Result<SimulationResponse> {
+        let ixs = self.swap_route_instructions(bucket_name, creator, &[leg]);
+        self.simulate_instructions(ixs, creator, format!("Swap {} for {} in bucket '{}'", leg.input_mint, leg.output_mint, bucket_name)).await
+    }
+
+    /// Dry run of `claim_rewards` for the contributor's wallet
+    pub async fn simulate_claim_rewards(
+        &self,
+        bucket_name: &str,
+        creator: Pubkey,
+        token_mint: Pubkey,
+        contributor: Pubkey,
+    ) -> Result<SimulationResponse> {
+        let ixs = self.claim_rewards_instructions(bucket_name, creator, token_mint, contributor);
+        self.simulate_instructions(ixs, contributor, format!("Claim rewards from bucket '{}'", bucket_name)).await
+    }
+
+    /// Dry run of `close_bucket` for the creator's wallet
+    pub async fn simulate_close_bucket(
+        &self,
+        bucket_name: &str,
+        creator: Pubkey,
+    ) -> Result<SimulationResponse> {
+        let ixs = self.close_bucket_instructions(bucket_name, creator);
+        self.simulate_instructions(ixs, creator, format!("Close bucket '{}'", bucket_name)).await
+    }
+
+    /// Decode a wallet-signed transaction, check its signatures and that it calls
+    /// the ICM program, then send it without waiting for confirmation
+    pub async fn submit_signed_transaction(&self, encoded: &str) -> Result<SubmittedTransactionInfo> {
+        let bytes = BASE64_STANDARD.decode(encoded.trim())
+            .map_err(|e| anyhow!("Transaction is not valid base64: {}", e))?;
+        let tx: Transaction = bincode::deserialize(&bytes)
+            .map_err(|e| anyhow!("Transaction could not be decoded: {}", e))?;
+        tx.verify().map_err(|e| anyhow!("Transaction signatures are invalid: {}", e))?;
+
+        let icm_ix = tx.message.instructions.iter()
+            .find(|ix| tx.message.account_keys.get(ix.program_id_index as usize) == Some(&ICM_PROGRAM_ID))
+            .ok_or_else(|| anyhow!("Transaction does not invoke the ICM program"))?;
+        let action = icm_instruction_name(&icm_ix.data);
+        let bucket = icm_ix.accounts.first()
+            .and_then(|index| tx.message.account_keys.get(*index as usize))
+            .copied();
+        let fee_payer = *tx.message.account_keys.first()
+            .ok_or_else(|| anyhow!("Transaction has no fee payer"))?;
+        let tx = &tx;
+        let signature = self.rpc.call(|rpc| async move { rpc.send_transaction(tx).await }).await?;
+        tracing::info!("[submit_signed_transaction] Submitted {} ({}) from {}", signature, action.unwrap_or("unknown"), fee_payer);
+
+        Ok(SubmittedTransactionInfo {
+            signature,
+            fee_payer,
+            recent_blockhash: tx.message.recent_blockhash,
+            action,
+            bucket,
+        })
+    }
+
+    /// Current status of each signature; `None` when the cluster has not seen it
+    pub async fn signature_statuses(&self, signatures: &[Signature]) -> Result<Vec<Option<TransactionStatus>>> {
+        Ok(self.rpc.call(|rpc| async move { rpc.get_signature_statuses(signatures).await }).await?.value)
+    }
+
+    /// Whether a transaction using this blockhash can still land
+    pub async fn is_blockhash_valid(&self, blockhash: &Hash) -> Result<bool> {
+        Ok(self.rpc.call(|rpc| async move { rpc.is_blockhash_valid(blockhash, CommitmentConfig::processed()).await }).await?)
+    }
 
     fn encode_response(&self, sig: String, message: String) -> UnsignedTransactionResponse {
         UnsignedTransactionResponse { transaction: sig, message }
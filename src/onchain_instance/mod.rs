@@ -8,6 +8,25 @@
 //! - Transaction building for frontend signing
 //! - Account derivation and management
 //! - Integration with Anchor client
+//! - Shared RPC client with endpoint failover
+//! - Compute budget sizing and priority fees
+//! - Filtered, cached bucket listings
+//! - Claim payout previews
 
 /// ICM program instance and transaction builders
 pub mod instance;
+
+/// Typed ICM program errors
+pub mod errors;
+
+/// Shared nonblocking RPC client
+pub mod rpc;
+
+/// Compute unit limits and priority fees for program transactions
+pub mod compute_budget;
+
+/// Bucket list filters, sorting, pagination and cache
+pub mod pool_query;
+
+/// Expected claim payouts and their fee breakdown
+pub mod payout;
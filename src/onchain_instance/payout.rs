@@ -0,0 +1,244 @@
+//! Expected `claim_rewards` payouts.
+//!
+//! Only the program's IDL (`idls/icm_program.json`) ships with this server, not
+//! its source, so the split below follows the accounts `claim_rewards` reads
+//! rather than a quoted formula:
+//!
+//! - gross = vault balance × `PoolContribution.pool_share_percentage` / the
+//!   shares of contributors who have not claimed that token yet. Dividing by
+//!   the unclaimed shares keeps the estimate stable as other contributors
+//!   drain the vault. Before the program has stored shares, contribution
+//!   amounts stand in for them.
+//! - creator fee = gross × `Bucket.creator_fee_percent` bps, on every token
+//! - performance fee = (gross − contribution) × `Bucket.performance_fee` bps
+//! - program fee = gross × `ProgramState.fee_rate_bps`
+//!
+//! The last two only apply to USDC, the only mint with a cost basis and the
+//! fee vault's mint. A successful simulated claim reports what the program
+//! actually moves and replaces this estimate in `net_amount`.
+
+use serde::Serialize;
+
+const BPS_DENOMINATOR: u128 = 10_000;
+
+/// Fee rates applied to a claim, in basis points
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PayoutFeeRates {
+    pub creator_fee_bps: u64,
+    pub performance_fee_bps: u64,
+    pub program_fee_bps: u64,
+}
+
+/// Expected payout of one token, in base units of its mint
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenPayout {
+    pub mint: String,
+    pub decimals: u8,
+    pub vault_balance: u64,
+    /// Shares of contributors still entitled to this token's vault
+    pub unclaimed_shares: u64,
+    /// Contributor's share of the vault before fees
+    pub gross_amount: u64,
+    pub creator_fee: u64,
+    pub performance_fee: u64,
+    pub program_fee: u64,
+    /// Estimated amount reaching the contributor's token account, from the fees above
+    pub estimated_net_amount: u64,
+    /// What reaches the contributor's token account: the simulated transfer when
+    /// the simulation succeeded, the estimate otherwise
+    pub net_amount: u64,
+    pub net_amount_source: PayoutSource,
+    /// Whether the contributor already claimed this token
+    pub claimed: bool,
+    /// Amounts the simulated `claim_rewards` moves; `None` when not simulated
+    pub simulated: Option<SimulatedClaim>,
+}
+
+/// Where a payout amount comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayoutSource {
+    Simulated,
+    Estimate,
+}
+
+/// Token movements of a simulated `claim_rewards`, in base units
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulatedClaim {
+    pub success: bool,
+    /// Stable code of the ICM program error, e.g. `BUCKET_NOT_CLOSED`
+    pub error_code: Option<&'static str>,
+    pub error: Option<String>,
+    pub from_vault: u64,
+    pub to_contributor: u64,
+    pub to_fee_vault: u64,
+    /// Whether the simulated transfers equal `estimated_net_amount` and `program_fee`
+    pub matches_estimate: bool,
+}
+
+/// The simulated contributor transfer when the simulation succeeded, `estimate` otherwise
+pub fn authoritative_net_amount(estimate: u64, simulated: Option<&SimulatedClaim>) -> (u64, PayoutSource) {
+    match simulated {
+        Some(claim) if claim.success => (claim.to_contributor, PayoutSource::Simulated),
+        _ => (estimate, PayoutSource::Estimate),
+    }
+}
+
+/// Payout preview of one contributor across the bucket's tokens
+#[derive(Debug, Clone, Serialize)]
+pub struct PayoutPreview {
+    pub bucket: String,
+    pub bucket_name: String,
+    pub creator: String,
+    pub contributor: String,
+    pub status: String,
+    /// Contribution in USDC base units
+    pub contribution_amount: u64,
+    /// Bucket total raised in USDC base units
+    pub raised_amount: u64,
+    /// Share as stored by the program; the weight of this contributor's claim
+    pub pool_share_percentage: u64,
+    pub fee_rates: PayoutFeeRates,
+    pub tokens: Vec<TokenPayout>,
+}
+
+/// Fee breakdown of one token's payout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PayoutAmounts {
+    pub gross_amount: u64,
+    pub creator_fee: u64,
+    pub performance_fee: u64,
+    pub program_fee: u64,
+    pub net_amount: u64,
+}
+
+/// Split `vault_balance` for a holder of `share` out of the `unclaimed_shares`
+/// still entitled to it. `contribution` is the cost basis of the performance
+/// fee and `is_usdc` enables the performance and program fees.
+pub fn payout_amounts(
+    vault_balance: u64,
+    share: u64,
+    unclaimed_shares: u64,
+    contribution: u64,
+    rates: PayoutFeeRates,
+    is_usdc: bool,
+) -> PayoutAmounts {
+    let gross_amount = if unclaimed_shares == 0 {
+        0
+    } else {
+        (vault_balance as u128 * share.min(unclaimed_shares) as u128 / unclaimed_shares as u128) as u64
+    };
+    let bps = |amount: u64, rate: u64| (amount as u128 * rate as u128 / BPS_DENOMINATOR) as u64;
+
+    let creator_fee = bps(gross_amount, rates.creator_fee_bps);
+    let (performance_fee, program_fee) = if is_usdc {
+        (
+            bps(gross_amount.saturating_sub(contribution), rates.performance_fee_bps),
+            bps(gross_amount, rates.program_fee_bps),
+        )
+    } else {
+        (0, 0)
+    };
+
+    PayoutAmounts {
+        gross_amount,
+        creator_fee,
+        performance_fee,
+        program_fee,
+        net_amount: gross_amount
+            .saturating_sub(creator_fee)
+            .saturating_sub(performance_fee)
+            .saturating_sub(program_fee),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATES: PayoutFeeRates = PayoutFeeRates {
+        creator_fee_bps: 100,
+        performance_fee_bps: 2_000,
+        program_fee_bps: 50,
+    };
+
+    #[test]
+    fn usdc_payout_withholds_every_fee() {
+        let amounts = payout_amounts(1_000_000, 2_500, 10_000, 200_000, RATES, true);
+        assert_eq!(amounts, PayoutAmounts {
+            gross_amount: 250_000,
+            creator_fee: 2_500,
+            performance_fee: 10_000,
+            program_fee: 1_250,
+            net_amount: 236_250,
+        });
+    }
+
+    #[test]
+    fn other_tokens_only_pay_the_creator_fee() {
+        let amounts = payout_amounts(1_000_000, 2_500, 10_000, 200_000, RATES, false);
+        assert_eq!(amounts.gross_amount, 250_000);
+        assert_eq!(amounts.creator_fee, 2_500);
+        assert_eq!(amounts.performance_fee, 0);
+        assert_eq!(amounts.program_fee, 0);
+        assert_eq!(amounts.net_amount, 247_500);
+    }
+
+    #[test]
+    fn no_performance_fee_on_a_loss() {
+        let amounts = payout_amounts(600_000, 2_500, 10_000, 200_000, RATES, true);
+        assert_eq!(amounts.gross_amount, 150_000);
+        assert_eq!(amounts.performance_fee, 0);
+    }
+
+    #[test]
+    fn earlier_claims_do_not_shrink_the_share() {
+        // A quarter of the shares already claimed a quarter of the vault
+        let before = payout_amounts(1_000_000, 2_500, 10_000, 200_000, RATES, true);
+        let after = payout_amounts(750_000, 2_500, 7_500, 200_000, RATES, true);
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn share_is_capped_at_the_unclaimed_total() {
+        let amounts = payout_amounts(1_000_000, 20_000, 10_000, 0, RATES, false);
+        assert_eq!(amounts.gross_amount, 1_000_000);
+    }
+
+    fn simulated_claim(success: bool, to_contributor: u64) -> SimulatedClaim {
+        SimulatedClaim {
+            success,
+            error_code: None,
+            error: None,
+            from_vault: to_contributor,
+            to_contributor,
+            to_fee_vault: 0,
+            matches_estimate: false,
+        }
+    }
+
+    #[test]
+    fn successful_simulation_overrides_the_estimate() {
+        let claim = simulated_claim(true, 236_000);
+        assert_eq!(authoritative_net_amount(236_250, Some(&claim)), (236_000, PayoutSource::Simulated));
+    }
+
+    #[test]
+    fn failed_or_missing_simulation_keeps_the_estimate() {
+        let claim = simulated_claim(false, 0);
+        assert_eq!(authoritative_net_amount(236_250, Some(&claim)), (236_250, PayoutSource::Estimate));
+        assert_eq!(authoritative_net_amount(236_250, None), (236_250, PayoutSource::Estimate));
+    }
+
+    #[test]
+    fn nothing_to_split_without_unclaimed_shares() {
+        let amounts = payout_amounts(1_000_000, 0, 0, 200_000, RATES, true);
+        assert_eq!(amounts, PayoutAmounts {
+            gross_amount: 0,
+            creator_fee: 0,
+            performance_fee: 0,
+            program_fee: 0,
+            net_amount: 0,
+        });
+    }
+}
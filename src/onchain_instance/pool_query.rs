@@ -0,0 +1,246 @@
+//! Filtered, sorted and paginated bucket listings.
+//!
+//! Buckets are fetched with `getProgramAccounts`, narrowed by a memcmp on the
+//! creator when one is given, and kept in a short-TTL cache keyed by that
+//! creator filter. Status, name search, sorting and pagination run on the
+//! cached list: status follows the variable-length name and token mints in the
+//! account layout, so it has no fixed offset to memcmp on.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::state_structs::BucketInfo;
+
+/// How long a fetched bucket list is served from the cache
+pub const POOL_CACHE_TTL: Duration = Duration::from_secs(5);
+const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 200;
+
+/// Bucket account layout: discriminator, then creator
+pub const BUCKET_CREATOR_OFFSET: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolSort {
+    RaisedAmount,
+    /// The deadline of the current phase: contributions while raising, trading otherwise
+    Deadline,
+    ContributorCount,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// Query parameters of the pools list
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PoolQuery {
+    pub creator: Option<String>,
+    /// `Raising`, `Trading` or `Closed`, case-insensitive
+    pub status: Option<String>,
+    /// Case-insensitive substring of the bucket name
+    pub search: Option<String>,
+    pub sort_by: Option<PoolSort>,
+    /// Defaults to ascending for deadlines and descending otherwise
+    pub order: Option<SortOrder>,
+    /// 1-based page number
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// One page of buckets
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolPage {
+    pub pools: Vec<BucketInfo>,
+    /// Buckets matching the filters across all pages
+    pub total: usize,
+    pub page: usize,
+    pub limit: usize,
+}
+
+impl PoolQuery {
+    pub fn page(&self) -> usize {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+    }
+
+    /// Filter, sort and paginate buckets already narrowed to the creator
+    pub fn apply(&self, pools: &[BucketInfo]) -> PoolPage {
+        let search = self.search.as_deref().map(str::trim).filter(|s| !s.is_empty()).map(str::to_lowercase);
+        let mut matching: Vec<&BucketInfo> = pools.iter()
+            .filter(|pool| self.status.as_deref().is_none_or(|status| pool.account.status.eq_ignore_ascii_case(status)))
+            .filter(|pool| search.as_deref().is_none_or(|search| pool.account.name.to_lowercase().contains(search)))
+            .collect();
+
+        let sort_by = self.sort_by.unwrap_or(PoolSort::RaisedAmount);
+        let order = self.order.unwrap_or(match sort_by {
+            PoolSort::Deadline => SortOrder::Asc,
+            _ => SortOrder::Desc,
+        });
+        // Ties fall back to the bucket address so pages stay stable
+        matching.sort_by(|a, b| {
+            let ordering = match sort_by {
+                PoolSort::RaisedAmount => a.account.raised_amount.total_cmp(&b.account.raised_amount),
+                PoolSort::Deadline => current_deadline(a).cmp(&current_deadline(b)),
+                PoolSort::ContributorCount => a.account.contributor_count.cmp(&b.account.contributor_count),
+            };
+            let ordering = match order {
+                SortOrder::Asc => ordering,
+                SortOrder::Desc => ordering.reverse(),
+            };
+            ordering.then_with(|| a.public_key.cmp(&b.public_key))
+        });
+
+        let (page, limit) = (self.page(), self.limit());
+        PoolPage {
+            total: matching.len(),
+            pools: matching.into_iter().skip((page - 1) * limit).take(limit).cloned().collect(),
+            page,
+            limit,
+        }
+    }
+}
+
+fn current_deadline(pool: &BucketInfo) -> i64 {
+    let deadline = if pool.account.status == "Raising" {
+        &pool.account.contribution_deadline
+    } else {
+        &pool.account.trading_deadline
+    };
+    deadline.parse().unwrap_or(i64::MAX)
+}
+
+/// Bucket lists by creator filter; `None` holds every bucket
+#[derive(Debug)]
+pub struct PoolCache {
+    ttl: Duration,
+    entries: DashMap<Option<Pubkey>, (Instant, Arc<Vec<BucketInfo>>)>,
+}
+
+impl Default for PoolCache {
+    fn default() -> Self {
+        Self::new(POOL_CACHE_TTL)
+    }
+}
+
+impl PoolCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl, entries: DashMap::new() }
+    }
+
+    /// The cached list, unless it is older than the TTL
+    pub fn get(&self, creator: Option<Pubkey>) -> Option<Arc<Vec<BucketInfo>>> {
+        self.entries.get(&creator)
+            .filter(|entry| entry.0.elapsed() < self.ttl)
+            .map(|entry| Arc::clone(&entry.1))
+    }
+
+    pub fn insert(&self, creator: Option<Pubkey>, pools: Arc<Vec<BucketInfo>>) {
+        self.entries.insert(creator, (Instant::now(), pools));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_structs::BucketAccount;
+
+    fn bucket(public_key: &str, name: &str, status: &str, raised_amount: f64, contributor_count: u32, deadline: i64) -> BucketInfo {
+        BucketInfo {
+            public_key: public_key.to_string(),
+            account: BucketAccount {
+                creator: String::new(),
+                name: name.to_string(),
+                token_mints: Vec::new(),
+                contribution_deadline: deadline.to_string(),
+                trading_deadline: (deadline + 1_000).to_string(),
+                creator_fee_percent: 0,
+                status: status.to_string(),
+                trading_started_at: "0".to_string(),
+                closed_at: "0".to_string(),
+                bump: 0,
+                creator_profile: Pubkey::default(),
+                performance_fee: 0,
+                raised_amount,
+                contributor_count,
+                strategy: None,
+                time_remaining: None,
+            },
+        }
+    }
+
+    fn pools() -> Vec<BucketInfo> {
+        vec![
+            bucket("a", "Alpha Fund", "Raising", 500.0, 3, 300),
+            bucket("b", "Beta", "Trading", 900.0, 1, 100),
+            bucket("c", "alphabet", "Raising", 500.0, 7, 200),
+            bucket("d", "Delta", "Closed", 100.0, 7, 50),
+        ]
+    }
+
+    fn keys(page: &PoolPage) -> Vec<&str> {
+        page.pools.iter().map(|pool| pool.public_key.as_str()).collect()
+    }
+
+    #[test]
+    fn defaults_to_raised_amount_descending_with_address_ties() {
+        let page = PoolQuery::default().apply(&pools());
+        assert_eq!(keys(&page), ["b", "a", "c", "d"]);
+        assert_eq!((page.total, page.page, page.limit), (4, 1, DEFAULT_PAGE_SIZE));
+    }
+
+    #[test]
+    fn ties_break_by_address_in_either_order() {
+        let query = PoolQuery { sort_by: Some(PoolSort::ContributorCount), ..Default::default() };
+        assert_eq!(keys(&query.apply(&pools())), ["c", "d", "a", "b"]);
+
+        let query = PoolQuery { order: Some(SortOrder::Asc), ..query };
+        assert_eq!(keys(&query.apply(&pools())), ["b", "a", "c", "d"]);
+    }
+
+    #[test]
+    fn deadlines_sort_soonest_first_by_the_current_phase() {
+        // Raising buckets use the contribution deadline, others the trading deadline
+        let query = PoolQuery { sort_by: Some(PoolSort::Deadline), ..Default::default() };
+        assert_eq!(keys(&query.apply(&pools())), ["c", "a", "d", "b"]);
+    }
+
+    #[test]
+    fn filters_by_status_and_name_case_insensitively() {
+        let query = PoolQuery { status: Some("raising".to_string()), search: Some(" ALPHA ".to_string()), ..Default::default() };
+        let page = query.apply(&pools());
+        assert_eq!(keys(&page), ["a", "c"]);
+        assert_eq!(page.total, 2);
+    }
+
+    #[test]
+    fn pages_through_the_sorted_list() {
+        let query = PoolQuery { page: Some(2), limit: Some(3), ..Default::default() };
+        let page = query.apply(&pools());
+        assert_eq!(keys(&page), ["d"]);
+        assert_eq!((page.total, page.page, page.limit), (4, 2, 3));
+
+        let beyond = PoolQuery { page: Some(5), limit: Some(3), ..Default::default() }.apply(&pools());
+        assert!(beyond.pools.is_empty());
+        assert_eq!(beyond.total, 4);
+    }
+
+    #[test]
+    fn page_and_limit_are_clamped() {
+        let query = PoolQuery { page: Some(0), limit: Some(0), ..Default::default() };
+        assert_eq!((query.page(), query.limit()), (1, 1));
+        assert_eq!(keys(&query.apply(&pools())), ["b"]);
+
+        let query = PoolQuery { limit: Some(10_000), ..Default::default() };
+        assert_eq!(query.limit(), MAX_PAGE_SIZE);
+    }
+}
@@ -0,0 +1,117 @@
+//! Shared Solana RPC access.
+//!
+//! One nonblocking client per configured endpoint, created once and shared by
+//! every component so HTTP connections are reused. Calls go to the active
+//! endpoint and move on to the next one when it is unreachable.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use solana_client::client_error::{ClientError, ClientErrorKind, Result as ClientResult};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+
+/// Endpoints, commitment and timeouts for the shared client
+#[derive(Debug, Clone)]
+pub struct RpcConfig {
+    /// Tried in order; the first is the primary
+    pub urls: Vec<String>,
+    pub commitment: CommitmentConfig,
+    pub request_timeout: Duration,
+    /// How long `send_and_confirm_transaction` waits for confirmation
+    pub confirm_timeout: Duration,
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        Self {
+            urls: vec!["https://api.devnet.solana.com".to_string()],
+            commitment: CommitmentConfig::confirmed(),
+            request_timeout: Duration::from_secs(30),
+            confirm_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Nonblocking RPC clients for every configured endpoint, with failover
+pub struct SolanaRpc {
+    endpoints: Vec<Arc<RpcClient>>,
+    active: AtomicUsize,
+    commitment: CommitmentConfig,
+}
+
+impl SolanaRpc {
+    pub fn new(config: RpcConfig) -> Self {
+        let urls = if config.urls.is_empty() { RpcConfig::default().urls } else { config.urls };
+        let endpoints = urls.into_iter()
+            .map(|url| Arc::new(RpcClient::new_with_timeouts_and_commitment(
+                url,
+                config.request_timeout,
+                config.commitment,
+                config.confirm_timeout,
+            )))
+            .collect();
+
+        Self {
+            endpoints,
+            active: AtomicUsize::new(0),
+            commitment: config.commitment,
+        }
+    }
+
+    /// Default commitment used by every endpoint
+    pub fn commitment(&self) -> CommitmentConfig {
+        self.commitment
+    }
+
+    /// URL of the endpoint currently in use
+    pub fn url(&self) -> String {
+        self.current().1.url()
+    }
+
+    fn current(&self) -> (usize, Arc<RpcClient>) {
+        let index = self.active.load(Ordering::Relaxed) % self.endpoints.len();
+        (index, Arc::clone(&self.endpoints[index]))
+    }
+
+    /// Run an RPC call against the active endpoint, moving to the next endpoint
+    /// when it cannot be reached. Errors returned by a reachable node are final.
+    pub async fn call<T, F, Fut>(&self, op: F) -> ClientResult<T>
+    where
+        F: Fn(Arc<RpcClient>) -> Fut,
+        Fut: Future<Output = ClientResult<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            let (index, client) = self.current();
+            match op(Arc::clone(&client)).await {
+                Err(e) if is_transport_error(&e) && attempt + 1 < self.endpoints.len() => {
+                    let next = (index + 1) % self.endpoints.len();
+                    // Only the first caller to see this endpoint fail moves the pointer
+                    if self.active.compare_exchange(index, next, Ordering::Relaxed, Ordering::Relaxed).is_ok() {
+                        tracing::warn!("[call] RPC endpoint {} failed ({}), failing over to {}",
+                                       client.url(), e, self.endpoints[next].url());
+                    }
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for SolanaRpc {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SolanaRpc")
+            .field("urls", &self.endpoints.iter().map(|c| c.url()).collect::<Vec<_>>())
+            .field("active", &self.active.load(Ordering::Relaxed))
+            .field("commitment", &self.commitment)
+            .finish()
+    }
+}
+
+/// Connection-level failures where another endpoint may do better
+fn is_transport_error(error: &ClientError) -> bool {
+    matches!(error.kind(), ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_))
+}
@@ -1,12 +1,17 @@
+use std::convert::Infallible;
+use std::str::FromStr;
 use std::sync::Arc;
 use axum::{
-    extract::{State, Json},
+    extract::{State, Json, Query, ws::{Message, WebSocket, WebSocketUpgrade}},
     http::StatusCode,
-    response::Json as ResponseJson,
+    response::{Json as ResponseJson, Response, sse::{Event, KeepAlive, Sse}},
     Router, routing::{get, post},
 };
+use futures::Stream;
 use serde::{Deserialize, Serialize};
-use tracing::{info, warn};
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{debug, info, warn};
 // use tokio::sync::RwLock;
 
 use crate::agent::{
@@ -14,6 +19,9 @@ use crate::agent::{
     StrategyType, StrategyParameters, RiskLimits, ExecutionSettings,
     trading_agent::{TradingAgentConfig, TradingAgentConfigBuilder, AgentStats},
 };
+use crate::agent::position_sizing::SizingMode;
+use crate::agent::strategy::StrategyFactory;
+use crate::services::event_bus::EventEnvelope;
 use crate::server::AppState;
 
 /// Response for agent status endpoint
@@ -34,6 +42,8 @@ pub struct StartAgentRequest {
     pub data_fetch_interval_ms: Option<u64>,
     pub learning_enabled: Option<bool>,
     pub portfolio_id: uuid::Uuid,
+    /// Bucket PDA the agent trades for; required by every strategy but grid trading
+    pub pool_id: Option<String>,
 }
 
 /// Strategy configuration request format
@@ -46,6 +56,7 @@ pub struct StrategyConfigRequest {
     pub max_position_size_usd: Option<f64>,
     pub priority_fee_percentile: Option<u8>,
     pub max_priority_fee_lamports: Option<u64>,
+    pub position_sizing: Option<SizingMode>,
 }
 
 /// Request to update strategy configuration
@@ -104,18 +115,23 @@ pub async fn start_agent(
     // Convert strategy requests to actual strategy configs
     let mut strategy_configs = Vec::new();
     for strategy_req in request.strategies {
-        let strategy_config = convert_strategy_request(strategy_req)
+        let strategy_config = convert_strategy_request(strategy_req, request.pool_id.as_deref())
             .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid strategy config: {}", e)))?;
         strategy_configs.push(strategy_config);
     }
 
     // Build trading agent configuration
     let mut config_builder = TradingAgentConfigBuilder::new()
+        .with_agent_defaults(&state.config.agent)
         .with_openai_api_key(request.openai_api_key)
         .with_token_pairs(request.token_pairs)
         .with_strategy_configs(strategy_configs)
         .with_portfolio_id(request.portfolio_id);
 
+    if let Some(pool_id) = request.pool_id {
+        config_builder = config_builder.with_pool_id(pool_id);
+    }
+
     if let Some(interval) = request.data_fetch_interval_ms {
         config_builder = config_builder.with_data_fetch_interval(interval);
     }
@@ -126,16 +142,20 @@ pub async fn start_agent(
         .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid configuration: {}", e)))?;
 
     // Create new trading agent
-    let new_agent = TradingAgent::new(config, Arc::clone(&state.icm_client), state.db.pool().clone()).await
+    let new_agent = TradingAgent::new(config, Arc::clone(&state.icm_client), state.db.pool().clone(), &state.event_bus).await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to create agent: {}", e)))?;
 
-    // Start the agent (this would need to be run in a background task)
-    // For now, we'll just create it and mark it as ready
-    info!("Trading agent created successfully");
+    new_agent.start().await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to start agent: {}", e)))?;
+    info!("Trading agent started");
 
-    // Store the agent
+    // Replace and stop the previous agent
     let mut agent_guard = state.trading_agent.write().await;
-    *agent_guard = Some(new_agent);
+    if let Some(previous) = agent_guard.replace(new_agent)
+        && let Err(e) = previous.stop().await
+    {
+        warn!("Failed to stop previous trading agent: {}", e);
+    }
 
     Ok(ResponseJson(AgentStatusResponse {
         status: "started".to_string(),
@@ -196,12 +216,11 @@ pub async fn update_strategy(
 ) -> Result<ResponseJson<AgentStatusResponse>, (StatusCode, String)> {
     info!("Updating strategy configuration");
 
-    let strategy_config = convert_strategy_request(request.strategy_config)
-        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid strategy config: {}", e)))?;
-
     let agent_guard = state.trading_agent.read().await;
     
     if let Some(agent) = agent_guard.as_ref() {
+        let strategy_config = convert_strategy_request(request.strategy_config, agent.pool_id())
+            .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid strategy config: {}", e)))?;
         agent.update_strategy_config(strategy_config).await
             .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to update strategy: {}", e)))?;
         
@@ -269,8 +288,95 @@ pub async fn emergency_stop(
     }
 }
 
-/// Convert strategy request to actual strategy config
-fn convert_strategy_request(req: StrategyConfigRequest) -> Result<StrategyConfig, String> {
+/// Query parameters for the agent event streams
+#[derive(Debug, Deserialize)]
+pub struct EventStreamQuery {
+    /// Only events from the agent trading this pool
+    pub pool_id: Option<String>,
+    /// Comma-separated event types, e.g. "plan_created,trade_executed"
+    pub types: Option<String>,
+}
+
+impl EventStreamQuery {
+    fn matches(&self, envelope: &EventEnvelope) -> bool {
+        let pool_matches = self.pool_id.as_ref()
+            .is_none_or(|pool_id| envelope.pool_id.as_deref() == Some(pool_id.as_str()));
+        let type_matches = self.types.as_ref()
+            .is_none_or(|types| types.split(',').any(|kind| kind.trim() == envelope.event.kind()));
+        pool_matches && type_matches
+    }
+}
+
+/// Stream event bus events as Server-Sent Events
+pub async fn stream_events(
+    State(state): State<AppState>,
+    Query(query): Query<EventStreamQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    info!("[stream_events] New SSE subscriber (pool: {:?}, types: {:?})", query.pool_id, query.types);
+    let receiver = state.event_bus.subscribe();
+
+    let stream = futures::stream::unfold((receiver, query), |(mut receiver, query)| async move {
+        loop {
+            let event = match receiver.recv().await {
+                Ok(envelope) if query.matches(&envelope) => Event::default()
+                    .event(envelope.event.kind())
+                    .json_data(&envelope)
+                    .unwrap_or_else(|e| Event::default().event("error").data(e.to_string())),
+                Ok(_) => continue,
+                // Tell the client it missed events instead of silently dropping them
+                Err(RecvError::Lagged(skipped)) => Event::default().event("lagged").data(skipped.to_string()),
+                Err(RecvError::Closed) => return None,
+            };
+            return Some((Ok(event), (receiver, query)));
+        }
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// Stream event bus events over a WebSocket as JSON text frames
+pub async fn stream_events_ws(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    Query(query): Query<EventStreamQuery>,
+) -> Response {
+    info!("[stream_events_ws] New WebSocket subscriber (pool: {:?}, types: {:?})", query.pool_id, query.types);
+    let receiver = state.event_bus.subscribe();
+    ws.on_upgrade(move |socket| forward_events(socket, receiver, query))
+}
+
+async fn forward_events(
+    mut socket: WebSocket,
+    mut receiver: broadcast::Receiver<EventEnvelope>,
+    query: EventStreamQuery,
+) {
+    loop {
+        tokio::select! {
+            event = receiver.recv() => {
+                let payload = match event {
+                    Ok(envelope) if query.matches(&envelope) => serde_json::to_string(&envelope),
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(skipped)) => {
+                        serde_json::to_string(&serde_json::json!({ "type": "lagged", "skipped": skipped }))
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                let Ok(payload) = payload else { continue };
+                if socket.send(Message::Text(payload.into())).await.is_err() {
+                    break;
+                }
+            }
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                _ => {}
+            }
+        }
+    }
+    debug!("[forward_events] WebSocket subscriber disconnected");
+}
+
+/// Convert strategy request to actual strategy config trading for the bucket `pool_id`
+fn convert_strategy_request(req: StrategyConfigRequest, pool_id: Option<&str>) -> Result<StrategyConfig, String> {
     let strategy_type = match req.strategy_type.as_str() {
         "Arbitrage" => StrategyType::Arbitrage,
         "DCA" => StrategyType::DCA,
@@ -280,13 +386,26 @@ fn convert_strategy_request(req: StrategyConfigRequest) -> Result<StrategyConfig
         _ => return Err(format!("Unknown strategy type: {}", req.strategy_type)),
     };
 
+    // Plans swap out of the bucket vault, the same parameter `for_bucket` sets
+    let mut custom_params = std::collections::HashMap::new();
+    match pool_id {
+        Some(pool_id) => {
+            Pubkey::from_str(pool_id).map_err(|e| format!("Invalid pool_id {}: {}", pool_id, e))?;
+            custom_params.insert("bucket_pubkey".to_string(), serde_json::json!(pool_id));
+        }
+        None if StrategyFactory::requires_bucket(&strategy_type) => {
+            return Err(format!("{} trades a bucket vault and needs a pool_id", req.strategy_type));
+        }
+        None => {}
+    }
+
     let parameters = StrategyParameters {
         min_spread_bps: req.min_spread_bps.unwrap_or(50),
         max_slippage_bps: req.max_slippage_bps.unwrap_or(100),
         position_size_usd: req.position_size_usd.unwrap_or(1000.0),
         rebalance_threshold_pct: 0.05, // Default 5%
         lookback_periods: 24, // Default 24 periods
-        custom_params: std::collections::HashMap::new(),
+        custom_params,
     };
 
     let risk_limits = RiskLimits {
@@ -310,6 +429,7 @@ fn convert_strategy_request(req: StrategyConfigRequest) -> Result<StrategyConfig
         parameters,
         risk_limits,
         execution_settings,
+        position_sizing: req.position_sizing.unwrap_or_default(),
     })
 }
 
@@ -323,4 +443,6 @@ pub fn create_routes() -> Router<AppState> {
         .route("/api/v1/agent/strategy", post(update_strategy))
         .route("/api/v1/agent/rebalance", post(force_rebalance))
         .route("/api/v1/agent/emergency-stop", post(emergency_stop))
+        .route("/api/v1/agent/events", get(stream_events))
+        .route("/api/v1/agent/events/ws", get(stream_events_ws))
 }
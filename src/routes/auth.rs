@@ -96,14 +96,7 @@ pub async fn me(
         // fill other fields as needed
     };
 
-    // Fetch user's keypair for pool queries
-    let keypair = match crate::routes::icm::get_user_keypair_by_email(&user.email, &app_state).await {
-        Ok(kp) => kp,
-        Err(e) => {
-            return (StatusCode::INTERNAL_SERVER_ERROR, AxumJson(serde_json::json!({"error": "Failed to get user keypair"})));
-        }
-    };
-    let all_pools = match app_state.icm_client.get_all_pools_by_pda(keypair, app_state.db.pool()).await {
+    let all_pools = match app_state.icm_client.get_all_pools_by_pda(app_state.db.pool()).await {
         Ok(pools) => pools,
         Err(e) => {
             tracing::error!("Failed to fetch all pools: {}", e);
@@ -112,7 +105,7 @@ pub async fn me(
     };
 
     // Fetch wallet balances
-    let wallet_balances = match crate::routes::wallet::fetch_wallet_balances(&wallet_address).await {
+    let wallet_balances = match crate::routes::wallet::fetch_wallet_balances(&app_state.icm_client.rpc(), &app_state.config.usdc_mint, &wallet_address).await {
         Ok(balances) => serde_json::json!({
             "sol": balances.sol_balance,
             "usdc": balances.usdc_balance
@@ -184,14 +177,13 @@ pub async fn register(
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
     tracing::info!("[register] Creating on-chain creator profile for user: {}", pubkey_str);
-    match app_state.icm_client.create_profile_transaction(keypair_for_profile).await {
+    match app_state.icm_client.create_profile_transaction(&keypair_for_profile).await {
         Ok(profile_response) => {
             tracing::info!("[register] Creator profile created successfully with signature: {}", profile_response.transaction);
         },
         Err(e) => {
-            let error_str = e.to_string();
             // If profile already exists (shouldn't happen on registration, but handle it)
-            if error_str.contains("already in use") || error_str.contains("custom program error: 0x0") {
+            if crate::onchain_instance::errors::is_account_already_in_use(&e) {
                 tracing::info!("[register] Creator profile already exists for new user (unexpected but continuing)");
             } else {
                 tracing::error!("[register] Failed to create on-chain creator profile: {}", e);
@@ -301,15 +293,7 @@ pub async fn login(
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     let expires_at = claims.exp;
 
-    // Fetch user's keypair for pool queries
-    let keypair = match crate::routes::icm::get_user_keypair_by_email(&user.email, &app_state).await {
-        Ok(kp) => kp,
-        Err(e) => {
-            // tracing::error!("Failed to get user keypair: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
-        }
-    };
-    let all_pools = match app_state.icm_client.get_all_pools_by_pda(keypair, app_state.db.pool()).await {
+    let all_pools = match app_state.icm_client.get_all_pools_by_pda(app_state.db.pool()).await {
         Ok(pools) => pools,
         Err(e) => {
             tracing::error!("Failed to fetch all pools: {}", e);
@@ -318,7 +302,7 @@ pub async fn login(
     };
 
     // Fetch wallet balances
-    let wallet_balances = match crate::routes::wallet::fetch_wallet_balances(&wallet_address).await {
+    let wallet_balances = match crate::routes::wallet::fetch_wallet_balances(&app_state.icm_client.rpc(), &app_state.config.usdc_mint, &wallet_address).await {
         Ok(balances) => serde_json::json!({
             "sol": balances.sol_balance,
             "usdc": balances.usdc_balance
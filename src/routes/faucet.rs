@@ -1,6 +1,7 @@
 use axum::{Json, extract::{State, Request}, response::IntoResponse};
 use axum_extra::extract::cookie::CookieJar;
 use serde::{Deserialize, Serialize};
+use crate::onchain_instance::instance::usdc_to_lamports;
 use crate::server::AppState;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::{Keypair, Signer};
@@ -24,15 +25,9 @@ pub struct FaucetResponse {
     pub tx_signature: Option<String>,
 }
 
-const USDC_MINT: &str = "2RgRJx3z426TMCL84ZMXTRVCS5ee7iGVE4ogqcUAd3tg";
 const MAX_FAUCET_AMOUNT: f64 = 100.0; // 100 USDC (human-readable)
 const FAUCET_INTERVAL_SECS: u64 = 3 * 60 * 60; // 3 hours
 
-/// Convert human-readable USDC amount to lamports (multiply by 1e6)
-fn usdc_to_lamports(usdc_amount: f64) -> u64 {
-    (usdc_amount * 1_000_000.0) as u64
-}
-
 
 #[axum::debug_handler]
 pub async fn claim_faucet(
@@ -140,11 +135,8 @@ pub async fn claim_faucet(
 
     tracing::debug!("Faucet public key: {:?}", faucet_keypair.pubkey());
 
-    let usdc_mint = Pubkey::from_str(USDC_MINT).unwrap();
-    let cluster = state.icm_client.cluster.clone();
-    let client = anchor_client::Client::new_with_options(cluster, faucet_keypair.clone(), anchor_client::solana_sdk::commitment_config::CommitmentConfig::processed());
-    let program = client.program(crate::onchain_instance::instance::ICM_PROGRAM_ID).unwrap();
-    let rpc = program.rpc();
+    let usdc_mint = state.config.usdc_mint;
+    let rpc = state.icm_client.rpc();
 
     // Derive faucet and user ATAs
     let faucet_ata = spl_associated_token_account::get_associated_token_address(&faucet_keypair.pubkey(), &usdc_mint);
@@ -156,7 +148,7 @@ pub async fn claim_faucet(
     let mut instructions = Vec::new();
     
     // Check if user ATA exists
-    let user_ata_account = rpc.get_account(&user_ata).await;
+    let user_ata_account = rpc.call(|client| async move { client.get_account(&user_ata).await }).await;
     if user_ata_account.is_err() {
         // Create user's ATA if it doesn't exist
         let create_ata_ix = spl_associated_token_account::instruction::create_associated_token_account(
@@ -190,7 +182,7 @@ pub async fn claim_faucet(
     // Add SOL transfer to the beginning of instructions
     instructions.insert(0, sol_ix);
     
-    let recent_blockhash = match rpc.get_latest_blockhash().await {
+    let recent_blockhash = match rpc.call(|client| async move { client.get_latest_blockhash().await }).await {
         Ok(b) => b,
         Err(e) => {
             return Json(FaucetResponse {
@@ -210,7 +202,7 @@ pub async fn claim_faucet(
 
     // tracing::info!("Faucet transaction: {:?}", tx);
 
-    match rpc.send_and_confirm_transaction(&tx).await {
+    match rpc.call(|client| { let tx = &tx; async move { client.send_and_confirm_transaction(tx).await } }).await {
         Ok(sig) => {
             // Update last_faucet_claim in DB
             let _ = state.db.update_last_faucet_claim(&user_profile.user_pubkey, now).await;
@@ -1,6 +1,12 @@
-use axum::{Json, extract::{State, Extension}, response::IntoResponse};
+use axum::{Json, extract::{State, Extension}, http::StatusCode, response::IntoResponse};
 use serde::{Serialize};
 use crate::server::AppState;
+use crate::onchain_instance::compute_budget::ComputeBudgetOptions;
+use crate::onchain_instance::errors::{is_account_already_in_use, IcmProgramError};
+use crate::onchain_instance::instance::{lamports_to_usdc, usdc_to_lamports};
+use crate::onchain_instance::pool_query::{PoolPage, PoolQuery};
+use crate::agent::pool_resolver::RaydiumPool;
+use crate::services::event_bus::{DomainEvent, PoolCreated, ContributionMade, PoolFilled, TradingStarted, PoolClosed};
 use anchor_client::solana_sdk::signature::Keypair;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signer;
@@ -12,16 +18,17 @@ use chrono::{DateTime, Utc, Duration};
 use uuid;
 
 use crate::state_structs::{CreateBucketApiRequest, CreateBucketRequest, ContributeToBucketApiRequest, ContributeToBucketRequest,
-UnsignedTransactionResponse, GetBucketQuery, BucketInfo, TradingPool, CloseBucketRequest, GetCreatorProfileQuery, ClaimRewardsRequest, StartTradingRequest, SwapTokensRequest, InitializeProgramRequest};
+UnsignedTransactionResponse, GetBucketQuery, GetContributorQuery, BucketContributions, ContributionInfo, TradeRecordInfo, BucketInfo, TradingPool, CloseBucketRequest, GetCreatorProfileQuery, ClaimRewardsRequest, StartTradingRequest, SwapTokensRequest, InitializeProgramRequest, PayoutPreviewQuery};
+use crate::onchain_instance::payout::PayoutPreview;
 
-/// Convert human-readable USDC amount to lamports (multiply by 1e6)
-fn usdc_to_lamports(usdc_amount: f64) -> u64 {
-    (usdc_amount * 1_000_000.0) as u64
-}
-
-/// Convert lamports to human-readable USDC amount (divide by 1e6)
-fn lamports_to_usdc(lamports: u64) -> f64 {
-    lamports as f64 / 1_000_000.0
+/// Bucket PDA as a string, used to scope published events to a pool
+fn bucket_pool_id(bucket_name: &str, creator_pubkey: &str) -> Option<String> {
+    let creator = Pubkey::from_str(creator_pubkey).ok()?;
+    let (bucket_pda, _) = Pubkey::find_program_address(
+        &[b"bucket", bucket_name.as_bytes(), creator.as_ref()],
+        &crate::onchain_instance::instance::ICM_PROGRAM_ID,
+    );
+    Some(bucket_pda.to_string())
 }
 
 /// Format seconds into a human-readable time string
@@ -130,6 +137,12 @@ pub struct ApiResponse<T> {
     pub success: bool,
     pub data: Option<T>,
     pub error: Option<String>,
+    /// Stable code of the ICM program error, e.g. `BUCKET_NOT_TRADING`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<&'static str>,
+    /// HTTP status used when the response is returned directly from a handler
+    #[serde(skip)]
+    pub status: StatusCode,
 }
 
 impl<T> ApiResponse<T> {
@@ -138,6 +151,8 @@ impl<T> ApiResponse<T> {
             success: true,
             data: Some(data),
             error: None,
+            error_code: None,
+            status: StatusCode::OK,
         }
     }
 
@@ -146,10 +161,33 @@ impl<T> ApiResponse<T> {
             success: false,
             data: None,
             error: Some(message),
+            error_code: None,
+            status: StatusCode::OK,
+        }
+    }
+
+    /// Error response for a failed program call; ICM program errors carry their
+    /// API code, HTTP status and user-facing message
+    pub fn from_error(error: &anyhow::Error) -> Self {
+        match IcmProgramError::from_error(error) {
+            Some(program_error) => Self {
+                success: false,
+                data: None,
+                error: Some(program_error.message().to_string()),
+                error_code: Some(program_error.api_code()),
+                status: program_error.status(),
+            },
+            None => Self::error(format!("{:#}", error)),
         }
     }
 }
 
+impl<T: Serialize> IntoResponse for ApiResponse<T> {
+    fn into_response(self) -> axum::response::Response {
+        (self.status, Json(self)).into_response()
+    }
+}
+
 // --- Helper: Get user keypair from DB by email ---
 pub async fn get_user_keypair_by_email(email: &str, state: &AppState) -> Result<Keypair, String> {
     // Fetch private_key from user_profiles by email (as in auth.rs)
@@ -212,22 +250,23 @@ pub async fn check_program_status(
 pub async fn initialize_program(
     State(state): State<AppState>,
     Extension(auth_user): Extension<crate::auth::models::AuthUser>,
+    Query(budget): Query<ComputeBudgetOptions>,
     Json(request): Json<InitializeProgramRequest>
-) -> ResponseJson<ApiResponse<UnsignedTransactionResponse>> {
+) -> ApiResponse<UnsignedTransactionResponse> {
     // Get user keypair
     let keypair = match get_user_keypair_by_email(&auth_user.email, &state).await {
         Ok(keypair) => keypair,
         Err(e) => {
-            return ResponseJson(ApiResponse::error(format!("Failed to get keypair: {}", e)));
+            return ApiResponse::error(format!("Failed to get keypair: {}", e));
         }
     };
 
     // Call the initialize program transaction
-    let result = state.icm_client.initialize_program_transaction(request, keypair).await;
+    let result = state.icm_client.with_compute_budget(budget).initialize_program_transaction(request, &keypair).await;
 
     match result {
-        Ok(response) => ResponseJson(ApiResponse::success(response)),
-        Err(e) => ResponseJson(ApiResponse::error(format!("Failed to initialize program: {}", e))),
+        Ok(response) => ApiResponse::success(response),
+        Err(e) => ApiResponse::from_error(&e.context("Failed to initialize program")),
     }
 }
 
@@ -235,23 +274,23 @@ pub async fn initialize_program(
 #[axum::debug_handler]
 pub async fn create_profile(
     State(state): State<AppState>,
-    Extension(auth_user): Extension<crate::auth::models::AuthUser>
-) -> ResponseJson<ApiResponse<UnsignedTransactionResponse>> {
+    Extension(auth_user): Extension<crate::auth::models::AuthUser>,
+    Query(budget): Query<ComputeBudgetOptions>,
+) -> ApiResponse<UnsignedTransactionResponse> {
     // Get user keypair
     let keypair = match get_user_keypair_by_email(&auth_user.email, &state).await {
         Ok(kp) => kp,
         Err(e) => {
             // tracing::error!("[create_profile] Failed to get user keypair: {}", e);
             let error_response = ApiResponse::<UnsignedTransactionResponse>::error(e.to_string());
-            return ResponseJson(error_response);
+            return error_response;
         }
     };
-    match state.icm_client.create_profile_transaction(keypair).await {
-        Ok(response) => ResponseJson(ApiResponse::success(response)),
+    match state.icm_client.with_compute_budget(budget).create_profile_transaction(&keypair).await {
+        Ok(response) => ApiResponse::success(response),
         Err(e) => {
             tracing::error!("[create_profile] Create profile error: {}", e);
-            let error_response = ApiResponse::<UnsignedTransactionResponse>::error(e.to_string());
-            ResponseJson(error_response)
+            ApiResponse::<UnsignedTransactionResponse>::from_error(&e)
         }
     }
 }
@@ -260,22 +299,13 @@ pub async fn create_profile(
 #[axum::debug_handler]
 pub async fn get_trading_pool(
     State(state): State<AppState>,
-    Extension(auth_user): Extension<crate::auth::models::AuthUser>,
+    Extension(_auth_user): Extension<crate::auth::models::AuthUser>,
     Query(query): Query<GetBucketQuery>,
 ) -> ResponseJson<ApiResponse<TradingPool>> {
-    // Get user keypair
-    let keypair = match get_user_keypair_by_email(&auth_user.email, &state).await {
-        Ok(kp) => kp,
-        Err(e) => {
-            // tracing::error!("[get_trading_pool] Failed to get user keypair: {}", e);
-            let error_response = ApiResponse::<TradingPool>::error(e.to_string());
-            return ResponseJson(error_response);
-        }
-    };
     let raised_amount = query.raised_amount.clone();
     let creator_pubkey = query.creator_pubkey.clone();
     let bucket_name = query.bucket_name.clone();
-    match state.icm_client.fetch_trading_pool_by_pda(query, keypair).await {
+    match state.icm_client.fetch_trading_pool_by_pda(query).await {
         Ok(pool) => {
             // Calculate contribution_percent if raised_amount is provided
             let (contribution_percent, raised_amount_str) = if let Some(raised) = raised_amount {
@@ -353,18 +383,9 @@ pub async fn get_trading_pool(
 #[axum::debug_handler]
 pub async fn get_all_pools_by_pda(
     State(state): State<AppState>,
-    Extension(auth_user): Extension<crate::auth::models::AuthUser>
+    Extension(_auth_user): Extension<crate::auth::models::AuthUser>
 ) -> ResponseJson<ApiResponse<Vec<BucketInfo>>> {
-    // Get user keypair
-    let keypair = match get_user_keypair_by_email(&auth_user.email, &state).await {
-        Ok(kp) => kp,
-        Err(e) => {
-            // tracing::error!("[get_all_pools] Failed to get user keypair: {}", e);
-            let error_response = ApiResponse::<Vec<BucketInfo>>::error(e.to_string());
-            return ResponseJson(error_response);
-        }
-    };
-    match state.icm_client.get_all_pools_by_pda(keypair, state.db.pool()).await {
+    match state.icm_client.get_all_pools_by_pda(state.db.pool()).await {
         Ok(all_pools) => ResponseJson(ApiResponse::success(all_pools)),
         Err(e) => {
             // tracing::error!("Failed to fetch all pools: {}", e);
@@ -373,27 +394,32 @@ pub async fn get_all_pools_by_pda(
     }
 }
 
+/// Buckets filtered by creator, status and name, sorted and paginated
+pub async fn list_pools(
+    State(state): State<AppState>,
+    Extension(_auth_user): Extension<crate::auth::models::AuthUser>,
+    Query(query): Query<PoolQuery>,
+) -> ApiResponse<PoolPage> {
+    match state.icm_client.query_pools(state.db.pool(), &query).await {
+        Ok(page) => ApiResponse::success(page),
+        Err(e) => {
+            tracing::error!("[list_pools] Failed to list pools: {}", e);
+            ApiResponse::error(e.to_string())
+        }
+    }
+}
+
 /// Get trading pool info endpoint
 #[axum::debug_handler]
 pub async fn get_trading_pool_info(
     State(state): State<AppState>,
-    Extension(auth_user): Extension<crate::auth::models::AuthUser>,
+    Extension(_auth_user): Extension<crate::auth::models::AuthUser>,
     Query(query): Query<GetBucketQuery>
 ) -> ResponseJson<ApiResponse<TradingPool>> {
-    // Get user keypair
-    let keypair = match get_user_keypair_by_email(&auth_user.email, &state).await {
-        Ok(kp) => kp,
-        Err(e) => {
-            tracing::error!("[get_trading_pool_info] Failed to get user keypair: {}", e);
-            let error_response = ApiResponse::<TradingPool>::error(e.to_string());
-            return ResponseJson(error_response);
-        }
-    };
-
     // Log query parameters
     tracing::debug!("[get_trading_pool_info] Query parameters: {:?}", query);
 
-    match state.icm_client.fetch_trading_pool_by_pda(query.clone(), keypair).await {
+    match state.icm_client.fetch_trading_pool_by_pda(query.clone()).await {
         Ok(pool_info) => {
             // Calculate contribution_percent if raised_amount is provided
             let (contribution_percent, raised_amount_str) = if let Some(raised) = query.raised_amount.clone() {
@@ -473,6 +499,7 @@ pub async fn get_trading_pool_info(
 pub async fn create_bucket(
     State(state): State<AppState>,
     Extension(auth_user): Extension<crate::auth::models::AuthUser>,
+    Query(budget): Query<ComputeBudgetOptions>,
     Json(request): Json<CreateBucketApiRequest>
 ) -> impl IntoResponse {
     // Get user keypair
@@ -481,7 +508,7 @@ pub async fn create_bucket(
         Err(e) => {
             // tracing::error!("[create_bucket] Failed to get user keypair: {}", e);
             let error_response = ApiResponse::<UnsignedTransactionResponse>::error(e.to_string());
-            return ResponseJson(error_response);
+            return error_response;
         }
     };
 
@@ -490,7 +517,7 @@ pub async fn create_bucket(
         creator_pubkey: keypair.pubkey().to_string(),
     };
     
-    match state.icm_client.fetch_creator_profile_by_pda(creator_query, keypair.insecure_clone()).await {
+    match state.icm_client.fetch_creator_profile_by_pda(creator_query).await {
         Ok(_) => {
             tracing::info!("[create_bucket] Creator profile exists, proceeding with bucket creation");
         },
@@ -499,7 +526,7 @@ pub async fn create_bucket(
             // Only try to create if it's actually missing (not other errors)
             if error_str.contains("Account does not exist") || error_str.contains("AccountNotFound") {
                 tracing::info!("[create_bucket] Creator profile doesn't exist, creating it first");
-                match state.icm_client.create_profile_transaction(keypair.insecure_clone()).await {
+                match state.icm_client.with_compute_budget(budget).create_profile_transaction(&keypair).await {
                     Ok(profile_response) => {
                         tracing::info!("[create_bucket] Creator profile created with signature: {}", profile_response.transaction);
                         
@@ -510,7 +537,7 @@ pub async fn create_bucket(
                         let verify_query = GetCreatorProfileQuery {
                             creator_pubkey: keypair.pubkey().to_string(),
                         };
-                        match state.icm_client.fetch_creator_profile_by_pda(verify_query, keypair.insecure_clone()).await {
+                        match state.icm_client.fetch_creator_profile_by_pda(verify_query).await {
                             Ok(_) => {
                                 tracing::info!("[create_bucket] Creator profile verified successfully");
                             },
@@ -520,16 +547,15 @@ pub async fn create_bucket(
                         }
                     },
                     Err(create_err) => {
-                        let create_error_str = create_err.to_string();
                         // If it's "already in use", the profile actually exists, so continue
-                        if create_error_str.contains("already in use") || create_error_str.contains("custom program error: 0x0") {
+                        if is_account_already_in_use(&create_err) {
                             tracing::info!("[create_bucket] Creator profile already exists (race condition), continuing");
                         } else {
                             tracing::error!("[create_bucket] Failed to create creator profile: {}", create_err);
                             let error_response = ApiResponse::<UnsignedTransactionResponse>::error(
                                 format!("Failed to create creator profile: {}", create_err)
                             );
-                            return ResponseJson(error_response);
+                            return error_response;
                         }
                     }
                 }
@@ -552,7 +578,7 @@ pub async fn create_bucket(
         management_fee: request.management_fee,
         strategy: request.strategy.clone(),
     };
-    match state.icm_client.create_bucket_transaction(instance_request, keypair.insecure_clone()).await {
+    match state.icm_client.with_compute_budget(budget).create_bucket_transaction(instance_request, &keypair).await {
         Ok(response) => {
             // Save pool information to database after successful blockchain transaction
             let creator_pubkey = keypair.pubkey().to_string();
@@ -576,13 +602,24 @@ pub async fn create_bucket(
                 tracing::info!("[create_bucket] Pool saved to database successfully - creator: {}, name: {}, strategy: {}", 
                     creator_pubkey, request.name, request.strategy);
             }
+
+            state.event_bus.publish(
+                bucket_pool_id(&request.name, &creator_pubkey),
+                DomainEvent::PoolCreated(PoolCreated {
+                    bucket_name: request.name.clone(),
+                    creator: creator_pubkey,
+                    strategy: request.strategy.clone(),
+                    token_mints: request.token_mints.clone(),
+                    target_amount_usdc: request.target_amount,
+                    signature: response.transaction.clone(),
+                }),
+            );
             
-            ResponseJson(ApiResponse::success(response))
+            ApiResponse::success(response)
         },
         Err(e) => {
             // tracing::error!("[create_bucket] Create bucket error: {}", e);
-            let error_response = ApiResponse::<UnsignedTransactionResponse>::error(e.to_string());
-            ResponseJson(error_response)
+            ApiResponse::<UnsignedTransactionResponse>::from_error(&e)
         }
     }
 }
@@ -592,6 +629,7 @@ pub async fn create_bucket(
 pub async fn contribute_to_bucket(
     State(state): State<AppState>,
     Extension(auth_user): Extension<crate::auth::models::AuthUser>,
+    Query(budget): Query<ComputeBudgetOptions>,
     Json(request): Json<ContributeToBucketApiRequest>
 ) -> impl IntoResponse {
     // Get user keypair
@@ -600,30 +638,74 @@ pub async fn contribute_to_bucket(
         Err(e) => {
             // tracing::error!("[contribute_to_bucket] Failed to get user keypair: {}", e);
             let error_response = ApiResponse::<UnsignedTransactionResponse>::error(e.to_string());
-            return ResponseJson(error_response);
+            return error_response;
         }
     };
+    let contributor = keypair.pubkey().to_string();
     // Convert to instance::ContributeToBucketRequest (convert human-readable USDC to lamports)
+    let amount = usdc_to_lamports(request.amount);
     let instance_request = ContributeToBucketRequest {
-        bucket_name: request.bucket_name,
-        amount: usdc_to_lamports(request.amount),
+        bucket_name: request.bucket_name.clone(),
+        amount,
         creator_pubkey: request.creator_pubkey.clone(),
     };
-    match state.icm_client.contribute_to_bucket_transaction(instance_request, keypair).await {
-        Ok(response) => ResponseJson(ApiResponse::success(response)),
+    match state.icm_client.with_compute_budget(budget).contribute_to_bucket_transaction(instance_request, &keypair).await {
+        Ok(response) => {
+            publish_if_filled(&state, &request.bucket_name, &request.creator_pubkey, amount, &response.transaction).await;
+            state.event_bus.publish(
+                bucket_pool_id(&request.bucket_name, &request.creator_pubkey),
+                DomainEvent::ContributionMade(ContributionMade {
+                    bucket_name: request.bucket_name,
+                    creator: request.creator_pubkey,
+                    contributor,
+                    amount_usdc: request.amount,
+                    signature: response.transaction.clone(),
+                }),
+            );
+            ApiResponse::success(response)
+        },
         Err(e) => {
             // tracing::error!("[contribute_to_bucket] Contribute to bucket error: {}", e);
-            let error_response = ApiResponse::<UnsignedTransactionResponse>::error(e.to_string());
-            ResponseJson(error_response)
+            ApiResponse::<UnsignedTransactionResponse>::from_error(&e)
         }
     }
 }
 
+/// Publish `PoolFilled` when a contribution of `amount` took the bucket to its target
+async fn publish_if_filled(state: &AppState, bucket_name: &str, creator_pubkey: &str, amount: u64, signature: &str) {
+    let Ok(creator) = Pubkey::from_str(creator_pubkey) else {
+        return;
+    };
+    let (raised, target) = match state.icm_client.fetch_bucket_funding(bucket_name, creator).await {
+        Ok(funding) => funding,
+        Err(e) => {
+            tracing::warn!("[publish_if_filled] Could not read funding of bucket {}: {}", bucket_name, e);
+            return;
+        }
+    };
+    // Only the contribution that crossed the target fills the pool
+    if target == 0 || raised < target || raised.saturating_sub(amount) >= target {
+        return;
+    }
+    tracing::info!("[publish_if_filled] Bucket {} reached its target of {}", bucket_name, target);
+    state.event_bus.publish(
+        bucket_pool_id(bucket_name, creator_pubkey),
+        DomainEvent::PoolFilled(PoolFilled {
+            bucket_name: bucket_name.to_string(),
+            creator: creator_pubkey.to_string(),
+            raised_amount_usdc: lamports_to_usdc(raised),
+            target_amount_usdc: lamports_to_usdc(target),
+            signature: signature.to_string(),
+        }),
+    );
+}
+
 /// Start trading endpoint
 #[axum::debug_handler]
 pub async fn start_trading(
     State(state): State<AppState>,
     Extension(auth_user): Extension<crate::auth::models::AuthUser>,
+    Query(budget): Query<ComputeBudgetOptions>,
     Json(request): Json<StartTradingRequest>
 ) -> impl IntoResponse {
     tracing::error!("[start_trading] 🔥 START_TRADING FUNCTION CALLED - This should appear in logs!");
@@ -639,7 +721,7 @@ pub async fn start_trading(
         Err(e) => {
             tracing::error!("[start_trading] Failed to get user keypair: {}", e);
             let error_response = ApiResponse::<UnsignedTransactionResponse>::error(e.to_string());
-            return ResponseJson(error_response);
+            return error_response;
         }
     };
 
@@ -674,64 +756,13 @@ pub async fn start_trading(
     ).await {
         tracing::error!("[start_trading] Failed to save pool to database: {}", e);
         let error_response = ApiResponse::<UnsignedTransactionResponse>::error("Failed to save pool to database".to_string());
-        return ResponseJson(error_response);
+        return error_response;
     }
     tracing::info!("[start_trading] Successfully saved trading pool to database");
 
-    // Derive token pairs from token_bucket (all unique pairs)
-    tracing::info!("[start_trading] Deriving token pairs from token bucket: {:?}", request.token_bucket);
-    let mut token_pairs = Vec::new();
-    let tokens = &request.token_bucket;
-    for i in 0..tokens.len() {
-        for j in 0..tokens.len() {
-            if i != j {
-                token_pairs.push((tokens[i].clone(), tokens[j].clone()));
-            }
-        }
-    }
-    tracing::info!("[start_trading] Generated {} token pairs", token_pairs.len());
-
-    // Build a default StrategyConfig from the strategy string
-    tracing::info!("[start_trading] Building strategy config for: {}", request.strategy);
-    let strategy_type = match request.strategy.to_lowercase().as_str() {
-        "arbitrage" => crate::agent::types::StrategyType::Arbitrage,
-        "gridtrading" | "grid_trading" => crate::agent::types::StrategyType::GridTrading,
-        "dca" => crate::agent::types::StrategyType::DCA,
-        "meanreversion" | "mean_reversion" => crate::agent::types::StrategyType::MeanReversion,
-        "trendfollowing" | "trend_following" => crate::agent::types::StrategyType::TrendFollowing,
-        _ => crate::agent::types::StrategyType::DCA,
-    };
-    tracing::info!("[start_trading] Selected strategy type: {:?}", strategy_type);
-    let strategy_config = crate::agent::types::StrategyConfig {
-        strategy_type,
-        parameters: crate::agent::types::StrategyParameters {
-            min_spread_bps: 10,
-            max_slippage_bps: 50,
-            position_size_usd: 100.0,
-            rebalance_threshold_pct: 5.0,
-            lookback_periods: 10,
-            custom_params: std::collections::HashMap::new(),
-        },
-        risk_limits: crate::agent::types::RiskLimits {
-            max_position_size_usd: 1000.0,
-            max_daily_loss_pct: 10.0,
-            max_drawdown_pct: 20.0,
-            stop_loss_pct: 5.0,
-            take_profit_pct: 10.0,
-        },
-        execution_settings: crate::agent::types::ExecutionSettings {
-            priority_fee_percentile: 90,
-            max_priority_fee_lamports: 10000,
-            transaction_timeout_ms: 60000,
-            retry_attempts: 3,
-            jito_tip_lamports: 0,
-        },
-    };
-
-    // Get OpenAI API key from environment
-    tracing::info!("[start_trading] Getting OpenAI API key from environment");
-    let openai_api_key = std::env::var("OPENAI_API_KEY")
-        .unwrap_or_else(|_| {
+    // Get OpenAI API key from the server configuration
+    let openai_api_key = state.config.agent.openai_api_key.clone()
+        .unwrap_or_else(|| {
             tracing::warn!("[start_trading] OPENAI_API_KEY not set, using default");
             "sk-proj-default".to_string()
         });
@@ -741,24 +772,33 @@ pub async fn start_trading(
     tracing::info!("[start_trading] Request details - bucket_name: {}, creator_pubkey: {}, strategy: {}", 
         request.bucket_name, request.creator_pubkey, request.strategy);
     
-    let tx_response = match state.icm_client.start_trading_transaction(request.clone(), keypair).await {
+    let tx_response = match state.icm_client.with_compute_budget(budget).start_trading_transaction(request.clone(), &keypair).await {
         Ok(response) => {
             tracing::info!("[start_trading] Blockchain transaction created successfully: {}", response.transaction);
+            state.event_bus.publish(
+                bucket_pool_id(&request.bucket_name, &request.creator_pubkey),
+                DomainEvent::TradingStarted(TradingStarted {
+                    bucket_name: request.bucket_name.clone(),
+                    creator: request.creator_pubkey.clone(),
+                    strategy: request.strategy.clone(),
+                    signature: response.transaction.clone(),
+                }),
+            );
             response
         },
         Err(e) => {
             tracing::error!("[start_trading] Failed to create blockchain transaction: {}", e);
             tracing::error!("[start_trading] Error details: {:?}", e);
-            let error_response = ApiResponse::<UnsignedTransactionResponse>::error(format!("Failed to create blockchain transaction: {}", e));
-            return ResponseJson(error_response);
+            let error_response = ApiResponse::<UnsignedTransactionResponse>::from_error(&e.context("Failed to create blockchain transaction"));
+            return error_response;
         }
     };
 
     tracing::info!("[start_trading] Creating trading agent configuration");
     let agent_config = crate::agent::trading_agent::TradingAgentConfigBuilder::new()
+        .with_agent_defaults(&state.config.agent)
         .with_openai_api_key(openai_api_key)
-        .with_token_pairs(token_pairs)
-        .with_strategy_configs(vec![strategy_config])
+        .for_bucket(bucket_pool_id(&request.bucket_name, &request.creator_pubkey), &request.strategy, &request.token_bucket)
         .with_portfolio_id(uuid::Uuid::parse_str(&pool_id).unwrap())
         .build();
     
@@ -767,14 +807,24 @@ pub async fn start_trading(
             tracing::info!("[start_trading] Agent config created successfully, spawning trading agent");
             let icm_client = state.icm_client.clone();
             let db_pool = state.db.pool().clone();
+            let event_bus = state.event_bus.clone();
+            let agents = state.agents.clone();
+            let pool_id = bucket_pool_id(&request.bucket_name, &request.creator_pubkey);
             tokio::spawn(async move {
-                match crate::agent::trading_agent::TradingAgent::new(config, icm_client, db_pool).await {
-                    Ok(agent) => {
-                        tracing::info!("[start_trading] Trading agent created successfully");
-                    },
+                let agent = match crate::agent::trading_agent::TradingAgent::new(config, icm_client, db_pool, &event_bus).await {
+                    Ok(agent) => agent,
                     Err(e) => {
                         tracing::error!("[start_trading] Failed to create trading agent: {}", e);
+                        return;
                     }
+                };
+                let Some(pool_id) = pool_id else {
+                    tracing::error!("[start_trading] No bucket PDA to register the trading agent under");
+                    return;
+                };
+                match agents.start(pool_id, agent).await {
+                    Ok(()) => tracing::info!("[start_trading] Trading agent started"),
+                    Err(e) => tracing::error!("[start_trading] Failed to start trading agent: {}", e),
                 }
             });
         },
@@ -784,7 +834,36 @@ pub async fn start_trading(
     }
 
     tracing::info!("[start_trading] Returning successful response");
-    ResponseJson(ApiResponse::success(tx_response))
+    ApiResponse::success(tx_response)
+}
+
+/// Raydium accounts from the request when all four are given, otherwise from the pool resolver
+pub(crate) async fn resolve_swap_pool(
+    state: &AppState,
+    request: &SwapTokensRequest,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+) -> Result<RaydiumPool, String> {
+    if let (Some(amm), Some(amm_authority), Some(coin), Some(pc)) = (
+        &request.amm,
+        &request.amm_authority,
+        &request.pool_coin_token_account,
+        &request.pool_pc_token_account,
+    ) {
+        let parse = |value: &str, field: &str| {
+            Pubkey::from_str(value).map_err(|_| format!("Invalid {} address", field))
+        };
+        return Ok(RaydiumPool {
+            amm: parse(amm, "amm")?,
+            amm_authority: parse(amm_authority, "amm_authority")?,
+            pool_coin_token_account: parse(coin, "pool_coin_token_account")?,
+            pool_pc_token_account: parse(pc, "pool_pc_token_account")?,
+            coin_mint: input_mint,
+            pc_mint: output_mint,
+        });
+    }
+
+    state.pool_resolver.resolve(input_mint, output_mint).await.map_err(|e| e.to_string())
 }
 
 /// Swap tokens endpoint
@@ -792,6 +871,7 @@ pub async fn start_trading(
 pub async fn swap_tokens(
     State(state): State<AppState>,
     Extension(auth_user): Extension<crate::auth::models::AuthUser>,
+    Query(budget): Query<ComputeBudgetOptions>,
     Json(request): Json<SwapTokensRequest>
 ) -> impl IntoResponse {
     // Get user keypair
@@ -800,7 +880,7 @@ pub async fn swap_tokens(
         Err(e) => {
             tracing::error!("[swap_tokens] Failed to get user keypair: {}", e);
             let error_response = ApiResponse::<UnsignedTransactionResponse>::error(e.to_string());
-            return ResponseJson(error_response);
+            return error_response;
         }
     };
     // Convert to instance::SwapTokensRequest
@@ -821,25 +901,15 @@ pub async fn swap_tokens(
     let input_mint = Pubkey::from_str(&request.input_mint).unwrap();
     let output_mint = Pubkey::from_str(&request.output_mint).unwrap();
 
-    // Get Raydium AMM parameters from environment or request
-    let raydium_amm_program = Pubkey::from_str(&std::env::var("RAYDIUM_AMM_PROGRAM").unwrap_or_else(|_| "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8".to_string())).expect("Invalid RAYDIUM_AMM_PROGRAM");
-    
-    // Parse AMM accounts from request or use defaults/environment
-    let amm = request.amm.as_ref()
-        .map(|s| Pubkey::from_str(s).expect("Invalid AMM address"))
-        .unwrap_or_else(|| Pubkey::from_str(&std::env::var("DEFAULT_AMM").unwrap_or_default()).expect("Invalid DEFAULT_AMM"));
-    
-    let amm_authority = request.amm_authority.as_ref()
-        .map(|s| Pubkey::from_str(s).expect("Invalid AMM authority"))
-        .unwrap_or_else(|| Pubkey::from_str(&std::env::var("DEFAULT_AMM_AUTHORITY").unwrap_or_default()).expect("Invalid DEFAULT_AMM_AUTHORITY"));
-    
-    let pool_coin_token_account = request.pool_coin_token_account.as_ref()
-        .map(|s| Pubkey::from_str(s).expect("Invalid pool coin token account"))
-        .unwrap_or_else(|| Pubkey::from_str(&std::env::var("DEFAULT_POOL_COIN_TOKEN_ACCOUNT").unwrap_or_default()).expect("Invalid DEFAULT_POOL_COIN_TOKEN_ACCOUNT"));
-    
-    let pool_pc_token_account = request.pool_pc_token_account.as_ref()
-        .map(|s| Pubkey::from_str(s).expect("Invalid pool pc token account"))
-        .unwrap_or_else(|| Pubkey::from_str(&std::env::var("DEFAULT_POOL_PC_TOKEN_ACCOUNT").unwrap_or_default()).expect("Invalid DEFAULT_POOL_PC_TOKEN_ACCOUNT"));
+    // Raydium accounts from the request, or discovered for this mint pair
+    let raydium_amm_program = state.pool_resolver.program_id();
+    let pool = match resolve_swap_pool(&state, &request, input_mint, output_mint).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            tracing::error!("[swap_tokens] Failed to resolve Raydium pool: {}", e);
+            return ApiResponse::<UnsignedTransactionResponse>::error(e);
+        }
+    };
 
     // For user_authority, use the bucket authority (derived from bucket)
     let creator = keypair.pubkey();
@@ -849,24 +919,23 @@ pub async fn swap_tokens(
     );
     let user_authority = bucket_pda; // The bucket PDA acts as the user authority
 
-    match state.icm_client.agent_swap_tokens_transaction(
+    match state.icm_client.with_compute_budget(budget).agent_swap_tokens_transaction(
         instance_request,
-        keypair,
+        &keypair,
         bucket_name,
         input_mint,
         output_mint,
         raydium_amm_program,
-        amm,
-        amm_authority,
-        pool_coin_token_account,
-        pool_pc_token_account,
+        pool.amm,
+        pool.amm_authority,
+        pool.pool_coin_token_account,
+        pool.pool_pc_token_account,
         user_authority,
     ).await {
-        Ok(response) => ResponseJson(ApiResponse::success(response)),
+        Ok(response) => ApiResponse::success(response),
         Err(e) => {
             tracing::error!("[swap_tokens] Agent swap tokens error: {}", e);
-            let error_response = ApiResponse::<UnsignedTransactionResponse>::error(e.to_string());
-            ResponseJson(error_response)
+            ApiResponse::<UnsignedTransactionResponse>::from_error(&e)
         }
     }
 }
@@ -876,6 +945,7 @@ pub async fn swap_tokens(
 pub async fn agent_swap_tokens(
     State(state): State<AppState>,
     Extension(auth_user): Extension<crate::auth::models::AuthUser>,
+    Query(budget): Query<ComputeBudgetOptions>,
     Json(request): Json<SwapTokensRequest>
 ) -> impl IntoResponse {
     // Get user keypair
@@ -884,7 +954,7 @@ pub async fn agent_swap_tokens(
         Err(e) => {
             tracing::error!("[agent_swap_tokens] Failed to get user keypair: {}", e);
             let error_response = ApiResponse::<UnsignedTransactionResponse>::error(e.to_string());
-            return ResponseJson(error_response);
+            return error_response;
         }
     };
     // Convert to instance::SwapTokensRequest
@@ -905,25 +975,15 @@ pub async fn agent_swap_tokens(
     let input_mint = Pubkey::from_str(&request.input_mint).unwrap();
     let output_mint = Pubkey::from_str(&request.output_mint).unwrap();
 
-    // Get Raydium AMM parameters from environment or request
-    let raydium_amm_program = Pubkey::from_str(&std::env::var("RAYDIUM_AMM_PROGRAM").unwrap_or_else(|_| "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8".to_string())).expect("Invalid RAYDIUM_AMM_PROGRAM");
-    
-    // Parse AMM accounts from request or use defaults/environment
-    let amm = request.amm.as_ref()
-        .map(|s| Pubkey::from_str(s).expect("Invalid AMM address"))
-        .unwrap_or_else(|| Pubkey::from_str(&std::env::var("DEFAULT_AMM").unwrap_or_default()).expect("Invalid DEFAULT_AMM"));
-    
-    let amm_authority = request.amm_authority.as_ref()
-        .map(|s| Pubkey::from_str(s).expect("Invalid AMM authority"))
-        .unwrap_or_else(|| Pubkey::from_str(&std::env::var("DEFAULT_AMM_AUTHORITY").unwrap_or_default()).expect("Invalid DEFAULT_AMM_AUTHORITY"));
-    
-    let pool_coin_token_account = request.pool_coin_token_account.as_ref()
-        .map(|s| Pubkey::from_str(s).expect("Invalid pool coin token account"))
-        .unwrap_or_else(|| Pubkey::from_str(&std::env::var("DEFAULT_POOL_COIN_TOKEN_ACCOUNT").unwrap_or_default()).expect("Invalid DEFAULT_POOL_COIN_TOKEN_ACCOUNT"));
-    
-    let pool_pc_token_account = request.pool_pc_token_account.as_ref()
-        .map(|s| Pubkey::from_str(s).expect("Invalid pool pc token account"))
-        .unwrap_or_else(|| Pubkey::from_str(&std::env::var("DEFAULT_POOL_PC_TOKEN_ACCOUNT").unwrap_or_default()).expect("Invalid DEFAULT_POOL_PC_TOKEN_ACCOUNT"));
+    // Raydium accounts from the request, or discovered for this mint pair
+    let raydium_amm_program = state.pool_resolver.program_id();
+    let pool = match resolve_swap_pool(&state, &request, input_mint, output_mint).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            tracing::error!("[agent_swap_tokens] Failed to resolve Raydium pool: {}", e);
+            return ApiResponse::<UnsignedTransactionResponse>::error(e);
+        }
+    };
 
     // For user_authority, use the bucket authority (derived from bucket)
     let creator = keypair.pubkey();
@@ -933,24 +993,23 @@ pub async fn agent_swap_tokens(
     );
     let user_authority = bucket_pda; // The bucket PDA acts as the user authority
 
-    match state.icm_client.agent_swap_tokens_transaction(
+    match state.icm_client.with_compute_budget(budget).agent_swap_tokens_transaction(
         instance_request,
-        keypair,
+        &keypair,
         bucket_name,
         input_mint,
         output_mint,
         raydium_amm_program,
-        amm,
-        amm_authority,
-        pool_coin_token_account,
-        pool_pc_token_account,
+        pool.amm,
+        pool.amm_authority,
+        pool.pool_coin_token_account,
+        pool.pool_pc_token_account,
         user_authority,
     ).await {
-        Ok(response) => ResponseJson(ApiResponse::success(response)),
+        Ok(response) => ApiResponse::success(response),
         Err(e) => {
             tracing::error!("[agent_swap_tokens] Agent swap tokens error: {}", e);
-            let error_response = ApiResponse::<UnsignedTransactionResponse>::error(e.to_string());
-            ResponseJson(error_response)
+            ApiResponse::<UnsignedTransactionResponse>::from_error(&e)
         }
     }
 }
@@ -960,6 +1019,7 @@ pub async fn agent_swap_tokens(
 pub async fn claim_rewards(
     State(state): State<AppState>,
     Extension(auth_user): Extension<crate::auth::models::AuthUser>,
+    Query(budget): Query<ComputeBudgetOptions>,
     Json(request): Json<ClaimRewardsRequest>
 ) -> impl IntoResponse {
     // Get user keypair
@@ -968,20 +1028,20 @@ pub async fn claim_rewards(
         Err(e) => {
             tracing::error!("[claim_rewards] Failed to get user keypair: {}", e);
             let error_response = ApiResponse::<UnsignedTransactionResponse>::error(e.to_string());
-            return ResponseJson(error_response);
+            return error_response;
         }
     };
     // Convert to instance::ClaimRewardsRequest
     let instance_request = ClaimRewardsRequest {
         bucket_name: request.bucket_name,
         token_mint: request.token_mint,
+        creator_pubkey: request.creator_pubkey,
     };
-    match state.icm_client.claim_rewards_transaction(instance_request, keypair).await {
-        Ok(response) => ResponseJson(ApiResponse::success(response)),
+    match state.icm_client.with_compute_budget(budget).claim_rewards_transaction(instance_request, &keypair).await {
+        Ok(response) => ApiResponse::success(response),
         Err(e) => {
             tracing::error!("[claim_rewards] Claim rewards error: {}", e);
-            let error_response = ApiResponse::<UnsignedTransactionResponse>::error(e.to_string());
-            ResponseJson(error_response)
+            ApiResponse::<UnsignedTransactionResponse>::from_error(&e)
         }
     }
 }
@@ -991,6 +1051,7 @@ pub async fn claim_rewards(
 pub async fn close_bucket(
     State(state): State<AppState>,
     Extension(auth_user): Extension<crate::auth::models::AuthUser>,
+    Query(budget): Query<ComputeBudgetOptions>,
     Json(request): Json<CloseBucketRequest>
 ) -> impl IntoResponse {
     // Get user keypair
@@ -999,21 +1060,143 @@ pub async fn close_bucket(
         Err(e) => {
             tracing::error!("[close_bucket] Failed to get user keypair: {}", e);
             let error_response = ApiResponse::<UnsignedTransactionResponse>::error(e.to_string());
-            return ResponseJson(error_response);
+            return error_response;
         }
     };
     // Convert to instance::CloseBucketRequest
+    let creator_pubkey = keypair.pubkey().to_string();
     let instance_request = CloseBucketRequest {
-        bucket_name: request.bucket_name,
-        creator_pubkey: keypair.pubkey().to_string(),
+        bucket_name: request.bucket_name.clone(),
+        creator_pubkey: creator_pubkey.clone(),
     };
-    match state.icm_client.close_bucket_transaction(instance_request, keypair).await {
-        Ok(response) => ResponseJson(ApiResponse::success(response)),
+    match state.icm_client.with_compute_budget(budget).close_bucket_transaction(instance_request, &keypair).await {
+        Ok(response) => {
+            state.event_bus.publish(
+                bucket_pool_id(&request.bucket_name, &creator_pubkey),
+                DomainEvent::PoolClosed(PoolClosed {
+                    bucket_name: request.bucket_name,
+                    creator: creator_pubkey,
+                    signature: response.transaction.clone(),
+                }),
+            );
+            ApiResponse::success(response)
+        },
         Err(e) => {
             tracing::error!("[close_bucket] Close bucket error: {}", e);
-            let error_response = ApiResponse::<UnsignedTransactionResponse>::error(e.to_string());
-            ResponseJson(error_response)
+            ApiResponse::<UnsignedTransactionResponse>::from_error(&e)
+        }
+    }
+}
+
+/// Contributions to a bucket with each contributor's amount and share
+#[axum::debug_handler]
+pub async fn get_bucket_contributions(
+    State(state): State<AppState>,
+    Query(query): Query<GetBucketQuery>,
+) -> ResponseJson<ApiResponse<BucketContributions>> {
+    let creator = match Pubkey::from_str(&query.creator_pubkey) {
+        Ok(pk) => pk,
+        Err(e) => return ResponseJson(ApiResponse::error(format!("Invalid creator_pubkey: {}", e))),
+    };
+    match state.icm_client.fetch_bucket_contributions(&query.bucket_name, creator).await {
+        Ok(contributions) => ResponseJson(ApiResponse::success(contributions)),
+        Err(e) => {
+            tracing::error!("[get_bucket_contributions] Failed to fetch contributions for '{}': {}", query.bucket_name, e);
+            ResponseJson(ApiResponse::error(e.to_string()))
+        }
+    }
+}
+
+/// Expected `claim_rewards` payout per token with its fee breakdown; the caller's wallet by default
+#[axum::debug_handler]
+pub async fn get_payout_preview(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<crate::auth::models::AuthUser>,
+    Query(query): Query<PayoutPreviewQuery>,
+) -> ResponseJson<ApiResponse<PayoutPreview>> {
+    let creator = match Pubkey::from_str(&query.creator_pubkey) {
+        Ok(pk) => pk,
+        Err(e) => return ResponseJson(ApiResponse::error(format!("Invalid creator_pubkey: {}", e))),
+    };
+    let contributor = match query.contributor_pubkey.as_deref() {
+        Some(pubkey) => match Pubkey::from_str(pubkey) {
+            Ok(pk) => pk,
+            Err(e) => return ResponseJson(ApiResponse::error(format!("Invalid contributor_pubkey: {}", e))),
+        },
+        None => match get_user_keypair_by_email(&auth_user.email, &state).await {
+            Ok(kp) => kp.pubkey(),
+            Err(e) => return ResponseJson(ApiResponse::error(e)),
+        },
+    };
+    let simulate = query.simulate.unwrap_or(true);
+    match state.icm_client.claim_payout_preview(&query.bucket_name, creator, contributor, simulate).await {
+        Ok(preview) => ResponseJson(ApiResponse::success(preview)),
+        Err(e) => {
+            tracing::error!("[get_payout_preview] Failed to preview payout of '{}' for {}: {}", query.bucket_name, contributor, e);
+            ResponseJson(ApiResponse::from_error(&e))
         }
     }
 }
 
+/// Most recent lifecycle actions the scheduler took on a bucket
+#[axum::debug_handler]
+pub async fn get_bucket_lifecycle(
+    State(state): State<AppState>,
+    Query(query): Query<GetBucketQuery>,
+) -> ResponseJson<ApiResponse<Vec<crate::database::models::BucketLifecycleAction>>> {
+    let Some(bucket_pda) = bucket_pool_id(&query.bucket_name, &query.creator_pubkey) else {
+        return ResponseJson(ApiResponse::error("Invalid creator_pubkey".to_string()));
+    };
+    match crate::database::models::BucketLifecycleAction::fetch_for_bucket(state.db.pool(), &bucket_pda, 100).await {
+        Ok(actions) => ResponseJson(ApiResponse::success(actions)),
+        Err(e) => {
+            tracing::error!("[get_bucket_lifecycle] Failed to fetch lifecycle actions for '{}': {}", query.bucket_name, e);
+            ResponseJson(ApiResponse::error(e.to_string()))
+        }
+    }
+}
+
+/// Contribution records of one wallet across buckets; the caller's wallet by default
+#[axum::debug_handler]
+pub async fn get_contributor_records(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<crate::auth::models::AuthUser>,
+    Query(query): Query<GetContributorQuery>,
+) -> ResponseJson<ApiResponse<Vec<ContributionInfo>>> {
+    let contributor = match query.contributor_pubkey.as_deref() {
+        Some(pubkey) => match Pubkey::from_str(pubkey) {
+            Ok(pk) => pk,
+            Err(e) => return ResponseJson(ApiResponse::error(format!("Invalid contributor_pubkey: {}", e))),
+        },
+        None => match get_user_keypair_by_email(&auth_user.email, &state).await {
+            Ok(kp) => kp.pubkey(),
+            Err(e) => return ResponseJson(ApiResponse::error(e)),
+        },
+    };
+    match state.icm_client.fetch_contributor_records(contributor).await {
+        Ok(records) => ResponseJson(ApiResponse::success(records)),
+        Err(e) => {
+            tracing::error!("[get_contributor_records] Failed to fetch records for {}: {}", contributor, e);
+            ResponseJson(ApiResponse::error(e.to_string()))
+        }
+    }
+}
+
+/// On-chain trade records of a bucket
+#[axum::debug_handler]
+pub async fn get_bucket_trade_records(
+    State(state): State<AppState>,
+    Query(query): Query<GetBucketQuery>,
+) -> ResponseJson<ApiResponse<Vec<TradeRecordInfo>>> {
+    let creator = match Pubkey::from_str(&query.creator_pubkey) {
+        Ok(pk) => pk,
+        Err(e) => return ResponseJson(ApiResponse::error(format!("Invalid creator_pubkey: {}", e))),
+    };
+    match state.icm_client.fetch_bucket_trade_records(&query.bucket_name, creator).await {
+        Ok(records) => ResponseJson(ApiResponse::success(records)),
+        Err(e) => {
+            tracing::error!("[get_bucket_trade_records] Failed to fetch trade records for '{}': {}", query.bucket_name, e);
+            ResponseJson(ApiResponse::error(e.to_string()))
+        }
+    }
+}
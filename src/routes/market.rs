@@ -0,0 +1,92 @@
+//! # Market Data Routes
+//!
+//! Read-only market data aggregated by the trading agent, such as OHLCV candles for charts.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json as ResponseJson,
+    Router, routing::get,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use tracing::{error, info};
+
+use crate::agent::candles::CandleInterval;
+use crate::database::models::MarketCandle;
+use crate::server::AppState;
+
+/// Default and maximum number of candles per response
+const DEFAULT_CANDLE_LIMIT: i64 = 300;
+const MAX_CANDLE_LIMIT: i64 = 1_000;
+
+/// Query parameters for the candles endpoint
+#[derive(Debug, Deserialize)]
+pub struct CandlesQuery {
+    pub input_mint: String,
+    pub output_mint: String,
+    /// "1m", "5m" or "1h"
+    pub interval: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+}
+
+/// OHLCV candles for a pair, oldest first
+#[derive(Debug, Serialize)]
+pub struct CandlesResponse {
+    pub input_mint: String,
+    pub output_mint: String,
+    pub interval: CandleInterval,
+    pub candles: Vec<MarketCandle>,
+}
+
+/// Get OHLCV candles for a token pair
+pub async fn get_candles(
+    State(state): State<AppState>,
+    Query(query): Query<CandlesQuery>,
+) -> Result<ResponseJson<CandlesResponse>, (StatusCode, String)> {
+    let interval = match query.interval.as_deref() {
+        Some(interval) => CandleInterval::from_str(interval)
+            .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?,
+        None => CandleInterval::FiveMinutes,
+    };
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or_else(|| to - interval.retention());
+    if from > to {
+        return Err((StatusCode::BAD_REQUEST, "`from` must be before `to`".to_string()));
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_CANDLE_LIMIT).clamp(1, MAX_CANDLE_LIMIT);
+
+    info!("[get_candles] {} {}/{} from {} to {}", interval.as_str(), query.input_mint, query.output_mint, from, to);
+
+    // Include the candle that is still open at `to`
+    let candles = MarketCandle::fetch_range(
+        state.db.pool(),
+        &query.input_mint,
+        &query.output_mint,
+        interval.as_str(),
+        from,
+        to + Duration::seconds(interval.seconds()),
+        limit,
+    )
+    .await
+    .map_err(|e| {
+        error!("[get_candles] Failed to load candles: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to load candles: {}", e))
+    })?;
+
+    Ok(ResponseJson(CandlesResponse {
+        input_mint: query.input_mint,
+        output_mint: query.output_mint,
+        interval,
+        candles,
+    }))
+}
+
+/// Create market data routes
+pub fn create_routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/market/candles", get(get_candles))
+}
@@ -8,6 +8,11 @@ pub mod auth;
 // - `health`: Health check and monitoring endpoints
 // - `icm`: ICM program transaction endpoints
 // - `agent`: AI-powered trading agent endpoints
+// - `market`: Market data endpoints (candles)
+// - `webhooks`: Webhook endpoints and delivery log
+// - `transactions`: Unsigned transactions for user wallets and submission tracking
+// - `treasury`: Protocol fee vault dashboard and fee withdrawal
+// - `simulate`: Dry runs of bucket instructions
 //
 // - ## Adding New Routes
 // - To add new route modules:
@@ -29,7 +34,22 @@ pub mod icm;
 
 /// AI-powered trading agent endpoints
 pub mod agent;
+
+/// Market data endpoints
+pub mod market;
 pub mod faucet;
 
 /// Wallet and balance-related endpoints
 pub mod wallet;
+
+/// Webhook registration, delivery log and replay
+pub mod webhooks;
+
+/// Unsigned transactions for user wallets, submission and tracking
+pub mod transactions;
+
+/// Protocol fee vault dashboard and fee withdrawal
+pub mod treasury;
+
+/// Dry runs of bucket instructions without submitting them
+pub mod simulate;
@@ -0,0 +1,188 @@
+//! # Simulation Routes
+//!
+//! Dry runs of the ICM bucket instructions. Each endpoint builds the same
+//! instructions as the live route, runs `simulateTransaction` and returns the
+//! program logs, compute units, the decoded program error and the expected
+//! balance changes. Nothing is signed or submitted.
+//!
+//! The wallet defaults to the caller's custodial wallet; pass `wallet_pubkey`
+//! to simulate for a connected wallet instead.
+//!
+//! All endpoints require authentication via JWT middleware.
+
+use std::str::FromStr;
+use axum::{
+    extract::{Extension, Json, State},
+    http::StatusCode,
+    response::Json as ResponseJson,
+    routing::post,
+    Router,
+};
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+
+use crate::auth::models::AuthUser;
+use crate::onchain_instance::instance::{usdc_to_lamports, SwapLeg};
+use crate::routes::transactions::{build_error, parse_pubkey, WalletBucketRequest};
+use crate::server::AppState;
+use crate::state_structs::{
+    ClaimRewardsRequest, ContributeToBucketApiRequest, ContributeToBucketRequest, CreateBucketApiRequest,
+    CreateBucketRequest, SimulationResponse, SwapTokensRequest,
+};
+
+/// A simulation request for an optional wallet
+#[derive(Deserialize)]
+pub struct SimulateRequest<T> {
+    /// Wallet that would sign and pay; the caller's custodial wallet when omitted
+    pub wallet_pubkey: Option<String>,
+    #[serde(flatten)]
+    pub request: T,
+}
+
+/// The given wallet, or the caller's custodial wallet
+async fn wallet(state: &AppState, auth_user: &AuthUser, wallet_pubkey: Option<&str>) -> Result<Pubkey, (StatusCode, String)> {
+    match wallet_pubkey {
+        Some(wallet_pubkey) => parse_pubkey("wallet_pubkey", wallet_pubkey),
+        None => crate::routes::icm::get_user_keypair_by_email(&auth_user.email, state)
+            .await
+            .map(|keypair| keypair.pubkey())
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e)),
+    }
+}
+
+/// Simulate `create_bucket`, including `create_profile` when the creator has none
+pub async fn simulate_create_bucket(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<SimulateRequest<CreateBucketApiRequest>>,
+) -> Result<ResponseJson<SimulationResponse>, (StatusCode, String)> {
+    let creator = wallet(&state, &auth_user, request.wallet_pubkey.as_deref()).await?;
+    let bucket = request.request;
+    let instance_request = CreateBucketRequest {
+        name: bucket.name,
+        token_mints: bucket.token_mints,
+        contribution_window_minutes: bucket.contribution_window_minutes,
+        trading_window_minutes: bucket.trading_window_minutes,
+        creator_fee_percent: bucket.creator_fee_percent,
+        target_amount: usdc_to_lamports(bucket.target_amount),
+        min_contribution: usdc_to_lamports(bucket.min_contribution),
+        max_contribution: usdc_to_lamports(bucket.max_contribution),
+        management_fee: bucket.management_fee,
+        strategy: bucket.strategy,
+    };
+
+    let response = state.icm_client.simulate_create_bucket(instance_request, creator)
+        .await
+        .map_err(|e| build_error("Failed to simulate create bucket", e))?;
+    Ok(ResponseJson(response))
+}
+
+/// Simulate `contribute_to_bucket`
+pub async fn simulate_contribute(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<SimulateRequest<ContributeToBucketApiRequest>>,
+) -> Result<ResponseJson<SimulationResponse>, (StatusCode, String)> {
+    let contributor = wallet(&state, &auth_user, request.wallet_pubkey.as_deref()).await?;
+    let contribution = request.request;
+    parse_pubkey("creator_pubkey", &contribution.creator_pubkey)?;
+    let instance_request = ContributeToBucketRequest {
+        bucket_name: contribution.bucket_name,
+        amount: usdc_to_lamports(contribution.amount),
+        creator_pubkey: contribution.creator_pubkey,
+    };
+
+    let response = state.icm_client.simulate_contribute(instance_request, contributor)
+        .await
+        .map_err(|e| build_error("Failed to simulate contribution", e))?;
+    Ok(ResponseJson(response))
+}
+
+/// Simulate `start_trading`; the creator signs
+pub async fn simulate_start_trading(
+    State(state): State<AppState>,
+    Json(request): Json<WalletBucketRequest>,
+) -> Result<ResponseJson<SimulationResponse>, (StatusCode, String)> {
+    let creator = parse_pubkey("creator_pubkey", &request.creator_pubkey)?;
+    let response = state.icm_client.simulate_start_trading(&request.bucket_name, creator)
+        .await
+        .map_err(|e| build_error("Failed to simulate start trading", e))?;
+    Ok(ResponseJson(response))
+}
+
+/// Simulate a `swap_tokens` leg; the wallet is the bucket creator
+pub async fn simulate_swap(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<SimulateRequest<SwapTokensRequest>>,
+) -> Result<ResponseJson<SimulationResponse>, (StatusCode, String)> {
+    let creator = wallet(&state, &auth_user, request.wallet_pubkey.as_deref()).await?;
+    let swap = request.request;
+    let input_mint = parse_pubkey("input_mint", &swap.input_mint)?;
+    let output_mint = parse_pubkey("output_mint", &swap.output_mint)?;
+    let pool = crate::routes::icm::resolve_swap_pool(&state, &swap, input_mint, output_mint)
+        .await
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Failed to resolve Raydium pool: {}", e)))?;
+
+    let leg = SwapLeg {
+        input_mint,
+        output_mint,
+        in_amount: swap.in_amount,
+        quoted_out_amount: swap.quoted_out_amount,
+        slippage_bps: swap.slippage_bps,
+        raydium_amm_program: state.pool_resolver.program_id(),
+        amm: pool.amm,
+        amm_authority: pool.amm_authority,
+        pool_coin_token_account: pool.pool_coin_token_account,
+        pool_pc_token_account: pool.pool_pc_token_account,
+    };
+    let response = state.icm_client.simulate_swap(&swap.bucket, creator, leg)
+        .await
+        .map_err(|e| build_error("Failed to simulate swap", e))?;
+    Ok(ResponseJson(response))
+}
+
+/// Simulate `claim_rewards` for one token
+pub async fn simulate_claim_rewards(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<SimulateRequest<ClaimRewardsRequest>>,
+) -> Result<ResponseJson<SimulationResponse>, (StatusCode, String)> {
+    let contributor = wallet(&state, &auth_user, request.wallet_pubkey.as_deref()).await?;
+    let claim = request.request;
+    let token_mint = parse_pubkey("token_mint", &claim.token_mint)?;
+    let creator = match &claim.creator_pubkey {
+        Some(creator) => Pubkey::from_str(creator)
+            .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid creator_pubkey: {}", e)))?,
+        None => contributor,
+    };
+
+    let response = state.icm_client.simulate_claim_rewards(&claim.bucket_name, creator, token_mint, contributor)
+        .await
+        .map_err(|e| build_error("Failed to simulate claim rewards", e))?;
+    Ok(ResponseJson(response))
+}
+
+/// Simulate `close_bucket`; the creator signs
+pub async fn simulate_close_bucket(
+    State(state): State<AppState>,
+    Json(request): Json<WalletBucketRequest>,
+) -> Result<ResponseJson<SimulationResponse>, (StatusCode, String)> {
+    let creator = parse_pubkey("creator_pubkey", &request.creator_pubkey)?;
+    let response = state.icm_client.simulate_close_bucket(&request.bucket_name, creator)
+        .await
+        .map_err(|e| build_error("Failed to simulate close bucket", e))?;
+    Ok(ResponseJson(response))
+}
+
+/// Create simulation routes
+pub fn create_routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/simulate/create-bucket", post(simulate_create_bucket))
+        .route("/api/v1/simulate/contribute", post(simulate_contribute))
+        .route("/api/v1/simulate/start-trading", post(simulate_start_trading))
+        .route("/api/v1/simulate/swap", post(simulate_swap))
+        .route("/api/v1/simulate/claim-rewards", post(simulate_claim_rewards))
+        .route("/api/v1/simulate/close-bucket", post(simulate_close_bucket))
+}
@@ -0,0 +1,261 @@
+//! # Wallet Transaction Routes
+//!
+//! Non-custodial counterparts of the bucket endpoints. Each builds the same
+//! instructions as the custodial route and returns an unsigned transaction for
+//! the user's own wallet; the signed transaction is then submitted here and
+//! tracked until it finalizes. No private key ever reaches the server.
+//! Builders accept `compute_unit_limit`, `compute_unit_price`,
+//! `priority_fee_percentile` and `max_priority_fee_lamports` query overrides.
+//!
+//! All endpoints require authentication via JWT middleware.
+
+use std::str::FromStr;
+use axum::{
+    extract::{Extension, Json, Path, Query, State},
+    http::StatusCode,
+    response::Json as ResponseJson,
+    routing::{get, post},
+    Router,
+};
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use tracing::{error, info};
+
+use crate::auth::models::AuthUser;
+use crate::database::models::SubmittedTransaction;
+use crate::onchain_instance::compute_budget::ComputeBudgetOptions;
+use crate::onchain_instance::errors::IcmProgramError;
+use crate::onchain_instance::instance::usdc_to_lamports;
+use crate::server::AppState;
+use crate::state_structs::{
+    ClaimRewardsRequest, ContributeToBucketApiRequest, ContributeToBucketRequest, CreateBucketApiRequest, CreateBucketRequest,
+    WalletTransactionResponse,
+};
+
+#[derive(Deserialize)]
+pub struct WalletCreateBucketRequest {
+    /// Creator wallet that will sign and pay
+    pub wallet_pubkey: String,
+    #[serde(flatten)]
+    pub bucket: CreateBucketApiRequest,
+}
+
+#[derive(Deserialize)]
+pub struct WalletContributeRequest {
+    /// Contributor wallet that will sign and pay
+    pub wallet_pubkey: String,
+    #[serde(flatten)]
+    pub contribution: ContributeToBucketApiRequest,
+}
+
+/// Start trading or close a bucket; the creator's wallet signs
+#[derive(Deserialize)]
+pub struct WalletBucketRequest {
+    pub bucket_name: String,
+    pub creator_pubkey: String,
+}
+
+#[derive(Deserialize)]
+pub struct WalletWithdrawFeesRequest {
+    /// Program owner wallet that will sign and pay
+    pub wallet_pubkey: String,
+    /// USDC to withdraw; the whole vault balance when omitted
+    pub amount: Option<f64>,
+}
+
+#[derive(Deserialize)]
+pub struct WalletClaimRewardsRequest {
+    /// Contributor wallet that will sign, pay and receive the payout
+    pub wallet_pubkey: String,
+    #[serde(flatten)]
+    pub claim: ClaimRewardsRequest,
+}
+
+#[derive(Deserialize)]
+pub struct SubmitTransactionRequest {
+    /// Base64 bincode-serialized, fully signed transaction
+    pub transaction: String,
+}
+
+pub(crate) fn parse_pubkey(field: &str, value: &str) -> Result<Pubkey, (StatusCode, String)> {
+    Pubkey::from_str(value).map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid {}: {}", field, e)))
+}
+
+pub(crate) fn build_error(context: &str, e: anyhow::Error) -> (StatusCode, String) {
+    error!("[transactions] {}: {}", context, e);
+    match IcmProgramError::from_error(&e) {
+        Some(program_error) => (
+            program_error.status(),
+            format!("{}: {} [{}]", context, program_error.message(), program_error.api_code()),
+        ),
+        None => (StatusCode::BAD_REQUEST, format!("{}: {}", context, e)),
+    }
+}
+
+/// Unsigned `create_bucket` transaction for the creator's wallet
+pub async fn wallet_create_bucket(
+    State(state): State<AppState>,
+    Query(budget): Query<ComputeBudgetOptions>,
+    Json(request): Json<WalletCreateBucketRequest>,
+) -> Result<ResponseJson<WalletTransactionResponse>, (StatusCode, String)> {
+    let creator = parse_pubkey("wallet_pubkey", &request.wallet_pubkey)?;
+    let bucket = request.bucket;
+    let instance_request = CreateBucketRequest {
+        name: bucket.name,
+        token_mints: bucket.token_mints,
+        contribution_window_minutes: bucket.contribution_window_minutes,
+        trading_window_minutes: bucket.trading_window_minutes,
+        creator_fee_percent: bucket.creator_fee_percent,
+        target_amount: usdc_to_lamports(bucket.target_amount),
+        min_contribution: usdc_to_lamports(bucket.min_contribution),
+        max_contribution: usdc_to_lamports(bucket.max_contribution),
+        management_fee: bucket.management_fee,
+        strategy: bucket.strategy,
+    };
+
+    let response = state.icm_client.with_compute_budget(budget).wallet_create_bucket_transaction(instance_request, creator)
+        .await
+        .map_err(|e| build_error("Failed to build create bucket transaction", e))?;
+    Ok(ResponseJson(response))
+}
+
+/// Unsigned `contribute_to_bucket` transaction for the contributor's wallet
+pub async fn wallet_contribute(
+    State(state): State<AppState>,
+    Query(budget): Query<ComputeBudgetOptions>,
+    Json(request): Json<WalletContributeRequest>,
+) -> Result<ResponseJson<WalletTransactionResponse>, (StatusCode, String)> {
+    let contributor = parse_pubkey("wallet_pubkey", &request.wallet_pubkey)?;
+    let contribution = request.contribution;
+    parse_pubkey("creator_pubkey", &contribution.creator_pubkey)?;
+    let instance_request = ContributeToBucketRequest {
+        bucket_name: contribution.bucket_name,
+        amount: usdc_to_lamports(contribution.amount),
+        creator_pubkey: contribution.creator_pubkey,
+    };
+
+    let response = state.icm_client.with_compute_budget(budget).wallet_contribute_transaction(instance_request, contributor)
+        .await
+        .map_err(|e| build_error("Failed to build contribution transaction", e))?;
+    Ok(ResponseJson(response))
+}
+
+/// Unsigned `start_trading` transaction for the creator's wallet
+pub async fn wallet_start_trading(
+    State(state): State<AppState>,
+    Query(budget): Query<ComputeBudgetOptions>,
+    Json(request): Json<WalletBucketRequest>,
+) -> Result<ResponseJson<WalletTransactionResponse>, (StatusCode, String)> {
+    let creator = parse_pubkey("creator_pubkey", &request.creator_pubkey)?;
+    let response = state.icm_client.with_compute_budget(budget).wallet_start_trading_transaction(&request.bucket_name, creator)
+        .await
+        .map_err(|e| build_error("Failed to build start trading transaction", e))?;
+    Ok(ResponseJson(response))
+}
+
+/// Unsigned `close_bucket` transaction for the creator's wallet
+pub async fn wallet_close_bucket(
+    State(state): State<AppState>,
+    Query(budget): Query<ComputeBudgetOptions>,
+    Json(request): Json<WalletBucketRequest>,
+) -> Result<ResponseJson<WalletTransactionResponse>, (StatusCode, String)> {
+    let creator = parse_pubkey("creator_pubkey", &request.creator_pubkey)?;
+    let response = state.icm_client.with_compute_budget(budget).wallet_close_bucket_transaction(&request.bucket_name, creator)
+        .await
+        .map_err(|e| build_error("Failed to build close bucket transaction", e))?;
+    Ok(ResponseJson(response))
+}
+
+/// Unsigned `withdraw_fees` transaction for the program owner's wallet
+pub async fn wallet_withdraw_fees(
+    State(state): State<AppState>,
+    Query(budget): Query<ComputeBudgetOptions>,
+    Json(request): Json<WalletWithdrawFeesRequest>,
+) -> Result<ResponseJson<WalletTransactionResponse>, (StatusCode, String)> {
+    let owner = parse_pubkey("wallet_pubkey", &request.wallet_pubkey)?;
+    let response = state.icm_client.with_compute_budget(budget).wallet_withdraw_fees_transaction(request.amount.map(usdc_to_lamports), owner)
+        .await
+        .map_err(|e| build_error("Failed to build withdraw fees transaction", e))?;
+    Ok(ResponseJson(response))
+}
+
+/// Unsigned `claim_rewards` transaction for one token, for the contributor's wallet
+pub async fn wallet_claim_rewards(
+    State(state): State<AppState>,
+    Query(budget): Query<ComputeBudgetOptions>,
+    Json(request): Json<WalletClaimRewardsRequest>,
+) -> Result<ResponseJson<WalletTransactionResponse>, (StatusCode, String)> {
+    let contributor = parse_pubkey("wallet_pubkey", &request.wallet_pubkey)?;
+    let claim = request.claim;
+    let token_mint = parse_pubkey("token_mint", &claim.token_mint)?;
+    let creator = match &claim.creator_pubkey {
+        Some(creator) => parse_pubkey("creator_pubkey", creator)?,
+        None => contributor,
+    };
+
+    let response = state.icm_client.with_compute_budget(budget)
+        .wallet_claim_rewards_transaction(&claim.bucket_name, creator, token_mint, contributor)
+        .await
+        .map_err(|e| build_error("Failed to build claim rewards transaction", e))?;
+    Ok(ResponseJson(response))
+}
+
+/// Submit a wallet-signed transaction and start tracking it
+pub async fn submit_transaction(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<SubmitTransactionRequest>,
+) -> Result<ResponseJson<SubmittedTransaction>, (StatusCode, String)> {
+    let submitted = state.icm_client.submit_signed_transaction(&request.transaction)
+        .await
+        .map_err(|e| build_error("Failed to submit transaction", e))?;
+
+    let record = SubmittedTransaction::insert(
+        state.db.pool(),
+        &submitted.signature.to_string(),
+        auth_user.id,
+        &submitted.fee_payer.to_string(),
+        submitted.action,
+        submitted.bucket.map(|bucket| bucket.to_string()).as_deref(),
+        &submitted.recent_blockhash.to_string(),
+    )
+    .await
+    .map_err(|e| {
+        // Already on its way to the cluster; only tracking failed
+        error!("[submit_transaction] Failed to record {}: {}", submitted.signature, e);
+        (StatusCode::INTERNAL_SERVER_ERROR, format!("Transaction {} submitted but could not be tracked: {}", submitted.signature, e))
+    })?;
+
+    info!("[submit_transaction] User {} submitted {}", auth_user.id, record.signature);
+    Ok(ResponseJson(record))
+}
+
+/// Latest known status of a submitted transaction
+pub async fn get_transaction(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(signature): Path<String>,
+) -> Result<ResponseJson<SubmittedTransaction>, (StatusCode, String)> {
+    let record = SubmittedTransaction::fetch_by_signature(state.db.pool(), &signature)
+        .await
+        .map_err(|e| {
+            error!("[get_transaction] Failed to load {}: {}", signature, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to load transaction: {}", e))
+        })?
+        .filter(|record| record.user_id == auth_user.id)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Transaction not found".to_string()))?;
+    Ok(ResponseJson(record))
+}
+
+/// Create wallet transaction routes
+pub fn create_routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/transactions/unsigned/create-bucket", post(wallet_create_bucket))
+        .route("/api/v1/transactions/unsigned/contribute", post(wallet_contribute))
+        .route("/api/v1/transactions/unsigned/start-trading", post(wallet_start_trading))
+        .route("/api/v1/transactions/unsigned/close-bucket", post(wallet_close_bucket))
+        .route("/api/v1/transactions/unsigned/claim-rewards", post(wallet_claim_rewards))
+        .route("/api/v1/transactions/unsigned/withdraw-fees", post(wallet_withdraw_fees))
+        .route("/api/v1/transactions/submit", post(submit_transaction))
+        .route("/api/v1/transactions/{signature}", get(get_transaction))
+}
@@ -0,0 +1,215 @@
+//! # Treasury Routes
+//!
+//! Protocol fee vault dashboard and fee withdrawal for the program owner.
+//! The program keeps no per-pool fee record, so fees per pool and the
+//! withdrawal history come from the fee vault movements the program indexer
+//! records; they lag the chain by the indexer's finalized polling.
+//!
+//! All endpoints require authentication via JWT middleware.
+
+use std::collections::HashMap;
+use axum::{
+    extract::{Extension, Json, Query, State},
+    http::StatusCode,
+    response::Json as ResponseJson,
+    routing::{get, post},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use solana_sdk::signature::Signer;
+use tracing::{error, info, warn};
+
+use crate::auth::models::AuthUser;
+use crate::database::models::FeeVaultMovementRecord;
+use crate::onchain_instance::compute_budget::ComputeBudgetOptions;
+use crate::onchain_instance::errors::IcmProgramError;
+use crate::onchain_instance::instance::{lamports_to_usdc, usdc_to_lamports};
+use crate::server::AppState;
+use crate::state_structs::UnsignedTransactionResponse;
+
+const DEFAULT_WITHDRAWAL_LIMIT: i64 = 100;
+const MAX_WITHDRAWAL_LIMIT: i64 = 1000;
+
+#[derive(Debug, Serialize)]
+pub struct TreasuryResponse {
+    pub owner: String,
+    pub usdc_mint: String,
+    pub fee_vault: String,
+    pub fee_rate_bps: u16,
+    /// Current fee vault balance in USDC
+    pub vault_balance: f64,
+    /// Lifetime fees recorded by the program in USDC
+    pub total_fees_collected: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PoolFeesResponse {
+    /// Bucket PDA the fees were charged on
+    pub pool_id: String,
+    pub bucket_name: Option<String>,
+    pub creator: Option<String>,
+    /// Fees paid into the vault in USDC
+    pub total_fees: f64,
+    /// Fees per ICM instruction, e.g. `contribute_to_bucket`
+    pub fees_by_action: HashMap<String, f64>,
+    pub fee_transactions: u32,
+    pub last_fee_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeeWithdrawalResponse {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// Amount withdrawn in USDC
+    pub amount: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WithdrawalsQuery {
+    /// Number of withdrawals to return
+    pub limit: Option<i64>,
+}
+
+impl WithdrawalsQuery {
+    fn limit(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_WITHDRAWAL_LIMIT).clamp(1, MAX_WITHDRAWAL_LIMIT)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WithdrawFeesRequest {
+    /// USDC to withdraw; the whole vault balance when omitted
+    pub amount: Option<f64>,
+}
+
+fn rpc_error(context: &str, e: anyhow::Error) -> (StatusCode, String) {
+    error!("[treasury] {}: {}", context, e);
+    (StatusCode::BAD_GATEWAY, format!("{}: {}", context, e))
+}
+
+fn db_error(context: &str, e: anyhow::Error) -> (StatusCode, String) {
+    error!("[treasury] {}: {}", context, e);
+    (StatusCode::INTERNAL_SERVER_ERROR, context.to_string())
+}
+
+/// Fee vault balance and protocol fee settings
+pub async fn get_treasury(
+    State(state): State<AppState>,
+) -> Result<ResponseJson<TreasuryResponse>, (StatusCode, String)> {
+    let info = state.icm_client.fee_vault_info()
+        .await
+        .map_err(|e| rpc_error("Failed to load fee vault", e))?;
+
+    Ok(ResponseJson(TreasuryResponse {
+        owner: info.owner.to_string(),
+        usdc_mint: info.usdc_mint.to_string(),
+        fee_vault: info.fee_vault.to_string(),
+        fee_rate_bps: info.fee_rate_bps,
+        vault_balance: lamports_to_usdc(info.balance),
+        total_fees_collected: lamports_to_usdc(info.total_fees_collected),
+    }))
+}
+
+/// Fees paid into the vault per pool, highest first
+pub async fn get_pool_fees(
+    State(state): State<AppState>,
+) -> Result<ResponseJson<Vec<PoolFeesResponse>>, (StatusCode, String)> {
+    let totals = FeeVaultMovementRecord::fee_totals(state.db.pool())
+        .await
+        .map_err(|e| db_error("Failed to load pool fees", e))?;
+
+    // Buckets created before the indexer's first pass may have no pool row yet
+    let mut pools: HashMap<String, PoolFeesResponse> = HashMap::new();
+    for total in totals {
+        let amount = lamports_to_usdc(total.total as u64);
+        let entry = pools.entry(total.bucket_pda.clone()).or_insert_with(|| PoolFeesResponse {
+            pool_id: total.bucket_pda,
+            bucket_name: total.bucket_name,
+            creator: total.creator_pubkey,
+            total_fees: 0.0,
+            fees_by_action: HashMap::new(),
+            fee_transactions: 0,
+            last_fee_at: None,
+        });
+        entry.total_fees += amount;
+        *entry.fees_by_action.entry(total.action.unwrap_or_else(|| "unknown".to_string())).or_default() += amount;
+        entry.fee_transactions += total.transactions as u32;
+        entry.last_fee_at = entry.last_fee_at.max(total.last_fee_at.map(|time| time.timestamp()));
+    }
+
+    let mut pools: Vec<_> = pools.into_values().collect();
+    pools.sort_by(|a, b| b.total_fees.total_cmp(&a.total_fees));
+    Ok(ResponseJson(pools))
+}
+
+/// Withdrawals from the fee vault, newest first
+pub async fn get_withdrawals(
+    State(state): State<AppState>,
+    Query(query): Query<WithdrawalsQuery>,
+) -> Result<ResponseJson<Vec<FeeWithdrawalResponse>>, (StatusCode, String)> {
+    let movements = FeeVaultMovementRecord::withdrawals(state.db.pool(), query.limit())
+        .await
+        .map_err(|e| db_error("Failed to load fee withdrawals", e))?;
+
+    let withdrawals = movements.into_iter()
+        .map(|m| FeeWithdrawalResponse {
+            signature: m.signature,
+            slot: m.slot as u64,
+            block_time: m.block_time.map(|time| time.timestamp()),
+            amount: lamports_to_usdc(m.delta.unsigned_abs()),
+        })
+        .collect();
+    Ok(ResponseJson(withdrawals))
+}
+
+/// Withdraw protocol fees with the caller's custodial key; only the program owner may call this
+pub async fn withdraw_fees(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(budget): Query<ComputeBudgetOptions>,
+    Json(request): Json<WithdrawFeesRequest>,
+) -> Result<ResponseJson<UnsignedTransactionResponse>, (StatusCode, String)> {
+    if request.amount.is_some_and(|amount| !amount.is_finite() || amount <= 0.0) {
+        return Err((StatusCode::BAD_REQUEST, "Withdrawal amount must be greater than zero".to_string()));
+    }
+
+    let keypair = crate::routes::icm::get_user_keypair_by_email(&auth_user.email, &state)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+    let info = state.icm_client.fee_vault_info()
+        .await
+        .map_err(|e| rpc_error("Failed to load fee vault", e))?;
+    if keypair.pubkey() != info.owner {
+        warn!("[withdraw_fees] User {} is not the program owner", auth_user.id);
+        return Err((StatusCode::FORBIDDEN, "Only the program owner can withdraw fees".to_string()));
+    }
+
+    let response = state.icm_client
+        .with_compute_budget(budget)
+        .withdraw_fees_transaction(request.amount.map(usdc_to_lamports), &keypair)
+        .await
+        .map_err(|e| {
+            error!("[withdraw_fees] Withdrawal failed: {}", e);
+            match IcmProgramError::from_error(&e) {
+                Some(program_error) => (
+                    program_error.status(),
+                    format!("Withdrawal failed: {} [{}]", program_error.message(), program_error.api_code()),
+                ),
+                None => (StatusCode::BAD_REQUEST, format!("Withdrawal failed: {}", e)),
+            }
+        })?;
+
+    info!("[withdraw_fees] Owner withdrew {:?} USDC in {}", request.amount, response.transaction);
+    Ok(ResponseJson(response))
+}
+
+/// Create treasury routes
+pub fn create_routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/treasury", get(get_treasury))
+        .route("/api/v1/treasury/fees", get(get_pool_fees))
+        .route("/api/v1/treasury/withdrawals", get(get_withdrawals))
+        .route("/api/v1/treasury/withdraw", post(withdraw_fees))
+}
@@ -14,11 +14,11 @@ use axum::{
     Router,
 };
 use serde::{Deserialize, Serialize};
-use solana_client::rpc_client::RpcClient;
 use solana_sdk::pubkey::Pubkey;
 use std::str::FromStr;
 use tracing::{debug, error, info, warn};
 
+use crate::onchain_instance::rpc::SolanaRpc;
 use crate::server::AppState;
 
 /// Request parameters for wallet balance endpoint
@@ -80,14 +80,10 @@ pub async fn get_wallet_balance(
         }
     };
 
-    // Create RPC client
-    let rpc_client = RpcClient::new(
-        std::env::var("SOLANA_RPC_URL")
-            .unwrap_or_else(|_| "https://api.devnet.solana.com".to_string())
-    );
+    let rpc = state.icm_client.rpc();
 
     // Fetch SOL balance
-    let sol_balance = match rpc_client.get_balance(&pubkey) {
+    let sol_balance = match rpc.call(|client| async move { client.get_balance(&pubkey).await }).await {
         Ok(balance_lamports) => {
             debug!("SOL balance in lamports: {}", balance_lamports);
             balance_lamports as f64 / 1e9 // Convert lamports to SOL
@@ -104,24 +100,9 @@ pub async fn get_wallet_balance(
     };
 
     // Fetch USDC balance
-    // Note: You'll need to define the USDC token mint address for your environment
-    // For devnet, common USDC mint: Gh9ZwEmdLJ8DscKNTkTqPbNwLNNBjuSzaG9Vp2KGtKJr
-    let usdc_mint = match std::env::var("USDC_MINT_ADDRESS") {
-        Ok(mint) => match Pubkey::from_str(&mint) {
-            Ok(pk) => pk,
-            Err(e) => {
-                warn!("Invalid USDC mint address in environment: {}", e);
-                // Fallback to devnet USDC mint
-                Pubkey::from_str("Gh9ZwEmdLJ8DscKNTkTqPbNwLNNBjuSzaG9Vp2KGtKJr").unwrap()
-            }
-        },
-        Err(_) => {
-            // Default to devnet USDC mint if not set
-            Pubkey::from_str("Gh9ZwEmdLJ8DscKNTkTqPbNwLNNBjuSzaG9Vp2KGtKJr").unwrap()
-        }
-    };
+    let usdc_mint = state.config.usdc_mint;
 
-    let usdc_balance = match get_token_balance(&rpc_client, &pubkey, &usdc_mint).await {
+    let usdc_balance = match get_token_balance(&rpc, &pubkey, &usdc_mint).await {
         Ok(balance) => balance,
         Err(e) => {
             warn!("Failed to fetch USDC balance for {}: {}", query.public_key, e);
@@ -144,7 +125,7 @@ pub async fn get_wallet_balance(
 
 /// Helper function to get SOL and USDC balances for a wallet
 /// This can be used by auth endpoints to include balance data
-pub async fn fetch_wallet_balances(public_key_str: &str) -> Result<WalletBalanceResponse, String> {
+pub async fn fetch_wallet_balances(rpc: &SolanaRpc, usdc_mint: &Pubkey, public_key_str: &str) -> Result<WalletBalanceResponse, String> {
     // Validate and parse the public key
     let pubkey = match Pubkey::from_str(public_key_str) {
         Ok(pk) => pk,
@@ -153,14 +134,8 @@ pub async fn fetch_wallet_balances(public_key_str: &str) -> Result<WalletBalance
         }
     };
 
-    // Create RPC client
-    let rpc_client = RpcClient::new(
-        std::env::var("SOLANA_RPC_URL")
-            .unwrap_or_else(|_| "https://api.devnet.solana.com".to_string())
-    );
-
     // Fetch SOL balance
-    let sol_balance = match rpc_client.get_balance(&pubkey) {
+    let sol_balance = match rpc.call(|client| async move { client.get_balance(&pubkey).await }).await {
         Ok(balance_lamports) => {
             debug!("SOL balance in lamports: {}", balance_lamports);
             balance_lamports as f64 / 1e9 // Convert lamports to SOL
@@ -172,22 +147,7 @@ pub async fn fetch_wallet_balances(public_key_str: &str) -> Result<WalletBalance
     };
 
     // Fetch USDC balance
-    let usdc_mint = match std::env::var("USDC_MINT_ADDRESS") {
-        Ok(mint) => match Pubkey::from_str(&mint) {
-            Ok(pk) => pk,
-            Err(e) => {
-                warn!("Invalid USDC mint address in environment: {}", e);
-                // Fallback to devnet USDC mint
-                Pubkey::from_str("Gh9ZwEmdLJ8DscKNTkTqPbNwLNNBjuSzaG9Vp2KGtKJr").unwrap()
-            }
-        },
-        Err(_) => {
-            // Default to devnet USDC mint if not set
-            Pubkey::from_str("Gh9ZwEmdLJ8DscKNTkTqPbNwLNNBjuSzaG9Vp2KGtKJr").unwrap()
-        }
-    };
-
-    let usdc_balance = match get_token_balance(&rpc_client, &pubkey, &usdc_mint).await {
+    let usdc_balance = match get_token_balance(rpc, &pubkey, usdc_mint).await {
         Ok(balance) => balance,
         Err(e) => {
             warn!("Failed to fetch USDC balance for {}: {}", public_key_str, e);
@@ -204,7 +164,7 @@ pub async fn fetch_wallet_balances(public_key_str: &str) -> Result<WalletBalance
 
 /// Helper function to get SPL token balance
 async fn get_token_balance(
-    rpc_client: &RpcClient,
+    rpc: &SolanaRpc,
     wallet_pubkey: &Pubkey,
     token_mint: &Pubkey,
 ) -> Result<f64, Box<dyn std::error::Error>> {
@@ -213,10 +173,12 @@ async fn get_token_balance(
     use spl_token::state::Account as TokenAccount;
 
     // Find the associated token account
-    let token_accounts = rpc_client.get_token_accounts_by_owner(
-        wallet_pubkey,
-        solana_client::rpc_request::TokenAccountsFilter::Mint(*token_mint),
-    )?;
+    let token_accounts = rpc.call(|client| async move {
+        client.get_token_accounts_by_owner(
+            wallet_pubkey,
+            solana_client::rpc_request::TokenAccountsFilter::Mint(*token_mint),
+        ).await
+    }).await?;
 
     if token_accounts.is_empty() {
         debug!("No token account found for mint {} and wallet {}", token_mint, wallet_pubkey);
@@ -235,7 +197,7 @@ async fn get_token_balance(
         }
     };
     
-    let account_data = match rpc_client.get_account_data(&token_account_pubkey) {
+    let account_data = match rpc.call(|client| async move { client.get_account_data(&token_account_pubkey).await }).await {
         Ok(data) => data,
         Err(e) => {
             debug!("Failed to get token account data: {}", e);
@@ -0,0 +1,158 @@
+//! # Webhook Routes
+//!
+//! Register endpoints that receive signed pool and trade events, inspect the
+//! delivery log and replay past deliveries.
+//!
+//! All endpoints require authentication via JWT middleware.
+
+use axum::{
+    extract::{Extension, Json, Path, Query, State},
+    http::StatusCode,
+    response::Json as ResponseJson,
+    routing::{delete, get, post},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::auth::models::AuthUser;
+use crate::database::models::{WebhookDelivery, WebhookEndpoint};
+use crate::server::AppState;
+use crate::services::webhooks::{generate_secret, validate_endpoint_url, WEBHOOK_EVENT_TYPES};
+
+/// Default and maximum number of deliveries per response
+const DEFAULT_DELIVERY_LIMIT: i64 = 50;
+const MAX_DELIVERY_LIMIT: i64 = 500;
+
+/// Request to register a webhook endpoint
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    /// Event types to deliver; all types when omitted
+    pub event_types: Option<Vec<String>>,
+    /// Only deliver events for this bucket PDA
+    pub pool_id: Option<String>,
+}
+
+/// Newly registered endpoint with its signing secret
+#[derive(Debug, Serialize)]
+pub struct CreateWebhookResponse {
+    pub endpoint: WebhookEndpoint,
+    /// Shown only once; used to verify `X-ICM-Signature`
+    pub secret: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeliveriesQuery {
+    pub limit: Option<i64>,
+}
+
+fn internal_error(context: &str, e: anyhow::Error) -> (StatusCode, String) {
+    error!("[webhooks] {}: {}", context, e);
+    (StatusCode::INTERNAL_SERVER_ERROR, format!("{}: {}", context, e))
+}
+
+/// Register a webhook endpoint for the authenticated user
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<CreateWebhookRequest>,
+) -> Result<ResponseJson<CreateWebhookResponse>, (StatusCode, String)> {
+    let url = validate_endpoint_url(&request.url)
+        .await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    let event_types = request.event_types.unwrap_or_default();
+    if let Some(unknown) = event_types.iter().find(|t| !WEBHOOK_EVENT_TYPES.contains(&t.as_str())) {
+        return Err((StatusCode::BAD_REQUEST, format!(
+            "Unknown event type '{}', expected one of: {}", unknown, WEBHOOK_EVENT_TYPES.join(", ")
+        )));
+    }
+
+    let secret = generate_secret().map_err(|e| internal_error("Failed to create secret", e))?;
+    let endpoint = WebhookEndpoint::insert(
+        state.db.pool(),
+        auth_user.id,
+        url.as_str(),
+        &secret,
+        &event_types,
+        request.pool_id.as_deref(),
+    )
+    .await
+    .map_err(|e| internal_error("Failed to register webhook", e))?;
+
+    info!("[create_webhook] User {} registered webhook {} -> {}", auth_user.id, endpoint.id, endpoint.url);
+    Ok(ResponseJson(CreateWebhookResponse { endpoint, secret }))
+}
+
+/// List the authenticated user's webhook endpoints
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<ResponseJson<Vec<WebhookEndpoint>>, (StatusCode, String)> {
+    let endpoints = WebhookEndpoint::fetch_by_user(state.db.pool(), auth_user.id)
+        .await
+        .map_err(|e| internal_error("Failed to load webhooks", e))?;
+    Ok(ResponseJson(endpoints))
+}
+
+/// Remove a webhook endpoint and its delivery log
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let deleted = WebhookEndpoint::delete(state.db.pool(), auth_user.id, id)
+        .await
+        .map_err(|e| internal_error("Failed to delete webhook", e))?;
+    if deleted {
+        info!("[delete_webhook] User {} deleted webhook {}", auth_user.id, id);
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err((StatusCode::NOT_FOUND, "Webhook not found".to_string()))
+    }
+}
+
+/// Recent deliveries for one of the user's endpoints, newest first
+pub async fn list_deliveries(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<DeliveriesQuery>,
+) -> Result<ResponseJson<Vec<WebhookDelivery>>, (StatusCode, String)> {
+    let endpoint = WebhookEndpoint::fetch_by_id(state.db.pool(), id)
+        .await
+        .map_err(|e| internal_error("Failed to load webhook", e))?;
+    if endpoint.is_none_or(|endpoint| endpoint.user_id != auth_user.id) {
+        return Err((StatusCode::NOT_FOUND, "Webhook not found".to_string()));
+    }
+
+    let limit = query.limit.unwrap_or(DEFAULT_DELIVERY_LIMIT).clamp(1, MAX_DELIVERY_LIMIT);
+    let deliveries = WebhookDelivery::fetch_by_endpoint(state.db.pool(), id, limit)
+        .await
+        .map_err(|e| internal_error("Failed to load deliveries", e))?;
+    Ok(ResponseJson(deliveries))
+}
+
+/// Send a past delivery's payload again
+pub async fn replay_delivery(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<ResponseJson<WebhookDelivery>, (StatusCode, String)> {
+    let delivery = state.webhooks.replay(id, auth_user.id)
+        .await
+        .map_err(|e| internal_error("Failed to replay delivery", e))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Delivery not found".to_string()))?;
+    Ok(ResponseJson(delivery))
+}
+
+/// Create webhook routes
+pub fn create_routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/webhooks", post(create_webhook).get(list_webhooks))
+        .route("/api/v1/webhooks/{id}", delete(delete_webhook))
+        .route("/api/v1/webhooks/{id}/deliveries", get(list_deliveries))
+        .route("/api/v1/webhooks/deliveries/{id}/replay", post(replay_delivery))
+}
@@ -6,8 +6,6 @@ use axum::{Router, routing::{get, post}};
 use tower_http::cors::{CorsLayer};
 use tokio::net::TcpListener;
 use std::sync::Arc;
-use anchor_client::Cluster;
-use solana_sdk::signature::Keypair;
 use tokio::sync::RwLock;
 
 use crate::routes::health::ping;
@@ -20,20 +18,37 @@ use crate::agent::TradingAgent;
 pub struct AppState {
     pub icm_client: Arc<IcmProgramInstance>,
     pub trading_agent: Arc<RwLock<Option<TradingAgent>>>,
+    /// Running bucket agents, by bucket PDA
+    pub agents: Arc<crate::agent::registry::AgentRegistry>,
     pub jwt_service: Arc<crate::auth::jwt::JwtService>,
     pub db: Arc<crate::database::connection::DatabaseConnection>,
+    pub pool_resolver: Arc<crate::agent::pool_resolver::RaydiumPoolResolver>,
+    /// Domain events published by routes and the trading agent
+    pub event_bus: Arc<crate::services::event_bus::EventBus>,
+    pub webhooks: Arc<crate::services::webhooks::WebhookDispatcher>,
+    /// Validated server configuration
+    pub config: Arc<crate::config::AppConfig>,
 }
 
 /// Starts the ICM (Intelligent Content Management) HTTP server.
 ///
 /// This function initializes and starts the web server with all configured routes.
-/// The server binds to localhost on port 3000 and serves the application using
+/// The server binds to the configured port and serves the application using
 /// the Axum web framework with Tokio runtime.
 pub async fn start() {
+    // Load and validate configuration before touching the network
+    let config = match crate::config::AppConfig::load() {
+        Ok(config) => Arc::new(config),
+        Err(e) => {
+            tracing::error!("{:#}", e);
+            panic!("Cannot start server with invalid configuration");
+        }
+    };
+
     // Initialize the ICM program instance
-    let payer = Keypair::new(); // TODO: Load from secure storage in production
-    let icm_instance = match IcmProgramInstance::new(Cluster::Devnet, payer) {
-        Ok(instance) => Arc::new(instance),
+    let rpc = Arc::new(crate::onchain_instance::rpc::SolanaRpc::new(config.rpc.clone()));
+    let icm_instance = match IcmProgramInstance::new(config.cluster.clone(), config.usdc_mint, rpc) {
+        Ok(instance) => Arc::new(instance.with_compute_budget_policy(config.compute_budget)),
         Err(e) => {
             tracing::error!("Failed to initialize ICM program instance: {}", e);
             panic!("Cannot start server without ICM program instance");
@@ -41,19 +56,63 @@ pub async fn start() {
     };
 
     // Create JWT service first
-    let jwt_secret = std::env::var("JWT_SECRET").unwrap_or_else(|_| "dev_secret".to_string());
-    let jwt_service = Arc::new(crate::auth::jwt::JwtService::new(&jwt_secret));
+    let jwt_service = Arc::new(crate::auth::jwt::JwtService::new(&config.jwt_secret));
 
     // Initialize database connection
     let db_config = crate::database::connection::DatabaseConfig::from_env().expect("Failed to load DB config from env");
     let db = Arc::new(crate::database::connection::DatabaseConnection::new(db_config).await.expect("Failed to connect to DB"));
 
+    // Raydium pool discovery for swaps that don't pass AMM accounts
+    let pool_resolver = Arc::new(crate::agent::pool_resolver::RaydiumPoolResolver::from_config(
+        icm_instance.rpc(),
+        &config.agent.pool_resolver,
+    ));
+
+    // Domain events, with webhook delivery subscribed from the start
+    let event_bus = Arc::new(crate::services::event_bus::EventBus::new());
+    let webhooks = Arc::new(crate::services::webhooks::WebhookDispatcher::new(
+        db.pool().clone(),
+        crate::services::webhooks::WebhookConfig::default(),
+    ));
+    Arc::clone(&webhooks).start(&event_bus);
+
+    // Status tracking for wallet-signed transactions submitted through the API
+    Arc::new(crate::services::transaction_tracker::TransactionTracker::new(
+        db.pool().clone(),
+        icm_instance.clone(),
+    )).start();
+
+    // Mirror on-chain bucket activity into the pool tables
+    Arc::new(crate::services::indexer::ProgramIndexer::new(
+        db.pool().clone(),
+        icm_instance.clone(),
+    )).start();
+
+    // Bucket agents started by the API and the scheduler
+    let agents = Arc::new(crate::agent::registry::AgentRegistry::new());
+
+    // Start, unwind and close custodial buckets on their deadlines
+    Arc::new(crate::services::bucket_scheduler::BucketScheduler::new(
+        config.scheduler,
+        db.pool().clone(),
+        icm_instance.clone(),
+        pool_resolver.clone(),
+        event_bus.clone(),
+        agents.clone(),
+        config.agent.clone(),
+    )).start();
+
     // Create application state
     let app_state = AppState {
         icm_client: icm_instance,
         trading_agent: Arc::new(RwLock::new(None)),
+        agents,
         jwt_service: jwt_service.clone(),
         db: db.clone(),
+        pool_resolver,
+        event_bus,
+        webhooks,
+        config: config.clone(),
     };
 
     // Import the AuthMiddleware
@@ -73,7 +132,13 @@ pub async fn start() {
         .route("/api/v1/bucket/claim-rewards", post(crate::routes::icm::claim_rewards))
         .route("/api/v1/bucket/close", post(crate::routes::icm::close_bucket))
         .route("/api/v1/bucket/all", get(crate::routes::icm::get_all_pools_by_pda))
+        .route("/api/v1/bucket/list", get(crate::routes::icm::list_pools))
         .route("/api/v1/bucket/trading_pools", post(crate::routes::icm::get_trading_pool_info))
+        .route("/api/v1/bucket/contributions", get(crate::routes::icm::get_bucket_contributions))
+        .route("/api/v1/bucket/lifecycle", get(crate::routes::icm::get_bucket_lifecycle))
+        .route("/api/v1/bucket/payout-preview", get(crate::routes::icm::get_payout_preview))
+        .route("/api/v1/bucket/trades", get(crate::routes::icm::get_bucket_trade_records))
+        .route("/api/v1/profile/contributions", get(crate::routes::icm::get_contributor_records))
         .layer(middleware::from_fn_with_state(jwt_service.clone(), AuthMiddleware::validate_token));
 
     // Faucet route (requires auth)
@@ -82,6 +147,26 @@ pub async fn start() {
         .layer(middleware::from_fn_with_state(jwt_service.clone(), AuthMiddleware::validate_token))
         .with_state(Arc::new(app_state.clone()));
 
+    // Webhook routes (requires auth)
+    let webhook_routes = Router::new()
+        .merge(crate::routes::webhooks::create_routes())
+        .layer(middleware::from_fn_with_state(jwt_service.clone(), AuthMiddleware::validate_token));
+
+    // Wallet-signed transaction routes (requires auth)
+    let transaction_routes = Router::new()
+        .merge(crate::routes::transactions::create_routes())
+        .layer(middleware::from_fn_with_state(jwt_service.clone(), AuthMiddleware::validate_token));
+
+    // Treasury routes (requires auth)
+    let treasury_routes = Router::new()
+        .merge(crate::routes::treasury::create_routes())
+        .layer(middleware::from_fn_with_state(jwt_service.clone(), AuthMiddleware::validate_token));
+
+    // Simulation routes (requires auth)
+    let simulate_routes = Router::new()
+        .merge(crate::routes::simulate::create_routes())
+        .layer(middleware::from_fn_with_state(jwt_service.clone(), AuthMiddleware::validate_token));
+
     // Wallet routes (requires auth)
     let wallet_routes = Router::new()
         .merge(crate::routes::wallet::create_routes())
@@ -94,18 +179,25 @@ pub async fn start() {
         .merge(bucket_routes)
         .merge(faucet_routes)
         .merge(wallet_routes)
+        .merge(webhook_routes)
+        .merge(transaction_routes)
+        .merge(treasury_routes)
+        .merge(simulate_routes)
         // Merge agent routes
         .merge(agent::create_routes())
+        // Merge market data routes
+        .merge(crate::routes::market::create_routes())
         // Merge auth routes
         .merge(crate::routes::auth::create_auth_routes())
         .layer(
             ServiceBuilder::new()
                 .layer(
                     CorsLayer::new()
-                        .allow_origin(["https://fr-icm-ui.vercel.app".parse::<axum::http::HeaderValue>().unwrap(), "http://localhost:3001".parse::<axum::http::HeaderValue>().unwrap()]) // Allow frontend origin
+                        .allow_origin(config.allowed_origins().expect("CORS origins validated at load")) // Allow frontend origins
                         .allow_methods([
                             axum::http::Method::GET,
                             axum::http::Method::POST,
+                            axum::http::Method::DELETE,
                             axum::http::Method::OPTIONS,
                         ])
                         .allow_headers([
@@ -119,12 +211,8 @@ pub async fn start() {
         )
         .with_state(app_state);
 
-    // Define the server address - use $PORT if set (Heroku), otherwise default to 3000
-    let port = std::env::var("PORT")
-        .ok()
-        .and_then(|p| p.parse().ok())
-        .unwrap_or(3000);
-    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));
+    // Define the server address - $PORT (Heroku) is read by the config layer, default 3000
+    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], config.port));
 
     // Create a TCP listener bound to the specified address
     let listener = TcpListener::bind(addr).await.expect(
@@ -137,8 +225,8 @@ pub async fn start() {
     tracing::info!("🏥 Health check available at http://{}/ping", addr);
     tracing::info!("📊 ICM Program endpoints available at http://{}/api/v1/bucket/*", addr);
     tracing::info!("🤖 AI Trading Agent endpoints available at http://{}/api/v1/agent/*", addr);
-    tracing::info!("🔧 Environment: Development");
-    tracing::info!("🌐 Cluster: Devnet");
+    tracing::info!("🔧 Environment: {}", config.environment);
+    tracing::info!("🌐 Cluster: {} via {}", config.cluster, config.rpc.urls.join(", "));
 
     // Start serving the application
     axum::serve(listener, app).await.unwrap();
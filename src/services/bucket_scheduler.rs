@@ -0,0 +1,415 @@
+//! Bucket Lifecycle Scheduler
+//!
+//! Moves buckets through their phases as `contribution_deadline` and
+//! `trading_deadline` pass: starts trading once a funded bucket's contribution
+//! window closes and launches its agent, swaps positions back to USDC shortly
+//! before the trading deadline, and closes the bucket when trading ends. Trading
+//! buckets without a running agent, e.g. after a restart, get it relaunched once
+//! per process; an agent stopped through the API afterwards stays stopped. Only
+//! buckets whose creator has a custodial wallet can be signed for; every action
+//! is recorded in `bucket_lifecycle_actions`.
+
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use dashmap::DashSet;
+use deadpool_postgres::Pool;
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use futures::FutureExt;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+use crate::agent::circuit_breaker::CircuitBreakers;
+use crate::agent::data_fetcher::DataFetcher;
+use crate::agent::pool_resolver::RaydiumPoolResolver;
+use crate::agent::token_metadata::TokenMetadataCache;
+use crate::agent::registry::AgentRegistry;
+use crate::agent::trading_agent::{TradingAgent, TradingAgentConfigBuilder};
+use crate::config::AgentDefaults;
+use crate::database::models::{BucketLifecycleAction, UserProfile};
+use crate::onchain_instance::instance::{IcmProgramInstance, SwapLeg};
+use crate::services::event_bus::{DomainEvent, EventBus, PoolClosed, TradingStarted};
+use crate::state_structs::{BucketInfo, CloseBucketRequest, StartTradingRequest};
+
+/// Scheduler settings
+#[derive(Debug, Clone, Copy)]
+pub struct SchedulerConfig {
+    pub enabled: bool,
+    pub poll_interval: Duration,
+    /// How long before the trading deadline positions are swapped back to USDC
+    pub unwind_lead: Duration,
+    pub unwind_slippage_bps: u16,
+    /// Wait after a failed action before trying it again
+    pub retry_backoff: Duration,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            poll_interval: Duration::from_secs(30),
+            unwind_lead: Duration::from_secs(15 * 60),
+            unwind_slippage_bps: 100,
+            retry_backoff: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+impl SchedulerConfig {
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn with_unwind_lead(mut self, unwind_lead: Duration) -> Self {
+        self.unwind_lead = unwind_lead;
+        self
+    }
+
+    pub fn with_unwind_slippage_bps(mut self, slippage_bps: u16) -> Self {
+        self.unwind_slippage_bps = slippage_bps;
+        self
+    }
+}
+
+/// The next lifecycle step a bucket is due for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    StartTrading,
+    ResumeAgent,
+    Unwind,
+    Close,
+}
+
+impl Stage {
+    fn action(self) -> &'static str {
+        match self {
+            Stage::StartTrading => "start_trading",
+            Stage::ResumeAgent => "start_agent",
+            Stage::Unwind => "unwind",
+            Stage::Close => "close_bucket",
+        }
+    }
+
+    /// The step `bucket` is due for at `now`, if any. `agent_launched` is whether
+    /// its agent is running or was already launched by this process.
+    fn due(bucket: &BucketInfo, now: i64, unwind_lead: i64, agent_launched: bool) -> Option<Stage> {
+        let account = &bucket.account;
+        let contribution_deadline: i64 = account.contribution_deadline.parse().ok()?;
+        let trading_deadline: i64 = account.trading_deadline.parse().ok()?;
+        match account.status.as_str() {
+            // The program rejects start_trading without contributions
+            "Raising" if now >= contribution_deadline && account.raised_amount > 0.0 => Some(Stage::StartTrading),
+            "Trading" if now >= trading_deadline => Some(Stage::Close),
+            // Swaps are rejected once the trading deadline has passed
+            "Trading" if now >= trading_deadline - unwind_lead => Some(Stage::Unwind),
+            "Trading" if !agent_launched => Some(Stage::ResumeAgent),
+            _ => None,
+        }
+    }
+}
+
+/// Starts, unwinds and closes buckets on their deadlines
+pub struct BucketScheduler {
+    config: SchedulerConfig,
+    db_pool: Pool,
+    icm_client: Arc<IcmProgramInstance>,
+    pool_resolver: Arc<RaydiumPoolResolver>,
+    event_bus: Arc<EventBus>,
+    agent_defaults: AgentDefaults,
+    quotes: DataFetcher,
+    agents: Arc<AgentRegistry>,
+    /// Buckets whose agent this scheduler launched since the server started
+    launched: DashSet<String>,
+}
+
+impl BucketScheduler {
+    pub fn new(
+        config: SchedulerConfig,
+        db_pool: Pool,
+        icm_client: Arc<IcmProgramInstance>,
+        pool_resolver: Arc<RaydiumPoolResolver>,
+        event_bus: Arc<EventBus>,
+        agents: Arc<AgentRegistry>,
+        agent_defaults: AgentDefaults,
+    ) -> Self {
+        let (quotes, _) = DataFetcher::new(
+            Vec::new(),
+            agent_defaults.data_fetch_interval_ms,
+            Arc::new(TokenMetadataCache::new(icm_client.rpc())),
+            Arc::new(CircuitBreakers::new(agent_defaults.circuit_breakers.clone())),
+        );
+        Self {
+            config,
+            db_pool,
+            icm_client,
+            pool_resolver,
+            event_bus,
+            agent_defaults,
+            quotes,
+            agents,
+            launched: DashSet::new(),
+        }
+    }
+
+    /// Start the scheduling loop
+    pub fn start(self: Arc<Self>) {
+        if !self.config.enabled {
+            info!("[start] Bucket scheduler disabled");
+            return;
+        }
+        tokio::spawn(async move {
+            let mut timer = tokio::time::interval(self.config.poll_interval);
+            loop {
+                timer.tick().await;
+                self.tick().await;
+            }
+        });
+        info!("[start] Bucket scheduler running");
+    }
+
+    /// Advance every bucket that is due
+    async fn tick(&self) {
+        let buckets = match self.icm_client.get_all_pools_by_pda(&self.db_pool).await {
+            Ok(buckets) => buckets,
+            Err(e) => {
+                warn!("[tick] Failed to load buckets: {}", e);
+                return;
+            }
+        };
+
+        let now = Utc::now().timestamp();
+        let unwind_lead = self.config.unwind_lead.as_secs() as i64;
+        for bucket in &buckets {
+            let agent_launched = self.agents.contains(&bucket.public_key) || self.launched.contains(&bucket.public_key);
+            let Some(stage) = Stage::due(bucket, now, unwind_lead, agent_launched) else { continue };
+            // A panic while advancing one bucket must not end the loop for the rest
+            match AssertUnwindSafe(self.advance(bucket, stage)).catch_unwind().await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => warn!("[tick] {} for bucket {} failed: {}", stage.action(), bucket.public_key, e),
+                Err(_) => error!("[tick] {} for bucket {} panicked", stage.action(), bucket.public_key),
+            }
+        }
+    }
+
+    async fn advance(&self, bucket: &BucketInfo, stage: Stage) -> Result<()> {
+        // Lifecycle instructions must be signed by the creator
+        let Some(creator) = self.creator_keypair(&bucket.account.creator).await? else {
+            return Ok(());
+        };
+        if self.in_backoff(bucket, stage.action()).await? {
+            return Ok(());
+        }
+
+        match stage {
+            Stage::StartTrading => self.start_trading(bucket, &creator).await,
+            Stage::ResumeAgent => self.resume_agent(bucket).await,
+            Stage::Unwind => self.unwind(bucket, &creator).await,
+            Stage::Close => self.close(bucket, &creator).await,
+        }
+        Ok(())
+    }
+
+    /// Custodial keypair of the creator, if the server holds one
+    async fn creator_keypair(&self, creator: &str) -> Result<Option<Keypair>> {
+        let Some(private_key) = UserProfile::fetch_private_key_by_pubkey(&self.db_pool, creator).await? else {
+            return Ok(None);
+        };
+        let bytes: Vec<u8> = private_key.into_iter().map(|b| b as u8).collect();
+        let keypair = Keypair::try_from(&bytes[..]).map_err(|e| anyhow!("Invalid keypair for {}: {}", creator, e))?;
+        Ok(Some(keypair))
+    }
+
+    /// Whether the last attempt at `action` failed within the retry backoff
+    async fn in_backoff(&self, bucket: &BucketInfo, action: &str) -> Result<bool> {
+        let latest = BucketLifecycleAction::fetch_latest(&self.db_pool, &bucket.public_key, action).await?;
+        let backoff = chrono::Duration::from_std(self.config.retry_backoff)?;
+        Ok(latest.is_some_and(|entry| entry.status == "failed" && entry.created_at + backoff > Utc::now()))
+    }
+
+    async fn start_trading(&self, bucket: &BucketInfo, creator: &Keypair) {
+        let account = &bucket.account;
+        let strategy = account.strategy.clone().unwrap_or_else(|| "dca".to_string());
+        let trading_end_time = account.trading_deadline.parse().ok()
+            .and_then(|deadline| chrono::DateTime::<Utc>::from_timestamp(deadline, 0))
+            .unwrap_or_else(Utc::now);
+        // Only the bucket name and creator reach the instruction
+        let request = StartTradingRequest {
+            bucket_name: account.name.clone(),
+            strategy: strategy.clone(),
+            token_bucket: account.token_mints.clone(),
+            total_amount_available_to_trade: (account.raised_amount * 1_000_000.0) as i64,
+            trading_end_time: trading_end_time.to_rfc3339(),
+            management_fee: 0,
+            creator_pubkey: account.creator.clone(),
+            pool_name: account.name.clone(),
+        };
+
+        let signature = match self.icm_client.start_trading_transaction(request, creator).await {
+            Ok(response) => response.transaction,
+            Err(e) => {
+                self.record(bucket, "start_trading", Err(format!("{:#}", e)), json!({})).await;
+                return;
+            }
+        };
+        self.event_bus.publish(
+            Some(bucket.public_key.clone()),
+            DomainEvent::TradingStarted(TradingStarted {
+                bucket_name: account.name.clone(),
+                creator: account.creator.clone(),
+                strategy: strategy.clone(),
+                signature: signature.clone(),
+            }),
+        );
+        self.record(bucket, "start_trading", Ok(Some(signature)), json!({ "raised_amount": account.raised_amount })).await;
+
+        let outcome = self.launch_agent(bucket, &strategy).await.map(|_| None);
+        self.record(bucket, "start_agent", outcome, json!({ "strategy": strategy })).await;
+    }
+
+    /// Relaunch the agent of a trading bucket that has none, e.g. after a restart
+    async fn resume_agent(&self, bucket: &BucketInfo) {
+        let strategy = bucket.account.strategy.clone().unwrap_or_else(|| "dca".to_string());
+        let outcome = self.launch_agent(bucket, &strategy).await.map(|_| None);
+        self.record(bucket, "start_agent", outcome, json!({ "strategy": strategy, "resumed": true })).await;
+    }
+
+    /// Create the bucket's agent and start it
+    async fn launch_agent(&self, bucket: &BucketInfo, strategy: &str) -> Result<(), String> {
+        let config = TradingAgentConfigBuilder::new()
+            .with_agent_defaults(&self.agent_defaults)
+            .for_bucket(Some(bucket.public_key.clone()), strategy, &bucket.account.token_mints)
+            .with_portfolio_id(Uuid::new_v4())
+            .build()
+            .map_err(|e| e.to_string())?;
+        let agent = TradingAgent::new(config, self.icm_client.clone(), self.db_pool.clone(), &self.event_bus)
+            .await
+            .map_err(|e| e.to_string())?;
+        self.agents.start(bucket.public_key.clone(), agent)
+            .await
+            .map_err(|e| e.to_string())?;
+        self.launched.insert(bucket.public_key.clone());
+        Ok(())
+    }
+
+    /// Stop the bucket's agent and swap every token balance back to USDC
+    async fn unwind(&self, bucket: &BucketInfo, creator: &Keypair) {
+        self.stop_agent(bucket).await;
+
+        let usdc_mint = self.icm_client.usdc_mint();
+        let balances = match self.icm_client.bucket_vault_balances(&bucket.account.name, creator.pubkey()).await {
+            Ok(balances) => balances,
+            Err(e) => {
+                self.record(bucket, "unwind", Err(format!("{:#}", e)), json!({})).await;
+                return;
+            }
+        };
+
+        for (mint, amount) in balances.into_iter().filter(|(mint, amount)| *mint != usdc_mint && *amount > 0) {
+            let details = json!({ "input_mint": mint.to_string(), "output_mint": usdc_mint.to_string(), "in_amount": amount });
+            match self.swap_to_usdc(&bucket.account.name, creator, mint, amount).await {
+                Ok((signature, quoted_out_amount)) => {
+                    let mut details = details;
+                    details["quoted_out_amount"] = json!(quoted_out_amount);
+                    self.record(bucket, "unwind", Ok(Some(signature)), details).await;
+                }
+                Err(e) => self.record(bucket, "unwind", Err(format!("{:#}", e)), details).await,
+            }
+        }
+    }
+
+    /// Swap `amount` of `mint` in the bucket vault to USDC; returns the signature and quoted output
+    async fn swap_to_usdc(&self, bucket_name: &str, creator: &Keypair, mint: Pubkey, amount: u64) -> Result<(String, u64)> {
+        let usdc_mint = self.icm_client.usdc_mint();
+        let quote = self.quotes.fetch_quote(&mint.to_string(), &usdc_mint.to_string(), amount).await
+            .map_err(|e| anyhow!("Quote failed: {}", e))?;
+        let pool = self.pool_resolver.resolve(mint, usdc_mint).await
+            .map_err(|e| anyhow!("No Raydium pool: {}", e))?;
+        let leg = SwapLeg {
+            input_mint: mint,
+            output_mint: usdc_mint,
+            in_amount: amount,
+            quoted_out_amount: quote.output_amount,
+            slippage_bps: self.config.unwind_slippage_bps,
+            raydium_amm_program: self.pool_resolver.program_id(),
+            amm: pool.amm,
+            amm_authority: pool.amm_authority,
+            pool_coin_token_account: pool.pool_coin_token_account,
+            pool_pc_token_account: pool.pool_pc_token_account,
+        };
+        let response = self.icm_client.agent_swap_route_transaction(creator, bucket_name, &[leg]).await?;
+        Ok((response.transaction, quote.output_amount))
+    }
+
+    async fn close(&self, bucket: &BucketInfo, creator: &Keypair) {
+        self.stop_agent(bucket).await;
+
+        let account = &bucket.account;
+        let request = CloseBucketRequest {
+            bucket_name: account.name.clone(),
+            creator_pubkey: account.creator.clone(),
+        };
+        match self.icm_client.close_bucket_transaction(request, creator).await {
+            Ok(response) => {
+                self.event_bus.publish(
+                    Some(bucket.public_key.clone()),
+                    DomainEvent::PoolClosed(PoolClosed {
+                        bucket_name: account.name.clone(),
+                        creator: account.creator.clone(),
+                        signature: response.transaction.clone(),
+                    }),
+                );
+                self.record(bucket, "close_bucket", Ok(Some(response.transaction)), json!({})).await;
+            }
+            Err(e) => self.record(bucket, "close_bucket", Err(format!("{:#}", e)), json!({})).await,
+        }
+    }
+
+    /// Stop the bucket's running agent, if any
+    async fn stop_agent(&self, bucket: &BucketInfo) {
+        let outcome = match self.agents.stop(&bucket.public_key).await {
+            Ok(false) => return,
+            Ok(true) => Ok(None),
+            Err(e) => Err(e.to_string()),
+        };
+        self.record(bucket, "stop_agent", outcome, json!({})).await;
+    }
+
+    /// Log an action; `outcome` is its transaction signature, if any, or the error
+    async fn record(&self, bucket: &BucketInfo, action: &str, outcome: Result<Option<String>, String>, details: serde_json::Value) {
+        let (status, signature, error) = match outcome {
+            Ok(signature) => {
+                info!("[record] {} succeeded for bucket {}", action, bucket.public_key);
+                ("succeeded", signature, None)
+            }
+            Err(e) => {
+                warn!("[record] {} failed for bucket {}: {}", action, bucket.public_key, e);
+                ("failed", None, Some(e))
+            }
+        };
+
+        let entry = BucketLifecycleAction {
+            id: Uuid::new_v4(),
+            bucket_pda: bucket.public_key.clone(),
+            bucket_name: bucket.account.name.clone(),
+            creator_pubkey: bucket.account.creator.clone(),
+            action: action.to_string(),
+            status: status.to_string(),
+            signature,
+            details,
+            error,
+            created_at: Utc::now(),
+        };
+        if let Err(e) = entry.insert(&self.db_pool).await {
+            warn!("[record] Failed to log {} for bucket {}: {}", action, bucket.public_key, e);
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! Event Bus Service
+//!
+//! Process-wide broadcast of typed domain events, and the only event channel in
+//! the server: the per-pool agent stream is a filtered view of it. Routes, the
+//! bucket scheduler and agent components (through an [`EventPublisher`] tagged
+//! with their pool) publish; the webhook dispatcher and the SSE and WebSocket
+//! streams each subscribe independently.
+
+use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+use crate::agent::confirmation::TransactionCosts;
+use crate::agent::executor::ExecutionResult;
+use crate::agent::router::RollbackReport;
+use crate::agent::sliced_execution::SliceFill;
+use crate::agent::types::{Position, QuoteData, TradingPlan};
+
+/// Events buffered per subscriber before a slow one starts missing them
+const EVENT_BUFFER: usize = 1024;
+
+/// Everything published on the bus
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum DomainEvent {
+    PoolCreated(PoolCreated),
+    ContributionMade(ContributionMade),
+    PoolFilled(PoolFilled),
+    TradingStarted(TradingStarted),
+    PlanCreated(Box<TradingPlan>),
+    TradeExecuted(ExecutionSummary),
+    PoolClosed(PoolClosed),
+    /// Agent telemetry, mostly of interest to live dashboards
+    QuoteReceived(Box<QuoteData>),
+    RiskRejected(RiskRejection),
+    StopLossTriggered(StopLossTriggered),
+    PositionsChanged(HashMap<String, Position>),
+    ComponentHealth(ComponentHealth),
+}
+
+impl DomainEvent {
+    /// Name used for filtering and as the SSE event name
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DomainEvent::PoolCreated(_) => "pool_created",
+            DomainEvent::ContributionMade(_) => "contribution_made",
+            DomainEvent::PoolFilled(_) => "pool_filled",
+            DomainEvent::TradingStarted(_) => "trading_started",
+            DomainEvent::PlanCreated(_) => "plan_created",
+            DomainEvent::TradeExecuted(_) => "trade_executed",
+            DomainEvent::PoolClosed(_) => "pool_closed",
+            DomainEvent::QuoteReceived(_) => "quote_received",
+            DomainEvent::RiskRejected(_) => "risk_rejected",
+            DomainEvent::StopLossTriggered(_) => "stop_loss_triggered",
+            DomainEvent::PositionsChanged(_) => "positions_changed",
+            DomainEvent::ComponentHealth(_) => "component_health",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolCreated {
+    pub bucket_name: String,
+    pub creator: String,
+    pub strategy: String,
+    pub token_mints: Vec<String>,
+    pub target_amount_usdc: f64,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ContributionMade {
+    pub bucket_name: String,
+    pub creator: String,
+    pub contributor: String,
+    pub amount_usdc: f64,
+    pub signature: String,
+}
+
+/// A contribution that took the raised amount to the bucket's target
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolFilled {
+    pub bucket_name: String,
+    pub creator: String,
+    pub raised_amount_usdc: f64,
+    pub target_amount_usdc: f64,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TradingStarted {
+    pub bucket_name: String,
+    pub creator: String,
+    pub strategy: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolClosed {
+    pub bucket_name: String,
+    pub creator: String,
+    pub signature: String,
+}
+
+/// A quote or plan the agent refused to act on
+#[derive(Debug, Clone, Serialize)]
+pub struct RiskRejection {
+    pub plan_id: Option<Uuid>,
+    pub input_mint: String,
+    pub output_mint: String,
+    pub reason: String,
+}
+
+/// A position whose price fell past its strategy's stop-loss
+#[derive(Debug, Clone, Serialize)]
+pub struct StopLossTriggered {
+    pub bucket: String,
+    pub token_mint: String,
+    pub entry_price: f64,
+    pub current_price: f64,
+    pub loss_pct: f64,
+    pub stop_loss_pct: f64,
+}
+
+/// Outcome of an executed plan with its child order fills, without per-leg detail
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutionSummary {
+    pub plan_id: Uuid,
+    pub success: bool,
+    pub transaction_signature: Option<String>,
+    pub execution_time_ms: u64,
+    pub actual_slippage_bps: Option<u16>,
+    pub actual_output_amount: Option<u64>,
+    pub error_message: Option<String>,
+    pub costs: TransactionCosts,
+    pub route_legs: usize,
+    /// Child order fills when the plan was executed in slices
+    pub slices: Vec<SliceFill>,
+    /// How tokens stranded by a failed route leg were unwound
+    pub rollback: Option<RollbackReport>,
+}
+
+impl From<&ExecutionResult> for ExecutionSummary {
+    fn from(result: &ExecutionResult) -> Self {
+        Self {
+            plan_id: result.plan_id,
+            success: result.success,
+            transaction_signature: result.transaction_signature.clone(),
+            execution_time_ms: result.execution_time_ms,
+            actual_slippage_bps: result.actual_slippage_bps,
+            actual_output_amount: result.actual_output_amount,
+            error_message: result.error_message.clone(),
+            costs: result.costs,
+            route_legs: result.route_legs.len(),
+            slices: result.slices.clone(),
+            rollback: result.rollback.clone(),
+        }
+    }
+}
+
+/// A component starting, stopping or failing
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentHealth {
+    pub component: String,
+    pub healthy: bool,
+    pub detail: Option<String>,
+}
+
+/// Event as delivered to subscribers
+#[derive(Debug, Clone, Serialize)]
+pub struct EventEnvelope {
+    pub id: Uuid,
+    /// Bucket PDA the event concerns, when it concerns one
+    pub pool_id: Option<String>,
+    pub timestamp: DateTime<Utc>,
+    pub event: DomainEvent,
+}
+
+/// Broadcast bus shared through `AppState`
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<EventEnvelope>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUFFER);
+        Self { sender }
+    }
+
+    /// Publish an event; dropped silently when nobody is subscribed
+    pub fn publish(&self, pool_id: Option<String>, event: DomainEvent) {
+        let _ = self.sender.send(EventEnvelope {
+            id: Uuid::new_v4(),
+            pool_id,
+            timestamp: Utc::now(),
+            event,
+        });
+    }
+
+    /// Publisher that tags every event with `pool_id`
+    pub fn publisher(&self, pool_id: Option<String>) -> EventPublisher {
+        EventPublisher {
+            bus: self.clone(),
+            pool_id,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<EventEnvelope> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Handle agent components use to publish events for their pool
+#[derive(Debug, Clone)]
+pub struct EventPublisher {
+    bus: EventBus,
+    pool_id: Option<String>,
+}
+
+impl EventPublisher {
+    pub fn publish(&self, event: DomainEvent) {
+        self.bus.publish(self.pool_id.clone(), event);
+    }
+
+    pub fn pool_id(&self) -> Option<&str> {
+        self.pool_id.as_deref()
+    }
+
+    pub fn component_health(&self, component: &str, healthy: bool, detail: Option<String>) {
+        self.publish(DomainEvent::ComponentHealth(ComponentHealth {
+            component: component.to_string(),
+            healthy,
+            detail,
+        }));
+    }
+}
@@ -0,0 +1,162 @@
+//! Program Indexer Service
+//!
+//! Pages through the ICM program's finalized transactions and mirrors bucket
+//! creates, contributions, swaps, claims and closes into `trading_pools`,
+//! `pool_contributions` and `trade_records`, so activity that bypassed the API
+//! reaches the database too. Fee vault deposits and withdrawals go to
+//! `fee_vault_movements` for the treasury routes. A checkpoint signature lets it
+//! resume after a restart.
+
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::Pool;
+use futures::{StreamExt, TryStreamExt};
+use solana_sdk::signature::Signature;
+use tracing::{debug, info, warn};
+use crate::database::models::{IndexedFeeMovement, IndexedInstruction, IndexedPoolChange, IndexerCheckpoint};
+use crate::onchain_instance::instance::{IcmProgramInstance, ProgramAction, ProgramInstruction, ICM_PROGRAM_ID};
+
+/// How often new program transactions are fetched
+const POLL_SECS: u64 = 15;
+/// Transactions fetched per round trip to the database
+const FETCH_BATCH: usize = 64;
+/// Concurrent `getTransaction` calls
+const FETCH_CONCURRENCY: usize = 8;
+
+/// Indexes ICM program transactions into the pool tables
+#[derive(Debug)]
+pub struct ProgramIndexer {
+    db_pool: Pool,
+    icm_client: Arc<IcmProgramInstance>,
+}
+
+impl ProgramIndexer {
+    pub fn new(db_pool: Pool, icm_client: Arc<IcmProgramInstance>) -> Self {
+        Self { db_pool, icm_client }
+    }
+
+    /// Start the indexing loop
+    pub fn start(self: Arc<Self>) {
+        tokio::spawn(async move {
+            let mut timer = tokio::time::interval(Duration::from_secs(POLL_SECS));
+            loop {
+                timer.tick().await;
+                if let Err(e) = self.index().await {
+                    warn!("[index] Indexing stopped at the last checkpoint: {}", e);
+                }
+            }
+        });
+        info!("[start] Program indexer running");
+    }
+
+    /// Index every transaction after the checkpoint, oldest first
+    async fn index(&self) -> Result<()> {
+        let checkpoint_name = ICM_PROGRAM_ID.to_string();
+        let until = IndexerCheckpoint::fetch(&self.db_pool, &checkpoint_name)
+            .await?
+            .map(|checkpoint| Signature::from_str(&checkpoint.last_signature))
+            .transpose()?;
+        let signatures = self.icm_client.program_signatures(until).await?;
+        if signatures.is_empty() {
+            return Ok(());
+        }
+        debug!("[index] {} new program transactions", signatures.len());
+
+        for batch in signatures.chunks(FETCH_BATCH) {
+            let transactions: Vec<_> = futures::stream::iter(batch.to_vec())
+                .map(|entry| async move {
+                    if entry.failed {
+                        return Ok(None);
+                    }
+                    self.icm_client.program_transaction(entry.signature).await
+                })
+                .buffered(FETCH_CONCURRENCY)
+                .try_collect()
+                .await?;
+
+            for (entry, transaction) in batch.iter().zip(transactions) {
+                let block_time = transaction.as_ref()
+                    .and_then(|tx| tx.block_time)
+                    .and_then(|time| DateTime::<Utc>::from_timestamp(time, 0));
+                let instructions: Vec<IndexedInstruction> = transaction.as_ref()
+                    .map(|tx| tx.instructions.iter().map(|ix| self.indexed_instruction(ix, block_time)).collect())
+                    .unwrap_or_default();
+                let fee_movement = transaction.as_ref()
+                    .and_then(|tx| tx.fee_vault_movement.as_ref())
+                    .map(|movement| IndexedFeeMovement {
+                        action: movement.action.map(str::to_string),
+                        bucket_pda: movement.bucket.map(|bucket| bucket.to_string()),
+                        delta: movement.delta as i64,
+                    });
+
+                IndexerCheckpoint::advance(
+                    &self.db_pool,
+                    &checkpoint_name,
+                    &entry.signature.to_string(),
+                    entry.slot as i64,
+                    block_time,
+                    &instructions,
+                    fee_movement.as_ref(),
+                ).await?;
+            }
+        }
+
+        info!("[index] Indexed {} program transactions", signatures.len());
+        Ok(())
+    }
+
+    fn indexed_instruction(&self, ix: &ProgramInstruction, block_time: Option<DateTime<Utc>>) -> IndexedInstruction {
+        let change = match &ix.action {
+            ProgramAction::CreateBucket {
+                creator, name, token_mints, contribution_window_minutes, trading_window_minutes, target_amount, management_fee,
+            } => {
+                let windows = *contribution_window_minutes as i64 + *trading_window_minutes as i64;
+                IndexedPoolChange::Created {
+                    creator_pubkey: creator.to_string(),
+                    name: name.clone(),
+                    token_bucket: token_mints.iter().map(|mint| mint.to_string()).collect(),
+                    target_amount: *target_amount as i64,
+                    trading_end_time: block_time.unwrap_or_else(Utc::now) + chrono::Duration::minutes(windows),
+                    management_fee: *management_fee as i32,
+                }
+            }
+            ProgramAction::Contribute { contributor, amount } => IndexedPoolChange::Contributed {
+                contributor_pubkey: contributor.to_string(),
+                amount: *amount as i64,
+            },
+            ProgramAction::StartTrading => IndexedPoolChange::TradingStarted,
+            ProgramAction::Swap { input_mint, output_mint, amount_in, amount_out, .. } => {
+                // Mirrors the program's TradeType
+                let usdc_mint = self.icm_client.usdc_mint();
+                let trade_type = if *input_mint == usdc_mint {
+                    "BuyToken"
+                } else if *output_mint == usdc_mint {
+                    "SellToken"
+                } else {
+                    "Rebalance"
+                };
+                IndexedPoolChange::Traded {
+                    trade_type: trade_type.to_string(),
+                    from_token: input_mint.to_string(),
+                    to_token: output_mint.to_string(),
+                    amount_in: *amount_in as i64,
+                    amount_out: *amount_out as i64,
+                }
+            }
+            ProgramAction::ClaimRewards { contributor } => IndexedPoolChange::Claimed {
+                contributor_pubkey: contributor.to_string(),
+            },
+            ProgramAction::CloseBucket => IndexedPoolChange::Closed,
+        };
+
+        IndexedInstruction {
+            instruction_index: ix.index as i16,
+            action: ix.action.name().to_string(),
+            bucket_pda: ix.bucket.to_string(),
+            change,
+        }
+    }
+}
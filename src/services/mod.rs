@@ -5,6 +5,11 @@
 
 pub mod agent_executor;
 pub mod portfolio_tracker;
+pub mod event_bus;
+pub mod webhooks;
+pub mod transaction_tracker;
+pub mod indexer;
+pub mod bucket_scheduler;
 // pub mod swap_engine;
 
 // // Re-exports for easier access
@@ -0,0 +1,111 @@
+//! Transaction Tracker Service
+//!
+//! Follows wallet-signed transactions relayed through the API until they
+//! finalize, fail, or can no longer land because their blockhash expired.
+
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use deadpool_postgres::Pool;
+use solana_sdk::hash::Hash;
+use solana_sdk::signature::Signature;
+use solana_transaction_status_client_types::TransactionConfirmationStatus;
+use tracing::{debug, info, warn};
+use crate::database::models::SubmittedTransaction;
+use crate::onchain_instance::instance::IcmProgramInstance;
+
+/// How often pending transactions are re-checked
+const POLL_SECS: u64 = 5;
+/// Upper bound of `getSignatureStatuses` per call
+const POLL_BATCH: i64 = 256;
+
+/// Polls the cluster for the status of submitted transactions
+#[derive(Debug)]
+pub struct TransactionTracker {
+    db_pool: Pool,
+    icm_client: Arc<IcmProgramInstance>,
+}
+
+impl TransactionTracker {
+    pub fn new(db_pool: Pool, icm_client: Arc<IcmProgramInstance>) -> Self {
+        Self { db_pool, icm_client }
+    }
+
+    /// Start the polling loop
+    pub fn start(self: Arc<Self>) {
+        tokio::spawn(async move {
+            let mut timer = tokio::time::interval(Duration::from_secs(POLL_SECS));
+            loop {
+                timer.tick().await;
+                self.poll().await;
+            }
+        });
+        info!("[start] Transaction tracker running");
+    }
+
+    async fn poll(&self) {
+        let pending = match SubmittedTransaction::fetch_pending(&self.db_pool, POLL_BATCH).await {
+            Ok(pending) => pending,
+            Err(e) => {
+                warn!("[poll] Failed to load pending transactions: {}", e);
+                return;
+            }
+        };
+        if pending.is_empty() {
+            return;
+        }
+
+        // Check expiry before statuses: a blockhash that is already invalid
+        // cannot be used by a transaction the status query has not seen yet.
+        let mut expired = Vec::with_capacity(pending.len());
+        for tx in &pending {
+            let is_expired = match Hash::from_str(&tx.recent_blockhash) {
+                Ok(hash) if tx.status == "submitted" => {
+                    matches!(self.icm_client.is_blockhash_valid(&hash).await, Ok(false))
+                }
+                _ => false,
+            };
+            expired.push(is_expired);
+        }
+
+        let signatures = pending.iter()
+            .map(|tx| Signature::from_str(&tx.signature).unwrap_or_default())
+            .collect::<Vec<_>>();
+        let statuses = match self.icm_client.signature_statuses(&signatures).await {
+            Ok(statuses) => statuses,
+            Err(e) => {
+                warn!("[poll] Failed to fetch signature statuses: {}", e);
+                return;
+            }
+        };
+
+        for ((mut tx, status), is_expired) in pending.into_iter().zip(statuses).zip(expired) {
+            let previous = tx.status.clone();
+            match status {
+                Some(status) => {
+                    tx.slot = Some(status.slot as i64);
+                    if let Some(err) = status.err {
+                        tx.status = "failed".to_string();
+                        tx.error = Some(err.to_string());
+                    } else if matches!(status.confirmation_status, Some(TransactionConfirmationStatus::Finalized)) {
+                        tx.status = "finalized".to_string();
+                    } else if matches!(status.confirmation_status, Some(TransactionConfirmationStatus::Confirmed)) {
+                        tx.status = "confirmed".to_string();
+                    }
+                }
+                None if is_expired => {
+                    tx.status = "expired".to_string();
+                    tx.error = Some("Blockhash expired before the transaction landed".to_string());
+                }
+                None => {}
+            }
+
+            if tx.status != previous {
+                debug!("[poll] {} {} -> {}", tx.signature, previous, tx.status);
+                if let Err(e) = tx.update_status(&self.db_pool).await {
+                    warn!("[poll] Failed to update transaction {}: {}", tx.signature, e);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,426 @@
+//! Webhook Service
+//!
+//! Delivers domain events from the event bus to user-registered endpoints.
+//! Payloads are signed with HMAC-SHA256, failed deliveries are retried with
+//! exponential backoff, and every attempt is recorded in `webhook_deliveries`.
+//! Endpoints must be https and may only resolve to public addresses, checked at
+//! registration and again by the resolver on every connection.
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use deadpool_postgres::Pool;
+use ring::hmac;
+use ring::rand::{SecureRandom, SystemRandom};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+use crate::database::models::{WebhookDelivery, WebhookEndpoint};
+use crate::services::event_bus::{EventBus, EventEnvelope};
+
+/// Event types that can be delivered to webhooks; agent telemetry is stream-only
+pub const WEBHOOK_EVENT_TYPES: [&str; 9] = [
+    "pool_created",
+    "contribution_made",
+    "pool_filled",
+    "trading_started",
+    "plan_created",
+    "trade_executed",
+    "risk_rejected",
+    "stop_loss_triggered",
+    "pool_closed",
+];
+
+/// How often pending retries are picked up
+const RETRY_POLL_SECS: u64 = 15;
+const RETRY_BATCH: i64 = 100;
+
+pub const SIGNATURE_HEADER: &str = "X-ICM-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-ICM-Timestamp";
+pub const EVENT_HEADER: &str = "X-ICM-Event";
+pub const DELIVERY_HEADER: &str = "X-ICM-Delivery";
+
+/// Retry and timeout settings for deliveries
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    pub max_attempts: i32,
+    pub initial_backoff_secs: i64,
+    pub max_backoff_secs: i64,
+    pub request_timeout_secs: u64,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 8,
+            initial_backoff_secs: 10,
+            max_backoff_secs: 3_600,
+            request_timeout_secs: 10,
+        }
+    }
+}
+
+/// Fans bus events out to matching endpoints and retries failed deliveries
+#[derive(Debug)]
+pub struct WebhookDispatcher {
+    db_pool: Pool,
+    http_client: reqwest::Client,
+    config: WebhookConfig,
+}
+
+impl WebhookDispatcher {
+    pub fn new(db_pool: Pool, config: WebhookConfig) -> Self {
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.request_timeout_secs))
+            .redirect(reqwest::redirect::Policy::none())
+            .dns_resolver(Arc::new(PublicResolver))
+            .build()
+            .expect("Failed to create HTTP client");
+        Self { db_pool, http_client, config }
+    }
+
+    /// Subscribe to the bus and start the retry loop
+    pub fn start(self: Arc<Self>, bus: &EventBus) {
+        let mut receiver = bus.subscribe();
+        let dispatcher = Arc::clone(&self);
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(envelope) => dispatcher.dispatch(&envelope).await,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("[start] Webhook dispatcher missed {} events", skipped);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
+        tokio::spawn(async move {
+            let mut timer = tokio::time::interval(Duration::from_secs(RETRY_POLL_SECS));
+            loop {
+                timer.tick().await;
+                self.retry_due().await;
+            }
+        });
+        info!("[start] Webhook dispatcher running");
+    }
+
+    /// Queue a delivery for every endpoint subscribed to the event. Sends run in
+    /// their own tasks so a slow endpoint never holds up the bus.
+    async fn dispatch(self: &Arc<Self>, envelope: &EventEnvelope) {
+        let event_type = envelope.event.kind();
+        if !WEBHOOK_EVENT_TYPES.contains(&event_type) {
+            return;
+        }
+
+        let endpoints = match WebhookEndpoint::fetch_subscribed(&self.db_pool, event_type, envelope.pool_id.as_deref()).await {
+            Ok(endpoints) => endpoints,
+            Err(e) => {
+                error!("[dispatch] Failed to load webhook endpoints: {}", e);
+                return;
+            }
+        };
+        if endpoints.is_empty() {
+            return;
+        }
+
+        let payload = match serde_json::to_value(envelope) {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!("[dispatch] Failed to serialize event {}: {}", envelope.id, e);
+                return;
+            }
+        };
+
+        for endpoint in endpoints {
+            match WebhookDelivery::insert(&self.db_pool, endpoint.id, envelope.id, event_type, &payload).await {
+                Ok(delivery) => {
+                    let dispatcher = Arc::clone(self);
+                    tokio::spawn(async move { dispatcher.attempt(delivery, &endpoint).await });
+                }
+                Err(e) => error!("[dispatch] Failed to queue delivery to {}: {}", endpoint.url, e),
+            }
+        }
+    }
+
+    async fn retry_due(&self) {
+        let due = match WebhookDelivery::fetch_due(&self.db_pool, RETRY_BATCH).await {
+            Ok(due) => due,
+            Err(e) => {
+                warn!("[retry_due] Failed to load pending deliveries: {}", e);
+                return;
+            }
+        };
+
+        for delivery in due {
+            match WebhookEndpoint::fetch_by_id(&self.db_pool, delivery.endpoint_id).await {
+                Ok(Some(endpoint)) if endpoint.is_active => self.attempt(delivery, &endpoint).await,
+                Ok(_) => {
+                    let mut delivery = delivery;
+                    delivery.status = "failed".to_string();
+                    delivery.last_error = Some("Endpoint is inactive".to_string());
+                    delivery.next_attempt_at = None;
+                    self.save(&delivery).await;
+                }
+                Err(e) => warn!("[retry_due] Failed to load endpoint {}: {}", delivery.endpoint_id, e),
+            }
+        }
+    }
+
+    /// Send a delivery once and record the outcome, scheduling a retry on failure
+    async fn attempt(&self, mut delivery: WebhookDelivery, endpoint: &WebhookEndpoint) {
+        // Endpoints registered before the URL rules, or with an IP literal the resolver never sees
+        if let Err(e) = validate_endpoint_url(&endpoint.url).await {
+            warn!("[attempt] Refusing delivery {} to {}: {}", delivery.id, endpoint.url, e);
+            delivery.attempts += 1;
+            delivery.status = "failed".to_string();
+            delivery.last_error = Some(e.to_string());
+            delivery.next_attempt_at = None;
+            self.save(&delivery).await;
+            return;
+        }
+
+        let body = delivery.payload.to_string();
+        let timestamp = Utc::now().timestamp();
+        let signature = sign_payload(&endpoint.secret, timestamp, &body);
+
+        let response = self.http_client
+            .post(&endpoint.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, format!("sha256={}", signature))
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(EVENT_HEADER, &delivery.event_type)
+            .header(DELIVERY_HEADER, delivery.id.to_string())
+            .body(body)
+            .send()
+            .await;
+
+        delivery.attempts += 1;
+        let error = match response {
+            Ok(response) => {
+                delivery.response_status = Some(response.status().as_u16() as i32);
+                if response.status().is_success() {
+                    None
+                } else {
+                    Some(format!("Endpoint responded with {}", response.status()))
+                }
+            }
+            Err(e) => {
+                delivery.response_status = None;
+                Some(e.to_string())
+            }
+        };
+
+        match error {
+            None => {
+                delivery.status = "delivered".to_string();
+                delivery.last_error = None;
+                delivery.next_attempt_at = None;
+                delivery.delivered_at = Some(Utc::now());
+                debug!("[attempt] Delivered {} to {}", delivery.event_type, endpoint.url);
+            }
+            Some(error) if delivery.attempts >= self.config.max_attempts => {
+                warn!("[attempt] Giving up on delivery {} to {} after {} attempts: {}",
+                      delivery.id, endpoint.url, delivery.attempts, error);
+                delivery.status = "failed".to_string();
+                delivery.last_error = Some(error);
+                delivery.next_attempt_at = None;
+            }
+            Some(error) => {
+                let backoff = self.backoff_secs(delivery.attempts);
+                debug!("[attempt] Delivery {} to {} failed ({}), retrying in {}s",
+                       delivery.id, endpoint.url, error, backoff);
+                delivery.status = "pending".to_string();
+                delivery.last_error = Some(error);
+                delivery.next_attempt_at = Some(Utc::now() + chrono::Duration::seconds(backoff));
+            }
+        }
+
+        self.save(&delivery).await;
+    }
+
+    async fn save(&self, delivery: &WebhookDelivery) {
+        if let Err(e) = delivery.record_attempt(&self.db_pool).await {
+            error!("[save] Failed to record webhook delivery {}: {}", delivery.id, e);
+        }
+    }
+
+    /// Delay before the next attempt: doubles per attempt, capped
+    fn backoff_secs(&self, attempts: i32) -> i64 {
+        let exponent = (attempts - 1).clamp(0, 30) as u32;
+        self.config.initial_backoff_secs
+            .saturating_mul(2i64.saturating_pow(exponent))
+            .min(self.config.max_backoff_secs)
+    }
+
+    /// Send a stored delivery's payload again as a new delivery.
+    /// Returns `None` when the delivery does not exist or belongs to another user.
+    pub async fn replay(&self, delivery_id: Uuid, user_id: Uuid) -> Result<Option<WebhookDelivery>> {
+        let Some(original) = WebhookDelivery::fetch_by_id(&self.db_pool, delivery_id).await? else {
+            return Ok(None);
+        };
+        let Some(endpoint) = WebhookEndpoint::fetch_by_id(&self.db_pool, original.endpoint_id).await?
+            .filter(|endpoint| endpoint.user_id == user_id)
+        else {
+            return Ok(None);
+        };
+
+        let replay = WebhookDelivery::insert(
+            &self.db_pool,
+            endpoint.id,
+            original.event_id,
+            &original.event_type,
+            &original.payload,
+        ).await?;
+        let replay_id = replay.id;
+        info!("[replay] Replaying delivery {} as {}", delivery_id, replay_id);
+        self.attempt(replay, &endpoint).await;
+
+        WebhookDelivery::fetch_by_id(&self.db_pool, replay_id).await
+    }
+}
+
+/// Parse a webhook URL, requiring https and a host that resolves only to public addresses
+pub async fn validate_endpoint_url(raw: &str) -> Result<url::Url> {
+    let url = url::Url::parse(raw).map_err(|e| anyhow!("Invalid URL: {}", e))?;
+    if url.scheme() != "https" {
+        return Err(anyhow!("Webhook URL must use https"));
+    }
+    let host = url.host_str().ok_or_else(|| anyhow!("Webhook URL has no host"))?;
+    public_addrs(host, url.port_or_known_default().unwrap_or(443)).await?;
+    Ok(url)
+}
+
+/// Resolve `host`, refusing it when any address is not publicly routable
+async fn public_addrs(host: &str, port: u16) -> Result<Vec<SocketAddr>> {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port)).await
+        .map_err(|e| anyhow!("Cannot resolve {}: {}", host, e))?
+        .collect();
+    if addrs.is_empty() {
+        return Err(anyhow!("{} does not resolve to any address", host));
+    }
+    if let Some(addr) = addrs.iter().find(|addr| !is_public_ip(addr.ip())) {
+        return Err(anyhow!("{} resolves to non-public address {}", host, addr.ip()));
+    }
+    Ok(addrs)
+}
+
+/// Whether an address is routable on the public internet
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, c, _] = v4.octets();
+            !(v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_documentation()
+                || v4.is_multicast()
+                || a == 0
+                || a >= 240
+                || (a == 100 && (64..128).contains(&b)) // carrier-grade NAT
+                || (a == 192 && b == 0 && c == 0)
+                || (a == 198 && (b == 18 || b == 19)))
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(v4));
+            }
+            let first = v6.segments()[0];
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                || (first & 0xfe00) == 0xfc00 // unique local
+                || (first & 0xffc0) == 0xfe80) // link-local
+        }
+    }
+}
+
+/// Resolver for deliveries; a host re-pointed at an internal address after
+/// registration fails to connect instead of reaching it
+struct PublicResolver;
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs = public_addrs(&host, 0).await?;
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// Hex HMAC-SHA256 of `"{timestamp}.{body}"`, sent as `sha256=<hex>` in `X-ICM-Signature`
+pub fn sign_payload(secret: &str, timestamp: i64, body: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let tag = hmac::sign(&key, format!("{}.{}", timestamp, body).as_bytes());
+    tag.as_ref().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Random signing secret for a new endpoint
+pub fn generate_secret() -> Result<String> {
+    let mut bytes = [0u8; 32];
+    SystemRandom::new().fill(&mut bytes)
+        .map_err(|_| anyhow!("Failed to generate webhook secret"))?;
+    Ok(format!("whsec_{}", bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn public(ip: &str) -> bool {
+        is_public_ip(ip.parse().unwrap())
+    }
+
+    #[test]
+    fn rejects_private_and_reserved_ipv4() {
+        for ip in [
+            "10.0.0.1", "172.16.5.4", "172.31.255.255", "192.168.1.1", // RFC 1918
+            "100.64.0.1", "100.127.255.254", // carrier-grade NAT
+            "127.0.0.1", "169.254.169.254", "0.0.0.0", "255.255.255.255",
+            "192.0.0.8", "198.18.0.1", "192.0.2.1", "224.0.0.1", "240.0.0.1",
+        ] {
+            assert!(!public(ip), "{} should not be public", ip);
+        }
+    }
+
+    #[test]
+    fn accepts_public_ipv4() {
+        for ip in ["8.8.8.8", "1.1.1.1", "100.63.255.255", "100.128.0.1", "172.32.0.1"] {
+            assert!(public(ip), "{} should be public", ip);
+        }
+    }
+
+    #[test]
+    fn ipv4_mapped_ipv6_is_checked_as_ipv4() {
+        assert!(!public("::ffff:10.0.0.1"));
+        assert!(!public("::ffff:127.0.0.1"));
+        assert!(!public("::ffff:169.254.169.254"));
+        assert!(public("::ffff:8.8.8.8"));
+    }
+
+    #[test]
+    fn rejects_local_ipv6() {
+        for ip in ["::1", "::", "fc00::1", "fd12:3456:789a::1", "fe80::1", "febf::1", "ff02::1"] {
+            assert!(!public(ip), "{} should not be public", ip);
+        }
+        assert!(public("2001:4860:4860::8888"));
+    }
+
+    #[test]
+    fn signs_timestamp_and_body_with_hmac_sha256() {
+        let signature = sign_payload("whsec_test", 1_700_000_000, r#"{"event":"trade_executed"}"#);
+        assert_eq!(signature, "ac2d439051a25f698f4d36b3a06be0e30468c327e442a1bc45c3f949d0ffa0ed");
+    }
+
+    #[test]
+    fn signature_covers_the_timestamp() {
+        let body = r#"{"event":"trade_executed"}"#;
+        assert_ne!(sign_payload("whsec_test", 1_700_000_000, body), sign_payload("whsec_test", 1_700_000_001, body));
+    }
+}
@@ -67,6 +67,50 @@ pub struct UnsignedTransactionResponse {
     pub message: String,
 }
 
+/// Unsigned transaction for the user's own wallet to sign and submit
+#[derive(Debug, serde::Serialize)]
+pub struct WalletTransactionResponse {
+    /// Base64 bincode-serialized transaction with the recent blockhash set
+    pub transaction: String,
+    pub recent_blockhash: String,
+    /// The transaction can no longer land after this block height
+    pub last_valid_block_height: u64,
+    pub fee_payer: String,
+    pub message: String,
+}
+
+/// Result of simulating a transaction without submitting it
+#[derive(Debug, serde::Serialize)]
+pub struct SimulationResponse {
+    /// Whether the transaction would succeed
+    pub success: bool,
+    pub message: String,
+    pub fee_payer: String,
+    pub units_consumed: Option<u64>,
+    pub logs: Vec<String>,
+    pub error: Option<String>,
+    /// Stable code of the ICM program error, e.g. `CONTRIBUTION_DEADLINE_PASSED`
+    pub error_code: Option<&'static str>,
+    /// Raw custom program error code
+    pub program_error_code: Option<u32>,
+    /// Balances the transaction would change; empty when it fails
+    pub balance_changes: Vec<BalanceChange>,
+}
+
+/// Expected change of one SOL or SPL token balance, in base units
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BalanceChange {
+    pub account: String,
+    /// Token mint; `None` for SOL
+    pub mint: Option<String>,
+    /// Wallet owning the token account; `None` for SOL
+    pub owner: Option<String>,
+    pub decimals: u8,
+    pub before: u64,
+    pub after: u64,
+    pub delta: i64,
+}
+
 
 // --- Request structs ---
 
@@ -151,6 +195,8 @@ pub struct SwapTokensRequest {
 pub struct ClaimRewardsRequest {
     pub bucket_name: String,
     pub token_mint: String,
+    /// Bucket creator; defaults to the claiming wallet
+    pub creator_pubkey: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -177,6 +223,75 @@ pub struct GetCreatorProfileQuery {
     pub creator_pubkey: String,
 }
 
+/// Contribution records of one wallet; the caller's own wallet when omitted
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct GetContributorQuery {
+    pub contributor_pubkey: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PayoutPreviewQuery {
+    pub bucket_name: String,
+    pub creator_pubkey: String,
+    /// Defaults to the caller's wallet
+    pub contributor_pubkey: Option<String>,
+    /// Check the estimate against a simulated claim; defaults to true
+    pub simulate: Option<bool>,
+}
+
+/// A `PoolContribution` account, amounts in USDC
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PoolContributionInfo {
+    pub address: String,
+    pub contribution_amount: f64,
+    /// Share as stored by the program
+    pub pool_share_percentage: u64,
+    pub claimed: bool,
+}
+
+/// A `ContributionRecord` account with its bucket and pool contribution, amounts in USDC
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ContributionInfo {
+    pub contribution_record: String,
+    pub contributor: String,
+    pub bucket: String,
+    /// `None` once the bucket account is closed
+    pub bucket_name: Option<String>,
+    pub creator: Option<String>,
+    pub token_mint: String,
+    pub amount: f64,
+    pub timestamp: i64,
+    /// Percent of the bucket's raised amount
+    pub share_percent: Option<f64>,
+    pub pool_contribution: Option<PoolContributionInfo>,
+}
+
+/// Every contribution to one bucket
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BucketContributions {
+    pub bucket: String,
+    pub bucket_name: String,
+    pub creator: String,
+    pub raised_amount: f64,
+    pub contributor_count: u32,
+    pub contributions: Vec<ContributionInfo>,
+}
+
+/// A `TradeRecord` account; amounts in UI units of their mints
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TradeRecordInfo {
+    pub address: String,
+    pub pool_id: String,
+    pub trade_id: u64,
+    pub timestamp: i64,
+    pub trade_type: String,
+    pub from_token: String,
+    pub to_token: String,
+    pub amount_in: f64,
+    pub amount_out: f64,
+    pub success: bool,
+}
+
 // GetBucketQuery
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct GetBucketQuery {